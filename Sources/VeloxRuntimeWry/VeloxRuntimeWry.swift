@@ -96,7 +96,7 @@ public enum VeloxRuntimeWry {
     }
   }
 
-  private static let expectedFfiAbiVersion: UInt32 = 2
+  private static let expectedFfiAbiVersion: UInt32 = 3
   private static let ffiAbiLock = NSLock()
   private static var ffiAbiValidated = false
 
@@ -236,6 +236,9 @@ public enum VeloxRuntimeWry {
     }
 
     /// A response from the custom protocol handler.
+    ///
+    /// Returning a 3xx status with a `Location` header (absolute, or relative to the
+    /// request URL) redirects page loads. Subresource requests receive the response as-is.
     public struct Response: Sendable {
       /// HTTP status code (e.g., 200, 404).
       public var status: Int
@@ -330,6 +333,8 @@ public enum VeloxRuntimeWry {
     public var proxyUrl: String?
     /// Webview background throttling policy.
     public var backgroundThrottling: BackgroundThrottlingPolicy?
+    /// Content-Security-Policy added to every page as a `<meta>` tag.
+    public var contentSecurityPolicy: String?
 
     public init(
       url: String = "",
@@ -346,7 +351,8 @@ public enum VeloxRuntimeWry {
       javascriptDisabled: Bool? = nil,
       scrollBarStyle: ScrollBarStyle? = nil,
       proxyUrl: String? = nil,
-      backgroundThrottling: BackgroundThrottlingPolicy? = nil
+      backgroundThrottling: BackgroundThrottlingPolicy? = nil,
+      contentSecurityPolicy: String? = nil
     ) {
       self.url = url
       self.customProtocols = customProtocols
@@ -363,6 +369,7 @@ public enum VeloxRuntimeWry {
       self.scrollBarStyle = scrollBarStyle
       self.proxyUrl = proxyUrl
       self.backgroundThrottling = backgroundThrottling
+      self.contentSecurityPolicy = contentSecurityPolicy
     }
   }
 
@@ -460,6 +467,8 @@ public enum VeloxRuntimeWry {
       public var defaultValue: String?
       public var okLabel: String?
       public var cancelLabel: String?
+      /// Shows a multi-line text area instead of a single-line field.
+      public var multiline: Bool
 
       public init(
         title: String? = nil,
@@ -467,7 +476,8 @@ public enum VeloxRuntimeWry {
         placeholder: String? = nil,
         defaultValue: String? = nil,
         okLabel: String? = nil,
-        cancelLabel: String? = nil
+        cancelLabel: String? = nil,
+        multiline: Bool = false
       ) {
         self.title = title
         self.message = message
@@ -475,6 +485,7 @@ public enum VeloxRuntimeWry {
         self.defaultValue = defaultValue
         self.okLabel = okLabel
         self.cancelLabel = cancelLabel
+        self.multiline = multiline
       }
     }
 
@@ -786,7 +797,8 @@ public enum VeloxRuntimeWry {
         placeholder: placeholderPointer.flatMap { UnsafePointer($0) },
         default_value: defaultValuePointer.flatMap { UnsafePointer($0) },
         ok_label: okLabelPointer.flatMap { UnsafePointer($0) },
-        cancel_label: cancelLabelPointer.flatMap { UnsafePointer($0) }
+        cancel_label: cancelLabelPointer.flatMap { UnsafePointer($0) },
+        multiline: options.multiline
       )
 
       let result = withUnsafePointer(to: &ffiOptions) { pointer in
@@ -1410,7 +1422,20 @@ public extension VeloxRuntimeWry {
             has_shadow: optionalBoolFlag(configuration.shadow),
             titlebar_transparent: titlebarFlags.transparent,
             titlebar_hidden: titlebarFlags.hidden,
-            titlebar_buttons_hidden: titlebarFlags.buttonsHidden
+            titlebar_buttons_hidden: titlebarFlags.buttonsHidden,
+            x: 0,
+            y: 0,
+            has_position: false,
+            min_width: 0,
+            min_height: 0,
+            max_width: 0,
+            max_height: 0,
+            decorations: -1,
+            resizable: -1,
+            transparent: false,
+            always_on_top: false,
+            skip_taskbar: false,
+            theme: VELOX_WINDOW_THEME_UNSPECIFIED
           )
           return withUnsafePointer(to: &native) { pointer in
             guard let handle = velox_window_build(raw, pointer) else {
@@ -1652,41 +1677,51 @@ public extension VeloxRuntimeWry {
       return withOptionalCString(configuration.url) { urlPointer in
         return withOptionalCString(configuration.proxyUrl ?? "") { proxyPointer in
           return withOptionalCString(configuration.dataDirectory ?? "") { dataDirectoryPointer in
-            var native = VeloxWebviewConfig(
-              url: urlPointer,
-              custom_protocols: VeloxCustomProtocolList(protocols: nil, count: 0),
-              devtools: configuration.devtools,
-              is_child: configuration.isChild,
-              x: configuration.x,
-              y: configuration.y,
-              width: configuration.width,
-              height: configuration.height,
-              accept_first_mouse: optionalBoolFlag(configuration.acceptFirstMouse),
-              incognito: optionalBoolFlag(configuration.incognito),
-              javascript_disabled: optionalBoolFlag(configuration.javascriptDisabled),
-              background_throttling: backgroundThrottlingFlag(configuration.backgroundThrottling),
-              scroll_bar_style: scrollBarStyleFlag(configuration.scrollBarStyle),
-              proxy_url: proxyPointer,
-              data_directory: dataDirectoryPointer
-            )
-
-            return definitions.withUnsafeBufferPointer { buffer in
-              if let baseAddress = buffer.baseAddress, buffer.count > 0 {
-                native.custom_protocols = VeloxCustomProtocolList(
-                  protocols: baseAddress,
-                  count: buffer.count
-                )
-              }
-
-              return withUnsafePointer(to: native) { pointer in
-                guard let handle = velox_webview_build(raw, pointer) else {
-                  return nil
+            return withOptionalCString(configuration.contentSecurityPolicy ?? "") { cspPointer in
+              var native = VeloxWebviewConfig(
+                url: urlPointer,
+                custom_protocols: VeloxCustomProtocolList(protocols: nil, count: 0),
+                devtools: configuration.devtools,
+                is_child: configuration.isChild,
+                x: configuration.x,
+                y: configuration.y,
+                width: configuration.width,
+                height: configuration.height,
+                accept_first_mouse: optionalBoolFlag(configuration.acceptFirstMouse),
+                incognito: optionalBoolFlag(configuration.incognito),
+                javascript_disabled: optionalBoolFlag(configuration.javascriptDisabled),
+                background_throttling: backgroundThrottlingFlag(configuration.backgroundThrottling),
+                scroll_bar_style: scrollBarStyleFlag(configuration.scrollBarStyle),
+                proxy_url: proxyPointer,
+                data_directory: dataDirectoryPointer,
+                script_error_handler: nil,
+                script_error_user_data: nil,
+                initialization_script: nil,
+                user_agent: nil,
+                transparent: false,
+                background_color: VeloxColor(red: 0, green: 0, blue: 0, alpha: 0),
+                has_background_color: false,
+                csp: cspPointer
+              )
+
+              return definitions.withUnsafeBufferPointer { buffer in
+                if let baseAddress = buffer.baseAddress, buffer.count > 0 {
+                  native.custom_protocols = VeloxCustomProtocolList(
+                    protocols: baseAddress,
+                    count: buffer.count
+                  )
                 }
-                guard let webview = Webview(raw: handle) else {
-                  return nil
+
+                return withUnsafePointer(to: native) { pointer in
+                  guard let handle = velox_webview_build(raw, pointer) else {
+                    return nil
+                  }
+                  guard let webview = Webview(raw: handle) else {
+                    return nil
+                  }
+                  webview.installCustomProtocolHandlers(handlerBoxes)
+                  return register(webview: webview)
                 }
-                webview.installCustomProtocolHandlers(handlerBoxes)
-                return register(webview: webview)
               }
             }
           }
@@ -1811,6 +1846,16 @@ public extension VeloxRuntimeWry {
       }
     }
 
+    @discardableResult
+    public func setOpacity(_ opacity: Double) -> Bool {
+      velox_window_set_opacity(raw, opacity)
+    }
+
+    public func opacity() -> Double? {
+      var value = 0.0
+      return velox_window_get_opacity(raw, &value) ? value : nil
+    }
+
     @discardableResult
     public func setTheme(_ theme: Theme?) -> Bool {
       let ffiTheme: VeloxWindowTheme
@@ -2069,6 +2114,40 @@ public extension VeloxRuntimeWry {
       url.withCString { velox_webview_navigate(raw, $0) }
     }
 
+    /// Replaces the handler for a scheme registered when the webview was created.
+    /// Passing `nil` unregisters it so every request receives a 404.
+    @discardableResult
+    public func replaceCustomProtocolHandler(
+      scheme: String,
+      handler: VeloxRuntimeWry.CustomProtocol.Handler?
+    ) -> Bool {
+      guard let handler else {
+        return scheme.withCString { velox_webview_replace_protocol_handler(raw, $0, nil, nil) }
+      }
+      let box = VeloxCustomProtocolHandlerBox(handler: handler)
+      let replaced = scheme.withCString {
+        velox_webview_replace_protocol_handler(
+          raw,
+          $0,
+          velox_custom_protocol_handler_trampoline,
+          Unmanaged.passUnretained(box).toOpaque()
+        )
+      }
+      if replaced {
+        // The previous box may still be serving an in-flight request, so keep it alive too.
+        customProtocolHandlers.append(box)
+      }
+      return replaced
+    }
+
+    /// The URL currently loaded in the webview, if any.
+    public var url: String? {
+      guard let pointer = velox_webview_get_url(raw) else {
+        return nil
+      }
+      return String(cString: pointer)
+    }
+
     @discardableResult
     public func reload() -> Bool {
       velox_webview_reload(raw)
@@ -2079,6 +2158,37 @@ public extension VeloxRuntimeWry {
       script.withCString { velox_webview_evaluate_script(raw, $0) }
     }
 
+    @discardableResult
+    public func postMessage(event: String, json: String) -> Bool {
+      event.withCString { eventPtr in
+        json.withCString { velox_webview_post_message(raw, eventPtr, $0) }
+      }
+    }
+
+    @discardableResult
+    public func setAcceptFirstMouse(_ accept: Bool) -> Bool {
+      velox_webview_set_accept_first_mouse(raw, accept)
+    }
+
+    public func isIncognito() -> Bool {
+      velox_webview_is_incognito(raw)
+    }
+
+    @discardableResult
+    public func setContentSecurityPolicy(_ policy: String) -> Bool {
+      policy.withCString { velox_webview_set_content_security_policy(raw, $0) }
+    }
+
+    @discardableResult
+    public func focus() -> Bool {
+      velox_webview_focus(raw)
+    }
+
+    @discardableResult
+    public func blur() -> Bool {
+      velox_webview_blur(raw)
+    }
+
     @discardableResult
     public func setZoom(_ scale: Double) -> Bool {
       velox_webview_set_zoom(raw, scale)
@@ -3814,7 +3924,8 @@ public extension VeloxRuntimeWry {
       title: String? = nil,
       tooltip: String? = nil,
       visible: Bool = true,
-      showMenuOnLeftClick: Bool = true
+      showMenuOnLeftClick: Bool = true,
+      iconPath: String? = nil
     ) {
       guard Thread.isMainThread else {
         return nil
@@ -3825,7 +3936,11 @@ public extension VeloxRuntimeWry {
         title: nil,
         tooltip: nil,
         visible: visible,
-        show_menu_on_left_click: showMenuOnLeftClick
+        show_menu_on_left_click: showMenuOnLeftClick,
+        icon_rgba: nil,
+        icon_rgba_width: 0,
+        icon_rgba_height: 0,
+        icon_path: nil
       )
 
       let handle: UnsafeMutablePointer<VeloxTrayHandle>? = withOptionalCString(identifier ?? "") { identifierPointer in
@@ -3834,7 +3949,10 @@ public extension VeloxRuntimeWry {
           config.title = titlePointer
           return withOptionalCString(tooltip ?? "") { tooltipPointer in
             config.tooltip = tooltipPointer
-            return velox_tray_new(&config)
+            return withOptionalCString(iconPath ?? "") { iconPathPointer in
+              config.icon_path = iconPathPointer
+              return velox_tray_new(&config)
+            }
           }
         }
       }
@@ -3887,6 +4005,15 @@ public extension VeloxRuntimeWry {
       return velox_tray_set_show_menu_on_left_click(raw, enable)
     }
 
+    /// Badges the dock icon (macOS) or taskbar button (Windows); 0 clears the badge.
+    @discardableResult
+    public func setBadgeCount(_ count: Int32) -> Bool {
+      guard Thread.isMainThread else {
+        return false
+      }
+      return velox_tray_set_badge_count(raw, count)
+    }
+
     @discardableResult
     public func setMenu(_ menu: MenuBar?) -> Bool {
       guard Thread.isMainThread else {
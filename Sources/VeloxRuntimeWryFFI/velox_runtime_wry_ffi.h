@@ -13,6 +13,23 @@ const char *velox_runtime_wry_library_name(void);
 uint32_t velox_runtime_wry_ffi_abi_version(void);
 const char *velox_runtime_wry_crate_version(void);
 const char *velox_runtime_wry_webview_version(void);
+/// Message of the last platform or library failure on this thread, or ""; valid until the next call
+const char *velox_last_error_message(void);
+
+/// Failure categories returned by the `_checked` variants of bool functions.
+typedef enum {
+  VELOX_ERROR_CODE_SUCCESS = 0,
+  VELOX_ERROR_CODE_NULL_POINTER = 1,
+  VELOX_ERROR_CODE_INVALID_ARGUMENT = 2,
+  VELOX_ERROR_CODE_PLATFORM_UNSUPPORTED = 3,
+  VELOX_ERROR_CODE_WINDOW_CREATION_FAILED = 4,
+  VELOX_ERROR_CODE_WEBVIEW_CREATION_FAILED = 5,
+  VELOX_ERROR_CODE_IO_ERROR = 6,
+  VELOX_ERROR_CODE_UNKNOWN = 7,
+} VeloxErrorCode;
+
+/// Category of the last failure on this thread; successful calls leave it unchanged
+VeloxErrorCode velox_last_error_code(void);
 
 typedef struct {
   char _unused;
@@ -22,6 +39,10 @@ typedef struct {
   char _unused;
 } VeloxEventLoopProxyHandle;
 
+typedef struct {
+  char _unused;
+} VeloxEventLoopPumpHandle;
+
 typedef struct {
   char _unused;
 } VeloxWindowHandle;
@@ -40,6 +61,11 @@ typedef struct {
   const char *tooltip;
   bool visible;
   bool show_menu_on_left_click;
+  /// Initial icon pixels; take precedence over `icon_path` when both are set.
+  const uint8_t *icon_rgba;
+  uint32_t icon_rgba_width;
+  uint32_t icon_rgba_height;
+  const char *icon_path;
 } VeloxTrayConfig;
 
 #if defined(__APPLE__)
@@ -78,6 +104,27 @@ typedef enum {
   VELOX_MENU_ITEM_KIND_ICON = 3,
   VELOX_MENU_ITEM_KIND_SUBMENU = 4,
 } VeloxMenuItemKind;
+
+typedef enum {
+  VELOX_PREDEFINED_MENU_ITEM_KIND_SEPARATOR = 0,
+  VELOX_PREDEFINED_MENU_ITEM_KIND_COPY = 1,
+  VELOX_PREDEFINED_MENU_ITEM_KIND_CUT = 2,
+  VELOX_PREDEFINED_MENU_ITEM_KIND_PASTE = 3,
+  VELOX_PREDEFINED_MENU_ITEM_KIND_SELECT_ALL = 4,
+  VELOX_PREDEFINED_MENU_ITEM_KIND_UNDO = 5,
+  VELOX_PREDEFINED_MENU_ITEM_KIND_REDO = 6,
+  VELOX_PREDEFINED_MENU_ITEM_KIND_MINIMIZE = 7,
+  VELOX_PREDEFINED_MENU_ITEM_KIND_MAXIMIZE = 8,
+  VELOX_PREDEFINED_MENU_ITEM_KIND_FULLSCREEN = 9,
+  VELOX_PREDEFINED_MENU_ITEM_KIND_HIDE = 10,
+  VELOX_PREDEFINED_MENU_ITEM_KIND_HIDE_OTHERS = 11,
+  VELOX_PREDEFINED_MENU_ITEM_KIND_SHOW_ALL = 12,
+  VELOX_PREDEFINED_MENU_ITEM_KIND_CLOSE_WINDOW = 13,
+  VELOX_PREDEFINED_MENU_ITEM_KIND_QUIT = 14,
+  VELOX_PREDEFINED_MENU_ITEM_KIND_ABOUT = 15,
+  VELOX_PREDEFINED_MENU_ITEM_KIND_SERVICES = 16,
+  VELOX_PREDEFINED_MENU_ITEM_KIND_BRING_ALL_TO_FRONT = 17,
+} VeloxPredefinedMenuItemKind;
 #endif
 
 typedef enum {
@@ -86,6 +133,23 @@ typedef enum {
   VELOX_CONTROL_FLOW_EXIT = 2,
 } VeloxEventLoopControlFlow;
 
+/// Event categories delivered to the pump callback.
+typedef struct {
+  bool window;
+  bool input;
+  bool cursor;
+  bool device;
+  bool redraw;
+  bool user;
+  bool lifecycle;
+} VeloxEventFilter;
+
+typedef enum {
+  VELOX_WINDOW_THEME_UNSPECIFIED = 0,
+  VELOX_WINDOW_THEME_LIGHT = 1,
+  VELOX_WINDOW_THEME_DARK = 2,
+} VeloxWindowTheme;
+
 typedef struct {
   uint32_t width;
   uint32_t height;
@@ -95,6 +159,23 @@ typedef struct {
   int8_t titlebar_transparent;
   int8_t titlebar_hidden;
   int8_t titlebar_buttons_hidden;
+  /// Initial outer position in logical pixels, used when `has_position` is set
+  double x;
+  double y;
+  bool has_position;
+  /// Size limits in logical pixels; zero leaves a limit unset
+  double min_width;
+  double min_height;
+  double max_width;
+  double max_height;
+  /// Tri-state flags; -1 leaves the platform default
+  int8_t decorations;
+  int8_t resizable;
+  bool transparent;
+  bool always_on_top;
+  /// Windows and Linux only
+  bool skip_taskbar;
+  VeloxWindowTheme theme;
 } VeloxWindowConfig;
 
 typedef struct {
@@ -172,6 +253,8 @@ typedef struct {
   const char *default_value;
   const char *ok_label;
   const char *cancel_label;
+  /// Multi-line text area instead of a single-line field.
+  bool multiline;
 } VeloxPromptDialogOptions;
 
 typedef struct {
@@ -194,6 +277,14 @@ typedef struct {
   size_t len;
 } VeloxCustomProtocolBuffer;
 
+/// Straight-alpha RGBA pixels, `width * height * 4` bytes, top row first.
+typedef struct {
+  const uint8_t *rgba;
+  size_t len;
+  uint32_t width;
+  uint32_t height;
+} VeloxIconBuffer;
+
 typedef struct {
   const char *url;
   const char *method;
@@ -204,6 +295,7 @@ typedef struct {
 
 typedef void (*VeloxCustomProtocolResponseFree)(void *user_data);
 
+/// A 3xx status with a Location header redirects document loads on every platform.
 typedef struct {
   uint16_t status;
   VeloxCustomProtocolHeaderList headers;
@@ -227,12 +319,21 @@ bool velox_custom_protocol_handler_trampoline(
 
 void velox_custom_protocol_response_free_trampoline(void *user_data);
 
+typedef void (*VeloxCustomProtocolUserDataRelease)(void *user_data);
+
 typedef struct {
   const char *scheme;
   VeloxCustomProtocolHandler handler;
   void *user_data;
+  /// Reference-count `user_data` so it can be shared by several webviews
+  bool copy_user_data;
+  /// Called once the last reference to a reference-counted `user_data` is released
+  VeloxCustomProtocolUserDataRelease release_user_data;
 } VeloxCustomProtocolDefinition;
 
+bool velox_custom_protocol_definition_retain(const VeloxCustomProtocolDefinition *definition);
+bool velox_custom_protocol_definition_release(const VeloxCustomProtocolDefinition *definition);
+
 typedef struct VeloxCustomProtocolList {
   const VeloxCustomProtocolDefinition *protocols;
   size_t count;
@@ -249,6 +350,34 @@ typedef enum {
   VELOX_SCROLL_BAR_STYLE_FLUENT_OVERLAY = 1,
 } VeloxScrollBarStyle;
 
+typedef void (*VeloxScriptErrorHandler)(
+  const char *message,
+  const char *source,
+  uint32_t line,
+  void *user_data
+);
+
+typedef struct {
+  uint8_t red;
+  uint8_t green;
+  uint8_t blue;
+  uint8_t alpha;
+} VeloxColor;
+
+typedef struct {
+  const char *title;
+  VeloxColor initial_color;
+} VeloxColorPickerOptions;
+
+typedef struct {
+  const char *title;
+  const char *body;
+  const char *subtitle;
+  const char *icon_path;
+  /// Reported by the `notification-activated` event when the notification is clicked.
+  const char *action_id;
+} VeloxNotificationOptions;
+
 typedef struct {
   const char *url;
   VeloxCustomProtocolList custom_protocols;
@@ -265,7 +394,7 @@ typedef struct {
   double height;
   /// Whether clicking an inactive window also clicks through to the webview (macOS)
   int8_t accept_first_mouse;
-  /// Enable private browsing mode for the webview
+  /// Enable private browsing mode for the webview (-1 means unset)
   int8_t incognito;
   /// Disable JavaScript execution in the webview
   int8_t javascript_disabled;
@@ -275,17 +404,26 @@ typedef struct {
   int32_t scroll_bar_style;
   /// Proxy URL (http://host:port or socks5://host:port)
   const char *proxy_url;
-  /// Custom data directory for the webview context
+  /// Custom data directory for the webview context; must exist and be writable
   const char *data_directory;
+  /// Called for uncaught JavaScript errors and unhandled promise rejections
+  VeloxScriptErrorHandler script_error_handler;
+  /// Opaque pointer passed back to `script_error_handler`
+  void *script_error_user_data;
+  /// JavaScript run at document start of every page, before the first navigation
+  const char *initialization_script;
+  /// Custom User-Agent string (null keeps the platform default)
+  const char *user_agent;
+  /// Draw no background so a transparent window shows through (macOS needs `macos-private-api`)
+  bool transparent;
+  /// Fill shown before content paints and behind transparent page areas
+  VeloxColor background_color;
+  /// Whether `background_color` is applied
+  bool has_background_color;
+  /// Content-Security-Policy injected as a <meta> tag into every page (null for none)
+  const char *csp;
 } VeloxWebviewConfig;
 
-typedef struct {
-  uint8_t red;
-  uint8_t green;
-  uint8_t blue;
-  uint8_t alpha;
-} VeloxColor;
-
 typedef struct {
   double x;
   double y;
@@ -296,17 +434,62 @@ typedef struct {
   double height;
 } VeloxSize;
 
-typedef enum {
-  VELOX_WINDOW_THEME_UNSPECIFIED = 0,
-  VELOX_WINDOW_THEME_LIGHT = 1,
-  VELOX_WINDOW_THEME_DARK = 2,
-} VeloxWindowTheme;
-
 typedef enum {
   VELOX_USER_ATTENTION_TYPE_INFORMATIONAL = 0,
   VELOX_USER_ATTENTION_TYPE_CRITICAL = 1,
 } VeloxUserAttentionType;
 
+typedef enum {
+  VELOX_PROGRESS_STATE_NONE = 0,
+  VELOX_PROGRESS_STATE_NORMAL = 1,
+  VELOX_PROGRESS_STATE_INDETERMINATE = 2,
+  VELOX_PROGRESS_STATE_PAUSED = 3,
+  VELOX_PROGRESS_STATE_ERROR = 4,
+} VeloxProgressState;
+
+typedef enum {
+  VELOX_VIBRANCY_DISABLED = 0,
+  VELOX_VIBRANCY_TITLEBAR = 1,
+  VELOX_VIBRANCY_MENU = 2,
+  VELOX_VIBRANCY_POPOVER = 3,
+  VELOX_VIBRANCY_SIDEBAR = 4,
+  VELOX_VIBRANCY_HEADER_VIEW = 5,
+  VELOX_VIBRANCY_SHEET = 6,
+  VELOX_VIBRANCY_WINDOW_BACKGROUND = 7,
+  VELOX_VIBRANCY_HUD_WINDOW = 8,
+  VELOX_VIBRANCY_FULL_SCREEN_UI = 9,
+  VELOX_VIBRANCY_TOOL_TIP = 10,
+  VELOX_VIBRANCY_CONTENT_BACKGROUND = 11,
+  VELOX_VIBRANCY_UNDER_WINDOW_BACKGROUND = 12,
+  VELOX_VIBRANCY_UNDER_PAGE_BACKGROUND = 13,
+} VeloxVibrancy;
+
+typedef enum {
+  VELOX_WINDOW_LEVEL_DESKTOP = 0,
+  VELOX_WINDOW_LEVEL_NORMAL = 1,
+  VELOX_WINDOW_LEVEL_FLOATING = 2,
+  VELOX_WINDOW_LEVEL_ABOVE_SCREEN_SAVER = 3,
+  VELOX_WINDOW_LEVEL_POPUP_MENU = 4,
+} VeloxWindowLevel;
+
+/// Everything velox_window_build_v2 applies before the window is first shown
+typedef struct {
+  VeloxWindowConfig base;
+  bool visible;
+  bool focusable;
+  VeloxWindowLevel level;
+  /// Optional RGBA icon; ignored when `icon_rgba` is null
+  const uint8_t *icon_rgba;
+  uint32_t icon_rgba_width;
+  uint32_t icon_rgba_height;
+  /// Optional PNG or ICO icon path, used when no RGBA icon is given
+  const char *icon_path;
+  /// macOS only
+  bool fullsize_content_view;
+  /// macOS only
+  VeloxVibrancy vibrancy;
+} VeloxWindowConfigV2;
+
 typedef enum {
   VELOX_RESIZE_DIRECTION_EAST = 0,
   VELOX_RESIZE_DIRECTION_NORTH = 1,
@@ -318,6 +501,99 @@ typedef enum {
   VELOX_RESIZE_DIRECTION_WEST = 7,
 } VeloxResizeDirection;
 
+typedef struct {
+  double x;
+  double y;
+  double width;
+  double height;
+} VeloxRect;
+
+typedef struct {
+  double top;
+  double right;
+  double bottom;
+  double left;
+} VeloxEdgeInsets;
+
+/// `NSWindowCollectionBehavior` flags, one field per bit.
+typedef struct {
+  bool can_join_all_spaces;
+  bool move_to_active_space;
+  bool managed;
+  bool transient;
+  bool stationary;
+  bool participates_in_cycle;
+  bool ignores_cycle;
+} VeloxCollectionBehavior;
+
+typedef struct {
+  bool shift;
+  bool control;
+  bool alt;
+  bool meta;
+} VeloxModifiers;
+
+typedef enum {
+  VELOX_CURSOR_ICON_DEFAULT = 0,
+  VELOX_CURSOR_ICON_CROSSHAIR = 1,
+  VELOX_CURSOR_ICON_HAND = 2,
+  VELOX_CURSOR_ICON_ARROW = 3,
+  VELOX_CURSOR_ICON_MOVE = 4,
+  VELOX_CURSOR_ICON_TEXT = 5,
+  VELOX_CURSOR_ICON_WAIT = 6,
+  VELOX_CURSOR_ICON_HELP = 7,
+  VELOX_CURSOR_ICON_PROGRESS = 8,
+  VELOX_CURSOR_ICON_NOT_ALLOWED = 9,
+  VELOX_CURSOR_ICON_CONTEXT_MENU = 10,
+  VELOX_CURSOR_ICON_CELL = 11,
+  VELOX_CURSOR_ICON_VERTICAL_TEXT = 12,
+  VELOX_CURSOR_ICON_ALIAS = 13,
+  VELOX_CURSOR_ICON_COPY = 14,
+  VELOX_CURSOR_ICON_NO_DROP = 15,
+  VELOX_CURSOR_ICON_GRAB = 16,
+  VELOX_CURSOR_ICON_GRABBING = 17,
+  VELOX_CURSOR_ICON_ALL_SCROLL = 18,
+  VELOX_CURSOR_ICON_ZOOM_IN = 19,
+  VELOX_CURSOR_ICON_ZOOM_OUT = 20,
+  VELOX_CURSOR_ICON_E_RESIZE = 21,
+  VELOX_CURSOR_ICON_N_RESIZE = 22,
+  VELOX_CURSOR_ICON_NE_RESIZE = 23,
+  VELOX_CURSOR_ICON_NW_RESIZE = 24,
+  VELOX_CURSOR_ICON_S_RESIZE = 25,
+  VELOX_CURSOR_ICON_SE_RESIZE = 26,
+  VELOX_CURSOR_ICON_SW_RESIZE = 27,
+  VELOX_CURSOR_ICON_W_RESIZE = 28,
+  VELOX_CURSOR_ICON_EW_RESIZE = 29,
+  VELOX_CURSOR_ICON_NS_RESIZE = 30,
+  VELOX_CURSOR_ICON_NESW_RESIZE = 31,
+  VELOX_CURSOR_ICON_NWSE_RESIZE = 32,
+  VELOX_CURSOR_ICON_COL_RESIZE = 33,
+  VELOX_CURSOR_ICON_ROW_RESIZE = 34,
+} VeloxCursorIcon;
+
+typedef enum {
+  VELOX_SCROLL_INDICATOR_STYLE_DEFAULT = 0,
+  VELOX_SCROLL_INDICATOR_STYLE_DARK = 1,
+  VELOX_SCROLL_INDICATOR_STYLE_LIGHT = 2,
+  VELOX_SCROLL_INDICATOR_STYLE_HIDDEN = 3,
+} VeloxScrollIndicatorStyle;
+
+typedef enum {
+  VELOX_MEDIA_CAPTURE_POLICY_DENY = 0,
+  VELOX_MEDIA_CAPTURE_POLICY_ALLOW = 1,
+  VELOX_MEDIA_CAPTURE_POLICY_PROMPT_USER = 2,
+} VeloxMediaCapturePolicy;
+
+typedef enum {
+  VELOX_CORS_POLICY_DEFAULT = 0,
+  VELOX_CORS_POLICY_DISABLED = 1,
+  VELOX_CORS_POLICY_ALLOW_SPECIFIC_ORIGINS = 2,
+} VeloxCorsPolicy;
+
+typedef void (*VeloxWindowNotificationHandler)(void *user_data);
+
+typedef void (*VeloxBytesCallback)(const uint8_t *data, size_t len, void *user_data);
+
 typedef VeloxEventLoopControlFlow (*VeloxEventLoopCallback)(const char *event_description, void *user_data);
 
 VeloxEventLoopHandle *velox_event_loop_new(void);
@@ -327,6 +603,39 @@ void velox_event_loop_pump(
   VeloxEventLoopCallback callback,
   void *user_data
 );
+/// Returns true if the timeout ended the pump, false if the callback or an exit request did.
+bool velox_event_loop_pump_with_timeout(
+  VeloxEventLoopHandle *event_loop,
+  VeloxEventLoopCallback callback,
+  void *user_data,
+  uint64_t timeout_ms
+);
+VeloxEventLoopPumpHandle *velox_event_loop_pump_async_start(
+  VeloxEventLoopHandle *event_loop,
+  VeloxEventLoopCallback callback,
+  void *user_data
+);
+void velox_event_loop_pump_async_run(VeloxEventLoopPumpHandle *handle);
+bool velox_event_loop_pump_async_cancel(VeloxEventLoopPumpHandle *handle);
+void velox_event_loop_pump_async_free(VeloxEventLoopPumpHandle *handle);
+
+typedef void (*VeloxLifecycleHandler)(void *user_data);
+
+bool velox_event_loop_set_suspend_handler(
+  VeloxEventLoopHandle *event_loop,
+  VeloxLifecycleHandler handler,
+  void *user_data
+);
+bool velox_event_loop_set_resume_handler(
+  VeloxEventLoopHandle *event_loop,
+  VeloxLifecycleHandler handler,
+  void *user_data
+);
+/// App-level accessibility mode reported as an accessibility-enabled/-disabled event; the native
+/// accessibility APIs already answer assistive clients on demand, so no platform switch changes.
+bool velox_event_loop_set_accessibility_enabled(VeloxEventLoopHandle *event_loop, bool enabled);
+/// Skips serializing and delivering events outside the filter's categories.
+bool velox_event_loop_set_filter(VeloxEventLoopHandle *event_loop, VeloxEventFilter filter);
 
 VeloxEventLoopProxyHandle *velox_event_loop_create_proxy(VeloxEventLoopHandle *event_loop);
 bool velox_event_loop_proxy_request_exit(VeloxEventLoopProxyHandle *proxy);
@@ -334,18 +643,71 @@ bool velox_event_loop_proxy_send_user_event(
   VeloxEventLoopProxyHandle *proxy,
   const char *payload
 );
+/// Runs `callback` on the event loop thread; callable from any thread.
+bool velox_event_loop_post_task(
+  VeloxEventLoopProxyHandle *proxy,
+  void (*callback)(void *user_data),
+  void *user_data
+);
+/// Returns a timer id, or 0 on failure. Callbacks run on the event loop thread.
+uint64_t velox_event_loop_add_timer(
+  VeloxEventLoopProxyHandle *proxy,
+  uint64_t interval_ms,
+  bool repeat,
+  void (*callback)(void *user_data),
+  void *user_data
+);
+/// Only cancels timers added through a proxy of the same event loop.
+bool velox_event_loop_cancel_timer(VeloxEventLoopProxyHandle *proxy, uint64_t id);
 void velox_event_loop_proxy_free(VeloxEventLoopProxyHandle *proxy);
+bool velox_event_loop_get_primary_monitor_scale(
+  VeloxEventLoopHandle *event_loop,
+  double *scale_factor
+);
+bool velox_event_loop_get_monitor_scale_at(
+  VeloxEventLoopHandle *event_loop,
+  double x,
+  double y,
+  double *scale_factor
+);
+/// Monitor JSON without a window; null if no monitor is detected.
+const char *velox_event_loop_get_primary_monitor(VeloxEventLoopHandle *event_loop);
+const char *velox_event_loop_get_available_monitors(VeloxEventLoopHandle *event_loop);
 
 VeloxWindowHandle *velox_window_build(VeloxEventLoopHandle *event_loop, const VeloxWindowConfig *config);
+/// Applies every property while hidden, then shows the window if `visible` is set.
+VeloxWindowHandle *velox_window_build_v2(VeloxEventLoopHandle *event_loop, const VeloxWindowConfigV2 *config);
 void velox_window_free(VeloxWindowHandle *window);
 const char *velox_window_identifier(VeloxWindowHandle *window);
 bool velox_window_set_title(VeloxWindowHandle *window, const char *title);
 bool velox_window_set_fullscreen(VeloxWindowHandle *window, bool fullscreen);
 bool velox_window_set_decorations(VeloxWindowHandle *window, bool decorations);
 bool velox_window_set_shadow(VeloxWindowHandle *window, bool shadow);
+/// Title bar styling is macOS only; other platforms return false.
+bool velox_window_set_titlebar_transparent(VeloxWindowHandle *window, bool transparent);
+bool velox_window_set_title_visible(VeloxWindowHandle *window, bool visible);
+bool velox_window_set_fullsize_content_view(VeloxWindowHandle *window, bool enabled);
+bool velox_window_set_traffic_lights_offset(VeloxWindowHandle *window, double x, double y);
+bool velox_window_set_document_edited(VeloxWindowHandle *window, bool edited);
+/// Pass NULL to clear the title bar proxy icon.
+bool velox_window_set_represented_filename(VeloxWindowHandle *window, const char *path);
+bool velox_window_set_movable_by_window_background(VeloxWindowHandle *window, bool movable);
+/// Needs a transparent window and webview to be visible.
+bool velox_window_set_vibrancy(VeloxWindowHandle *window, VeloxVibrancy vibrancy);
+bool velox_window_set_collection_behavior(
+  VeloxWindowHandle *window,
+  VeloxCollectionBehavior behavior
+);
+/// Zero insets outside macOS.
+bool velox_window_get_safe_area_insets(VeloxWindowHandle *window, VeloxEdgeInsets *insets);
+/// Sets `--velox-safe-area-inset-{top,right,bottom,left}` in every webview of the window.
+bool velox_window_apply_safe_area_insets(VeloxWindowHandle *window);
+bool velox_window_set_accessibility_label(VeloxWindowHandle *window, const char *label);
 bool velox_window_set_resizable(VeloxWindowHandle *window, bool resizable);
 bool velox_window_set_always_on_top(VeloxWindowHandle *window, bool on_top);
 bool velox_window_set_always_on_bottom(VeloxWindowHandle *window, bool on_bottom);
+/// Outside macOS only desktop, normal and floating are supported.
+bool velox_window_set_level(VeloxWindowHandle *window, VeloxWindowLevel level);
 bool velox_window_set_visible_on_all_workspaces(
   VeloxWindowHandle *window,
   bool visible_on_all_workspaces
@@ -358,7 +720,19 @@ bool velox_window_set_minimizable(VeloxWindowHandle *window, bool minimizable);
 bool velox_window_set_maximizable(VeloxWindowHandle *window, bool maximizable);
 bool velox_window_set_closable(VeloxWindowHandle *window, bool closable);
 bool velox_window_set_skip_taskbar(VeloxWindowHandle *window, bool skip);
+/// Window icons are unsupported on macOS, where these return false.
+bool velox_window_set_icon(VeloxWindowHandle *window, const VeloxIconBuffer *icon);
+bool velox_window_set_icon_from_rgba(
+  VeloxWindowHandle *window,
+  const uint8_t *rgba,
+  uint32_t width,
+  uint32_t height
+);
+bool velox_window_set_icon_from_path(VeloxWindowHandle *window, const char *path);
 bool velox_window_set_background_color(VeloxWindowHandle *window, const VeloxColor *color);
+/// Opacity is clamped to [0.0, 1.0].
+bool velox_window_set_opacity(VeloxWindowHandle *window, double opacity);
+bool velox_window_get_opacity(VeloxWindowHandle *window, double *out);
 bool velox_window_set_theme(VeloxWindowHandle *window, VeloxWindowTheme theme);
 const char *velox_window_title(VeloxWindowHandle *window);
 bool velox_window_is_fullscreen(VeloxWindowHandle *window);
@@ -373,6 +747,7 @@ bool velox_window_is_minimizable(VeloxWindowHandle *window);
 bool velox_window_is_maximizable(VeloxWindowHandle *window);
 bool velox_window_is_closable(VeloxWindowHandle *window);
 bool velox_window_scale_factor(VeloxWindowHandle *window, double *scale_factor);
+bool velox_window_get_display_refresh_rate(VeloxWindowHandle *window, double *refresh_rate);
 bool velox_window_inner_position(VeloxWindowHandle *window, VeloxPoint *position);
 bool velox_window_outer_position(VeloxWindowHandle *window, VeloxPoint *position);
 bool velox_window_inner_size(VeloxWindowHandle *window, VeloxSize *size);
@@ -380,29 +755,118 @@ bool velox_window_outer_size(VeloxWindowHandle *window, VeloxSize *size);
 const char *velox_window_current_monitor(VeloxWindowHandle *window);
 const char *velox_window_primary_monitor(VeloxWindowHandle *window);
 const char *velox_window_available_monitors(VeloxWindowHandle *window);
+const char *velox_window_get_monitor_video_modes(VeloxWindowHandle *window);
+bool velox_window_set_exclusive_video_mode(
+  VeloxWindowHandle *window,
+  uint32_t width,
+  uint32_t height,
+  uint32_t refresh_rate_mhz
+);
 const char *velox_window_monitor_from_point(VeloxWindowHandle *window, VeloxPoint point);
 bool velox_window_cursor_position(VeloxWindowHandle *window, VeloxPoint *position);
 bool velox_window_request_redraw(VeloxWindowHandle *window);
 bool velox_window_set_size(VeloxWindowHandle *window, double width, double height);
 bool velox_window_set_position(VeloxWindowHandle *window, double x, double y);
+bool velox_window_center(VeloxWindowHandle *window);
+bool velox_window_center_on_monitor(VeloxWindowHandle *window, const char *monitor_name);
 bool velox_window_set_min_size(VeloxWindowHandle *window, double width, double height);
 bool velox_window_set_max_size(VeloxWindowHandle *window, double width, double height);
+/// Passing 0 for both ratios clears the lock.
+bool velox_window_set_aspect_ratio(
+  VeloxWindowHandle *window,
+  double width_ratio,
+  double height_ratio
+);
+bool velox_window_clear_aspect_ratio(VeloxWindowHandle *window);
 bool velox_window_request_user_attention(
   VeloxWindowHandle *window,
   VeloxUserAttentionType attention_type
 );
 bool velox_window_clear_user_attention(VeloxWindowHandle *window);
+/// `progress` ranges from 0.0 to 1.0; on macOS and Linux the indicator is app-wide.
+bool velox_window_set_progress_indicator(
+  VeloxWindowHandle *window,
+  VeloxProgressState state,
+  double progress
+);
+/// Taskbar overlay icons are Windows only; other platforms return false.
+bool velox_window_set_overlay_icon(
+  VeloxWindowHandle *window,
+  const uint8_t *rgba,
+  uint32_t width,
+  uint32_t height,
+  const char *description
+);
+bool velox_window_clear_overlay_icon(VeloxWindowHandle *window);
+/// Returns RGBA rows, top row first, or NULL on failure; free with `velox_screenshot_free`.
+uint8_t *velox_window_take_screenshot(VeloxWindowHandle *window, uint32_t *width, uint32_t *height);
+void velox_screenshot_free(uint8_t *data, uint32_t width, uint32_t height);
 bool velox_window_focus(VeloxWindowHandle *window);
 bool velox_window_set_focusable(VeloxWindowHandle *window, bool focusable);
 bool velox_window_set_cursor_grab(VeloxWindowHandle *window, bool grab);
+VeloxErrorCode velox_window_set_cursor_grab_checked(VeloxWindowHandle *window, bool grab);
 bool velox_window_set_cursor_visible(VeloxWindowHandle *window, bool visible);
+bool velox_window_set_cursor_icon(VeloxWindowHandle *window, VeloxCursorIcon icon);
+/// Custom cursors are Linux only; other platforms return false.
+bool velox_window_set_cursor_icon_from_rgba(
+  VeloxWindowHandle *window,
+  const uint8_t *rgba,
+  uint32_t width,
+  uint32_t height,
+  uint32_t hot_x,
+  uint32_t hot_y
+);
 bool velox_window_set_cursor_position(
   VeloxWindowHandle *window,
   double x,
   double y
 );
+VeloxErrorCode velox_window_set_cursor_position_checked(
+  VeloxWindowHandle *window,
+  double x,
+  double y
+);
+bool velox_window_set_ime_position(VeloxWindowHandle *window, double x, double y);
+bool velox_window_set_ime_allowed(VeloxWindowHandle *window, bool allowed);
+uint64_t velox_window_add_cursor_tracking_rect(
+  VeloxWindowHandle *window,
+  VeloxRect rect,
+  VeloxCursorIcon cursor
+);
+bool velox_window_remove_cursor_tracking_rect(VeloxWindowHandle *window, uint64_t id);
+
+typedef void (*VeloxSizeCallback)(double width, double height, void *user_data);
+
+bool velox_window_set_content_size_observer(
+  VeloxWindowHandle *window,
+  double max_width,
+  double max_height,
+  VeloxSizeCallback observer,
+  void *user_data
+);
 bool velox_window_set_ignore_cursor_events(VeloxWindowHandle *window, bool ignore);
+VeloxErrorCode velox_window_set_ignore_cursor_events_checked(
+  VeloxWindowHandle *window,
+  bool ignore
+);
+#ifdef VELOX_DEBUG_OVERLAY
+/// Outlines each webview in its page and, on macOS and Windows, with a native layer. Only
+/// exported when built with the `debug-overlay` feature, so define VELOX_DEBUG_OVERLAY to match.
+bool velox_window_set_debug_overlay(VeloxWindowHandle *window, bool enabled);
+#endif
 bool velox_window_start_dragging(VeloxWindowHandle *window);
+VeloxErrorCode velox_window_start_dragging_checked(VeloxWindowHandle *window);
+/// Regions are logical pixels relative to the content area; pass zero regions to clear.
+bool velox_window_set_drag_regions(
+  VeloxWindowHandle *window,
+  const VeloxRect *regions,
+  size_t count
+);
+bool velox_window_set_no_drag_regions(
+  VeloxWindowHandle *window,
+  const VeloxRect *regions,
+  size_t count
+);
 bool velox_window_start_resize_dragging(
   VeloxWindowHandle *window,
   VeloxResizeDirection direction
@@ -412,21 +876,225 @@ VeloxDialogSelection velox_dialog_open(const VeloxDialogOpenOptions *options);
 VeloxDialogSelection velox_dialog_save(const VeloxDialogSaveOptions *options);
 void velox_dialog_selection_free(VeloxDialogSelection selection);
 bool velox_dialog_message(const VeloxMessageDialogOptions *options);
+
+typedef void (*VeloxDialogSelectionCallback)(VeloxDialogSelection selection, void *user_data);
+typedef void (*VeloxDialogResultCallback)(bool accepted, void *user_data);
+
+/// Non-blocking dialogs; callbacks run on the event loop thread and own the selection.
+bool velox_dialog_open_async(
+  const VeloxDialogOpenOptions *options,
+  VeloxDialogSelectionCallback callback,
+  void *user_data
+);
+bool velox_dialog_save_async(
+  const VeloxDialogSaveOptions *options,
+  VeloxDialogSelectionCallback callback,
+  void *user_data
+);
+bool velox_dialog_message_async(
+  const VeloxMessageDialogOptions *options,
+  VeloxDialogResultCallback callback,
+  void *user_data
+);
 bool velox_dialog_confirm(const VeloxConfirmDialogOptions *options);
 bool velox_dialog_ask(const VeloxAskDialogOptions *options);
 VeloxPromptDialogResult velox_dialog_prompt(const VeloxPromptDialogOptions *options);
 void velox_dialog_prompt_result_free(VeloxPromptDialogResult result);
+/// Modal RGB colour chooser; the initial alpha is kept. False if cancelled.
+bool velox_dialog_pick_color(const VeloxColorPickerOptions *options, VeloxColor *result);
+/// Needs an app bundle on macOS and an explicit AppUserModelID on Windows
+bool velox_notification_send(const VeloxNotificationOptions *options);
+
+/// Returns null when the clipboard holds no text; free with `velox_clipboard_text_free`.
+char *velox_clipboard_read_text(void);
+void velox_clipboard_text_free(char *text);
+bool velox_clipboard_write_text(const char *text);
+VeloxErrorCode velox_clipboard_write_text_checked(const char *text);
+/// RGBA rows or NULL; free with `velox_clipboard_image_free`
+uint8_t *velox_clipboard_read_image(uint32_t *width, uint32_t *height);
+void velox_clipboard_image_free(uint8_t *data, uint32_t width, uint32_t height);
+bool velox_clipboard_write_image(const uint8_t *rgba, uint32_t width, uint32_t height);
+
+typedef struct {
+  const char *id;
+  /// Accelerator such as `CmdOrCtrl+Shift+Space`.
+  const char *accelerator;
+} VeloxHotkeyConfig;
+
+/// Global shortcut reported as a `hotkey` event; registering an id again replaces it
+bool velox_hotkey_register(VeloxEventLoopHandle *event_loop, const VeloxHotkeyConfig *config);
+VeloxErrorCode velox_hotkey_register_checked(
+  VeloxEventLoopHandle *event_loop,
+  const VeloxHotkeyConfig *config
+);
+bool velox_hotkey_unregister(VeloxEventLoopHandle *event_loop, const char *id);
+
+/// Open an http, https or mailto URL with the system default handler; other schemes are
+/// rejected with VELOX_ERROR_CODE_INVALID_ARGUMENT.
+bool velox_open_url_in_default_browser(const char *url);
+VeloxErrorCode velox_open_url_in_default_browser_checked(const char *url);
+/// Show the folder containing `path` with the item selected in the platform file manager.
+bool velox_reveal_path_in_file_manager(const char *path);
 
 VeloxWebviewHandle *velox_webview_build(VeloxWindowHandle *window, const VeloxWebviewConfig *config);
+/// Stores the new webview, or null on failure, in `webview`.
+VeloxErrorCode velox_webview_build_checked(
+  VeloxWindowHandle *window,
+  const VeloxWebviewConfig *config,
+  VeloxWebviewHandle **webview
+);
 void velox_webview_free(VeloxWebviewHandle *webview);
 const char *velox_webview_identifier(VeloxWebviewHandle *webview);
 bool velox_webview_navigate(VeloxWebviewHandle *webview, const char *url);
+VeloxErrorCode velox_webview_navigate_checked(VeloxWebviewHandle *webview, const char *url);
 bool velox_webview_reload(VeloxWebviewHandle *webview);
+VeloxErrorCode velox_webview_reload_checked(VeloxWebviewHandle *webview);
+/// Only visible when the window is transparent too.
+bool velox_webview_set_transparent(VeloxWebviewHandle *webview, bool transparent);
+/// A null color restores the default opaque white.
+bool velox_webview_set_background_color(VeloxWebviewHandle *webview, const VeloxColor *color);
+bool velox_webview_load_html(VeloxWebviewHandle *webview, const char *html, const char *base_url);
+/// Swaps the handler of a scheme registered at build time; a null handler returns 404s.
+bool velox_webview_replace_protocol_handler(
+  VeloxWebviewHandle *webview,
+  const char *scheme,
+  VeloxCustomProtocolHandler handler,
+  void *user_data
+);
+const char *velox_webview_get_url(VeloxWebviewHandle *webview);
+const char *velox_webview_get_title(VeloxWebviewHandle *webview);
+/// Devtools are only available in debug builds or with the `devtools` feature.
+bool velox_webview_open_devtools(VeloxWebviewHandle *webview);
+bool velox_webview_close_devtools(VeloxWebviewHandle *webview);
+bool velox_webview_is_devtools_open(VeloxWebviewHandle *webview);
+bool velox_webview_navigate_back(VeloxWebviewHandle *webview);
+bool velox_webview_navigate_forward(VeloxWebviewHandle *webview);
+bool velox_webview_can_go_back(VeloxWebviewHandle *webview);
+bool velox_webview_can_go_forward(VeloxWebviewHandle *webview);
 bool velox_webview_evaluate_script(VeloxWebviewHandle *webview, const char *script);
+VeloxErrorCode velox_webview_evaluate_script_checked(VeloxWebviewHandle *webview, const char *script);
+/// Dispatches `payload` (valid JSON) to the page as a CustomEvent named `event_name`.
+bool velox_webview_post_message(VeloxWebviewHandle *webview, const char *event_name, const char *payload);
+/// Lets a click on an inactive window reach the page (macOS only; false elsewhere).
+bool velox_webview_set_accept_first_mouse(VeloxWebviewHandle *webview, bool accept);
+/// Whether the webview was built in private browsing mode.
+bool velox_webview_is_incognito(VeloxWebviewHandle *webview);
+
+typedef void (*VeloxIpcHandler)(const char *message, void *user_data);
+
+bool velox_webview_set_ipc_handler(
+  VeloxWebviewHandle *webview,
+  VeloxIpcHandler handler,
+  void *user_data
+);
+
+typedef bool (*VeloxNavigationHandler)(const char *url, void *user_data);
+
+/// Return true to allow the navigation; a handler that panics denies it.
+bool velox_webview_set_navigation_handler(
+  VeloxWebviewHandle *webview,
+  VeloxNavigationHandler handler,
+  void *user_data
+);
+
+/// Return true once the app has opened the URL itself.
+bool velox_webview_set_new_window_handler(
+  VeloxWebviewHandle *webview,
+  VeloxNavigationHandler handler,
+  void *user_data
+);
+
+typedef const char *(*VeloxDownloadStartedHandler)(
+  const char *url,
+  const char *suggested_filename,
+  void *user_data
+);
+typedef void (*VeloxDownloadCompletedHandler)(const char *path, bool success, void *user_data);
+
+typedef struct {
+  /// Returns the path to save to, or null to cancel; read before the handler's next call
+  VeloxDownloadStartedHandler started_handler;
+  VeloxDownloadCompletedHandler completed_handler;
+  void *user_data;
+} VeloxDownloadConfig;
+
+bool velox_webview_set_download_handler(
+  VeloxWebviewHandle *webview,
+  const VeloxDownloadConfig *config
+);
+
+/// `result` is the JSON return value, or `{"error": "<message>"}` when the script threw
+typedef void (*VeloxScriptResultCallback)(const char *result, void *user_data);
+
+bool velox_webview_evaluate_script_with_callback(
+  VeloxWebviewHandle *webview,
+  const char *script,
+  VeloxScriptResultCallback callback,
+  void *user_data
+);
+bool velox_webview_focus(VeloxWebviewHandle *webview);
+/// Returns keyboard focus to the parent window.
+bool velox_webview_blur(VeloxWebviewHandle *webview);
+bool velox_webview_focus_element_by_id(VeloxWebviewHandle *webview, const char *element_id);
+bool velox_webview_blur_active_element(VeloxWebviewHandle *webview);
+bool velox_webview_fire_mouse_event(
+  VeloxWebviewHandle *webview,
+  const char *event_type,
+  double x,
+  double y,
+  uint8_t button,
+  VeloxModifiers modifiers
+);
 bool velox_webview_set_zoom(VeloxWebviewHandle *webview, double scale_factor);
+/// Scale only the page text; the level is re-applied to each page loaded afterwards.
+bool velox_webview_set_text_zoom(VeloxWebviewHandle *webview, double level);
+bool velox_webview_get_text_zoom(VeloxWebviewHandle *webview, double *level);
+bool velox_webview_export_session_state(
+  VeloxWebviewHandle *webview,
+  VeloxBytesCallback callback,
+  void *user_data
+);
+bool velox_webview_import_session_state(
+  VeloxWebviewHandle *webview,
+  const uint8_t *data,
+  size_t len
+);
+bool velox_webview_set_user_agent(VeloxWebviewHandle *webview, const char *user_agent);
+bool velox_webview_add_initialization_script(VeloxWebviewHandle *webview, const char *script);
+
+typedef void (*VeloxSnapshotCallback)(
+  const uint8_t *data,
+  uint32_t width,
+  uint32_t height,
+  void *user_data
+);
+
+/// `data` is RGBA or null on failure; free it with `velox_snapshot_free`
+bool velox_webview_take_snapshot(
+  VeloxWebviewHandle *webview,
+  VeloxSnapshotCallback callback,
+  void *user_data
+);
+void velox_snapshot_free(uint8_t *data);
+/// Webview in a hidden host window, rendered through snapshots; null on Windows
+VeloxWebviewHandle *velox_webview_build_offscreen(
+  VeloxEventLoopHandle *event_loop,
+  const VeloxWebviewConfig *config,
+  uint32_t width,
+  uint32_t height
+);
+/// Frames arrive while the event loop is pumped; free each with `velox_snapshot_free`.
+bool velox_webview_set_offscreen_paint_callback(
+  VeloxWebviewHandle *webview,
+  VeloxSnapshotCallback callback,
+  void *user_data
+);
 bool velox_webview_show(VeloxWebviewHandle *webview);
 bool velox_webview_hide(VeloxWebviewHandle *webview);
 bool velox_webview_clear_browsing_data(VeloxWebviewHandle *webview);
+bool velox_webview_clear_cookies(VeloxWebviewHandle *webview);
+bool velox_webview_clear_local_storage(VeloxWebviewHandle *webview);
+bool velox_webview_clear_cache(VeloxWebviewHandle *webview);
 bool velox_webview_set_bounds(
   VeloxWebviewHandle *webview,
   double x,
@@ -434,16 +1102,100 @@ bool velox_webview_set_bounds(
   double width,
   double height
 );
+/// Fails for webviews not created as children; null outputs are skipped.
+bool velox_webview_get_bounds(
+  VeloxWebviewHandle *webview,
+  double *x,
+  double *y,
+  double *width,
+  double *height
+);
+bool velox_webview_set_cors_policy(
+  VeloxWebviewHandle *webview,
+  VeloxCorsPolicy policy,
+  const char *const *allowed_origins,
+  size_t count
+);
+bool velox_webview_set_media_capture_policy(VeloxWebviewHandle *webview, VeloxMediaCapturePolicy policy);
+/// The style is re-applied to each page the webview loads afterwards.
+bool velox_webview_set_scroll_indicator_style(
+  VeloxWebviewHandle *webview,
+  VeloxScrollIndicatorStyle style
+);
+/// Adds a CSP <meta> tag to the current page only. It covers later requests, cannot loosen a
+/// header policy and ignores `frame-ancestors`, `report-uri` and `sandbox`.
+bool velox_webview_set_content_security_policy(VeloxWebviewHandle *webview, const char *policy);
+bool velox_webview_inject_css(
+  VeloxWebviewHandle *webview,
+  const char *css,
+  const char *id
+);
 
 VeloxTrayHandle *velox_tray_new(const VeloxTrayConfig *config);
+/// Stores the new tray, or null on failure, in `tray`.
+VeloxErrorCode velox_tray_new_checked(const VeloxTrayConfig *config, VeloxTrayHandle **tray);
 void velox_tray_free(VeloxTrayHandle *handle);
 const char *velox_tray_identifier(VeloxTrayHandle *handle);
+/// Titles are ignored on Windows.
 bool velox_tray_set_title(VeloxTrayHandle *handle, const char *title);
 bool velox_tray_set_tooltip(VeloxTrayHandle *handle, const char *tooltip);
 bool velox_tray_set_visible(VeloxTrayHandle *handle, bool visible);
+bool velox_tray_set_icon_from_rgba(
+  VeloxTrayHandle *handle,
+  const uint8_t *rgba,
+  uint32_t width,
+  uint32_t height
+);
+VeloxErrorCode velox_tray_set_icon_from_rgba_checked(
+  VeloxTrayHandle *handle,
+  const uint8_t *rgba,
+  uint32_t width,
+  uint32_t height
+);
+/// Loads a PNG or ICO file.
+bool velox_tray_set_icon_from_path(VeloxTrayHandle *handle, const char *path);
+VeloxErrorCode velox_tray_set_icon_from_path_checked(VeloxTrayHandle *handle, const char *path);
+/// Template icons adapt to the menu bar appearance (macOS only).
+bool velox_tray_set_icon_as_template(VeloxTrayHandle *handle, bool is_template);
 bool velox_tray_set_show_menu_on_left_click(VeloxTrayHandle *handle, bool enable);
+/// Badge the dock icon or taskbar button, not the tray icon; false on Linux
+bool velox_tray_set_badge_count(VeloxTrayHandle *handle, int32_t count);
 
 #if defined(__APPLE__)
+bool velox_window_set_fullscreen_animation_duration(VeloxWindowHandle *window, uint32_t duration_ms);
+bool velox_window_set_appears_inactive(VeloxWindowHandle *window, bool inactive);
+bool velox_window_set_allows_hdr(VeloxWindowHandle *window, bool enabled);
+
+typedef enum {
+  VELOX_TOUCH_BAR_ITEM_KIND_BUTTON = 0,
+  VELOX_TOUCH_BAR_ITEM_KIND_LABEL = 1,
+  VELOX_TOUCH_BAR_ITEM_KIND_SLIDER = 2,
+  VELOX_TOUCH_BAR_ITEM_KIND_SPACER = 3,
+} VeloxTouchBarItemKind;
+
+typedef struct {
+  const char *identifier;
+  VeloxTouchBarItemKind kind;
+  /// Button title or label text
+  const char *title;
+} VeloxTouchBarItem;
+
+bool velox_window_set_touchbar(
+  VeloxWindowHandle *window,
+  const VeloxTouchBarItem *items,
+  size_t count
+);
+bool velox_window_set_fullscreen_will_enter_handler(
+  VeloxWindowHandle *window,
+  VeloxWindowNotificationHandler handler,
+  void *user_data
+);
+bool velox_window_set_fullscreen_did_enter_handler(
+  VeloxWindowHandle *window,
+  VeloxWindowNotificationHandler handler,
+  void *user_data
+);
+
 typedef enum {
   VELOX_ACTIVATION_POLICY_REGULAR = 0,
   VELOX_ACTIVATION_POLICY_ACCESSORY = 1,
@@ -455,6 +1207,22 @@ bool velox_event_loop_set_dock_visibility(VeloxEventLoopHandle *event_loop, bool
 bool velox_event_loop_hide_application(VeloxEventLoopHandle *event_loop);
 bool velox_event_loop_show_application(VeloxEventLoopHandle *event_loop);
 
+typedef enum {
+  VELOX_PERMISSION_STATE_GRANTED = 0,
+  VELOX_PERMISSION_STATE_DENIED = 1,
+  VELOX_PERMISSION_STATE_NOT_REQUIRED = 2,
+  VELOX_PERMISSION_STATE_UNSUPPORTED = 3,
+} VeloxPermissionState;
+
+typedef void (*VeloxPermissionCallback)(VeloxPermissionState state, void *user_data);
+
+/// macOS reports NOT_REQUIRED; elsewhere the callback gets UNSUPPORTED and this returns false.
+bool velox_request_permission_always_on_top(
+  VeloxEventLoopHandle *event_loop,
+  VeloxPermissionCallback callback,
+  void *user_data
+);
+
 VeloxMenuBarHandle *velox_menu_bar_new(void);
 VeloxMenuBarHandle *velox_menu_bar_new_with_id(const char *identifier);
 void velox_menu_bar_free(VeloxMenuBarHandle *menu);
@@ -463,6 +1231,10 @@ bool velox_menu_bar_append_submenu(
   VeloxMenuBarHandle *menu,
   VeloxSubmenuHandle *submenu
 );
+VeloxErrorCode velox_menu_bar_append_submenu_checked(
+  VeloxMenuBarHandle *menu,
+  VeloxSubmenuHandle *submenu
+);
 bool velox_menu_bar_append(
   VeloxMenuBarHandle *menu,
   VeloxMenuItemKind kind,
@@ -488,6 +1260,14 @@ bool velox_menu_bar_remove_at(
   VeloxMenuBarHandle *menu,
   size_t position
 );
+/// Insert `submenu` at `index`; indices past the end append.
+bool velox_menu_bar_insert_submenu_at_index(
+  VeloxMenuBarHandle *menu,
+  VeloxSubmenuHandle *submenu,
+  size_t index
+);
+/// Remove the top-level submenu whose menu id is `submenu_id`.
+bool velox_menu_bar_remove_submenu(VeloxMenuBarHandle *menu, const char *submenu_id);
 bool velox_menu_bar_popup(
   VeloxMenuBarHandle *menu,
   VeloxWindowHandle *window,
@@ -497,6 +1277,8 @@ bool velox_menu_bar_popup(
   bool is_logical
 );
 bool velox_menu_bar_set_app_menu(VeloxMenuBarHandle *menu);
+/// Show `menu` as a context menu at logical `x`/`y` within `window`
+bool velox_context_menu_show(VeloxWindowHandle *window, VeloxMenuBarHandle *menu, double x, double y);
 
 VeloxSubmenuHandle *velox_submenu_new(const char *title, bool enabled);
 VeloxSubmenuHandle *velox_submenu_new_with_id(
@@ -508,6 +1290,9 @@ void velox_submenu_free(VeloxSubmenuHandle *submenu);
 const char *velox_submenu_identifier(VeloxSubmenuHandle *submenu);
 const char *velox_submenu_text(VeloxSubmenuHandle *submenu);
 bool velox_submenu_set_text(VeloxSubmenuHandle *submenu, const char *title);
+/// Aliases of `velox_submenu_text` / `velox_submenu_set_text`.
+const char *velox_submenu_get_title(VeloxSubmenuHandle *submenu);
+bool velox_submenu_set_title(VeloxSubmenuHandle *submenu, const char *title);
 bool velox_submenu_is_enabled(VeloxSubmenuHandle *submenu);
 bool velox_submenu_set_enabled(VeloxSubmenuHandle *submenu, bool enabled);
 bool velox_submenu_set_native_icon(VeloxSubmenuHandle *submenu, const char *icon);
@@ -515,6 +1300,10 @@ bool velox_submenu_append_item(
   VeloxSubmenuHandle *submenu,
   VeloxMenuItemHandle *item
 );
+VeloxErrorCode velox_submenu_append_item_checked(
+  VeloxSubmenuHandle *submenu,
+  VeloxMenuItemHandle *item
+);
 bool velox_submenu_append(
   VeloxSubmenuHandle *submenu,
   VeloxMenuItemKind kind,
@@ -531,6 +1320,22 @@ bool velox_submenu_insert(
   void *item,
   size_t position
 );
+/// Insert at `index`; indices past the end append.
+bool velox_submenu_insert_item_at_index(
+  VeloxSubmenuHandle *submenu,
+  VeloxMenuItemHandle *item,
+  size_t index
+);
+bool velox_submenu_insert_check_item_at_index(
+  VeloxSubmenuHandle *submenu,
+  VeloxCheckMenuItemHandle *item,
+  size_t index
+);
+bool velox_submenu_insert_separator_at_index(
+  VeloxSubmenuHandle *submenu,
+  VeloxSeparatorHandle *separator,
+  size_t index
+);
 bool velox_submenu_remove(
   VeloxSubmenuHandle *submenu,
   VeloxMenuItemKind kind,
@@ -540,6 +1345,8 @@ bool velox_submenu_remove_at(
   VeloxSubmenuHandle *submenu,
   size_t position
 );
+/// Remove the item whose menu id is `item_id`.
+bool velox_submenu_remove_item(VeloxSubmenuHandle *submenu, const char *item_id);
 bool velox_submenu_popup(
   VeloxSubmenuHandle *submenu,
   VeloxWindowHandle *window,
@@ -594,10 +1401,14 @@ bool velox_predefined_menu_item_set_text(VeloxPredefinedMenuItemHandle *item, co
 VeloxSeparatorHandle *velox_separator_new(void);
 void velox_separator_free(VeloxSeparatorHandle *separator);
 const char *velox_separator_identifier(VeloxSeparatorHandle *separator);
+/// Append a standard item of `kind` with its platform default title.
+bool velox_submenu_append_predefined(VeloxSubmenuHandle *submenu, VeloxPredefinedMenuItemKind kind);
+/// The submenu keeps its own reference, so the separator may be freed afterwards
 bool velox_submenu_append_separator(
   VeloxSubmenuHandle *submenu,
   VeloxSeparatorHandle *separator
 );
+bool velox_submenu_append_new_separator(VeloxSubmenuHandle *submenu);
 
 VeloxCheckMenuItemHandle *velox_check_menu_item_new(
   const char *identifier,
@@ -622,6 +1433,15 @@ bool velox_submenu_append_check_item(
 
 bool velox_tray_set_menu(VeloxTrayHandle *handle, VeloxMenuBarHandle *menu);
 
+typedef VeloxMenuBarHandle *(*VeloxMenuProvider)(void *user_data);
+
+/// The tray takes ownership of every menu handle returned by `provider`; false on Linux.
+bool velox_tray_set_menu_provider(
+  VeloxTrayHandle *handle,
+  VeloxMenuProvider provider,
+  void *user_data
+);
+
 void velox_app_state_force_launched(void);
 #endif
 
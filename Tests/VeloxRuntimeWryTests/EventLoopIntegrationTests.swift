@@ -195,4 +195,198 @@ final class EventLoopIntegrationTests: XCTestCase {
   
     XCTAssertTrue(state.sawUserEvent, "User event payload was never observed")
   }
+
+  func testCustomProtocolRedirectIsFollowed() throws {
+#if canImport(AppKit)
+    if ProcessInfo.processInfo.environment["VELOX_ENABLE_UI_TESTS"] != "1" {
+      throw XCTSkip("UI integration tests disabled")
+    }
+    AppKitHost.prepareIfNeeded()
+#else
+    throw XCTSkip("UI integration tests unavailable on this platform")
+#endif
+
+    final class RedirectState: @unchecked Sendable {
+      var requestedPaths: [String] = []
+      var finalURL: String?
+    }
+
+    let state = RedirectState()
+    var skipReason: String?
+
+    let redirectProtocol = VeloxRuntimeWry.CustomProtocol(scheme: "velox-redirect") { request in
+      let path = URL(string: request.url)?.path ?? ""
+      state.requestedPaths.append(path)
+      if path == "/start" {
+        return VeloxRuntimeWry.CustomProtocol.Response(status: 302, headers: ["Location": "/final"])
+      }
+      return VeloxRuntimeWry.CustomProtocol.Response(
+        mimeType: "text/html",
+        body: Data("<html><body>final</body></html>".utf8)
+      )
+    }
+
+    do {
+      try runOnMain {
+        let loop = try EventLoopHolder.shared()
+
+        guard let window = loop.makeWindow(configuration: .init(width: 320, height: 240, title: "Redirect")) else {
+          skipReason = "Window creation not supported in this environment"
+          return
+        }
+
+        guard
+          let webview = window.makeWebview(
+            configuration: .init(url: "velox-redirect://localhost/start", customProtocols: [redirectProtocol])
+          )
+        else {
+          skipReason = "Webview creation not supported in this environment"
+          return
+        }
+
+        let deadline = Date().addingTimeInterval(10)
+        loop.pump { _ in
+          if state.requestedPaths.contains("/final"), let url = webview.url, url.hasSuffix("/final") {
+            state.finalURL = url
+            return .exit
+          }
+          return Date() > deadline ? .exit : .poll
+        }
+      }
+    } catch EventLoopHolder.Error.unavailable {
+      throw XCTSkip("Velox event loop unavailable on this platform")
+    }
+
+    if let reason = skipReason {
+      EventLoopHolder.reset()
+      throw XCTSkip(reason)
+    }
+
+    XCTAssertEqual(state.requestedPaths.first, "/start")
+    XCTAssertEqual(state.finalURL, "velox-redirect://localhost/final")
+  }
+
+  func testConfigContentSecurityPolicyBlocksDisallowedLoads() throws {
+#if canImport(AppKit)
+    if ProcessInfo.processInfo.environment["VELOX_ENABLE_UI_TESTS"] != "1" {
+      throw XCTSkip("UI integration tests disabled")
+    }
+    AppKitHost.prepareIfNeeded()
+#else
+    throw XCTSkip("UI integration tests unavailable on this platform")
+#endif
+
+    final class PolicyState: @unchecked Sendable {
+      var requestedPaths: [String] = []
+    }
+
+    let state = PolicyState()
+    var skipReason: String?
+
+    // The probe loads an image the policy forbids and reports the violation by loading a script.
+    let probe = """
+      document.addEventListener('securitypolicyviolation', function () {
+        var script = document.createElement('script');
+        script.src = '/violation.js';
+        document.body.appendChild(script);
+      });
+      var image = new Image();
+      image.src = '/blocked.png';
+      """
+    let policyProtocol = VeloxRuntimeWry.CustomProtocol(scheme: "velox-csp") { request in
+      let path = URL(string: request.url)?.path ?? ""
+      state.requestedPaths.append(path)
+      switch path {
+      case "/probe.js":
+        return VeloxRuntimeWry.CustomProtocol.Response(mimeType: "text/javascript", body: Data(probe.utf8))
+      case "/violation.js":
+        return VeloxRuntimeWry.CustomProtocol.Response(mimeType: "text/javascript", body: Data())
+      default:
+        return VeloxRuntimeWry.CustomProtocol.Response(
+          mimeType: "text/html",
+          body: Data("<html><head></head><body><script src=\"/probe.js\"></script></body></html>".utf8)
+        )
+      }
+    }
+
+    do {
+      try runOnMain {
+        let loop = try EventLoopHolder.shared()
+
+        guard let window = loop.makeWindow(configuration: .init(width: 320, height: 240, title: "CSP")) else {
+          skipReason = "Window creation not supported in this environment"
+          return
+        }
+
+        guard
+          window.makeWebview(
+            configuration: .init(
+              url: "velox-csp://localhost/index.html",
+              customProtocols: [policyProtocol],
+              contentSecurityPolicy: "img-src 'none'"
+            )
+          ) != nil
+        else {
+          skipReason = "Webview creation not supported in this environment"
+          return
+        }
+
+        let deadline = Date().addingTimeInterval(10)
+        loop.pump { _ in
+          if state.requestedPaths.contains("/violation.js") {
+            return .exit
+          }
+          return Date() > deadline ? .exit : .poll
+        }
+      }
+    } catch EventLoopHolder.Error.unavailable {
+      throw XCTSkip("Velox event loop unavailable on this platform")
+    }
+
+    if let reason = skipReason {
+      EventLoopHolder.reset()
+      throw XCTSkip(reason)
+    }
+
+    XCTAssertTrue(state.requestedPaths.contains("/violation.js"), "No CSP violation was reported")
+    XCTAssertFalse(state.requestedPaths.contains("/blocked.png"), "The policy did not block the image")
+  }
+
+  func testWindowOpacityRoundTrips() throws {
+#if canImport(AppKit)
+    if ProcessInfo.processInfo.environment["VELOX_ENABLE_UI_TESTS"] != "1" {
+      throw XCTSkip("UI integration tests disabled")
+    }
+    AppKitHost.prepareIfNeeded()
+#else
+    throw XCTSkip("UI integration tests unavailable on this platform")
+#endif
+
+    var opacity: Double?
+    var skipReason: String?
+
+    do {
+      try runOnMain {
+        let loop = try EventLoopHolder.shared()
+
+        guard let window = loop.makeWindow(configuration: .init(width: 320, height: 240, title: "Opacity")) else {
+          skipReason = "Window creation not supported in this environment"
+          return
+        }
+
+        XCTAssertTrue(window.setOpacity(0.5))
+        opacity = window.opacity()
+      }
+    } catch EventLoopHolder.Error.unavailable {
+      throw XCTSkip("Velox event loop unavailable on this platform")
+    }
+
+    if let reason = skipReason {
+      EventLoopHolder.reset()
+      throw XCTSkip(reason)
+    }
+
+    let value = try XCTUnwrap(opacity, "velox_window_get_opacity failed")
+    XCTAssertEqual(value, 0.5, accuracy: 0.01)
+  }
 }
@@ -1,18 +1,23 @@
+use std::collections::HashMap;
 use std::ffi::{CStr, CString};
 use std::os::raw::{c_char, c_void};
 use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::path::PathBuf;
 use std::ptr;
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 use std::rc::Rc;
-use std::sync::OnceLock;
-use std::{cell::RefCell, thread::LocalKey};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use std::{
+    cell::{Cell, RefCell},
+    thread::LocalKey,
+};
 
+use global_hotkey::{hotkey::HotKey, GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
 use raw_window_handle::{HasWindowHandle, RawWindowHandle};
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-use tray_icon::{menu::Menu as TrayMenu, TrayIcon, TrayIconBuilder, TrayIconEvent};
+use tray_icon::menu::Menu as TrayMenu;
+use tray_icon::{TrayIcon, TrayIconBuilder, TrayIconEvent};
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 use muda::{
     about_metadata::AboutMetadata,
     accelerator::Accelerator,
@@ -23,18 +28,19 @@ use muda::{
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Map};
 use tao::{
-    dpi::{LogicalPosition, LogicalSize, PhysicalPosition, Size},
+    dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize, Size},
     event::{
         ElementState, Event, MouseButton, MouseScrollDelta,
         WindowEvent as TaoWindowEvent,
     },
     event_loop::{ControlFlow, EventLoop, EventLoopBuilder, EventLoopProxy},
     keyboard::ModifiersState,
-    monitor::MonitorHandle,
+    monitor::{MonitorHandle, VideoMode},
     platform::run_return::EventLoopExtRunReturn,
     window::{
-        Fullscreen, ResizeDirection as TaoResizeDirection, Theme,
-        UserAttentionType as TaoUserAttentionType, Window, WindowBuilder as TaoWindowBuilder,
+        CursorIcon, Fullscreen, ProgressBarState, ProgressState,
+        ResizeDirection as TaoResizeDirection, Theme, UserAttentionType as TaoUserAttentionType,
+        Window, WindowBuilder as TaoWindowBuilder, WindowId,
     },
 };
 
@@ -54,11 +60,11 @@ use windows::Win32::Foundation::HWND;
 use url::Url;
 use wry::{
     http::{
-        header::{HeaderName, HeaderValue, CONTENT_TYPE},
+        header::{HeaderName, HeaderValue, ACCEPT, CONTENT_TYPE, LOCATION},
         Response as WryHttpResponse, StatusCode,
     },
-    BackgroundThrottlingPolicy, ProxyConfig, ProxyEndpoint, Rect, WebContext, WebView,
-    WebViewBuilder,
+    BackgroundThrottlingPolicy, NewWindowResponse, PageLoadEvent, ProxyConfig, ProxyEndpoint, Rect,
+    WebContext, WebView, WebViewBuilder,
 };
 #[cfg(target_os = "windows")]
 use wry::WebViewBuilderExtWindows;
@@ -70,38 +76,521 @@ static RUNTIME_VERSION: OnceLock<CString> = OnceLock::new();
 static WEBVIEW_VERSION: OnceLock<CString> = OnceLock::new();
 
 thread_local! {
+    /// Reason the most recent call on this thread failed, see `velox_last_error_message`
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::new("").expect("empty string"));
+    static LAST_ERROR_CODE: Cell<VeloxErrorCode> = const { Cell::new(VeloxErrorCode::Success) };
     static TITLE_BUFFER: RefCell<CString> = RefCell::new(CString::new("").expect("empty string"));
+    static URL_BUFFER: RefCell<CString> = RefCell::new(CString::new("").expect("empty string"));
     static MONITOR_BUFFER: RefCell<CString> = RefCell::new(CString::new("").expect("empty string"));
     static MONITOR_LIST_BUFFER: RefCell<CString> = RefCell::new(CString::new("").expect("empty string"));
+    static VIDEO_MODE_LIST_BUFFER: RefCell<CString> = RefCell::new(CString::new("").expect("empty string"));
+    static IME_STATE: RefCell<HashMap<WindowId, ImeState>> = RefCell::new(HashMap::new());
+    static CURSOR_TRACKING: RefCell<HashMap<WindowId, CursorTracking>> = RefCell::new(HashMap::new());
+    static DRAG_REGIONS: RefCell<HashMap<WindowId, DragRegions>> = RefCell::new(HashMap::new());
+    /// Width-to-height ratios enforced after user resizes where the platform has no native lock
+    static ASPECT_RATIOS: RefCell<HashMap<WindowId, AspectRatioLock>> = RefCell::new(HashMap::new());
+    /// Min/max inner sizes last applied to each window, since tao has no getters for them
+    static SIZE_LIMITS: RefCell<HashMap<WindowId, SizeLimits>> = RefCell::new(HashMap::new());
+    /// Webviews built into each window, used to reach them from window-level APIs
+    static WINDOW_WEBVIEWS: RefCell<HashMap<WindowId, Vec<*const VeloxWebviewHandle>>> = RefCell::new(HashMap::new());
+    /// Proxy of the most recently created event loop, used by native callbacks without a handle
+    static USER_EVENT_PROXY: RefCell<Option<EventLoopProxy<VeloxUserEvent>>> = RefCell::new(None);
+    static CONTENT_SIZE_OBSERVERS: RefCell<HashMap<WindowId, ContentSizeObserver>> = RefCell::new(HashMap::new());
+    /// Frame callbacks of offscreen webviews, keyed by handle address
+    static OFFSCREEN_PAINTERS: RefCell<HashMap<usize, OffscreenPainter>> = RefCell::new(HashMap::new());
+    #[cfg(target_os = "macos")]
+    static TOUCH_BAR_TARGET: RefCell<Option<objc2::rc::Retained<VeloxTouchBarTarget>>> = RefCell::new(None);
+    #[cfg(target_os = "macos")]
+    static NOTIFICATION_DELEGATE: RefCell<Option<objc2::rc::Retained<VeloxNotificationDelegate>>> = RefCell::new(None);
+    /// Kept alive so X11 and Wayland selections we own survive past the write call
+    static CLIPBOARD: RefCell<Option<arboard::Clipboard>> = RefCell::new(None);
+    /// Menus installed as the app, tray or context menu, searched for check item state
+    static INSTALLED_MENUS: RefCell<HashMap<String, InstalledMenu>> = RefCell::new(HashMap::new());
+    /// Tray menus rebuilt from a provider when their icon is pressed, keyed by tray id
+    #[cfg(target_os = "windows")]
+    static TRAY_MENU_PROVIDERS: RefCell<HashMap<String, (TrayMenu, Rc<TrayMenuProvider>)>> = RefCell::new(HashMap::new());
+    /// Runtime `acceptsFirstMouse:` answers, keyed by WKWebView address
+    #[cfg(target_os = "macos")]
+    static ACCEPT_FIRST_MOUSE: RefCell<HashMap<usize, bool>> = RefCell::new(HashMap::new());
+    /// NSWindow addresses that report themselves as not key
+    #[cfg(target_os = "macos")]
+    static APPEARS_INACTIVE: RefCell<std::collections::HashSet<usize>> = RefCell::new(std::collections::HashSet::new());
+    /// Native debug overlay outlines, keyed by window
+    #[cfg(all(feature = "debug-overlay", any(target_os = "macos", target_os = "windows")))]
+    static DEBUG_OUTLINES: RefCell<HashMap<WindowId, Vec<DebugOutline>>> = RefCell::new(HashMap::new());
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ImeState {
+    position: Option<(f64, f64)>,
+    allowed: bool,
+}
+
+impl Default for ImeState {
+    fn default() -> Self {
+        Self {
+            position: None,
+            allowed: true,
+        }
+    }
+}
+
+fn update_ime_state(window_id: WindowId, f: impl FnOnce(&mut ImeState)) {
+    IME_STATE.with(|state| f(state.borrow_mut().entry(window_id).or_default()));
 }
 
 #[derive(Debug, Clone)]
 enum VeloxUserEvent {
     Exit,
+    /// Only wakes a loop blocked waiting for events
+    Wake,
     Custom(String),
-    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    Accessibility(bool),
+    ContentSize(WindowId, f64, f64),
+    TitleChanged {
+        webview_id: String,
+        title: String,
+    },
     Menu(String),
-    #[cfg(any(target_os = "macos", target_os = "linux"))]
     Tray(VeloxTrayEvent),
+    Task(PostedTask),
+    Timer(u64),
+    /// A notification sent with an action id was clicked
+    NotificationActivated(String),
+    /// A global hotkey registered under this id was pressed
+    Hotkey(String),
+}
+
+#[derive(Clone)]
+struct PostedTask(Arc<Mutex<Option<Box<dyn FnOnce() + Send>>>>);
+
+impl PostedTask {
+    fn new(task: impl FnOnce() + Send + 'static) -> Self {
+        Self(Arc::new(Mutex::new(Some(Box::new(task)))))
+    }
+
+    fn run(&self) {
+        let task = self.0.lock().ok().and_then(|mut task| task.take());
+        if let Some(task) = task {
+            let _ = catch_unwind(AssertUnwindSafe(task));
+        }
+    }
+}
+
+struct TimerEntry {
+    proxy: EventLoopProxy<VeloxUserEvent>,
+    loop_id: u64,
+    interval: Duration,
+    repeat: bool,
+    /// Next fire time; `None` once a one-shot timer has fired and awaits dispatch
+    next: Option<Instant>,
+    callback: unsafe extern "C" fn(user_data: *mut c_void),
+    user_data: usize,
+}
+
+/// Timers for every event loop, fired from one background thread as `VeloxUserEvent::Timer`
+struct TimerScheduler {
+    timers: Mutex<HashMap<u64, TimerEntry>>,
+    wake: Condvar,
+    next_id: AtomicU64,
+}
+
+/// `None` if the timer thread could not be spawned
+static TIMER_SCHEDULER: OnceLock<Option<Arc<TimerScheduler>>> = OnceLock::new();
+
+static NEXT_EVENT_LOOP_ID: AtomicU64 = AtomicU64::new(1);
+
+fn timer_scheduler() -> Option<&'static Arc<TimerScheduler>> {
+    let scheduler = TIMER_SCHEDULER.get_or_init(|| {
+        let scheduler = Arc::new(TimerScheduler {
+            timers: Mutex::new(HashMap::new()),
+            wake: Condvar::new(),
+            next_id: AtomicU64::new(1),
+        });
+        let worker = Arc::clone(&scheduler);
+        std::thread::Builder::new()
+            .name("velox-timers".into())
+            .spawn(move || run_timer_thread(&worker))
+            .ok()?;
+        Some(scheduler)
+    });
+    if scheduler.is_none() {
+        set_last_error(VeloxErrorCode::Unknown, "failed to spawn timer thread");
+    }
+    scheduler.as_ref()
+}
+
+fn run_timer_thread(scheduler: &TimerScheduler) {
+    let Ok(mut timers) = scheduler.timers.lock() else {
+        return;
+    };
+    loop {
+        let now = Instant::now();
+        let mut closed = Vec::new();
+        for (&id, timer) in timers.iter_mut() {
+            let Some(next) = timer.next.filter(|next| *next <= now) else {
+                continue;
+            };
+            if timer.proxy.send_event(VeloxUserEvent::Timer(id)).is_err() {
+                closed.push(id);
+                continue;
+            }
+            // Skip missed ticks rather than firing a burst after a stall.
+            timer.next = timer
+                .repeat
+                .then(|| (next + timer.interval).max(now + Duration::from_millis(1)));
+        }
+        for id in closed {
+            timers.remove(&id);
+        }
+
+        let wait = timers
+            .values()
+            .filter_map(|timer| timer.next)
+            .min()
+            .map(|next| next.saturating_duration_since(Instant::now()));
+        timers = match wait {
+            Some(wait) => match scheduler.wake.wait_timeout(timers, wait) {
+                Ok((timers, _)) => timers,
+                Err(_) => return,
+            },
+            None => match scheduler.wake.wait(timers) {
+                Ok(timers) => timers,
+                Err(_) => return,
+            },
+        };
+    }
+}
+
+fn dispatch_timer(id: u64) {
+    let Some(scheduler) = TIMER_SCHEDULER.get().and_then(Option::as_ref) else {
+        return;
+    };
+    let (callback, user_data) = {
+        let Ok(mut timers) = scheduler.timers.lock() else {
+            return;
+        };
+        let Some(timer) = timers.get(&id) else {
+            return;
+        };
+        let (callback, user_data, repeat) = (timer.callback, timer.user_data, timer.repeat);
+        if !repeat {
+            timers.remove(&id);
+        }
+        (callback, user_data)
+    };
+    let _ = catch_unwind(AssertUnwindSafe(|| unsafe {
+        callback(user_data as *mut c_void)
+    }));
+}
+
+impl std::fmt::Debug for PostedTask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("PostedTask")
+    }
 }
 
 pub struct VeloxEventLoop {
     event_loop: EventLoop<VeloxUserEvent>,
+    suspend_handler: Cell<Option<LifecycleCallback>>,
+    resume_handler: Cell<Option<LifecycleCallback>>,
+    accessibility_enabled: Cell<bool>,
+    /// Categories passed to the pump callback; `None` passes everything
+    event_filter: Cell<Option<VeloxEventFilter>>,
+    /// Created on the first `velox_hotkey_register`
+    hotkeys: RefCell<Option<HotkeyRegistry>>,
+    /// Ties timers to the loop whose proxies created them
+    id: u64,
+}
+
+struct HotkeyRegistry {
+    manager: GlobalHotKeyManager,
+    hotkeys: HashMap<String, HotKey>,
+}
+
+/// Caller ids of registered hotkeys by native hotkey id, read from the event handler.
+static HOTKEY_IDS: OnceLock<Mutex<HashMap<u32, String>>> = OnceLock::new();
+
+/// Event categories delivered to the pump callback, one field per category
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct VeloxEventFilter {
+    /// Close, resize, move, focus, scale, theme and file drop window events
+    pub window: bool,
+    /// Keyboard, IME, modifier, mouse button, wheel and touch input
+    pub input: bool,
+    /// Cursor moved, entered and left
+    pub cursor: bool,
+    /// Raw device events
+    pub device: bool,
+    /// New events, redraw requests and the events-cleared markers
+    pub redraw: bool,
+    /// User, task, timer, menu and tray events
+    pub user: bool,
+    /// Suspend, resume, reopen, opened URLs and loop destruction
+    pub lifecycle: bool,
+}
+
+impl VeloxEventFilter {
+    fn allows(&self, event: &Event<VeloxUserEvent>) -> bool {
+        match event {
+            Event::NewEvents(_)
+            | Event::MainEventsCleared
+            | Event::RedrawRequested(_)
+            | Event::RedrawEventsCleared => self.redraw,
+            Event::UserEvent(_) => self.user,
+            Event::DeviceEvent { .. } => self.device,
+            Event::WindowEvent { event, .. } => match event {
+                TaoWindowEvent::CursorMoved { .. }
+                | TaoWindowEvent::CursorEntered { .. }
+                | TaoWindowEvent::CursorLeft { .. } => self.cursor,
+                TaoWindowEvent::KeyboardInput { .. }
+                | TaoWindowEvent::ReceivedImeText(_)
+                | TaoWindowEvent::ModifiersChanged(_)
+                | TaoWindowEvent::MouseInput { .. }
+                | TaoWindowEvent::MouseWheel { .. }
+                | TaoWindowEvent::Touch(_)
+                | TaoWindowEvent::TouchpadPressure { .. }
+                | TaoWindowEvent::AxisMotion { .. } => self.input,
+                _ => self.window,
+            },
+            _ => self.lifecycle,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct LifecycleCallback {
+    handler: unsafe extern "C" fn(user_data: *mut c_void),
+    user_data: *mut c_void,
+}
+
+impl LifecycleCallback {
+    fn new(handler: VeloxLifecycleHandler, user_data: *mut c_void) -> Option<Self> {
+        handler.map(|handler| Self { handler, user_data })
+    }
+
+    fn invoke(self) {
+        let _ = catch_unwind(AssertUnwindSafe(|| unsafe {
+            (self.handler)(self.user_data)
+        }));
+    }
+}
+
+#[derive(Clone, Copy)]
+struct IpcCallback {
+    handler: unsafe extern "C" fn(message: *const c_char, user_data: *mut c_void),
+    user_data: *mut c_void,
+}
+
+impl IpcCallback {
+    fn invoke(self, message: &str) {
+        let message =
+            CString::new(message).unwrap_or_else(|_| CString::new("").expect("empty string"));
+        let _ = catch_unwind(AssertUnwindSafe(|| unsafe {
+            (self.handler)(message.as_ptr(), self.user_data)
+        }));
+    }
+}
+
+#[derive(Clone, Copy)]
+struct NavigationCallback {
+    handler: unsafe extern "C" fn(url: *const c_char, user_data: *mut c_void) -> bool,
+    user_data: *mut c_void,
+}
+
+impl NavigationCallback {
+    /// Asks the handler about `url`; a panicking handler denies the navigation.
+    fn allows(self, url: &str) -> bool {
+        let Ok(url) = CString::new(url) else {
+            return false;
+        };
+        guard_panic_bool(|| unsafe { (self.handler)(url.as_ptr(), self.user_data) })
+    }
+}
+
+/// `user_data` is kept as an address because wry requires the handler to be `Send`
+#[derive(Clone, Copy)]
+struct NewWindowCallback {
+    handler: unsafe extern "C" fn(url: *const c_char, user_data: *mut c_void) -> bool,
+    user_data: usize,
+}
+
+pub type VeloxDownloadStartedHandler = Option<
+    unsafe extern "C" fn(
+        url: *const c_char,
+        suggested_filename: *const c_char,
+        user_data: *mut c_void,
+    ) -> *const c_char,
+>;
+
+pub type VeloxDownloadCompletedHandler =
+    Option<unsafe extern "C" fn(path: *const c_char, success: bool, user_data: *mut c_void)>;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct VeloxDownloadConfig {
+    /// Returns the path to save to, or null to cancel; read before the handler's next call
+    pub started_handler: VeloxDownloadStartedHandler,
+    pub completed_handler: VeloxDownloadCompletedHandler,
+    pub user_data: *mut c_void,
+}
+
+/// wry only reports title changes, so the last reported title is cached for the getter.
+#[derive(Default)]
+struct DocumentTitle {
+    title: RefCell<String>,
+    /// Filled in once the webview is built and its id is known
+    webview_id: RefCell<String>,
+}
+
+struct PageStyles {
+    text_zoom: Cell<f64>,
+    scroll_indicator_css: Cell<Option<&'static str>>,
+}
+
+impl Default for PageStyles {
+    fn default() -> Self {
+        Self {
+            text_zoom: Cell::new(1.0),
+            scroll_indicator_css: Cell::new(None),
+        }
+    }
+}
+
+impl PageStyles {
+    fn script(&self) -> Option<String> {
+        let level = self.text_zoom.get();
+        let mut script = (level != 1.0).then(|| text_zoom_script(level));
+        if let Some(css) = self.scroll_indicator_css.get() {
+            let css = style_element_script(SCROLL_INDICATOR_STYLE_ID, Some(css));
+            script = Some(script.unwrap_or_default() + &css);
+        }
+        script
+    }
 }
 
 pub struct VeloxEventLoopProxyHandle {
     proxy: EventLoopProxy<VeloxUserEvent>,
+    loop_id: u64,
+}
+
+pub struct VeloxEventLoopPumpHandle {
+    event_loop: *mut VeloxEventLoop,
+    callback: VeloxEventLoopCallback,
+    user_data: *mut c_void,
+    cancelled: AtomicBool,
+    proxy: EventLoopProxy<VeloxUserEvent>,
 }
 
 pub struct VeloxWindowHandle {
     window: Window,
     identifier: CString,
+    /// AppKit notification observers keyed by notification name
+    #[cfg(target_os = "macos")]
+    observers: RefCell<HashMap<&'static str, NotificationObserver>>,
+}
+
+#[cfg(target_os = "macos")]
+struct NotificationObserver {
+    token: objc2::rc::Retained<objc2::runtime::AnyObject>,
+}
+
+#[cfg(target_os = "macos")]
+impl Drop for NotificationObserver {
+    fn drop(&mut self) {
+        use objc2::{class, msg_send, runtime::AnyObject};
+
+        unsafe {
+            let center: *mut AnyObject = msg_send![class!(NSNotificationCenter), defaultCenter];
+            let _: () = msg_send![center, removeObserver: &*self.token];
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn observe_window_notification(
+    handle: &VeloxWindowHandle,
+    name: &'static str,
+    handler: VeloxWindowNotificationHandler,
+    user_data: *mut c_void,
+) -> bool {
+    use block2::RcBlock;
+    use objc2::{class, msg_send, rc::Retained, runtime::AnyObject};
+    use objc2_foundation::NSString;
+
+    let mut observers = handle.observers.borrow_mut();
+    observers.remove(name);
+    let Some(handler) = handler else {
+        return true;
+    };
+
+    let ns_window = handle.window.ns_window() as *mut AnyObject;
+    if ns_window.is_null() {
+        return false;
+    }
+    let block = RcBlock::new(move |_notification: *mut AnyObject| {
+        let _ = catch_unwind(AssertUnwindSafe(|| unsafe { handler(user_data) }));
+    });
+    let ns_name = NSString::from_str(name);
+    let token: Option<Retained<AnyObject>> = unsafe {
+        let center: *mut AnyObject = msg_send![class!(NSNotificationCenter), defaultCenter];
+        msg_send![
+            center,
+            addObserverForName: &*ns_name,
+            object: ns_window,
+            queue: ptr::null_mut::<AnyObject>(),
+            usingBlock: &*block
+        ]
+    };
+    let Some(token) = token else {
+        return false;
+    };
+    observers.insert(name, NotificationObserver { token });
+    true
 }
 
 pub struct VeloxWebviewHandle {
     webview: WebView,
     #[allow(dead_code)]
     context: Option<WebContext>,
+    /// Shared with the builder's page-load handler
+    page_styles: Rc<PageStyles>,
+    /// Last opaque fill, restored when transparency is turned off
+    #[cfg_attr(target_os = "macos", allow(dead_code))]
+    background_color: Cell<(u8, u8, u8, u8)>,
+    /// Receiver of `window.ipc.postMessage` payloads, shared with the builder's IPC handler
+    ipc_handler: Rc<Cell<Option<IpcCallback>>>,
+    /// Consulted before every navigation, shared with the builder's navigation handler
+    navigation_handler: Rc<Cell<Option<NavigationCallback>>>,
+    /// Download callbacks, shared with the builder's download handlers
+    download_handler: Rc<Cell<Option<VeloxDownloadConfig>>>,
+    /// Receiver of `target="_blank"` and `window.open()` requests
+    new_window_handler: Arc<Mutex<Option<NewWindowCallback>>>,
+    /// Latest document title, kept current by the builder's title handler
+    document_title: Rc<DocumentTitle>,
+    /// Reference-counted protocol `user_data` released when the webview is freed
+    shared_user_data: Vec<*mut c_void>,
+    /// Handler slots read by each scheme's protocol closure, keyed by scheme
+    protocol_handlers: HashMap<String, Arc<Mutex<ProtocolHandler>>>,
+    /// Policy consulted by the `permission-request` handler, connected on first use
+    #[cfg(any(target_os = "linux", target_os = "windows"))]
+    media_capture_policy: RefCell<Option<Rc<Cell<VeloxMediaCapturePolicy>>>>,
+    /// UI delegate answering media capture and forwarding the rest to wry's, installed on first use
+    #[cfg(target_os = "macos")]
+    media_capture_delegate: RefCell<Option<objc2::rc::Retained<VeloxMediaCaptureDelegate>>>,
+    /// Whether the webview was built as a child with explicit bounds
+    is_child: bool,
+    /// Whether the webview was built with a non-persistent data store
+    incognito: bool,
+    /// Parent window, whose DPI converts WebView2's physical bounds to logical ones
+    #[cfg(target_os = "windows")]
+    parent_hwnd: isize,
+    /// User-Agent WebView2 used before the first override, restored by a null override
+    #[cfg(target_os = "windows")]
+    default_user_agent: RefCell<Option<String>>,
+    /// Hidden host window of an offscreen webview; declared last so it outlives the webview
+    offscreen_host: Option<Box<VeloxWindowHandle>>,
 }
 
 #[repr(C)]
@@ -135,6 +624,49 @@ pub enum VeloxWindowTheme {
     Dark = 2,
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VeloxCorsPolicy {
+    Default = 0,
+    Disabled = 1,
+    AllowSpecificOrigins = 2,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VeloxScrollIndicatorStyle {
+    Default = 0,
+    Dark = 1,
+    Light = 2,
+    Hidden = 3,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VeloxMediaCapturePolicy {
+    Deny = 0,
+    Allow = 1,
+    PromptUser = 2,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VeloxTouchBarItemKind {
+    Button = 0,
+    Label = 1,
+    Slider = 2,
+    Spacer = 3,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct VeloxTouchBarItem {
+    pub identifier: *const c_char,
+    pub kind: VeloxTouchBarItemKind,
+    /// Button title or label text
+    pub title: *const c_char,
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum VeloxActivationPolicy {
@@ -143,7 +675,6 @@ pub enum VeloxActivationPolicy {
     Prohibited = 2,
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 pub struct VeloxMenuBarHandle {
     menu: Menu,
     submenus: Vec<Rc<RefCell<Submenu>>>,
@@ -151,91 +682,81 @@ pub struct VeloxMenuBarHandle {
     identifier: CString,
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 pub struct VeloxSubmenuHandle {
     submenu: Rc<RefCell<Submenu>>,
     identifier: CString,
     items: Vec<MenuItemKind>,
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
+enum InstalledMenu {
+    Menu(Menu),
+    Submenu(Submenu),
+}
+
+fn install_menu(slot: impl Into<String>, menu: Option<InstalledMenu>) {
+    INSTALLED_MENUS.with(|menus| {
+        let mut menus = menus.borrow_mut();
+        match menu {
+            Some(menu) => menus.insert(slot.into(), menu),
+            None => menus.remove(&slot.into()),
+        }
+    });
+}
+
+fn find_check_menu_item(items: Vec<MenuItemKind>, id: &str) -> Option<CheckMenuItem> {
+    items.into_iter().find_map(|item| match item {
+        MenuItemKind::Check(item) if item.id().as_ref() == id => Some(item),
+        MenuItemKind::Submenu(submenu) => find_check_menu_item(submenu.items(), id),
+        _ => None,
+    })
+}
+
+fn installed_check_menu_item(id: &str) -> Option<CheckMenuItem> {
+    INSTALLED_MENUS.with(|menus| {
+        menus.borrow().values().find_map(|menu| {
+            let items = match menu {
+                InstalledMenu::Menu(menu) => menu.items(),
+                InstalledMenu::Submenu(submenu) => submenu.items(),
+            };
+            find_check_menu_item(items, id)
+        })
+    })
+}
+
 pub struct VeloxMenuItemHandle {
     item: MenuItem,
     identifier: CString,
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 pub struct VeloxCheckMenuItemHandle {
     item: CheckMenuItem,
     identifier: CString,
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 pub struct VeloxIconMenuItemHandle {
     item: IconMenuItem,
     identifier: CString,
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 pub struct VeloxPredefinedMenuItemHandle {
     item: PredefinedMenuItem,
     identifier: CString,
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 pub struct VeloxSeparatorHandle {
     item: PredefinedMenuItem,
     identifier: CString,
 }
 
-#[cfg(target_os = "windows")]
-pub struct VeloxMenuBarHandle {
-    _private: (),
-}
-
-#[cfg(target_os = "windows")]
-pub struct VeloxSubmenuHandle {
-    _private: (),
-}
-
-#[cfg(target_os = "windows")]
-pub struct VeloxMenuItemHandle {
-    _private: (),
-}
-
-#[cfg(target_os = "windows")]
-pub struct VeloxCheckMenuItemHandle {
-    _private: (),
-}
-
-#[cfg(target_os = "windows")]
-pub struct VeloxIconMenuItemHandle {
-    _private: (),
-}
-
-#[cfg(target_os = "windows")]
-pub struct VeloxPredefinedMenuItemHandle {
-    _private: (),
-}
-
-#[cfg(target_os = "windows")]
-pub struct VeloxSeparatorHandle {
-    _private: (),
-}
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 pub struct VeloxTrayHandle {
     tray: TrayIcon,
     menu: Option<TrayMenu>,
     identifier: CString,
+    #[cfg(target_os = "macos")]
+    menu_delegate: Option<objc2::rc::Retained<VeloxTrayMenuDelegate>>,
 }
 
-#[cfg(target_os = "windows")]
-pub struct VeloxTrayHandle {
-    _private: (),
-}
-
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 #[derive(Debug, Clone)]
 struct VeloxTrayEvent {
     identifier: String,
@@ -246,7 +767,6 @@ struct VeloxTrayEvent {
     button_state: Option<String>,
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 #[derive(Debug, Clone, Copy)]
 enum VeloxTrayEventKind {
     Click,
@@ -256,7 +776,6 @@ enum VeloxTrayEventKind {
     Leave,
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 #[derive(Debug, Clone, Copy)]
 struct VeloxTrayRect {
     origin_x: f64,
@@ -265,7 +784,6 @@ struct VeloxTrayRect {
     height: f64,
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 impl From<tray_icon::Rect> for VeloxTrayRect {
     fn from(rect: tray_icon::Rect) -> Self {
         Self {
@@ -277,7 +795,6 @@ impl From<tray_icon::Rect> for VeloxTrayRect {
     }
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 impl From<tray_icon::TrayIconEvent> for VeloxTrayEvent {
     fn from(event: tray_icon::TrayIconEvent) -> Self {
         match event {
@@ -374,6 +891,23 @@ pub struct VeloxWindowConfig {
     pub titlebar_transparent: i8,
     pub titlebar_hidden: i8,
     pub titlebar_buttons_hidden: i8,
+    /// Initial outer position in logical pixels, used when `has_position` is set
+    pub x: f64,
+    pub y: f64,
+    pub has_position: bool,
+    /// Size limits in logical pixels; zero leaves a limit unset
+    pub min_width: f64,
+    pub min_height: f64,
+    pub max_width: f64,
+    pub max_height: f64,
+    /// Tri-state flags; -1 leaves the platform default
+    pub decorations: i8,
+    pub resizable: i8,
+    pub transparent: bool,
+    pub always_on_top: bool,
+    /// Windows and Linux only
+    pub skip_taskbar: bool,
+    pub theme: VeloxWindowTheme,
 }
 
 impl Default for VeloxWindowConfig {
@@ -387,19 +921,69 @@ impl Default for VeloxWindowConfig {
             titlebar_transparent: -1,
             titlebar_hidden: -1,
             titlebar_buttons_hidden: -1,
+            x: 0.0,
+            y: 0.0,
+            has_position: false,
+            min_width: 0.0,
+            min_height: 0.0,
+            max_width: 0.0,
+            max_height: 0.0,
+            decorations: -1,
+            resizable: -1,
+            transparent: false,
+            always_on_top: false,
+            skip_taskbar: false,
+            theme: VeloxWindowTheme::Unspecified,
         }
     }
 }
 
+/// Everything `velox_window_build_v2` applies before the window is first shown
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
-pub struct VeloxWebviewConfig {
-    pub url: *const c_char,
-    pub custom_protocols: VeloxCustomProtocolList,
-    pub devtools: bool,
-    /// If true, create as a child webview with bounds
-    pub is_child: bool,
-    /// X position for child webview (logical pixels)
+pub struct VeloxWindowConfigV2 {
+    pub base: VeloxWindowConfig,
+    pub visible: bool,
+    pub focusable: bool,
+    pub level: VeloxWindowLevel,
+    /// Optional RGBA icon; ignored when `icon_rgba` is null
+    pub icon_rgba: *const u8,
+    pub icon_rgba_width: u32,
+    pub icon_rgba_height: u32,
+    /// Optional PNG or ICO icon path, used when no RGBA icon is given
+    pub icon_path: *const c_char,
+    /// macOS only
+    pub fullsize_content_view: bool,
+    /// macOS only
+    pub vibrancy: VeloxVibrancy,
+}
+
+impl Default for VeloxWindowConfigV2 {
+    fn default() -> Self {
+        Self {
+            base: VeloxWindowConfig::default(),
+            visible: true,
+            focusable: true,
+            level: VeloxWindowLevel::Normal,
+            icon_rgba: ptr::null(),
+            icon_rgba_width: 0,
+            icon_rgba_height: 0,
+            icon_path: ptr::null(),
+            fullsize_content_view: false,
+            vibrancy: VeloxVibrancy::Disabled,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct VeloxWebviewConfig {
+    pub url: *const c_char,
+    pub custom_protocols: VeloxCustomProtocolList,
+    pub devtools: bool,
+    /// If true, create as a child webview with bounds
+    pub is_child: bool,
+    /// X position for child webview (logical pixels)
     pub x: f64,
     /// Y position for child webview (logical pixels)
     pub y: f64,
@@ -409,7 +993,7 @@ pub struct VeloxWebviewConfig {
     pub height: f64,
     /// Whether clicking an inactive window also clicks through to the webview (macOS)
     pub accept_first_mouse: i8,
-    /// Enable private browsing mode for the webview
+    /// Enable private browsing mode for the webview (-1 means unset)
     pub incognito: i8,
     /// Disable JavaScript execution in the webview
     pub javascript_disabled: i8,
@@ -419,8 +1003,24 @@ pub struct VeloxWebviewConfig {
     pub scroll_bar_style: i32,
     /// Proxy URL (http://host:port or socks5://host:port)
     pub proxy_url: *const c_char,
-    /// Custom data directory for the webview context
+    /// Custom data directory for the webview context; must exist and be writable
     pub data_directory: *const c_char,
+    /// Called for uncaught JavaScript errors and unhandled promise rejections
+    pub script_error_handler: VeloxScriptErrorHandler,
+    /// Opaque pointer passed back to `script_error_handler`
+    pub script_error_user_data: *mut c_void,
+    /// JavaScript run at document start of every page, before the first navigation
+    pub initialization_script: *const c_char,
+    /// Custom User-Agent string (null keeps the platform default)
+    pub user_agent: *const c_char,
+    /// Draw no background so a transparent window shows through (macOS needs `macos-private-api`)
+    pub transparent: bool,
+    /// Fill shown before content paints and behind transparent page areas
+    pub background_color: VeloxColor,
+    /// Whether `background_color` is applied
+    pub has_background_color: bool,
+    /// Content-Security-Policy injected as a `<meta>` tag into every page (null for none)
+    pub csp: *const c_char,
 }
 
 impl Default for VeloxWebviewConfig {
@@ -444,6 +1044,19 @@ impl Default for VeloxWebviewConfig {
             scroll_bar_style: -1,
             proxy_url: ptr::null(),
             data_directory: ptr::null(),
+            script_error_handler: None,
+            script_error_user_data: ptr::null_mut(),
+            initialization_script: ptr::null(),
+            user_agent: ptr::null(),
+            transparent: false,
+            background_color: VeloxColor {
+                red: 0,
+                green: 0,
+                blue: 0,
+                alpha: 0,
+            },
+            has_background_color: false,
+            csp: ptr::null(),
         }
     }
 }
@@ -456,6 +1069,11 @@ pub struct VeloxTrayConfig {
     pub tooltip: *const c_char,
     pub visible: bool,
     pub show_menu_on_left_click: bool,
+    /// Initial icon pixels; takes precedence over `icon_path` when both are set
+    pub icon_rgba: *const u8,
+    pub icon_rgba_width: u32,
+    pub icon_rgba_height: u32,
+    pub icon_path: *const c_char,
 }
 
 impl Default for VeloxTrayConfig {
@@ -466,10 +1084,22 @@ impl Default for VeloxTrayConfig {
             tooltip: ptr::null(),
             visible: true,
             show_menu_on_left_click: true,
+            icon_rgba: ptr::null(),
+            icon_rgba_width: 0,
+            icon_rgba_height: 0,
+            icon_path: ptr::null(),
         }
     }
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct VeloxHotkeyConfig {
+    pub id: *const c_char,
+    /// Accelerator such as `CmdOrCtrl+Shift+Space`
+    pub accelerator: *const c_char,
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default)]
 pub struct VeloxDialogFilter {
@@ -568,6 +1198,24 @@ pub struct VeloxAskDialogOptions {
     pub no_label: *const c_char,
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct VeloxNotificationOptions {
+    pub title: *const c_char,
+    pub body: *const c_char,
+    pub subtitle: *const c_char,
+    pub icon_path: *const c_char,
+    /// Reported by the `notification-activated` event when the notification is clicked
+    pub action_id: *const c_char,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct VeloxColorPickerOptions {
+    pub title: *const c_char,
+    pub initial_color: VeloxColor,
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default)]
 pub struct VeloxPromptDialogOptions {
@@ -577,6 +1225,8 @@ pub struct VeloxPromptDialogOptions {
     pub default_value: *const c_char,
     pub ok_label: *const c_char,
     pub cancel_label: *const c_char,
+    /// Show a multi-line text area instead of a single-line field
+    pub multiline: bool,
 }
 
 #[repr(C)]
@@ -600,6 +1250,64 @@ pub struct VeloxCustomProtocolDefinition {
     pub scheme: *const c_char,
     pub handler: VeloxCustomProtocolHandler,
     pub user_data: *mut c_void,
+    /// Reference-count `user_data` so it can be shared by several webviews
+    pub copy_user_data: bool,
+    /// Called once the last reference to a reference-counted `user_data` is released
+    pub release_user_data: VeloxCustomProtocolUserDataRelease,
+}
+
+#[derive(Clone, Copy)]
+struct ProtocolHandler {
+    handler: VeloxCustomProtocolHandler,
+    /// Stored as an address so the slot can be shared with wry's protocol closure
+    user_data: usize,
+}
+
+pub type VeloxCustomProtocolUserDataRelease = Option<unsafe extern "C" fn(user_data: *mut c_void)>;
+
+struct SharedProtocolUserData {
+    count: usize,
+    release: VeloxCustomProtocolUserDataRelease,
+}
+
+static SHARED_PROTOCOL_USER_DATA: OnceLock<Mutex<HashMap<usize, SharedProtocolUserData>>> =
+    OnceLock::new();
+
+fn shared_protocol_user_data() -> &'static Mutex<HashMap<usize, SharedProtocolUserData>> {
+    SHARED_PROTOCOL_USER_DATA.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn retain_protocol_user_data(user_data: *mut c_void, release: VeloxCustomProtocolUserDataRelease) {
+    let mut shared = shared_protocol_user_data()
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    shared
+        .entry(user_data as usize)
+        .or_insert(SharedProtocolUserData { count: 0, release })
+        .count += 1;
+}
+
+fn release_protocol_user_data(user_data: *mut c_void) -> bool {
+    let release = {
+        let mut shared = shared_protocol_user_data()
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let Some(entry) = shared.get_mut(&(user_data as usize)) else {
+            return false;
+        };
+        entry.count -= 1;
+        if entry.count > 0 {
+            return true;
+        }
+        shared
+            .remove(&(user_data as usize))
+            .and_then(|entry| entry.release)
+    };
+    // Invoke the release callback outside the lock so it may re-enter the FFI.
+    if let Some(release) = release {
+        let _ = catch_unwind(AssertUnwindSafe(|| unsafe { release(user_data) }));
+    }
+    true
 }
 
 #[repr(C)]
@@ -630,6 +1338,16 @@ pub struct VeloxCustomProtocolBuffer {
     pub len: usize,
 }
 
+/// Straight-alpha RGBA pixels, `width * height * 4` bytes, top row first
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct VeloxIconBuffer {
+    pub rgba: *const u8,
+    pub len: usize,
+    pub width: u32,
+    pub height: u32,
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default)]
 pub struct VeloxCustomProtocolRequest {
@@ -642,6 +1360,7 @@ pub struct VeloxCustomProtocolRequest {
 
 pub type VeloxCustomProtocolResponseFree = Option<unsafe extern "C" fn(user_data: *mut c_void)>;
 
+/// A 3xx `status` with a `Location` header redirects document loads
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default)]
 pub struct VeloxCustomProtocolResponse {
@@ -660,6 +1379,45 @@ pub enum VeloxUserAttentionType {
     Critical = 1,
 }
 
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VeloxProgressState {
+    None = 0,
+    Normal = 1,
+    Indeterminate = 2,
+    Paused = 3,
+    Error = 4,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VeloxVibrancy {
+    Disabled = 0,
+    Titlebar = 1,
+    Menu = 2,
+    Popover = 3,
+    Sidebar = 4,
+    HeaderView = 5,
+    Sheet = 6,
+    WindowBackground = 7,
+    HudWindow = 8,
+    FullScreenUI = 9,
+    ToolTip = 10,
+    ContentBackground = 11,
+    UnderWindowBackground = 12,
+    UnderPageBackground = 13,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VeloxWindowLevel {
+    Desktop = 0,
+    Normal = 1,
+    Floating = 2,
+    AboveScreenSaver = 3,
+    PopupMenu = 4,
+}
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum VeloxResizeDirection {
@@ -673,6 +1431,106 @@ pub enum VeloxResizeDirection {
     West = 7,
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct VeloxRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl VeloxRect {
+    fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.width && y < self.y + self.height
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct VeloxEdgeInsets {
+    pub top: f64,
+    pub right: f64,
+    pub bottom: f64,
+    pub left: f64,
+}
+
+/// `NSWindowCollectionBehavior` flags, one field per bit
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct VeloxCollectionBehavior {
+    pub can_join_all_spaces: bool,
+    pub move_to_active_space: bool,
+    pub managed: bool,
+    pub transient: bool,
+    pub stationary: bool,
+    pub participates_in_cycle: bool,
+    pub ignores_cycle: bool,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct VeloxModifiers {
+    pub shift: bool,
+    pub control: bool,
+    pub alt: bool,
+    pub meta: bool,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VeloxCursorIcon {
+    Default = 0,
+    Crosshair = 1,
+    Hand = 2,
+    Arrow = 3,
+    Move = 4,
+    Text = 5,
+    Wait = 6,
+    Help = 7,
+    Progress = 8,
+    NotAllowed = 9,
+    ContextMenu = 10,
+    Cell = 11,
+    VerticalText = 12,
+    Alias = 13,
+    Copy = 14,
+    NoDrop = 15,
+    Grab = 16,
+    Grabbing = 17,
+    AllScroll = 18,
+    ZoomIn = 19,
+    ZoomOut = 20,
+    EResize = 21,
+    NResize = 22,
+    NeResize = 23,
+    NwResize = 24,
+    SResize = 25,
+    SeResize = 26,
+    SwResize = 27,
+    WResize = 28,
+    EwResize = 29,
+    NsResize = 30,
+    NeswResize = 31,
+    NwseResize = 32,
+    ColResize = 33,
+    RowResize = 34,
+}
+
+pub type VeloxWindowNotificationHandler = Option<unsafe extern "C" fn(user_data: *mut c_void)>;
+
+pub type VeloxBytesCallback =
+    Option<unsafe extern "C" fn(data: *const u8, len: usize, user_data: *mut c_void)>;
+
+pub type VeloxScriptErrorHandler = Option<
+    unsafe extern "C" fn(
+        message: *const c_char,
+        source: *const c_char,
+        line: u32,
+        user_data: *mut c_void,
+    ),
+>;
+
 pub type VeloxEventLoopCallback = Option<
     extern "C" fn(
         event_description: *const c_char,
@@ -694,6 +1552,38 @@ fn opt_cstring(ptr: *const c_char) -> Option<String> {
     }
 }
 
+/// A null or empty path selects the default; otherwise the directory must exist and be writable.
+fn data_directory_from_ffi(path: *const c_char) -> Result<Option<PathBuf>, String> {
+    if path.is_null() {
+        return Ok(None);
+    }
+    let path = unsafe { CStr::from_ptr(path) }
+        .to_str()
+        .map_err(|_| "data directory is not valid UTF-8".to_string())?;
+    if path.is_empty() {
+        return Ok(None);
+    }
+    let path = PathBuf::from(path);
+    if !path.is_dir() {
+        return Err(format!("data directory {} does not exist", path.display()));
+    }
+    let probe = path.join(format!(".velox-write-check-{}", std::process::id()));
+    std::fs::File::create(&probe)
+        .map_err(|error| format!("data directory {} is not writable: {error}", path.display()))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(Some(path))
+}
+
+fn cstring_array(values: *const *const c_char, count: usize) -> Vec<String> {
+    if values.is_null() || count == 0 {
+        return Vec::new();
+    }
+    unsafe { std::slice::from_raw_parts(values, count) }
+        .iter()
+        .filter_map(|&value| opt_cstring(value))
+        .collect()
+}
+
 fn opt_bool(flag: i8) -> Option<bool> {
     match flag {
         -1 => None,
@@ -735,6 +1625,153 @@ fn parse_proxy_config(proxy_url: Option<String>) -> Option<ProxyConfig> {
     }
 }
 
+/// Makes `window.ipc.postMessage` accept any JSON-serializable value, not only strings.
+const IPC_BRIDGE: &str = r#"(function () {
+  var ipc = window.ipc;
+  if (!ipc || ipc.__veloxBridge) return;
+  var postMessage = ipc.postMessage.bind(ipc);
+  ipc.postMessage = function (message) {
+    postMessage(typeof message === 'string' ? message : JSON.stringify(message));
+  };
+  ipc.__veloxBridge = true;
+})();"#;
+
+/// Lets `velox_webview_post_message` deliver JSON payloads as `CustomEvent`s on `window`.
+const RECV_BRIDGE: &str = r#"(function () {
+  if (window.__velox_recv__) return;
+  window.__velox_recv__ = function (name, payload) {
+    window.dispatchEvent(new CustomEvent(name, { detail: payload }));
+  };
+})();"#;
+
+/// Forwards uncaught errors and unhandled rejections to the native side over IPC.
+const SCRIPT_ERROR_BRIDGE: &str = r#"(function () {
+  if (window.__veloxScriptErrorBridge) return;
+  window.__veloxScriptErrorBridge = true;
+  function report(message, source, line) {
+    try {
+      window.ipc.postMessage(JSON.stringify({
+        __velox_error: { message: String(message), source: String(source || ''), line: line || 0 }
+      }));
+    } catch (_) {}
+  }
+  window.addEventListener('error', function (event) {
+    report(event.message, event.filename, event.lineno);
+  });
+  window.addEventListener('unhandledrejection', function (event) {
+    var reason = event.reason;
+    report(reason instanceof Error ? reason.message : reason, 'unhandledrejection', 0);
+  });
+})();"#;
+
+#[derive(Deserialize)]
+struct ScriptErrorMessage {
+    #[serde(rename = "__velox_error")]
+    error: ScriptErrorPayload,
+}
+
+#[derive(Deserialize)]
+struct ScriptErrorPayload {
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    source: String,
+    #[serde(default)]
+    line: u32,
+}
+
+fn parse_script_error(body: &str) -> Option<ScriptErrorPayload> {
+    serde_json::from_str::<ScriptErrorMessage>(body)
+        .ok()
+        .map(|message| message.error)
+}
+
+/// Reports primary-button presses so the native side can start a drag inside a drag region.
+const DRAG_REGION_BRIDGE: &str = r#"(function () {
+  if (window.__veloxDragRegionBridge) return;
+  window.__veloxDragRegionBridge = true;
+  window.addEventListener('mousedown', function (event) {
+    if (event.button !== 0 || event.detail > 1) return;
+    try {
+      window.ipc.postMessage(JSON.stringify({
+        __velox_drag: [event.clientX, event.clientY],
+        token: '__VELOX_DRAG_TOKEN__'
+      }));
+    } catch (_) {}
+  }, true);
+})();"#;
+
+/// Random per process, so app IPC payloads cannot be mistaken for drag presses.
+fn drag_region_token() -> &'static str {
+    static TOKEN: OnceLock<String> = OnceLock::new();
+    TOKEN.get_or_init(|| {
+        use std::hash::{BuildHasher, Hasher};
+        let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+        hasher.write_u32(std::process::id());
+        format!("{:016x}", hasher.finish())
+    })
+}
+
+#[derive(Deserialize)]
+struct DragRegionMessage {
+    #[serde(rename = "__velox_drag")]
+    position: (f64, f64),
+    token: String,
+}
+
+fn parse_drag_region_press(body: &str, token: &str) -> Option<(f64, f64)> {
+    serde_json::from_str::<DragRegionMessage>(body)
+        .ok()
+        .filter(|message| message.token == token)
+        .map(|message| message.position)
+}
+
+fn inject_drag_region_bridge(window_id: WindowId) {
+    if !DRAG_REGIONS.with(|regions| regions.borrow().contains_key(&window_id)) {
+        return;
+    }
+    let handles = WINDOW_WEBVIEWS.with(|webviews| {
+        webviews
+            .borrow()
+            .get(&window_id)
+            .cloned()
+            .unwrap_or_default()
+    });
+    let script = DRAG_REGION_BRIDGE.replace("__VELOX_DRAG_TOKEN__", drag_region_token());
+    for handle in handles {
+        if let Some(handle) = unsafe { handle.as_ref() } {
+            let _ = handle.webview.evaluate_script(&script);
+        }
+    }
+}
+
+fn reapply_page_styles(window_id: WindowId, styles: &Rc<PageStyles>) {
+    let Some(script) = styles.script() else {
+        return;
+    };
+    let handles = WINDOW_WEBVIEWS.with(|webviews| {
+        webviews
+            .borrow()
+            .get(&window_id)
+            .cloned()
+            .unwrap_or_default()
+    });
+    for handle in handles {
+        if let Some(handle) = unsafe { handle.as_ref() } {
+            if Rc::ptr_eq(&handle.page_styles, styles) {
+                let _ = handle.webview.evaluate_script(&script);
+            }
+        }
+    }
+}
+
+fn text_zoom_script(level: f64) -> String {
+    format!(
+        "document.documentElement.style.fontSize = '{}%';",
+        level * 100.0
+    )
+}
+
 fn apply_parent_builder(builder: TaoWindowBuilder, parent: &Window) -> TaoWindowBuilder {
     #[cfg(target_os = "macos")]
     {
@@ -849,42 +1886,114 @@ fn prompt_result_from_string(value: Option<String>) -> VeloxPromptDialogResult {
     }
 }
 
-#[no_mangle]
-pub extern "C" fn velox_dialog_open(
-    options: *const VeloxDialogOpenOptions,
-) -> VeloxDialogSelection {
-    guard_panic_value(|| {
-        let Some(options) = (unsafe { options.as_ref() }) else {
-            return VeloxDialogSelection::default();
-        };
-
-        let mut dialog = FileDialog::new();
-        if let Some(title) = opt_cstring(options.title) {
-            dialog = dialog.set_title(&title);
-        }
-        if let Some(path) = opt_cstring(options.default_path) {
-            dialog = dialog.set_directory(std::path::Path::new(&path));
-        }
+fn open_dialog(options: &VeloxDialogOpenOptions) -> impl FnOnce() -> Vec<PathBuf> + Send {
+    let mut dialog = FileDialog::new();
+    if let Some(title) = opt_cstring(options.title) {
+        dialog = dialog.set_title(&title);
+    }
+    if let Some(path) = opt_cstring(options.default_path) {
+        dialog = dialog.set_directory(std::path::Path::new(&path));
+    }
 
-        if options.filter_count > 0 && !options.filters.is_null() && !options.allow_directories {
-            let filters =
-                unsafe { std::slice::from_raw_parts(options.filters, options.filter_count) };
-            dialog = dialog_apply_filters(dialog, filters);
-        }
+    if options.filter_count > 0 && !options.filters.is_null() && !options.allow_directories {
+        let filters = unsafe { std::slice::from_raw_parts(options.filters, options.filter_count) };
+        dialog = dialog_apply_filters(dialog, filters);
+    }
 
-        let selection_paths = if options.allow_directories {
-            if options.allow_multiple {
+    let allow_directories = options.allow_directories;
+    let allow_multiple = options.allow_multiple;
+    move || {
+        if allow_directories {
+            if allow_multiple {
                 dialog.pick_folders().unwrap_or_default()
             } else {
                 dialog.pick_folder().into_iter().collect()
             }
-        } else if options.allow_multiple {
+        } else if allow_multiple {
             dialog.pick_files().unwrap_or_default()
         } else {
             dialog.pick_file().into_iter().collect()
+        }
+    }
+}
+
+fn save_dialog(options: &VeloxDialogSaveOptions) -> impl FnOnce() -> Vec<PathBuf> + Send {
+    let mut dialog = FileDialog::new();
+    if let Some(title) = opt_cstring(options.title) {
+        dialog = dialog.set_title(&title);
+    }
+    if let Some(path) = opt_cstring(options.default_path) {
+        dialog = dialog.set_directory(std::path::Path::new(&path));
+    }
+    if let Some(name) = opt_cstring(options.default_name) {
+        dialog = dialog.set_file_name(&name);
+    }
+
+    if options.filter_count > 0 && !options.filters.is_null() {
+        let filters = unsafe { std::slice::from_raw_parts(options.filters, options.filter_count) };
+        dialog = dialog_apply_filters(dialog, filters);
+    }
+
+    move || dialog.save_file().into_iter().collect()
+}
+
+pub type VeloxDialogSelectionCallback =
+    Option<unsafe extern "C" fn(selection: VeloxDialogSelection, user_data: *mut c_void)>;
+pub type VeloxDialogResultCallback =
+    Option<unsafe extern "C" fn(accepted: bool, user_data: *mut c_void)>;
+
+/// Shows a blocking dialog off-thread and delivers its result on the event loop thread
+fn run_dialog_async<T: Default + Send + 'static>(
+    show: impl FnOnce() -> T + Send + 'static,
+    deliver: impl FnOnce(T) + Send + 'static,
+) -> bool {
+    let Some(proxy) = USER_EVENT_PROXY.with(|proxy| proxy.borrow().clone()) else {
+        return false;
+    };
+    let spawned = std::thread::Builder::new()
+        .name("velox-dialog".into())
+        .spawn(move || {
+            let result = catch_unwind(AssertUnwindSafe(show)).unwrap_or_default();
+            let task = PostedTask::new(move || deliver(result));
+            let _ = proxy.send_event(VeloxUserEvent::Task(task));
+        });
+    report_error(spawned, VeloxErrorCode::Unknown).is_some()
+}
+
+fn deliver_dialog_selection(
+    callback: unsafe extern "C" fn(VeloxDialogSelection, *mut c_void),
+    user_data: usize,
+) -> impl FnOnce(Vec<PathBuf>) + Send {
+    move |paths| unsafe { callback(dialog_selection_from_paths(paths), user_data as *mut c_void) }
+}
+
+#[no_mangle]
+pub extern "C" fn velox_dialog_open(
+    options: *const VeloxDialogOpenOptions,
+) -> VeloxDialogSelection {
+    guard_panic_value(|| {
+        let Some(options) = (unsafe { options.as_ref() }) else {
+            return VeloxDialogSelection::default();
         };
+        dialog_selection_from_paths(open_dialog(options)())
+    })
+}
 
-        dialog_selection_from_paths(selection_paths)
+/// Like `velox_dialog_open`, but the callback receives the selection on the event loop thread
+#[no_mangle]
+pub extern "C" fn velox_dialog_open_async(
+    options: *const VeloxDialogOpenOptions,
+    callback: VeloxDialogSelectionCallback,
+    user_data: *mut c_void,
+) -> bool {
+    guard_panic_bool(|| {
+        let (Some(options), Some(callback)) = (unsafe { options.as_ref() }, callback) else {
+            return false;
+        };
+        run_dialog_async(
+            open_dialog(options),
+            deliver_dialog_selection(callback, user_data as usize),
+        )
     })
 }
 
@@ -896,26 +2005,25 @@ pub extern "C" fn velox_dialog_save(
         let Some(options) = (unsafe { options.as_ref() }) else {
             return VeloxDialogSelection::default();
         };
+        dialog_selection_from_paths(save_dialog(options)())
+    })
+}
 
-        let mut dialog = FileDialog::new();
-        if let Some(title) = opt_cstring(options.title) {
-            dialog = dialog.set_title(&title);
-        }
-        if let Some(path) = opt_cstring(options.default_path) {
-            dialog = dialog.set_directory(std::path::Path::new(&path));
-        }
-        if let Some(name) = opt_cstring(options.default_name) {
-            dialog = dialog.set_file_name(&name);
-        }
-
-        if options.filter_count > 0 && !options.filters.is_null() {
-            let filters =
-                unsafe { std::slice::from_raw_parts(options.filters, options.filter_count) };
-            dialog = dialog_apply_filters(dialog, filters);
-        }
-
-        let selection_paths = dialog.save_file().into_iter().collect();
-        dialog_selection_from_paths(selection_paths)
+/// Like `velox_dialog_save`, but the callback receives the selection on the event loop thread
+#[no_mangle]
+pub extern "C" fn velox_dialog_save_async(
+    options: *const VeloxDialogSaveOptions,
+    callback: VeloxDialogSelectionCallback,
+    user_data: *mut c_void,
+) -> bool {
+    guard_panic_bool(|| {
+        let (Some(options), Some(callback)) = (unsafe { options.as_ref() }, callback) else {
+            return false;
+        };
+        run_dialog_async(
+            save_dialog(options),
+            deliver_dialog_selection(callback, user_data as usize),
+        )
     })
 }
 
@@ -936,8 +2044,86 @@ pub extern "C" fn velox_dialog_selection_free(selection: VeloxDialogSelection) {
     }
 }
 
+fn message_dialog(options: &VeloxMessageDialogOptions) -> impl FnOnce() -> bool + Send {
+    let mut dialog = MessageDialog::new();
+    if let Some(title) = opt_cstring(options.title) {
+        dialog = dialog.set_title(&title);
+    }
+    let message = opt_cstring(options.message).unwrap_or_default();
+    dialog = dialog.set_description(&message);
+
+    dialog = dialog.set_level(message_level_from_ffi(options.level));
+
+    let ok_label = opt_cstring(options.ok_label);
+    let cancel_label = opt_cstring(options.cancel_label);
+    let yes_label = opt_cstring(options.yes_label);
+    let no_label = opt_cstring(options.no_label);
+
+    dialog = match options.buttons {
+        VeloxMessageDialogButtons::Ok => {
+            if let Some(label) = ok_label {
+                dialog.set_buttons(MessageButtons::OkCustom(label))
+            } else {
+                dialog.set_buttons(MessageButtons::Ok)
+            }
+        }
+        VeloxMessageDialogButtons::OkCancel => {
+            if let (Some(ok), Some(cancel)) = (ok_label.clone(), cancel_label.clone()) {
+                dialog.set_buttons(MessageButtons::OkCancelCustom(ok, cancel))
+            } else {
+                dialog.set_buttons(MessageButtons::OkCancel)
+            }
+        }
+        VeloxMessageDialogButtons::YesNo => dialog.set_buttons(MessageButtons::YesNo),
+        VeloxMessageDialogButtons::YesNoCancel => {
+            if let (Some(yes), Some(no), Some(cancel)) =
+                (yes_label.clone(), no_label.clone(), cancel_label)
+            {
+                dialog.set_buttons(MessageButtons::YesNoCancelCustom(yes, no, cancel))
+            } else {
+                dialog.set_buttons(MessageButtons::YesNoCancel)
+            }
+        }
+    };
+
+    move || match dialog.show() {
+        MessageDialogResult::Ok | MessageDialogResult::Yes => true,
+        MessageDialogResult::Cancel | MessageDialogResult::No | MessageDialogResult::Custom(_) => {
+            false
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn velox_dialog_message(options: *const VeloxMessageDialogOptions) -> bool {
+    guard_panic_bool(|| {
+        let Some(options) = (unsafe { options.as_ref() }) else {
+            return false;
+        };
+        message_dialog(options)()
+    })
+}
+
+/// Like `velox_dialog_message`, but the callback receives the answer on the event loop thread
+#[no_mangle]
+pub extern "C" fn velox_dialog_message_async(
+    options: *const VeloxMessageDialogOptions,
+    callback: VeloxDialogResultCallback,
+    user_data: *mut c_void,
+) -> bool {
+    guard_panic_bool(|| {
+        let (Some(options), Some(callback)) = (unsafe { options.as_ref() }, callback) else {
+            return false;
+        };
+        let user_data = user_data as usize;
+        run_dialog_async(message_dialog(options), move |accepted| unsafe {
+            callback(accepted, user_data as *mut c_void)
+        })
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn velox_dialog_confirm(options: *const VeloxConfirmDialogOptions) -> bool {
     guard_panic_bool(|| {
         let Some(options) = (unsafe { options.as_ref() }) else {
             return false;
@@ -949,68 +2135,11 @@ pub extern "C" fn velox_dialog_message(options: *const VeloxMessageDialogOptions
         }
         let message = opt_cstring(options.message).unwrap_or_default();
         dialog = dialog.set_description(&message);
-
         dialog = dialog.set_level(message_level_from_ffi(options.level));
 
         let ok_label = opt_cstring(options.ok_label);
         let cancel_label = opt_cstring(options.cancel_label);
-        let yes_label = opt_cstring(options.yes_label);
-        let no_label = opt_cstring(options.no_label);
-
-        dialog = match options.buttons {
-            VeloxMessageDialogButtons::Ok => {
-                if let Some(label) = ok_label {
-                    dialog.set_buttons(MessageButtons::OkCustom(label))
-                } else {
-                    dialog.set_buttons(MessageButtons::Ok)
-                }
-            }
-            VeloxMessageDialogButtons::OkCancel => {
-                if let (Some(ok), Some(cancel)) = (ok_label.clone(), cancel_label.clone()) {
-                    dialog.set_buttons(MessageButtons::OkCancelCustom(ok, cancel))
-                } else {
-                    dialog.set_buttons(MessageButtons::OkCancel)
-                }
-            }
-            VeloxMessageDialogButtons::YesNo => dialog.set_buttons(MessageButtons::YesNo),
-            VeloxMessageDialogButtons::YesNoCancel => {
-                if let (Some(yes), Some(no), Some(cancel)) =
-                    (yes_label.clone(), no_label.clone(), cancel_label)
-                {
-                    dialog.set_buttons(MessageButtons::YesNoCancelCustom(yes, no, cancel))
-                } else {
-                    dialog.set_buttons(MessageButtons::YesNoCancel)
-                }
-            }
-        };
-
-        match dialog.show() {
-            MessageDialogResult::Ok | MessageDialogResult::Yes => true,
-            MessageDialogResult::Cancel
-            | MessageDialogResult::No
-            | MessageDialogResult::Custom(_) => false,
-        }
-    })
-}
-
-#[no_mangle]
-pub extern "C" fn velox_dialog_confirm(options: *const VeloxConfirmDialogOptions) -> bool {
-    guard_panic_bool(|| {
-        let Some(options) = (unsafe { options.as_ref() }) else {
-            return false;
-        };
-
-        let mut dialog = MessageDialog::new();
-        if let Some(title) = opt_cstring(options.title) {
-            dialog = dialog.set_title(&title);
-        }
-        let message = opt_cstring(options.message).unwrap_or_default();
-        dialog = dialog.set_description(&message);
-        dialog = dialog.set_level(message_level_from_ffi(options.level));
-
-        let ok_label = opt_cstring(options.ok_label);
-        let cancel_label = opt_cstring(options.cancel_label);
-        let positive_label = ok_label.clone();
+        let positive_label = ok_label.clone();
 
         dialog = if let (Some(ref ok), Some(ref cancel)) = (&ok_label, &cancel_label) {
             dialog.set_buttons(MessageButtons::OkCancelCustom(ok.clone(), cancel.clone()))
@@ -1065,6 +2194,304 @@ pub extern "C" fn velox_dialog_ask(options: *const VeloxAskDialogOptions) -> boo
     })
 }
 
+#[cfg(target_os = "macos")]
+fn multiline_prompt(
+    title: &str,
+    message: &str,
+    default_text: &str,
+    ok_label: &str,
+    cancel_label: &str,
+) -> Option<String> {
+    use objc2::{
+        class, msg_send,
+        rc::{Allocated, Retained},
+        runtime::AnyObject,
+    };
+    use objc2_foundation::{NSPoint, NSRect, NSSize, NSString};
+
+    const NS_ALERT_FIRST_BUTTON_RETURN: isize = 1000;
+    const NS_BEZEL_BORDER: usize = 2;
+
+    unsafe {
+        let alert: Option<Retained<AnyObject>> = msg_send![class!(NSAlert), new];
+        let alert = alert?;
+        let _: () = msg_send![&*alert, setMessageText: &*NSString::from_str(title)];
+        let _: () = msg_send![&*alert, setInformativeText: &*NSString::from_str(message)];
+        let _: *mut AnyObject =
+            msg_send![&*alert, addButtonWithTitle: &*NSString::from_str(ok_label)];
+        let _: *mut AnyObject =
+            msg_send![&*alert, addButtonWithTitle: &*NSString::from_str(cancel_label)];
+
+        let frame = NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(360.0, 140.0));
+        let allocated: Allocated<AnyObject> = msg_send![class!(NSScrollView), alloc];
+        let scroll_view: Option<Retained<AnyObject>> = msg_send![allocated, initWithFrame: frame];
+        let allocated: Allocated<AnyObject> = msg_send![class!(NSTextView), alloc];
+        let text_view: Option<Retained<AnyObject>> = msg_send![allocated, initWithFrame: frame];
+        let (scroll_view, text_view) = (scroll_view?, text_view?);
+        let _: () = msg_send![&*text_view, setString: &*NSString::from_str(default_text)];
+        let _: () = msg_send![&*scroll_view, setHasVerticalScroller: true];
+        let _: () = msg_send![&*scroll_view, setBorderType: NS_BEZEL_BORDER];
+        let _: () = msg_send![&*scroll_view, setDocumentView: &*text_view];
+        let _: () = msg_send![&*alert, setAccessoryView: &*scroll_view];
+
+        let window: *mut AnyObject = msg_send![&*alert, window];
+        let _: () = msg_send![window, setInitialFirstResponder: &*text_view];
+
+        let response: isize = msg_send![&*alert, runModal];
+        if response != NS_ALERT_FIRST_BUTTON_RETURN {
+            return None;
+        }
+        let value: Option<Retained<NSString>> = msg_send![&*text_view, string];
+        Some(value.map(|value| value.to_string()).unwrap_or_default())
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn multiline_prompt(
+    title: &str,
+    message: &str,
+    default_text: &str,
+    ok_label: &str,
+    cancel_label: &str,
+) -> Option<String> {
+    use windows::core::{w, PCWSTR};
+    use windows::Win32::Foundation::{HINSTANCE, LPARAM, LRESULT, WPARAM};
+    use windows::Win32::Graphics::Gdi::{GetStockObject, COLOR_BTNFACE, DEFAULT_GUI_FONT, HBRUSH};
+    use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows::Win32::UI::Input::KeyboardAndMouse::SetFocus;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        CreateWindowExW, DefWindowProcW, DestroyWindow, DispatchMessageW, GetMessageW,
+        GetSystemMetrics, GetWindowLongPtrW, GetWindowTextLengthW, GetWindowTextW,
+        IsDialogMessageW, LoadCursorW, PostQuitMessage, RegisterClassW, SendMessageW,
+        SetWindowLongPtrW, ShowWindow, TranslateMessage, BS_DEFPUSHBUTTON, BS_PUSHBUTTON,
+        CW_USEDEFAULT, ES_AUTOVSCROLL, ES_MULTILINE, ES_WANTRETURN, GWLP_USERDATA, HMENU, IDCANCEL,
+        IDC_ARROW, IDOK, MSG, SM_CXSCREEN, SM_CYSCREEN, SW_SHOW, WINDOW_EX_STYLE, WINDOW_STYLE,
+        WM_CLOSE, WM_COMMAND, WM_SETFONT, WNDCLASSW, WS_CAPTION, WS_CHILD, WS_EX_CLIENTEDGE,
+        WS_EX_DLGMODALFRAME, WS_EX_TOPMOST, WS_POPUP, WS_SYSMENU, WS_TABSTOP, WS_VISIBLE,
+        WS_VSCROLL,
+    };
+
+    const WIDTH: i32 = 420;
+    const HEIGHT: i32 = 300;
+
+    struct PromptState {
+        edit: HWND,
+        result: Option<Option<String>>,
+    }
+
+    unsafe extern "system" fn window_proc(
+        hwnd: HWND,
+        message: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> LRESULT {
+        let state = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut PromptState;
+        if let Some(state) = state.as_mut() {
+            match message {
+                WM_COMMAND if (wparam.0 & 0xffff) as i32 == IDOK.0 => {
+                    let length = GetWindowTextLengthW(state.edit).max(0) as usize;
+                    let mut text = vec![0u16; length + 1];
+                    let copied = GetWindowTextW(state.edit, &mut text).max(0) as usize;
+                    let value = String::from_utf16_lossy(&text[..copied]).replace("\r\n", "\n");
+                    state.result = Some(Some(value));
+                    return LRESULT(0);
+                }
+                WM_COMMAND if (wparam.0 & 0xffff) as i32 == IDCANCEL.0 => {
+                    state.result = Some(None);
+                    return LRESULT(0);
+                }
+                WM_CLOSE => {
+                    state.result = Some(None);
+                    return LRESULT(0);
+                }
+                _ => {}
+            }
+        }
+        DefWindowProcW(hwnd, message, wparam, lparam)
+    }
+
+    let wide = |text: &str| text.encode_utf16().chain(Some(0)).collect::<Vec<u16>>();
+    let title = wide(title);
+    let message = wide(message);
+    let default_text = wide(&default_text.replace('\n', "\r\n"));
+    let ok_label = wide(ok_label);
+    let cancel_label = wide(cancel_label);
+
+    unsafe {
+        let instance: HINSTANCE = GetModuleHandleW(None).ok()?.into();
+        let class_name = w!("VeloxMultilinePrompt");
+        let class = WNDCLASSW {
+            lpfnWndProc: Some(window_proc),
+            hInstance: instance,
+            hCursor: LoadCursorW(None, IDC_ARROW).unwrap_or_default(),
+            hbrBackground: HBRUSH((COLOR_BTNFACE.0 + 1) as _),
+            lpszClassName: class_name,
+            ..Default::default()
+        };
+        // Fails harmlessly once the class is registered by an earlier prompt.
+        RegisterClassW(&class);
+
+        let x = (GetSystemMetrics(SM_CXSCREEN) - WIDTH) / 2;
+        let y = (GetSystemMetrics(SM_CYSCREEN) - HEIGHT) / 2;
+        let hwnd = CreateWindowExW(
+            WS_EX_DLGMODALFRAME | WS_EX_TOPMOST,
+            class_name,
+            PCWSTR(title.as_ptr()),
+            WS_POPUP | WS_CAPTION | WS_SYSMENU,
+            if x > 0 { x } else { CW_USEDEFAULT },
+            if y > 0 { y } else { CW_USEDEFAULT },
+            WIDTH,
+            HEIGHT,
+            HWND::default(),
+            HMENU::default(),
+            instance,
+            None,
+        )
+        .ok()?;
+
+        let child = |ex_style: WINDOW_EX_STYLE,
+                     class: PCWSTR,
+                     text: &[u16],
+                     style: WINDOW_STYLE,
+                     bounds: (i32, i32, i32, i32),
+                     id: i32| {
+            let control = CreateWindowExW(
+                ex_style,
+                class,
+                PCWSTR(text.as_ptr()),
+                WS_CHILD | WS_VISIBLE | style,
+                bounds.0,
+                bounds.1,
+                bounds.2,
+                bounds.3,
+                hwnd,
+                HMENU(id as isize as _),
+                instance,
+                None,
+            )
+            .unwrap_or_default();
+            SendMessageW(
+                control,
+                WM_SETFONT,
+                WPARAM(GetStockObject(DEFAULT_GUI_FONT).0 as usize),
+                LPARAM(1),
+            );
+            control
+        };
+
+        child(
+            WINDOW_EX_STYLE::default(),
+            w!("STATIC"),
+            &message,
+            WINDOW_STYLE::default(),
+            (12, 12, WIDTH - 36, 36),
+            0,
+        );
+        let edit = child(
+            WS_EX_CLIENTEDGE,
+            w!("EDIT"),
+            &default_text,
+            WS_TABSTOP
+                | WS_VSCROLL
+                | WINDOW_STYLE((ES_MULTILINE | ES_AUTOVSCROLL | ES_WANTRETURN) as u32),
+            (12, 52, WIDTH - 36, HEIGHT - 140),
+            0,
+        );
+        child(
+            WINDOW_EX_STYLE::default(),
+            w!("BUTTON"),
+            &ok_label,
+            WS_TABSTOP | WINDOW_STYLE(BS_DEFPUSHBUTTON as u32),
+            (WIDTH - 204, HEIGHT - 78, 84, 26),
+            IDOK.0,
+        );
+        child(
+            WINDOW_EX_STYLE::default(),
+            w!("BUTTON"),
+            &cancel_label,
+            WS_TABSTOP | WINDOW_STYLE(BS_PUSHBUTTON as u32),
+            (WIDTH - 112, HEIGHT - 78, 84, 26),
+            IDCANCEL.0,
+        );
+
+        let state = Box::into_raw(Box::new(PromptState { edit, result: None }));
+        SetWindowLongPtrW(hwnd, GWLP_USERDATA, state as isize);
+        let _ = ShowWindow(hwnd, SW_SHOW);
+        let _ = SetFocus(edit);
+
+        let mut message = MSG::default();
+        while (*state).result.is_none() {
+            if !GetMessageW(&mut message, HWND::default(), 0, 0).as_bool() {
+                // Hand WM_QUIT back to the outer loop once the prompt is gone.
+                PostQuitMessage(message.wParam.0 as i32);
+                (*state).result = Some(None);
+                break;
+            }
+            if !IsDialogMessageW(hwnd, &message).as_bool() {
+                let _ = TranslateMessage(&message);
+                DispatchMessageW(&message);
+            }
+        }
+
+        SetWindowLongPtrW(hwnd, GWLP_USERDATA, 0);
+        let _ = DestroyWindow(hwnd);
+        Box::from_raw(state).result.flatten()
+    }
+}
+
+/// Multi-line entry through `zenity --text-info` or `kdialog`; the message becomes the title
+#[cfg(target_os = "linux")]
+fn multiline_prompt(
+    title: &str,
+    message: &str,
+    default_text: &str,
+    ok_label: &str,
+    cancel_label: &str,
+) -> Option<String> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let zenity = Command::new("zenity")
+        .args(["--text-info", "--editable", "--width=420", "--height=300"])
+        .arg(format!("--title={message}"))
+        .arg(format!("--ok-label={ok_label}"))
+        .arg(format!("--cancel-label={cancel_label}"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn();
+    let output = match zenity {
+        Ok(mut child) => {
+            if let Some(mut stdin) = child.stdin.take() {
+                let _ = stdin.write_all(default_text.as_bytes());
+            }
+            child.wait_with_output().ok()?
+        }
+        Err(_) => Command::new("kdialog")
+            .args(["--title", title, "--textinputbox", message, default_text])
+            .output()
+            .ok()?,
+    };
+    if !output.status.success() {
+        return None;
+    }
+    let mut value = String::from_utf8_lossy(&output.stdout).into_owned();
+    if value.ends_with('\n') {
+        value.pop();
+    }
+    Some(value)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn multiline_prompt(
+    _title: &str,
+    _message: &str,
+    _default_text: &str,
+    _ok_label: &str,
+    _cancel_label: &str,
+) -> Option<String> {
+    None
+}
+
 #[no_mangle]
 pub extern "C" fn velox_dialog_prompt(
     options: *const VeloxPromptDialogOptions,
@@ -1089,6 +2516,18 @@ pub extern "C" fn velox_dialog_prompt(
             title.as_str()
         };
 
+        if options.multiline {
+            let ok_label = opt_cstring(options.ok_label);
+            let cancel_label = opt_cstring(options.cancel_label);
+            return prompt_result_from_string(multiline_prompt(
+                title_ref,
+                &message,
+                &default_text,
+                ok_label.as_deref().unwrap_or("OK"),
+                cancel_label.as_deref().unwrap_or("Cancel"),
+            ));
+        }
+
         let input = tinyfiledialogs::input_box(title_ref, &message, default_text.as_str());
         prompt_result_from_string(input)
     })
@@ -1104,2910 +2543,8330 @@ pub extern "C" fn velox_dialog_prompt_result_free(result: VeloxPromptDialogResul
 }
 
 #[cfg(target_os = "macos")]
-fn activation_policy_from_ffi(policy: VeloxActivationPolicy) -> ActivationPolicy {
-    match policy {
-        VeloxActivationPolicy::Regular => ActivationPolicy::Regular,
-        VeloxActivationPolicy::Accessory => ActivationPolicy::Accessory,
-        VeloxActivationPolicy::Prohibited => ActivationPolicy::Prohibited,
-    }
-}
+const NS_MODAL_RESPONSE_OK: isize = 1;
+#[cfg(target_os = "macos")]
+const NS_MODAL_RESPONSE_CANCEL: isize = 0;
 
-fn monitor_to_json(monitor: &MonitorHandle) -> serde_json::Value {
-    let name = monitor.name().unwrap_or_default();
-    let position = monitor.position();
-    let size = monitor.size();
-    json!({
-        "name": name,
-        "scale_factor": monitor.scale_factor(),
-        "position": {
-            "x": position.x,
-            "y": position.y,
-        },
-        "size": {
-            "width": size.width,
-            "height": size.height,
+#[cfg(target_os = "macos")]
+objc2::define_class!(
+    #[unsafe(super(objc2_foundation::NSObject))]
+    #[name = "VeloxColorPanelTarget"]
+    struct VeloxColorPanelTarget;
+
+    impl VeloxColorPanelTarget {
+        #[unsafe(method(veloxAccept:))]
+        fn accept(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            stop_color_panel(NS_MODAL_RESPONSE_OK);
         }
-    })
-}
-
-fn write_json_to_buffer(
-    buffer: &'static LocalKey<RefCell<CString>>,
-    value: serde_json::Value,
-) -> *const c_char {
-    let json_string = value.to_string();
-    buffer.with(|cell| {
-        let mut storage = cell.borrow_mut();
-        *storage =
-            CString::new(json_string).unwrap_or_else(|_| CString::new("{}").expect("static JSON"));
-        storage.as_ptr()
-    })
-}
-
-fn write_string_to_buffer(
-    buffer: &'static LocalKey<RefCell<CString>>,
-    value: String,
-) -> *const c_char {
-    buffer.with(|cell| {
-        let mut storage = cell.borrow_mut();
-        *storage = CString::new(value).unwrap_or_else(|_| CString::new("").expect("empty string"));
-        storage.as_ptr()
-    })
-}
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-fn guard_panic<T>(f: impl FnOnce() -> *mut T) -> *mut T {
-    match catch_unwind(AssertUnwindSafe(f)) {
-        Ok(ptr) => ptr,
-        Err(_) => ptr::null_mut(),
+        #[unsafe(method(veloxCancel:))]
+        fn cancel(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            stop_color_panel(NS_MODAL_RESPONSE_CANCEL);
+        }
     }
-}
+);
 
-fn guard_panic_bool(f: impl FnOnce() -> bool) -> bool {
-    match catch_unwind(AssertUnwindSafe(f)) {
-        Ok(result) => result,
-        Err(_) => false,
-    }
-}
+#[cfg(target_os = "macos")]
+fn stop_color_panel(response: isize) {
+    use objc2::{class, msg_send, runtime::AnyObject};
 
-fn guard_panic_value<T: Default>(f: impl FnOnce() -> T) -> T {
-    match catch_unwind(AssertUnwindSafe(f)) {
-        Ok(value) => value,
-        Err(_) => T::default(),
+    unsafe {
+        let app: *mut AnyObject = msg_send![class!(NSApplication), sharedApplication];
+        let _: () = msg_send![app, stopModalWithCode: response];
     }
 }
 
-#[cfg(all(target_os = "macos", feature = "local-dev"))]
-#[no_mangle]
-pub extern "C" fn velox_app_state_force_launched() {
-    tao::platform::macos::force_app_state_launched_for_testing();
-}
+/// Runs the shared `NSColorPanel` modally with OK/Cancel buttons in its accessory view
+#[cfg(target_os = "macos")]
+fn pick_color(title: &str, initial: VeloxColor) -> Option<[u8; 3]> {
+    use objc2::{
+        class, msg_send,
+        rc::Retained,
+        runtime::{AnyObject, Sel},
+        sel, AllocAnyThread,
+    };
+    use objc2_foundation::{NSSize, NSString};
 
-#[cfg(all(target_os = "macos", not(feature = "local-dev")))]
-#[no_mangle]
-pub extern "C" fn velox_app_state_force_launched() {
-    // No-op when using crates.io tao (velox-testing feature not available)
-}
+    const NS_WINDOW_STYLE_MASK_CLOSABLE: usize = 1 << 1;
 
-fn with_window<R>(window: *mut VeloxWindowHandle, f: impl FnOnce(&Window) -> R) -> Option<R> {
-    unsafe { window.as_ref() }.map(|handle| f(&handle.window))
-}
+    let target: Retained<VeloxColorPanelTarget> = {
+        let this = VeloxColorPanelTarget::alloc().set_ivars(());
+        unsafe { msg_send![super(this), init] }
+    };
+    let button = |title: &str, action: Sel, key: &str| -> Retained<AnyObject> {
+        unsafe {
+            let button: Retained<AnyObject> = msg_send![
+                class!(NSButton),
+                buttonWithTitle: &*NSString::from_str(title),
+                target: &*target,
+                action: action
+            ];
+            let _: () = msg_send![&*button, setKeyEquivalent: &*NSString::from_str(key)];
+            button
+        }
+    };
 
-fn with_webview<R>(webview: *mut VeloxWebviewHandle, f: impl FnOnce(&WebView) -> R) -> Option<R> {
-    unsafe { webview.as_ref() }.map(|handle| f(&handle.webview))
-}
+    unsafe {
+        let panel: Option<Retained<AnyObject>> = msg_send![class!(NSColorPanel), sharedColorPanel];
+        let panel = panel?;
+        let app: *mut AnyObject = msg_send![class!(NSApplication), sharedApplication];
+
+        let views: Retained<AnyObject> = msg_send![class!(NSMutableArray), array];
+        let _: () = msg_send![&*views, addObject: &*button("Cancel", sel!(veloxCancel:), "\u{1b}")];
+        let _: () = msg_send![&*views, addObject: &*button("OK", sel!(veloxAccept:), "\r")];
+        let accessory: Retained<AnyObject> =
+            msg_send![class!(NSStackView), stackViewWithViews: &*views];
+        let size: NSSize = msg_send![&*accessory, fittingSize];
+        let _: () = msg_send![&*accessory, setFrameSize: size];
+
+        let color: *mut AnyObject = msg_send![
+            class!(NSColor),
+            colorWithSRGBRed: f64::from(initial.red) / 255.0,
+            green: f64::from(initial.green) / 255.0,
+            blue: f64::from(initial.blue) / 255.0,
+            alpha: 1.0f64
+        ];
+        // Without a close button the panel can only leave the modal loop through OK or Cancel.
+        let style_mask: usize = msg_send![&*panel, styleMask];
+        let _: () = msg_send![&*panel, setStyleMask: style_mask & !NS_WINDOW_STYLE_MASK_CLOSABLE];
+        let _: () = msg_send![&*panel, setTitle: &*NSString::from_str(title)];
+        let _: () = msg_send![&*panel, setShowsAlpha: false];
+        let _: () = msg_send![&*panel, setColor: color];
+        let _: () = msg_send![&*panel, setAccessoryView: &*accessory];
+
+        let response: isize = msg_send![app, runModalForWindow: &*panel];
+
+        let _: () = msg_send![&*panel, orderOut: ptr::null::<AnyObject>()];
+        let _: () = msg_send![&*panel, setAccessoryView: ptr::null::<AnyObject>()];
+        let _: () = msg_send![&*panel, setStyleMask: style_mask];
+        if response != NS_MODAL_RESPONSE_OK {
+            return None;
+        }
 
-fn tao_user_attention_from_ffi(kind: VeloxUserAttentionType) -> TaoUserAttentionType {
-    match kind {
-        VeloxUserAttentionType::Informational => TaoUserAttentionType::Informational,
-        VeloxUserAttentionType::Critical => TaoUserAttentionType::Critical,
+        let color: *mut AnyObject = msg_send![&*panel, color];
+        let srgb: *mut AnyObject = msg_send![class!(NSColorSpace), sRGBColorSpace];
+        let color: *mut AnyObject = msg_send![color, colorUsingColorSpace: srgb];
+        let color = color.as_ref()?;
+        let component = |value: f64| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let red: f64 = msg_send![color, redComponent];
+        let green: f64 = msg_send![color, greenComponent];
+        let blue: f64 = msg_send![color, blueComponent];
+        Some([component(red), component(green), component(blue)])
     }
 }
 
-fn tao_resize_direction_from_ffi(direction: VeloxResizeDirection) -> TaoResizeDirection {
-    match direction {
-        VeloxResizeDirection::East => TaoResizeDirection::East,
-        VeloxResizeDirection::North => TaoResizeDirection::North,
-        VeloxResizeDirection::NorthEast => TaoResizeDirection::NorthEast,
-        VeloxResizeDirection::NorthWest => TaoResizeDirection::NorthWest,
-        VeloxResizeDirection::South => TaoResizeDirection::South,
-        VeloxResizeDirection::SouthEast => TaoResizeDirection::SouthEast,
-        VeloxResizeDirection::SouthWest => TaoResizeDirection::SouthWest,
-        VeloxResizeDirection::West => TaoResizeDirection::West,
+/// `ChooseColorW` owned by the active window, which keeps it modal; it has no title to set
+#[cfg(target_os = "windows")]
+fn pick_color(_title: &str, initial: VeloxColor) -> Option<[u8; 3]> {
+    use windows::Win32::Foundation::COLORREF;
+    use windows::Win32::UI::Controls::Dialogs::{
+        ChooseColorW, CC_FULLOPEN, CC_RGBINIT, CHOOSECOLORW,
+    };
+    use windows::Win32::UI::Input::KeyboardAndMouse::GetActiveWindow;
+
+    let rgb = |[red, green, blue]: [u8; 3]| {
+        COLORREF(u32::from(red) | (u32::from(green) << 8) | (u32::from(blue) << 16))
+    };
+    let mut custom_colors = [COLORREF(0x00ff_ffff); 16];
+    let mut options = CHOOSECOLORW {
+        lStructSize: std::mem::size_of::<CHOOSECOLORW>() as u32,
+        hwndOwner: unsafe { GetActiveWindow() },
+        rgbResult: rgb([initial.red, initial.green, initial.blue]),
+        lpCustColors: custom_colors.as_mut_ptr(),
+        Flags: CC_RGBINIT | CC_FULLOPEN,
+        ..Default::default()
+    };
+    if !unsafe { ChooseColorW(&mut options) }.as_bool() {
+        return None;
     }
+    let value = options.rgbResult.0;
+    Some([value as u8, (value >> 8) as u8, (value >> 16) as u8])
 }
 
-#[no_mangle]
-pub extern "C" fn velox_runtime_wry_library_name() -> *const c_char {
-    cached_cstring(&LIBRARY_NAME, || "VeloxRuntimeWry".to_string())
+/// Colour selection through `zenity --color-selection` or `kdialog --getcolor`
+#[cfg(target_os = "linux")]
+fn pick_color(title: &str, initial: VeloxColor) -> Option<[u8; 3]> {
+    use std::process::Command;
+
+    let hex = format!(
+        "#{:02x}{:02x}{:02x}",
+        initial.red, initial.green, initial.blue
+    );
+    let output = Command::new("zenity")
+        .args(["--color-selection", "--modal"])
+        .arg(format!("--title={title}"))
+        .arg(format!("--color={hex}"))
+        .output()
+        .or_else(|_| {
+            Command::new("kdialog")
+                .args(["--title", title, "--getcolor", "--default", &hex])
+                .output()
+        })
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_dialog_color(String::from_utf8_lossy(&output.stdout).trim())
 }
 
-const VELOX_RUNTIME_WRY_FFI_ABI_VERSION: u32 = 2;
-
-#[no_mangle]
-pub extern "C" fn velox_runtime_wry_ffi_abi_version() -> u32 {
-    VELOX_RUNTIME_WRY_FFI_ABI_VERSION
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn pick_color(_title: &str, _initial: VeloxColor) -> Option<[u8; 3]> {
+    None
 }
 
-#[no_mangle]
-pub extern "C" fn velox_runtime_wry_crate_version() -> *const c_char {
-    cached_cstring(&RUNTIME_VERSION, || env!("CARGO_PKG_VERSION").to_string())
+/// Parses `#rrggbb`, zenity's 16-bit `#rrrrggggbbbb`, and `rgb(...)`/`rgba(...)` output
+#[cfg(target_os = "linux")]
+fn parse_dialog_color(value: &str) -> Option<[u8; 3]> {
+    if let Some(hex) = value.strip_prefix('#') {
+        let width = match hex.len() {
+            6 => 2,
+            12 => 4,
+            _ => return None,
+        };
+        let channel = |index: usize| {
+            let digits = hex.get(index * width..index * width + 2)?;
+            u8::from_str_radix(digits, 16).ok()
+        };
+        return Some([channel(0)?, channel(1)?, channel(2)?]);
+    }
+    let inner = value
+        .strip_prefix("rgba(")
+        .or_else(|| value.strip_prefix("rgb("))?
+        .strip_suffix(')')?;
+    let mut channels = inner.split(',').map(|part| part.trim().parse::<u8>().ok());
+    Some([channels.next()??, channels.next()??, channels.next()??])
 }
 
+/// Show a modal colour chooser; alpha is kept from `initial`, false if cancelled
 #[no_mangle]
-pub extern "C" fn velox_runtime_wry_webview_version() -> *const c_char {
-    cached_cstring(&WEBVIEW_VERSION, || {
-        wry::webview_version().unwrap_or_default()
+pub extern "C" fn velox_dialog_pick_color(
+    options: *const VeloxColorPickerOptions,
+    result: *mut VeloxColor,
+) -> bool {
+    guard_panic_bool(|| {
+        let (Some(options), Some(result)) =
+            (unsafe { options.as_ref() }, unsafe { result.as_mut() })
+        else {
+            return false;
+        };
+        let title = opt_cstring(options.title).unwrap_or_else(|| "Choose Color".to_string());
+        let initial = options.initial_color;
+        let Some([red, green, blue]) = pick_color(&title, initial) else {
+            return false;
+        };
+        *result = VeloxColor {
+            red,
+            green,
+            blue,
+            alpha: initial.alpha,
+        };
+        true
     })
 }
 
-#[no_mangle]
-pub extern "C" fn velox_event_loop_new() -> *mut VeloxEventLoop {
-    let event_loop = EventLoopBuilder::<VeloxUserEvent>::with_user_event().build();
+/// `userInfo` key carrying a notification's action id (macOS)
+#[cfg(target_os = "macos")]
+const NOTIFICATION_ACTION_KEY: &str = "velox_action_id";
 
-    #[cfg(any(target_os = "macos", target_os = "linux"))]
-    {
-        let proxy = event_loop.create_proxy();
-        MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
-            let _ = proxy.send_event(VeloxUserEvent::Menu(event.id().as_ref().to_string()));
-        }));
+#[cfg(target_os = "macos")]
+#[link(name = "UserNotifications", kind = "framework")]
+extern "C" {}
 
-        let tray_proxy = event_loop.create_proxy();
-        TrayIconEvent::set_event_handler(Some(move |event: TrayIconEvent| {
-            let _ = tray_proxy.send_event(VeloxUserEvent::Tray(event.into()));
-        }));
+#[cfg(target_os = "macos")]
+objc2::define_class!(
+    #[unsafe(super(objc2_foundation::NSObject))]
+    #[name = "VeloxNotificationDelegate"]
+    #[ivars = EventLoopProxy<VeloxUserEvent>]
+    struct VeloxNotificationDelegate;
+
+    impl VeloxNotificationDelegate {
+        #[unsafe(method(userNotificationCenter:willPresentNotification:withCompletionHandler:))]
+        fn will_present(
+            &self,
+            _center: &objc2::runtime::AnyObject,
+            _notification: &objc2::runtime::AnyObject,
+            completion: &block2::DynBlock<dyn Fn(usize)>,
+        ) {
+            // Show banners with sound even while the app is frontmost.
+            const UN_PRESENTATION_OPTIONS: usize = (1 << 1) | (1 << 2);
+            completion.call((UN_PRESENTATION_OPTIONS,));
+        }
+
+        #[unsafe(method(userNotificationCenter:didReceiveNotificationResponse:withCompletionHandler:))]
+        fn did_receive(
+            &self,
+            _center: &objc2::runtime::AnyObject,
+            response: &objc2::runtime::AnyObject,
+            completion: &block2::DynBlock<dyn Fn()>,
+        ) {
+            if let Some(action_id) = notification_response_action(response) {
+                let _ = self
+                    .ivars()
+                    .send_event(VeloxUserEvent::NotificationActivated(action_id));
+            }
+            completion.call(());
+        }
     }
+);
 
-    Box::into_raw(Box::new(VeloxEventLoop { event_loop }))
-}
+#[cfg(target_os = "macos")]
+fn notification_response_action(response: &objc2::runtime::AnyObject) -> Option<String> {
+    use objc2::{msg_send, rc::Retained, runtime::AnyObject};
+    use objc2_foundation::NSString;
 
-#[no_mangle]
-pub extern "C" fn velox_event_loop_free(event_loop: *mut VeloxEventLoop) {
-    if !event_loop.is_null() {
-        unsafe { drop(Box::from_raw(event_loop)) };
-        #[cfg(any(target_os = "macos", target_os = "linux"))]
-        MenuEvent::set_event_handler::<fn(MenuEvent)>(None);
-        #[cfg(any(target_os = "macos", target_os = "linux"))]
-        TrayIconEvent::set_event_handler::<fn(TrayIconEvent)>(None);
+    unsafe {
+        let notification: *mut AnyObject = msg_send![response, notification];
+        let request: *mut AnyObject = msg_send![notification, request];
+        let content: *mut AnyObject = msg_send![request, content];
+        let user_info: *mut AnyObject = msg_send![content, userInfo];
+        let key = NSString::from_str(NOTIFICATION_ACTION_KEY);
+        let action_id: Option<Retained<NSString>> = msg_send![user_info, objectForKey: &*key];
+        action_id.map(|action_id| action_id.to_string())
     }
 }
 
-#[no_mangle]
-pub extern "C" fn velox_event_loop_create_proxy(
-    event_loop: *mut VeloxEventLoop,
-) -> *mut VeloxEventLoopProxyHandle {
-    if event_loop.is_null() {
-        return ptr::null_mut();
+/// The system moves attachment files into its own store, so attach a temporary copy.
+#[cfg(target_os = "macos")]
+fn notification_attachment(
+    path: &str,
+    id: u64,
+) -> Option<objc2::rc::Retained<objc2::runtime::AnyObject>> {
+    use objc2::{class, msg_send, runtime::AnyObject};
+    use objc2_foundation::NSString;
+
+    let source = std::path::Path::new(path);
+    let extension = source.extension()?.to_str()?;
+    let copy = std::env::temp_dir().join(format!("velox-notification-{id}.{extension}"));
+    std::fs::copy(source, &copy).ok()?;
+    let copy = NSString::from_str(copy.to_str()?);
+    let identifier = NSString::from_str("icon");
+    unsafe {
+        let url: *mut AnyObject = msg_send![class!(NSURL), fileURLWithPath: &*copy];
+        msg_send![
+            class!(UNNotificationAttachment),
+            attachmentWithIdentifier: &*identifier,
+            URL: url,
+            options: ptr::null_mut::<AnyObject>(),
+            error: ptr::null_mut::<*mut AnyObject>()
+        ]
     }
-
-    #[cfg(all(target_os = "macos", feature = "local-dev"))]
-    tao::platform::macos::force_app_state_launched_for_testing();
-
-    let event_loop = unsafe { &mut *event_loop };
-    let proxy = event_loop.event_loop.create_proxy();
-    Box::into_raw(Box::new(VeloxEventLoopProxyHandle { proxy }))
 }
 
-#[no_mangle]
-pub extern "C" fn velox_event_loop_proxy_request_exit(
-    proxy: *mut VeloxEventLoopProxyHandle,
+#[cfg(target_os = "macos")]
+fn show_notification(
+    title: &str,
+    subtitle: Option<&str>,
+    body: &str,
+    icon_path: Option<&str>,
+    action_id: Option<String>,
+    proxy: Option<EventLoopProxy<VeloxUserEvent>>,
 ) -> bool {
-    if proxy.is_null() {
-        return false;
-    }
-
-    let proxy = unsafe { &mut *proxy };
-    proxy.proxy.send_event(VeloxUserEvent::Exit).is_ok()
-}
+    use block2::RcBlock;
+    use objc2::{
+        class, msg_send,
+        rc::Retained,
+        runtime::{AnyObject, Bool},
+        AllocAnyThread,
+    };
+    use objc2_foundation::NSString;
 
-#[no_mangle]
-pub extern "C" fn velox_event_loop_proxy_send_user_event(
-    proxy: *mut VeloxEventLoopProxyHandle,
-    payload: *const c_char,
-) -> bool {
-    if proxy.is_null() {
-        return false;
-    }
+    static NEXT_NOTIFICATION_ID: AtomicU64 = AtomicU64::new(1);
+    const UN_AUTHORIZATION_OPTIONS: usize = (1 << 0) | (1 << 1) | (1 << 2);
 
-    let proxy = unsafe { &mut *proxy };
-    let message = opt_cstring(payload).unwrap_or_default();
-    proxy
-        .proxy
-        .send_event(VeloxUserEvent::Custom(message))
-        .is_ok()
-}
+    unsafe {
+        // UNUserNotificationCenter raises an exception outside an app bundle.
+        let bundle: *mut AnyObject = msg_send![class!(NSBundle), mainBundle];
+        let bundle_identifier: *mut AnyObject = msg_send![bundle, bundleIdentifier];
+        if bundle_identifier.is_null() {
+            return false;
+        }
+        let center: Option<Retained<AnyObject>> =
+            msg_send![class!(UNUserNotificationCenter), currentNotificationCenter];
+        let Some(center) = center else {
+            return false;
+        };
 
-#[no_mangle]
-pub extern "C" fn velox_event_loop_proxy_free(proxy: *mut VeloxEventLoopProxyHandle) {
-    if !proxy.is_null() {
-        unsafe { drop(Box::from_raw(proxy)) };
-    }
-}
+        if let Some(proxy) = proxy {
+            NOTIFICATION_DELEGATE.with(|delegate| {
+                let mut delegate = delegate.borrow_mut();
+                if delegate.is_none() {
+                    let this = VeloxNotificationDelegate::alloc().set_ivars(proxy);
+                    let created: Retained<VeloxNotificationDelegate> = msg_send![super(this), init];
+                    let _: () = msg_send![&*center, setDelegate: &*created];
+                    *delegate = Some(created);
+                }
+            });
+        }
 
-#[no_mangle]
-pub extern "C" fn velox_event_loop_set_activation_policy(
-    event_loop: *mut VeloxEventLoop,
-    policy: VeloxActivationPolicy,
-) -> bool {
-    #[cfg(target_os = "macos")]
-    {
-        if event_loop.is_null() {
+        let id = NEXT_NOTIFICATION_ID.fetch_add(1, Ordering::Relaxed);
+        let content: Option<Retained<AnyObject>> =
+            msg_send![class!(UNMutableNotificationContent), new];
+        let Some(content) = content else {
             return false;
+        };
+        let _: () = msg_send![&*content, setTitle: &*NSString::from_str(title)];
+        if let Some(subtitle) = subtitle {
+            let _: () = msg_send![&*content, setSubtitle: &*NSString::from_str(subtitle)];
+        }
+        let _: () = msg_send![&*content, setBody: &*NSString::from_str(body)];
+        if let Some(action_id) = action_id {
+            let key = NSString::from_str(NOTIFICATION_ACTION_KEY);
+            let value = NSString::from_str(&action_id);
+            let user_info: *mut AnyObject =
+                msg_send![class!(NSDictionary), dictionaryWithObject: &*value, forKey: &*key];
+            let _: () = msg_send![&*content, setUserInfo: user_info];
+        }
+        if let Some(attachment) = icon_path.and_then(|path| notification_attachment(path, id)) {
+            let attachments: *mut AnyObject =
+                msg_send![class!(NSArray), arrayWithObject: &*attachment];
+            let _: () = msg_send![&*content, setAttachments: attachments];
         }
 
-        let event_loop = unsafe { &mut *event_loop };
-        event_loop
-            .event_loop
-            .set_activation_policy_at_runtime(activation_policy_from_ffi(policy));
+        let identifier = NSString::from_str(&format!("velox-notification-{id}"));
+        let request: Option<Retained<AnyObject>> = msg_send![
+            class!(UNNotificationRequest),
+            requestWithIdentifier: &*identifier,
+            content: &*content,
+            trigger: ptr::null_mut::<AnyObject>()
+        ];
+        let Some(request) = request else {
+            return false;
+        };
+
+        // Authorization is asked once; later requests complete immediately.
+        let notification_center = center.clone();
+        let on_authorized = RcBlock::new(move |granted: Bool, _error: *mut AnyObject| {
+            if granted.as_bool() {
+                let _: () = msg_send![
+                    &*notification_center,
+                    addNotificationRequest: &*request,
+                    withCompletionHandler: None::<&block2::DynBlock<dyn Fn(*mut AnyObject)>>
+                ];
+            }
+        });
+        let _: () = msg_send![
+            &*center,
+            requestAuthorizationWithOptions: UN_AUTHORIZATION_OPTIONS,
+            completionHandler: &*on_authorized
+        ];
         true
     }
+}
 
-    #[cfg(not(target_os = "macos"))]
-    {
-        let _ = (event_loop, policy);
-        false
-    }
+#[cfg(target_os = "windows")]
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
 }
 
-#[no_mangle]
-pub extern "C" fn velox_event_loop_set_dock_visibility(
-    event_loop: *mut VeloxEventLoop,
-    visible: bool,
+/// Toasts need the process's explicit AppUserModelID
+#[cfg(target_os = "windows")]
+fn show_notification(
+    title: &str,
+    subtitle: Option<&str>,
+    body: &str,
+    icon_path: Option<&str>,
+    action_id: Option<String>,
+    proxy: Option<EventLoopProxy<VeloxUserEvent>>,
 ) -> bool {
-    #[cfg(target_os = "macos")]
-    {
-        if event_loop.is_null() {
-            return false;
-        }
-
-        let event_loop = unsafe { &mut *event_loop };
-        event_loop.event_loop.set_dock_visibility(visible);
-        true
-    }
-
-    #[cfg(not(target_os = "macos"))]
-    {
-        let _ = (event_loop, visible);
-        false
+    use windows::core::HSTRING;
+    use windows::Data::Xml::Dom::XmlDocument;
+    use windows::Foundation::TypedEventHandler;
+    use windows::Win32::System::Com::CoTaskMemFree;
+    use windows::Win32::UI::Shell::GetCurrentProcessExplicitAppUserModelID;
+    use windows::UI::Notifications::{ToastNotification, ToastNotificationManager};
+
+    let mut texts = format!("<text>{}</text>", xml_escape(title));
+    if let Some(subtitle) = subtitle {
+        texts.push_str(&format!("<text>{}</text>", xml_escape(subtitle)));
     }
-}
+    texts.push_str(&format!("<text>{}</text>", xml_escape(body)));
+    let image = icon_path
+        .map(|path| {
+            format!(
+                "<image placement=\"appLogoOverride\" src=\"file:///{}\"/>",
+                xml_escape(&path.replace('\\', "/"))
+            )
+        })
+        .unwrap_or_default();
+    let toast_xml = format!(
+        "<toast><visual><binding template=\"ToastGeneric\">{image}{texts}</binding></visual></toast>"
+    );
+
+    let app_id = unsafe { GetCurrentProcessExplicitAppUserModelID() }
+        .ok()
+        .map(|id| {
+            let value = unsafe { id.to_string() }.unwrap_or_default();
+            unsafe { CoTaskMemFree(Some(id.0 as _)) };
+            value
+        })
+        .filter(|id| !id.is_empty());
+    let Some(app_id) = app_id else {
+        set_last_error(
+            VeloxErrorCode::PlatformUnsupported,
+            "notifications need an AppUserModelID; set one with \
+             SetCurrentProcessExplicitAppUserModelID",
+        );
+        return false;
+    };
 
-#[no_mangle]
-pub extern "C" fn velox_event_loop_hide_application(event_loop: *mut VeloxEventLoop) -> bool {
-    #[cfg(target_os = "macos")]
-    {
-        if event_loop.is_null() {
-            return false;
+    let show = || -> windows::core::Result<()> {
+        let document = XmlDocument::new()?;
+        document.LoadXml(&HSTRING::from(toast_xml.as_str()))?;
+        let toast = ToastNotification::CreateToastNotification(&document)?;
+        if let (Some(action_id), Some(proxy)) = (action_id.clone(), proxy.clone()) {
+            toast.Activated(&TypedEventHandler::new(move |_, _| {
+                let _ = proxy.send_event(VeloxUserEvent::NotificationActivated(action_id.clone()));
+                Ok(())
+            }))?;
         }
+        ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(app_id.as_str()))?
+            .Show(&toast)
+    };
+    report_error(show(), VeloxErrorCode::PlatformUnsupported).is_some()
+}
 
-        let event_loop = unsafe { &mut *event_loop };
-        event_loop.event_loop.hide_application();
-        true
+#[cfg(target_os = "linux")]
+fn show_notification(
+    title: &str,
+    subtitle: Option<&str>,
+    body: &str,
+    icon_path: Option<&str>,
+    action_id: Option<String>,
+    proxy: Option<EventLoopProxy<VeloxUserEvent>>,
+) -> bool {
+    // The notification spec has no subtitle, so it leads the body instead.
+    let body = match subtitle {
+        Some(subtitle) => format!("{subtitle}\n{body}"),
+        None => body.to_string(),
+    };
+    let mut notification = notify_rust::Notification::new();
+    notification.summary(title).body(&body);
+    if let Some(icon_path) = icon_path {
+        notification.icon(icon_path);
     }
-
-    #[cfg(not(target_os = "macos"))]
-    {
-        let _ = event_loop;
-        false
+    let activation = action_id.zip(proxy);
+    if activation.is_some() {
+        notification.action("default", "Open");
+    }
+    let Ok(handle) = notification.show() else {
+        return false;
+    };
+    if let Some((action_id, proxy)) = activation {
+        let _ = std::thread::Builder::new()
+            .name("velox-notification".into())
+            .spawn(move || {
+                handle.wait_for_action(|action| {
+                    if action == "default" {
+                        let _ = proxy.send_event(VeloxUserEvent::NotificationActivated(action_id));
+                    }
+                })
+            });
     }
+    true
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn show_notification(
+    _title: &str,
+    _subtitle: Option<&str>,
+    _body: &str,
+    _icon_path: Option<&str>,
+    _action_id: Option<String>,
+    _proxy: Option<EventLoopProxy<VeloxUserEvent>>,
+) -> bool {
+    false
 }
 
+/// Post a desktop notification; clicks arrive as `notification-activated` events
 #[no_mangle]
-pub extern "C" fn velox_event_loop_show_application(event_loop: *mut VeloxEventLoop) -> bool {
-    #[cfg(target_os = "macos")]
-    {
-        if event_loop.is_null() {
+pub extern "C" fn velox_notification_send(options: *const VeloxNotificationOptions) -> bool {
+    guard_panic_bool(|| {
+        let Some(options) = (unsafe { options.as_ref() }) else {
             return false;
-        }
-
-        let event_loop = unsafe { &mut *event_loop };
-        event_loop.event_loop.show_application();
-        true
-    }
+        };
+        let Some(title) = opt_cstring(options.title) else {
+            return false;
+        };
+        let body = opt_cstring(options.body).unwrap_or_default();
+        let subtitle = opt_cstring(options.subtitle);
+        let icon_path = opt_cstring(options.icon_path);
+        let action_id = opt_cstring(options.action_id);
+        let proxy = USER_EVENT_PROXY.with(|proxy| proxy.borrow().clone());
+        show_notification(
+            &title,
+            subtitle.as_deref(),
+            &body,
+            icon_path.as_deref(),
+            action_id,
+            proxy,
+        )
+    })
+}
 
-    #[cfg(not(target_os = "macos"))]
-    {
-        let _ = event_loop;
-        false
-    }
+fn with_clipboard<R>(f: impl FnOnce(&mut arboard::Clipboard) -> Option<R>) -> Option<R> {
+    CLIPBOARD.with(|clipboard| {
+        let mut clipboard = clipboard.borrow_mut();
+        if clipboard.is_none() {
+            *clipboard = report_error(
+                arboard::Clipboard::new(),
+                VeloxErrorCode::PlatformUnsupported,
+            );
+        }
+        f(clipboard.as_mut()?)
+    })
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-fn accelerator_from_ptr(ptr: *const c_char) -> Option<Accelerator> {
-    opt_cstring(ptr)?.parse().ok()
+/// Read the clipboard text, or null; release it with `velox_clipboard_text_free`
+#[no_mangle]
+pub extern "C" fn velox_clipboard_read_text() -> *mut c_char {
+    guard_panic(|| {
+        with_clipboard(|clipboard| report_error(clipboard.get_text(), VeloxErrorCode::IoError))
+            .and_then(|text| CString::new(text).ok())
+            .map_or(ptr::null_mut(), CString::into_raw)
+    })
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-fn native_icon_from_ptr(ptr: *const c_char) -> Option<NativeIcon> {
-    let value = opt_cstring(ptr)?;
-    let quoted = format!("\"{}\"", value);
-    serde_json::from_str(&quoted).ok()
+#[no_mangle]
+pub extern "C" fn velox_clipboard_text_free(text: *mut c_char) {
+    if !text.is_null() {
+        unsafe { drop(CString::from_raw(text)) };
+    }
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[repr(C)]
-#[derive(Clone, Copy, Debug)]
-pub enum VeloxMenuItemKind {
-    MenuItem = 0,
-    Predefined = 1,
-    Check = 2,
-    Icon = 3,
-    Submenu = 4,
+#[no_mangle]
+pub extern "C" fn velox_clipboard_write_text(text: *const c_char) -> bool {
+    velox_clipboard_write_text_checked(text) == VeloxErrorCode::Success
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 #[no_mangle]
-pub extern "C" fn velox_menu_bar_new() -> *mut VeloxMenuBarHandle {
-    guard_panic(|| {
-        let menu = Menu::new();
-        let identifier = CString::new(menu.id().as_ref()).expect("menu id contains null byte");
-        Box::into_raw(Box::new(VeloxMenuBarHandle {
-            menu,
-            submenus: Vec::new(),
-            items: Vec::new(),
-            identifier,
-        }))
+pub extern "C" fn velox_clipboard_write_text_checked(text: *const c_char) -> VeloxErrorCode {
+    guard_panic_value(|| {
+        let Some(text) = opt_cstring(text) else {
+            return VeloxErrorCode::NullPointer;
+        };
+        with_clipboard(|clipboard| {
+            Some(record_error(
+                clipboard.set_text(text),
+                VeloxErrorCode::IoError,
+            ))
+        })
+        .map_or(
+            VeloxErrorCode::PlatformUnsupported,
+            VeloxErrorCode::from_result,
+        )
     })
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
+/// Read the clipboard image as RGBA, or null; release it with `velox_clipboard_image_free`
 #[no_mangle]
-pub extern "C" fn velox_menu_bar_new_with_id(id: *const c_char) -> *mut VeloxMenuBarHandle {
+pub extern "C" fn velox_clipboard_read_image(width: *mut u32, height: *mut u32) -> *mut u8 {
     guard_panic(|| {
-        let identifier_string = opt_cstring(id).unwrap_or_default();
-        let menu = Menu::with_id(MenuId::new(identifier_string.clone()));
-        let identifier = CString::new(identifier_string).expect("menu id contains null byte");
-        Box::into_raw(Box::new(VeloxMenuBarHandle {
-            menu,
-            submenus: Vec::new(),
-            items: Vec::new(),
-            identifier,
-        }))
+        let Some(image) = with_clipboard(|clipboard| {
+            report_error(clipboard.get_image(), VeloxErrorCode::IoError)
+        }) else {
+            return ptr::null_mut();
+        };
+        if let Some(width) = unsafe { width.as_mut() } {
+            *width = image.width as u32;
+        }
+        if let Some(height) = unsafe { height.as_mut() } {
+            *height = image.height as u32;
+        }
+        Box::into_raw(image.bytes.into_owned().into_boxed_slice()) as *mut u8
     })
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
+/// Release a buffer returned by `velox_clipboard_read_image`
 #[no_mangle]
-pub extern "C" fn velox_menu_bar_free(menu: *mut VeloxMenuBarHandle) {
-    if !menu.is_null() {
-        unsafe { drop(Box::from_raw(menu)) };
-    }
+pub extern "C" fn velox_clipboard_image_free(data: *mut u8, width: u32, height: u32) {
+    velox_screenshot_free(data, width, height);
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 #[no_mangle]
-pub extern "C" fn velox_menu_bar_identifier(menu: *mut VeloxMenuBarHandle) -> *const c_char {
-    let Some(menu) = (unsafe { menu.as_ref() }) else {
-        return ptr::null();
-    };
-    menu.identifier.as_ptr()
+pub extern "C" fn velox_clipboard_write_image(rgba: *const u8, width: u32, height: u32) -> bool {
+    if rgba.is_null() || width == 0 || height == 0 {
+        return false;
+    }
+    guard_panic_bool(|| {
+        let len = width as usize * height as usize * 4;
+        let bytes = unsafe { std::slice::from_raw_parts(rgba, len) };
+        let image = arboard::ImageData {
+            width: width as usize,
+            height: height as usize,
+            bytes: bytes.into(),
+        };
+        with_clipboard(|clipboard| {
+            report_error(clipboard.set_image(image), VeloxErrorCode::IoError)
+        })
+        .is_some()
+    })
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
+/// Register a global shortcut, reported as a `hotkey` event; reusing an id replaces it
 #[no_mangle]
-pub extern "C" fn velox_menu_bar_append_submenu(
-    menu: *mut VeloxMenuBarHandle,
-    submenu: *mut VeloxSubmenuHandle,
+pub extern "C" fn velox_hotkey_register(
+    event_loop: *mut VeloxEventLoop,
+    config: *const VeloxHotkeyConfig,
 ) -> bool {
-    let Some(menu) = (unsafe { menu.as_mut() }) else {
-        return false;
+    velox_hotkey_register_checked(event_loop, config) == VeloxErrorCode::Success
+}
+
+#[no_mangle]
+pub extern "C" fn velox_hotkey_register_checked(
+    event_loop: *mut VeloxEventLoop,
+    config: *const VeloxHotkeyConfig,
+) -> VeloxErrorCode {
+    guard_panic_value(|| VeloxErrorCode::from_result(register_hotkey(event_loop, config)))
+}
+
+fn register_hotkey(
+    event_loop: *mut VeloxEventLoop,
+    config: *const VeloxHotkeyConfig,
+) -> Result<(), VeloxErrorCode> {
+    let Some(event_loop) = (unsafe { event_loop.as_ref() }) else {
+        return Err(VeloxErrorCode::NullPointer);
     };
-    let Some(submenu) = (unsafe { submenu.as_ref() }) else {
-        return false;
+    let Some(config) = (unsafe { config.as_ref() }) else {
+        return Err(VeloxErrorCode::NullPointer);
     };
-
-    let result = {
-        let submenu_ref = submenu.submenu.borrow();
-        menu.menu.append(&*submenu_ref)
+    let (Some(id), Some(accelerator)) = (opt_cstring(config.id), opt_cstring(config.accelerator))
+    else {
+        return Err(VeloxErrorCode::NullPointer);
     };
+    let hotkey = record_error(
+        accelerator.parse::<HotKey>(),
+        VeloxErrorCode::InvalidArgument,
+    )?;
+
+    let mut registry = event_loop.hotkeys.borrow_mut();
+    if registry.is_none() {
+        let manager = record_error(
+            GlobalHotKeyManager::new(),
+            VeloxErrorCode::PlatformUnsupported,
+        )?;
+        *registry = Some(HotkeyRegistry {
+            manager,
+            hotkeys: HashMap::new(),
+        });
+    }
+    let Some(registry) = registry.as_mut() else {
+        return Err(VeloxErrorCode::Unknown);
+    };
+    if registry.hotkeys.get(&id) == Some(&hotkey) {
+        return Ok(());
+    }
+    // Keep the old binding until the new one is registered, so a failure leaves it working.
+    record_error(
+        registry.manager.register(hotkey),
+        VeloxErrorCode::InvalidArgument,
+    )?;
+    if let Some(previous) = registry.hotkeys.remove(&id) {
+        unregister_hotkey(registry, previous);
+    }
+    if let Ok(mut ids) = HOTKEY_IDS.get_or_init(Default::default).lock() {
+        ids.insert(hotkey.id(), id.clone());
+    }
+    registry.hotkeys.insert(id, hotkey);
+    Ok(())
+}
 
-    if result.is_ok() {
-        menu.submenus.push(submenu.submenu.clone());
-        menu
-            .items
-            .push(MenuItemKind::Submenu(submenu.submenu.borrow().clone()));
-        true
-    } else {
-        false
+fn unregister_hotkey(registry: &HotkeyRegistry, hotkey: HotKey) {
+    let _ = registry.manager.unregister(hotkey);
+    if let Some(ids) = HOTKEY_IDS.get() {
+        if let Ok(mut ids) = ids.lock() {
+            ids.remove(&hotkey.id());
+        }
     }
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 #[no_mangle]
-pub extern "C" fn velox_menu_bar_append(
-    menu: *mut VeloxMenuBarHandle,
-    kind: VeloxMenuItemKind,
-    item: *mut c_void,
+pub extern "C" fn velox_hotkey_unregister(
+    event_loop: *mut VeloxEventLoop,
+    id: *const c_char,
 ) -> bool {
-    let Some(menu) = (unsafe { menu.as_mut() }) else {
+    let Some(event_loop) = (unsafe { event_loop.as_ref() }) else {
         return false;
     };
-
-    match kind {
-        VeloxMenuItemKind::MenuItem => {
-            let Some(item) = (unsafe { (item as *mut VeloxMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            if menu.menu.append(&item.item).is_ok() {
-                menu.items.push(MenuItemKind::MenuItem(item.item.clone()));
-                true
-            } else {
-                false
-            }
-        }
-        VeloxMenuItemKind::Predefined => {
-            let Some(item) = (unsafe { (item as *mut VeloxPredefinedMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            if menu.menu.append(&item.item).is_ok() {
-                menu.items.push(MenuItemKind::Predefined(item.item.clone()));
-                true
-            } else {
-                false
-            }
-        }
-        VeloxMenuItemKind::Check => {
-            let Some(item) = (unsafe { (item as *mut VeloxCheckMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            if menu.menu.append(&item.item).is_ok() {
-                menu.items.push(MenuItemKind::Check(item.item.clone()));
-                true
-            } else {
-                false
-            }
-        }
-        VeloxMenuItemKind::Icon => {
-            let Some(item) = (unsafe { (item as *mut VeloxIconMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            if menu.menu.append(&item.item).is_ok() {
-                menu.items.push(MenuItemKind::Icon(item.item.clone()));
-                true
-            } else {
-                false
-            }
-        }
-        VeloxMenuItemKind::Submenu => {
-            let Some(item) = (unsafe { (item as *mut VeloxSubmenuHandle).as_ref() }) else {
-                return false;
-            };
-            let submenu_ref = item.submenu.borrow();
-            if menu.menu.append(&*submenu_ref).is_ok() {
-                menu.submenus.push(item.submenu.clone());
-                menu.items.push(MenuItemKind::Submenu(submenu_ref.clone()));
-                true
-            } else {
-                false
-            }
-        }
-    }
-}
-
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_menu_bar_prepend(
-    menu: *mut VeloxMenuBarHandle,
-    kind: VeloxMenuItemKind,
-    item: *mut c_void,
-) -> bool {
-    let Some(menu) = (unsafe { menu.as_mut() }) else {
+    let Some(id) = opt_cstring(id) else {
         return false;
     };
-
-    match kind {
-        VeloxMenuItemKind::MenuItem => {
-            let Some(item) = (unsafe { (item as *mut VeloxMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            if menu.menu.prepend(&item.item).is_ok() {
-                menu.items.insert(0, MenuItemKind::MenuItem(item.item.clone()));
-                true
-            } else {
-                false
-            }
-        }
-        VeloxMenuItemKind::Predefined => {
-            let Some(item) = (unsafe { (item as *mut VeloxPredefinedMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            if menu.menu.prepend(&item.item).is_ok() {
-                menu.items.insert(0, MenuItemKind::Predefined(item.item.clone()));
-                true
-            } else {
-                false
-            }
-        }
-        VeloxMenuItemKind::Check => {
-            let Some(item) = (unsafe { (item as *mut VeloxCheckMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            if menu.menu.prepend(&item.item).is_ok() {
-                menu.items.insert(0, MenuItemKind::Check(item.item.clone()));
-                true
-            } else {
-                false
-            }
-        }
-        VeloxMenuItemKind::Icon => {
-            let Some(item) = (unsafe { (item as *mut VeloxIconMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            if menu.menu.prepend(&item.item).is_ok() {
-                menu.items.insert(0, MenuItemKind::Icon(item.item.clone()));
-                true
-            } else {
-                false
-            }
-        }
-        VeloxMenuItemKind::Submenu => {
-            let Some(item) = (unsafe { (item as *mut VeloxSubmenuHandle).as_ref() }) else {
-                return false;
-            };
-            let submenu_ref = item.submenu.borrow();
-            if menu.menu.prepend(&*submenu_ref).is_ok() {
-                menu.submenus.push(item.submenu.clone());
-                menu.items.insert(0, MenuItemKind::Submenu(submenu_ref.clone()));
-                true
-            } else {
-                false
-            }
-        }
-    }
-}
-
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_menu_bar_insert(
-    menu: *mut VeloxMenuBarHandle,
-    kind: VeloxMenuItemKind,
-    item: *mut c_void,
-    position: usize,
-) -> bool {
-    let Some(menu) = (unsafe { menu.as_mut() }) else {
+    let mut registry = event_loop.hotkeys.borrow_mut();
+    let Some(registry) = registry.as_mut() else {
+        return false;
+    };
+    let Some(hotkey) = registry.hotkeys.remove(&id) else {
         return false;
     };
+    unregister_hotkey(registry, hotkey);
+    true
+}
 
-    let insert_index = position.min(menu.items.len());
-    match kind {
-        VeloxMenuItemKind::MenuItem => {
-            let Some(item) = (unsafe { (item as *mut VeloxMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            if menu.menu.insert(&item.item, position).is_ok() {
-                menu.items.insert(insert_index, MenuItemKind::MenuItem(item.item.clone()));
-                true
-            } else {
-                false
-            }
-        }
-        VeloxMenuItemKind::Predefined => {
-            let Some(item) = (unsafe { (item as *mut VeloxPredefinedMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            if menu.menu.insert(&item.item, position).is_ok() {
-                menu.items.insert(insert_index, MenuItemKind::Predefined(item.item.clone()));
-                true
-            } else {
-                false
-            }
-        }
-        VeloxMenuItemKind::Check => {
-            let Some(item) = (unsafe { (item as *mut VeloxCheckMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            if menu.menu.insert(&item.item, position).is_ok() {
-                menu.items.insert(insert_index, MenuItemKind::Check(item.item.clone()));
-                true
-            } else {
-                false
-            }
-        }
-        VeloxMenuItemKind::Icon => {
-            let Some(item) = (unsafe { (item as *mut VeloxIconMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            if menu.menu.insert(&item.item, position).is_ok() {
-                menu.items.insert(insert_index, MenuItemKind::Icon(item.item.clone()));
-                true
-            } else {
-                false
-            }
-        }
-        VeloxMenuItemKind::Submenu => {
-            let Some(item) = (unsafe { (item as *mut VeloxSubmenuHandle).as_ref() }) else {
-                return false;
-            };
-            let submenu_ref = item.submenu.borrow();
-            if menu.menu.insert(&*submenu_ref, position).is_ok() {
-                menu.submenus.push(item.submenu.clone());
-                menu.items.insert(insert_index, MenuItemKind::Submenu(submenu_ref.clone()));
-                true
-            } else {
-                false
-            }
-        }
-    }
+/// Open an http(s) or mailto URL with the system's default handler
+#[no_mangle]
+pub extern "C" fn velox_open_url_in_default_browser(url: *const c_char) -> bool {
+    velox_open_url_in_default_browser_checked(url) == VeloxErrorCode::Success
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 #[no_mangle]
-pub extern "C" fn velox_menu_bar_remove(
-    menu: *mut VeloxMenuBarHandle,
-    kind: VeloxMenuItemKind,
-    item: *mut c_void,
-) -> bool {
-    let Some(menu) = (unsafe { menu.as_mut() }) else {
-        return false;
+pub extern "C" fn velox_open_url_in_default_browser_checked(url: *const c_char) -> VeloxErrorCode {
+    let Some(url) = opt_cstring(url) else {
+        return VeloxErrorCode::NullPointer;
     };
+    VeloxErrorCode::from_result(
+        record_error(browser_url(&url), VeloxErrorCode::InvalidArgument).and_then(|url| {
+            record_error(open::that_detached(url.as_str()), VeloxErrorCode::IoError)
+        }),
+    )
+}
 
-    match kind {
-        VeloxMenuItemKind::MenuItem => {
-            let Some(item) = (unsafe { (item as *mut VeloxMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            menu.menu.remove(&item.item).is_ok()
-        }
-        VeloxMenuItemKind::Predefined => {
-            let Some(item) = (unsafe { (item as *mut VeloxPredefinedMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            menu.menu.remove(&item.item).is_ok()
-        }
-        VeloxMenuItemKind::Check => {
-            let Some(item) = (unsafe { (item as *mut VeloxCheckMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            menu.menu.remove(&item.item).is_ok()
-        }
-        VeloxMenuItemKind::Icon => {
-            let Some(item) = (unsafe { (item as *mut VeloxIconMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            menu.menu.remove(&item.item).is_ok()
-        }
-        VeloxMenuItemKind::Submenu => {
-            let Some(item) = (unsafe { (item as *mut VeloxSubmenuHandle).as_ref() }) else {
-                return false;
-            };
-            let submenu_ref = item.submenu.borrow();
-            menu.menu.remove(&*submenu_ref).is_ok()
-        }
+const BROWSER_URL_SCHEMES: [&str; 3] = ["http", "https", "mailto"];
+
+/// Parse `url`, refusing schemes that would launch something other than a browser or mail client
+fn browser_url(url: &str) -> Result<Url, String> {
+    let url = Url::parse(url).map_err(|error| error.to_string())?;
+    if BROWSER_URL_SCHEMES.contains(&url.scheme()) {
+        Ok(url)
+    } else {
+        Err(format!("unsupported URL scheme `{}`", url.scheme()))
     }
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
+/// Open the folder containing `path` with it selected in the file manager
 #[no_mangle]
-pub extern "C" fn velox_menu_bar_remove_at(
-    menu: *mut VeloxMenuBarHandle,
-    position: usize,
-) -> bool {
-    let Some(menu) = (unsafe { menu.as_mut() }) else {
+pub extern "C" fn velox_reveal_path_in_file_manager(path: *const c_char) -> bool {
+    let Some(path) = opt_cstring(path) else {
+        return false;
+    };
+    let Ok(path) = PathBuf::from(path).canonicalize() else {
         return false;
     };
-    menu.menu.remove_at(position).is_some()
+    reveal_path(&path)
 }
 
 #[cfg(target_os = "macos")]
-#[no_mangle]
-pub extern "C" fn velox_menu_bar_set_app_menu(menu: *mut VeloxMenuBarHandle) -> bool {
-    let Some(menu) = (unsafe { menu.as_ref() }) else {
-        return false;
+fn reveal_path(path: &std::path::Path) -> bool {
+    std::process::Command::new("open")
+        .arg("-R")
+        .arg(path)
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+#[cfg(target_os = "windows")]
+fn reveal_path(path: &std::path::Path) -> bool {
+    // `canonicalize` yields verbatim `\\?\` paths, which Explorer does not accept.
+    let path = path.to_string_lossy();
+    let path = match path.strip_prefix(r"\\?\") {
+        Some(unc) if unc.starts_with(r"UNC\") => format!(r"\\{}", &unc[4..]),
+        Some(local) => local.to_string(),
+        None => path.into_owned(),
     };
-    menu.menu.init_for_nsapp();
-    true
+    let mut argument = std::ffi::OsString::from("/select,");
+    argument.push(path);
+    // Explorer reports a failure exit code even when it opened the window.
+    report_error(
+        std::process::Command::new("explorer").arg(argument).spawn(),
+        VeloxErrorCode::IoError,
+    )
+    .is_some()
+}
+
+#[cfg(target_os = "linux")]
+fn reveal_path(path: &std::path::Path) -> bool {
+    let shown = Url::from_file_path(path).is_ok_and(|uri| {
+        std::process::Command::new("dbus-send")
+            .args([
+                "--session",
+                "--dest=org.freedesktop.FileManager1",
+                "--type=method_call",
+                "/org/freedesktop/FileManager1",
+                "org.freedesktop.FileManager1.ShowItems",
+            ])
+            // dbus-send splits array elements on commas, which file URLs leave unescaped.
+            .arg(format!("array:string:{}", uri.as_str().replace(',', "%2C")))
+            .arg("string:")
+            .status()
+            .is_ok_and(|status| status.success())
+    });
+    shown
+        || path
+            .parent()
+            .is_some_and(|parent| open::that_detached(parent).is_ok())
 }
 
 #[cfg(target_os = "macos")]
-#[no_mangle]
-pub extern "C" fn velox_menu_bar_popup(
-    menu: *mut VeloxMenuBarHandle,
-    window: *mut VeloxWindowHandle,
-    x: f64,
-    y: f64,
-    has_position: bool,
-    is_logical: bool,
-) -> bool {
-    let Some(menu) = (unsafe { menu.as_ref() }) else {
-        return false;
-    };
-    let Some(window) = (unsafe { window.as_ref() }) else {
-        return false;
-    };
+fn activation_policy_from_ffi(policy: VeloxActivationPolicy) -> ActivationPolicy {
+    match policy {
+        VeloxActivationPolicy::Regular => ActivationPolicy::Regular,
+        VeloxActivationPolicy::Accessory => ActivationPolicy::Accessory,
+        VeloxActivationPolicy::Prohibited => ActivationPolicy::Prohibited,
+    }
+}
 
-    let position = if has_position {
-        if is_logical {
-            Some(muda::dpi::Position::Logical(LogicalPosition { x, y }))
-        } else {
-            let px = x.round() as i32;
-            let py = y.round() as i32;
-            Some(muda::dpi::Position::Physical(PhysicalPosition { x: px, y: py }))
+fn monitor_work_area(monitor: &MonitorHandle) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+    #[cfg(target_os = "macos")]
+    {
+        use objc2::{msg_send, runtime::AnyObject};
+        use objc2_foundation::NSRect;
+        use tao::platform::macos::MonitorHandleExtMacOS;
+
+        if let Some(screen) = monitor.ns_screen() {
+            let screen = unsafe { &*(screen as *const AnyObject) };
+            let frame: NSRect = unsafe { msg_send![screen, frame] };
+            let visible: NSRect = unsafe { msg_send![screen, visibleFrame] };
+            let scale = monitor.scale_factor();
+            let position = monitor.position();
+            // Cocoa uses a bottom-left origin, so measure the insets relative to the
+            // screen frame and apply them to tao's top-left based physical bounds.
+            let left = (visible.origin.x - frame.origin.x) * scale;
+            let top = ((frame.origin.y + frame.size.height)
+                - (visible.origin.y + visible.size.height))
+                * scale;
+            return (
+                PhysicalPosition::new(
+                    position.x + left.round() as i32,
+                    position.y + top.round() as i32,
+                ),
+                PhysicalSize::new(
+                    (visible.size.width * scale).round() as u32,
+                    (visible.size.height * scale).round() as u32,
+                ),
+            );
         }
-    } else {
-        None
-    };
+    }
 
-    unsafe { menu.menu.show_context_menu_for_nsview(window.window.ns_view(), position) }
-}
+    #[cfg(target_os = "windows")]
+    {
+        use tao::platform::windows::MonitorHandleExtWindows;
+        use windows::Win32::Graphics::Gdi::{GetMonitorInfoW, HMONITOR, MONITORINFO};
 
-#[cfg(target_os = "linux")]
-#[no_mangle]
-pub extern "C" fn velox_menu_bar_set_app_menu(_menu: *mut VeloxMenuBarHandle) -> bool {
-    // No NSApp equivalent on Linux — menus are per-window via GTK
-    false
+        let mut info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        let hmonitor = HMONITOR(monitor.hmonitor() as *mut c_void);
+        if unsafe { GetMonitorInfoW(hmonitor, &mut info) }.as_bool() {
+            let work = info.rcWork;
+            return (
+                PhysicalPosition::new(work.left, work.top),
+                PhysicalSize::new(
+                    (work.right - work.left).max(0) as u32,
+                    (work.bottom - work.top).max(0) as u32,
+                ),
+            );
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let scale = monitor.scale_factor();
+        let position = monitor.position();
+        let x = (position.x as f64 / scale).round() as i32;
+        let y = (position.y as f64 / scale).round() as i32;
+        if let Some(gdk_monitor) =
+            gtk::gdk::Display::default().and_then(|display| display.monitor_at_point(x, y))
+        {
+            let work = gdk_monitor.workarea();
+            return (
+                PhysicalPosition::new(
+                    (work.x() as f64 * scale).round() as i32,
+                    (work.y() as f64 * scale).round() as i32,
+                ),
+                PhysicalSize::new(
+                    (work.width() as f64 * scale).round() as u32,
+                    (work.height() as f64 * scale).round() as u32,
+                ),
+            );
+        }
+    }
+
+    (monitor.position(), monitor.size())
 }
 
-#[cfg(target_os = "linux")]
-#[no_mangle]
-pub extern "C" fn velox_menu_bar_popup(
-    menu: *mut VeloxMenuBarHandle,
-    window: *mut VeloxWindowHandle,
-    x: f64,
-    y: f64,
-    has_position: bool,
-    is_logical: bool,
-) -> bool {
-    let Some(menu) = (unsafe { menu.as_ref() }) else {
-        return false;
-    };
-    let Some(window) = (unsafe { window.as_ref() }) else {
-        return false;
-    };
+fn monitor_refresh_rate(monitor: &MonitorHandle) -> Option<f64> {
+    #[cfg(target_os = "macos")]
+    {
+        use objc2::{msg_send, runtime::AnyObject};
+        use tao::platform::macos::MonitorHandleExtMacOS;
 
-    let position = if has_position {
-        if is_logical {
-            Some(muda::dpi::Position::Logical(LogicalPosition { x, y }))
-        } else {
-            let px = x.round() as i32;
-            let py = y.round() as i32;
-            Some(muda::dpi::Position::Physical(PhysicalPosition { x: px, y: py }))
+        let screen = monitor.ns_screen()?;
+        let screen = unsafe { &*(screen as *const AnyObject) };
+        let fps: isize = unsafe { msg_send![screen, maximumFramesPerSecond] };
+        return (fps > 0).then_some(fps as f64);
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use tao::platform::windows::MonitorHandleExtWindows;
+        use windows::core::PCWSTR;
+        use windows::Win32::Graphics::Gdi::{
+            EnumDisplaySettingsW, GetMonitorInfoW, DEVMODEW, ENUM_CURRENT_SETTINGS, HMONITOR,
+            MONITORINFO, MONITORINFOEXW,
+        };
+
+        let mut info = MONITORINFOEXW::default();
+        info.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+        let hmonitor = HMONITOR(monitor.hmonitor() as *mut c_void);
+        if !unsafe { GetMonitorInfoW(hmonitor, &mut info as *mut _ as *mut MONITORINFO) }.as_bool()
+        {
+            return None;
         }
-    } else {
-        None
-    };
+        let mut mode = DEVMODEW {
+            dmSize: std::mem::size_of::<DEVMODEW>() as u16,
+            ..Default::default()
+        };
+        let found = unsafe {
+            EnumDisplaySettingsW(
+                PCWSTR(info.szDevice.as_ptr()),
+                ENUM_CURRENT_SETTINGS,
+                &mut mode,
+            )
+        }
+        .as_bool();
+        // A frequency of 0 or 1 means "hardware default" rather than a real rate.
+        return (found && mode.dmDisplayFrequency > 1).then_some(mode.dmDisplayFrequency as f64);
+    }
 
-    unsafe { menu.menu.show_context_menu_for_gtk_window(window.window.gtk_window().as_ref(), position) }
-}
+    #[cfg(target_os = "linux")]
+    {
+        let scale = monitor.scale_factor();
+        let position = monitor.position();
+        let x = (position.x as f64 / scale).round() as i32;
+        let y = (position.y as f64 / scale).round() as i32;
+        let gdk_monitor =
+            gtk::gdk::Display::default().and_then(|display| display.monitor_at_point(x, y))?;
+        // GDK reports the refresh rate in milli-Hertz.
+        let millihertz = gdk_monitor.refresh_rate();
+        return (millihertz > 0).then_some(millihertz as f64 / 1000.0);
+    }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_submenu_new(
-    title: *const c_char,
-    enabled: bool,
-) -> *mut VeloxSubmenuHandle {
-    guard_panic(|| {
-        let title = opt_cstring(title).unwrap_or_default();
-        let submenu = Submenu::new(title, enabled);
-        let identifier =
-            CString::new(submenu.id().as_ref()).expect("submenu id contains null byte");
-        Box::into_raw(Box::new(VeloxSubmenuHandle {
-            submenu: Rc::new(RefCell::new(submenu)),
-            identifier,
-            items: Vec::new(),
-        }))
-    })
+    #[allow(unreachable_code)]
+    {
+        let _ = monitor;
+        None
+    }
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_submenu_new_with_id(
-    id: *const c_char,
-    title: *const c_char,
-    enabled: bool,
-) -> *mut VeloxSubmenuHandle {
-    guard_panic(|| {
-        let title = opt_cstring(title).unwrap_or_default();
-        let id_string = opt_cstring(id).unwrap_or_default();
-        let submenu = Submenu::with_id(MenuId::new(id_string.clone()), title, enabled);
-        let identifier = CString::new(id_string).expect("submenu id contains null byte");
-        Box::into_raw(Box::new(VeloxSubmenuHandle {
-            submenu: Rc::new(RefCell::new(submenu)),
-            identifier,
-            items: Vec::new(),
-        }))
+fn monitor_to_json(monitor: &MonitorHandle) -> serde_json::Value {
+    let name = monitor.name().unwrap_or_default();
+    let position = monitor.position();
+    let size = monitor.size();
+    let (work_position, work_size) = monitor_work_area(monitor);
+    let video_modes: Vec<VideoMode> = monitor.video_modes().collect();
+    let refresh_rate = monitor_refresh_rate(monitor);
+    let refresh_hz = refresh_rate.map(|rate| rate.round() as u16);
+    let current_mode = current_video_mode(
+        &video_modes,
+        |mode| (mode.size(), mode.refresh_rate(), mode.bit_depth()),
+        size,
+        refresh_hz,
+    );
+    json!({
+        "name": name,
+        "scale_factor": monitor.scale_factor(),
+        "refresh_rate_millihertz": refresh_rate.map(|rate| (rate * 1000.0).round() as u32),
+        "bit_depth": current_mode.map(|mode| mode.bit_depth()),
+        "video_modes": video_modes.iter().map(video_mode_to_json).collect::<Vec<_>>(),
+        "position": {
+            "x": position.x,
+            "y": position.y,
+        },
+        "size": {
+            "width": size.width,
+            "height": size.height,
+        },
+        "work_area": {
+            "position": {
+                "x": work_position.x,
+                "y": work_position.y,
+            },
+            "size": {
+                "width": work_size.width,
+                "height": work_size.height,
+            }
+        }
     })
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_submenu_free(submenu: *mut VeloxSubmenuHandle) {
-    if !submenu.is_null() {
-        unsafe { drop(Box::from_raw(submenu)) };
-    }
+/// tao has no notion of the active mode, so pick the closest match by size and rate
+fn current_video_mode<M>(
+    modes: &[M],
+    describe: impl Fn(&M) -> (PhysicalSize<u32>, u16, u16),
+    size: PhysicalSize<u32>,
+    refresh_hz: Option<u16>,
+) -> Option<&M> {
+    modes
+        .iter()
+        .map(|mode| (mode, describe(mode)))
+        .filter(|(_, (mode_size, _, _))| *mode_size == size)
+        .filter(|(_, (_, rate, _))| refresh_hz.is_none() || refresh_hz == Some(*rate))
+        .max_by_key(|(_, (_, rate, bit_depth))| (*rate, *bit_depth))
+        .map(|(mode, _)| mode)
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_submenu_identifier(submenu: *mut VeloxSubmenuHandle) -> *const c_char {
-    let Some(submenu) = (unsafe { submenu.as_ref() }) else {
-        return ptr::null();
-    };
-    submenu.identifier.as_ptr()
+fn video_mode_to_json(mode: &VideoMode) -> serde_json::Value {
+    let size = mode.size();
+    json!({
+        "width": size.width,
+        "height": size.height,
+        "refresh_rate": mode.refresh_rate(),
+        "refresh_rate_millihertz": u32::from(mode.refresh_rate()) * 1000,
+        "bit_depth": mode.bit_depth(),
+    })
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_submenu_text(submenu: *mut VeloxSubmenuHandle) -> *const c_char {
-    guard_panic_value(|| {
-        let Some(submenu) = (unsafe { submenu.as_ref() }) else {
-            return ptr::null();
-        };
-        write_string_to_buffer(&TITLE_BUFFER, submenu.submenu.borrow().text())
+fn write_json_to_buffer(
+    buffer: &'static LocalKey<RefCell<CString>>,
+    value: serde_json::Value,
+) -> *const c_char {
+    let json_string = value.to_string();
+    buffer.with(|cell| {
+        let mut storage = cell.borrow_mut();
+        *storage =
+            CString::new(json_string).unwrap_or_else(|_| CString::new("{}").expect("static JSON"));
+        storage.as_ptr()
     })
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_submenu_set_text(
-    submenu: *mut VeloxSubmenuHandle,
-    title: *const c_char,
-) -> bool {
-    guard_panic_bool(|| {
-        let Some(submenu) = (unsafe { submenu.as_mut() }) else {
-            return false;
-        };
-        let text = opt_cstring(title).unwrap_or_default();
-        submenu.submenu.borrow_mut().set_text(text);
-        true
+fn write_string_to_buffer(
+    buffer: &'static LocalKey<RefCell<CString>>,
+    value: String,
+) -> *const c_char {
+    buffer.with(|cell| {
+        let mut storage = cell.borrow_mut();
+        *storage = CString::new(value).unwrap_or_else(|_| CString::new("").expect("empty string"));
+        storage.as_ptr()
     })
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_submenu_is_enabled(submenu: *mut VeloxSubmenuHandle) -> bool {
-    guard_panic_bool(|| {
-        let Some(submenu) = (unsafe { submenu.as_ref() }) else {
-            return false;
+/// Failure categories reported by the `_checked` functions and `velox_last_error_code`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VeloxErrorCode {
+    Success = 0,
+    NullPointer = 1,
+    InvalidArgument = 2,
+    PlatformUnsupported = 3,
+    WindowCreationFailed = 4,
+    WebviewCreationFailed = 5,
+    IoError = 6,
+    /// Also reported when a panic is caught at the FFI boundary, hence the `Default`
+    Unknown = 7,
+}
+
+impl Default for VeloxErrorCode {
+    fn default() -> Self {
+        Self::Unknown
+    }
+}
+
+impl VeloxErrorCode {
+    fn from_result(result: Result<(), VeloxErrorCode>) -> Self {
+        result.err().unwrap_or(Self::Success)
+    }
+
+    fn from_external(result: Result<(), tao::error::ExternalError>) -> Self {
+        let code = match &result {
+            Err(tao::error::ExternalError::NotSupported(_)) => Self::PlatformUnsupported,
+            _ => Self::Unknown,
         };
-        submenu.submenu.borrow().is_enabled()
-    })
+        Self::from_result(record_error(result, code))
+    }
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_submenu_set_enabled(
-    submenu: *mut VeloxSubmenuHandle,
-    enabled: bool,
-) -> bool {
-    let Some(submenu) = (unsafe { submenu.as_mut() }) else {
-        return false;
-    };
-    submenu.submenu.borrow_mut().set_enabled(enabled);
-    true
+fn set_last_error(code: VeloxErrorCode, message: &str) {
+    let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|error| *error.borrow_mut() = message);
+    LAST_ERROR_CODE.with(|error| error.set(code));
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_submenu_set_native_icon(
-    submenu: *mut VeloxSubmenuHandle,
-    native_icon: *const c_char,
-) -> bool {
-    let Some(submenu) = (unsafe { submenu.as_mut() }) else {
-        return false;
-    };
-    let icon = native_icon_from_ptr(native_icon);
-    submenu.submenu.borrow_mut().set_native_icon(icon);
-    true
+fn record_error<T, E: std::fmt::Display>(
+    result: Result<T, E>,
+    code: VeloxErrorCode,
+) -> Result<T, VeloxErrorCode> {
+    result.map_err(|error| {
+        set_last_error(code, &error.to_string());
+        code
+    })
 }
 
-#[cfg(target_os = "macos")]
-#[no_mangle]
-pub extern "C" fn velox_submenu_set_as_windows_menu_for_nsapp(
-    submenu: *mut VeloxSubmenuHandle,
-) -> bool {
-    let Some(submenu) = (unsafe { submenu.as_ref() }) else {
-        return false;
-    };
-    submenu.submenu.borrow().set_as_windows_menu_for_nsapp();
-    true
+fn report_error<T, E: std::fmt::Display>(result: Result<T, E>, code: VeloxErrorCode) -> Option<T> {
+    record_error(result, code).ok()
 }
 
-#[cfg(not(target_os = "macos"))]
-#[no_mangle]
-pub extern "C" fn velox_submenu_set_as_windows_menu_for_nsapp(
-    _submenu: *mut VeloxSubmenuHandle,
-) -> bool {
-    false
+fn report_panic(payload: Box<dyn std::any::Any + Send>) {
+    let detail = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic");
+    set_last_error(VeloxErrorCode::Unknown, &format!("panicked: {detail}"));
 }
 
-#[cfg(target_os = "macos")]
+fn guard_panic<T>(f: impl FnOnce() -> *mut T) -> *mut T {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(ptr) => ptr,
+        Err(payload) => {
+            report_panic(payload);
+            ptr::null_mut()
+        }
+    }
+}
+
+fn guard_panic_bool(f: impl FnOnce() -> bool) -> bool {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(result) => result,
+        Err(payload) => {
+            report_panic(payload);
+            false
+        }
+    }
+}
+
+fn guard_panic_value<T: Default>(f: impl FnOnce() -> T) -> T {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(value) => value,
+        Err(payload) => {
+            report_panic(payload);
+            T::default()
+        }
+    }
+}
+
+/// Message of the last failure on this thread; valid until the next FFI call
 #[no_mangle]
-pub extern "C" fn velox_submenu_set_as_help_menu_for_nsapp(
-    submenu: *mut VeloxSubmenuHandle,
-) -> bool {
-    let Some(submenu) = (unsafe { submenu.as_ref() }) else {
-        return false;
-    };
-    submenu.submenu.borrow().set_as_help_menu_for_nsapp();
-    true
+pub extern "C" fn velox_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|error| error.borrow().as_ptr())
 }
 
-#[cfg(not(target_os = "macos"))]
+/// Category of the last failure on this thread; successful calls leave it unchanged
 #[no_mangle]
-pub extern "C" fn velox_submenu_set_as_help_menu_for_nsapp(
-    _submenu: *mut VeloxSubmenuHandle,
-) -> bool {
-    false
+pub extern "C" fn velox_last_error_code() -> VeloxErrorCode {
+    LAST_ERROR_CODE.with(Cell::get)
 }
 
-#[cfg(target_os = "macos")]
+#[cfg(all(target_os = "macos", feature = "local-dev"))]
 #[no_mangle]
-pub extern "C" fn velox_submenu_popup(
-    submenu: *mut VeloxSubmenuHandle,
-    window: *mut VeloxWindowHandle,
-    x: f64,
-    y: f64,
-    has_position: bool,
-    is_logical: bool,
-) -> bool {
-    let Some(submenu) = (unsafe { submenu.as_ref() }) else {
-        return false;
-    };
-    let Some(window) = (unsafe { window.as_ref() }) else {
-        return false;
-    };
+pub extern "C" fn velox_app_state_force_launched() {
+    tao::platform::macos::force_app_state_launched_for_testing();
+}
 
-    let position = if has_position {
-        if is_logical {
-            Some(muda::dpi::Position::Logical(LogicalPosition { x, y }))
-        } else {
-            let px = x.round() as i32;
-            let py = y.round() as i32;
-            Some(muda::dpi::Position::Physical(PhysicalPosition { x: px, y: py }))
-        }
-    } else {
-        None
-    };
+#[cfg(all(target_os = "macos", not(feature = "local-dev")))]
+#[no_mangle]
+pub extern "C" fn velox_app_state_force_launched() {
+    // No-op when using crates.io tao (velox-testing feature not available)
+}
 
-    unsafe { submenu.submenu.borrow().show_context_menu_for_nsview(window.window.ns_view(), position) }
+fn with_window<R>(window: *mut VeloxWindowHandle, f: impl FnOnce(&Window) -> R) -> Option<R> {
+    unsafe { window.as_ref() }.map(|handle| f(&handle.window))
 }
 
-#[cfg(target_os = "linux")]
-#[no_mangle]
-pub extern "C" fn velox_submenu_popup(
-    submenu: *mut VeloxSubmenuHandle,
-    window: *mut VeloxWindowHandle,
-    x: f64,
-    y: f64,
-    has_position: bool,
-    is_logical: bool,
-) -> bool {
-    let Some(submenu) = (unsafe { submenu.as_ref() }) else {
-        return false;
-    };
-    let Some(window) = (unsafe { window.as_ref() }) else {
-        return false;
-    };
+fn with_webview<R>(webview: *mut VeloxWebviewHandle, f: impl FnOnce(&WebView) -> R) -> Option<R> {
+    unsafe { webview.as_ref() }.map(|handle| f(&handle.webview))
+}
 
-    let position = if has_position {
-        if is_logical {
-            Some(muda::dpi::Position::Logical(LogicalPosition { x, y }))
-        } else {
-            let px = x.round() as i32;
-            let py = y.round() as i32;
-            Some(muda::dpi::Position::Physical(PhysicalPosition { x: px, y: py }))
-        }
-    } else {
-        None
-    };
+fn tao_user_attention_from_ffi(kind: VeloxUserAttentionType) -> TaoUserAttentionType {
+    match kind {
+        VeloxUserAttentionType::Informational => TaoUserAttentionType::Informational,
+        VeloxUserAttentionType::Critical => TaoUserAttentionType::Critical,
+    }
+}
 
-    unsafe { submenu.submenu.borrow().show_context_menu_for_gtk_window(window.window.gtk_window().as_ref(), position) }
+fn tao_resize_direction_from_ffi(direction: VeloxResizeDirection) -> TaoResizeDirection {
+    match direction {
+        VeloxResizeDirection::East => TaoResizeDirection::East,
+        VeloxResizeDirection::North => TaoResizeDirection::North,
+        VeloxResizeDirection::NorthEast => TaoResizeDirection::NorthEast,
+        VeloxResizeDirection::NorthWest => TaoResizeDirection::NorthWest,
+        VeloxResizeDirection::South => TaoResizeDirection::South,
+        VeloxResizeDirection::SouthEast => TaoResizeDirection::SouthEast,
+        VeloxResizeDirection::SouthWest => TaoResizeDirection::SouthWest,
+        VeloxResizeDirection::West => TaoResizeDirection::West,
+    }
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_submenu_append_item(
-    submenu: *mut VeloxSubmenuHandle,
-    item: *mut VeloxMenuItemHandle,
-) -> bool {
-    let Some(submenu) = (unsafe { submenu.as_mut() }) else {
-        return false;
-    };
-    let Some(item) = (unsafe { item.as_ref() }) else {
-        return false;
-    };
+fn tao_cursor_icon_from_ffi(icon: VeloxCursorIcon) -> CursorIcon {
+    match icon {
+        VeloxCursorIcon::Default => CursorIcon::Default,
+        VeloxCursorIcon::Crosshair => CursorIcon::Crosshair,
+        VeloxCursorIcon::Hand => CursorIcon::Hand,
+        VeloxCursorIcon::Arrow => CursorIcon::Arrow,
+        VeloxCursorIcon::Move => CursorIcon::Move,
+        VeloxCursorIcon::Text => CursorIcon::Text,
+        VeloxCursorIcon::Wait => CursorIcon::Wait,
+        VeloxCursorIcon::Help => CursorIcon::Help,
+        VeloxCursorIcon::Progress => CursorIcon::Progress,
+        VeloxCursorIcon::NotAllowed => CursorIcon::NotAllowed,
+        VeloxCursorIcon::ContextMenu => CursorIcon::ContextMenu,
+        VeloxCursorIcon::Cell => CursorIcon::Cell,
+        VeloxCursorIcon::VerticalText => CursorIcon::VerticalText,
+        VeloxCursorIcon::Alias => CursorIcon::Alias,
+        VeloxCursorIcon::Copy => CursorIcon::Copy,
+        VeloxCursorIcon::NoDrop => CursorIcon::NoDrop,
+        VeloxCursorIcon::Grab => CursorIcon::Grab,
+        VeloxCursorIcon::Grabbing => CursorIcon::Grabbing,
+        VeloxCursorIcon::AllScroll => CursorIcon::AllScroll,
+        VeloxCursorIcon::ZoomIn => CursorIcon::ZoomIn,
+        VeloxCursorIcon::ZoomOut => CursorIcon::ZoomOut,
+        VeloxCursorIcon::EResize => CursorIcon::EResize,
+        VeloxCursorIcon::NResize => CursorIcon::NResize,
+        VeloxCursorIcon::NeResize => CursorIcon::NeResize,
+        VeloxCursorIcon::NwResize => CursorIcon::NwResize,
+        VeloxCursorIcon::SResize => CursorIcon::SResize,
+        VeloxCursorIcon::SeResize => CursorIcon::SeResize,
+        VeloxCursorIcon::SwResize => CursorIcon::SwResize,
+        VeloxCursorIcon::WResize => CursorIcon::WResize,
+        VeloxCursorIcon::EwResize => CursorIcon::EwResize,
+        VeloxCursorIcon::NsResize => CursorIcon::NsResize,
+        VeloxCursorIcon::NeswResize => CursorIcon::NeswResize,
+        VeloxCursorIcon::NwseResize => CursorIcon::NwseResize,
+        VeloxCursorIcon::ColResize => CursorIcon::ColResize,
+        VeloxCursorIcon::RowResize => CursorIcon::RowResize,
+    }
+}
 
-    if submenu.submenu.borrow().append(&item.item).is_ok() {
-        submenu.items.push(MenuItemKind::MenuItem(item.item.clone()));
-        true
-    } else {
-        false
+struct CursorTracking {
+    /// Owned by the `VeloxWindowHandle`; the entry is removed before the handle is freed.
+    window: *const Window,
+    rects: Vec<CursorTrackingRect>,
+    next_id: u64,
+    active: Option<u64>,
+}
+
+struct CursorTrackingRect {
+    id: u64,
+    rect: VeloxRect,
+    cursor: CursorIcon,
+}
+
+fn update_cursor_tracking(window_id: WindowId, position: PhysicalPosition<f64>) {
+    CURSOR_TRACKING.with(|tracking| {
+        let mut tracking = tracking.borrow_mut();
+        let Some(state) = tracking.get_mut(&window_id) else {
+            return;
+        };
+        let window = unsafe { &*state.window };
+        let logical: LogicalPosition<f64> = position.to_logical(window.scale_factor());
+        // Later rects sit on top of earlier ones.
+        let hit = state
+            .rects
+            .iter()
+            .rev()
+            .find(|entry| entry.rect.contains(logical.x, logical.y));
+        let hit_id = hit.map(|entry| entry.id);
+        if hit_id == state.active {
+            return;
+        }
+        window.set_cursor_icon(hit.map(|entry| entry.cursor).unwrap_or_default());
+        state.active = hit_id;
+    });
+}
+
+/// Rects (logical pixels) where a primary-button press moves the window, minus exclusions.
+struct DragRegions {
+    window: *const Window,
+    drag: Vec<VeloxRect>,
+    no_drag: Vec<VeloxRect>,
+    cursor: Option<(f64, f64)>,
+}
+
+impl DragRegions {
+    fn contains(&self, x: f64, y: f64) -> bool {
+        self.drag.iter().any(|rect| rect.contains(x, y))
+            && !self.no_drag.iter().any(|rect| rect.contains(x, y))
     }
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_submenu_append(
-    submenu: *mut VeloxSubmenuHandle,
-    kind: VeloxMenuItemKind,
-    item: *mut c_void,
-) -> bool {
-    let Some(submenu) = (unsafe { submenu.as_mut() }) else {
-        return false;
-    };
+fn start_region_drag(window_id: WindowId, position: Option<(f64, f64)>) -> bool {
+    DRAG_REGIONS.with(|regions| {
+        let regions = regions.borrow();
+        let Some(state) = regions.get(&window_id) else {
+            return false;
+        };
+        let Some((x, y)) = position.or(state.cursor) else {
+            return false;
+        };
+        if !state.contains(x, y) {
+            return false;
+        }
+        let window = unsafe { &*state.window };
+        report_error(window.drag_window(), VeloxErrorCode::Unknown).is_some()
+    })
+}
 
-    match kind {
-        VeloxMenuItemKind::MenuItem => {
-            let Some(item) = (unsafe { (item as *mut VeloxMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            if submenu.submenu.borrow().append(&item.item).is_ok() {
-                submenu.items.push(MenuItemKind::MenuItem(item.item.clone()));
-                true
-            } else {
-                false
-            }
+fn update_drag_region_cursor(window_id: WindowId, position: PhysicalPosition<f64>) {
+    DRAG_REGIONS.with(|regions| {
+        if let Some(state) = regions.borrow_mut().get_mut(&window_id) {
+            let window = unsafe { &*state.window };
+            let logical: LogicalPosition<f64> = position.to_logical(window.scale_factor());
+            state.cursor = Some((logical.x, logical.y));
         }
-        VeloxMenuItemKind::Predefined => {
-            let Some(item) = (unsafe { (item as *mut VeloxPredefinedMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            if submenu.submenu.borrow().append(&item.item).is_ok() {
-                submenu.items.push(MenuItemKind::Predefined(item.item.clone()));
-                true
-            } else {
-                false
-            }
+    });
+}
+
+fn reset_cursor_tracking(window_id: WindowId) {
+    CURSOR_TRACKING.with(|tracking| {
+        if let Some(state) = tracking.borrow_mut().get_mut(&window_id) {
+            state.active = None;
         }
-        VeloxMenuItemKind::Check => {
-            let Some(item) = (unsafe { (item as *mut VeloxCheckMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            if submenu.submenu.borrow().append(&item.item).is_ok() {
-                submenu.items.push(MenuItemKind::Check(item.item.clone()));
-                true
-            } else {
-                false
-            }
+    });
+}
+
+struct AspectRatioLock {
+    window: *const Window,
+    ratio: f64,
+}
+
+#[derive(Clone, Copy, Default)]
+struct SizeLimits {
+    min: Option<LogicalSize<f64>>,
+    max: Option<LogicalSize<f64>>,
+}
+
+fn update_size_limits(window_id: WindowId, update: impl FnOnce(&mut SizeLimits)) {
+    SIZE_LIMITS.with(|limits| update(limits.borrow_mut().entry(window_id).or_default()));
+}
+
+/// Min/max inner sizes of a window in physical pixels
+fn physical_size_limits(
+    window_id: WindowId,
+    scale_factor: f64,
+) -> (Option<(f64, f64)>, Option<(f64, f64)>) {
+    let limits = SIZE_LIMITS
+        .with(|limits| limits.borrow().get(&window_id).copied())
+        .unwrap_or_default();
+    let physical = |size: Option<LogicalSize<f64>>| {
+        size.map(|size| {
+            let size: PhysicalSize<f64> = size.to_physical(scale_factor);
+            (size.width, size.height)
+        })
+    };
+    (physical(limits.min), physical(limits.max))
+}
+
+/// Derive the other side of `size` from `ratio` (width / height), then fit it within `min`/`max`
+fn aspect_constrained_size(
+    size: (f64, f64),
+    ratio: f64,
+    height_driven: bool,
+    min: Option<(f64, f64)>,
+    max: Option<(f64, f64)>,
+) -> (f64, f64) {
+    let (mut width, mut height) = if height_driven {
+        (size.1 * ratio, size.1)
+    } else {
+        (size.0, size.0 / ratio)
+    };
+    // Scale as a whole so the ratio survives clamping whenever the limits allow it.
+    if let Some((max_width, max_height)) = max {
+        let scale = (max_width / width).min(max_height / height).min(1.0);
+        width *= scale;
+        height *= scale;
+    }
+    if let Some((min_width, min_height)) = min {
+        let scale = (min_width / width).max(min_height / height).max(1.0);
+        width *= scale;
+        height *= scale;
+    }
+    // Limits that cannot hold together with the ratio take precedence over it.
+    if let Some((max_width, max_height)) = max {
+        width = width.min(max_width);
+        height = height.min(max_height);
+    }
+    if let Some((min_width, min_height)) = min {
+        width = width.max(min_width);
+        height = height.max(min_height);
+    }
+    (width, height)
+}
+
+fn enforce_aspect_ratio(window_id: WindowId, size: PhysicalSize<u32>) {
+    ASPECT_RATIOS.with(|ratios| {
+        let ratios = ratios.borrow();
+        let Some(lock) = ratios.get(&window_id) else {
+            return;
+        };
+        if size.width == 0 || size.height == 0 {
+            return;
         }
-        VeloxMenuItemKind::Icon => {
-            let Some(item) = (unsafe { (item as *mut VeloxIconMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            if submenu.submenu.borrow().append(&item.item).is_ok() {
-                submenu.items.push(MenuItemKind::Icon(item.item.clone()));
-                true
-            } else {
-                false
-            }
+        let window = unsafe { &*lock.window };
+        let (min, max) = physical_size_limits(window_id, window.scale_factor());
+        let current = (f64::from(size.width), f64::from(size.height));
+        let (width, height) = aspect_constrained_size(current, lock.ratio, false, min, max);
+        let (width, height) = (width.round(), height.round());
+        // Ignore rounding noise so the correction cannot feed back into another resize.
+        if (width - current.0).abs() <= 1.0 && (height - current.1).abs() <= 1.0 {
+            return;
         }
-        VeloxMenuItemKind::Submenu => {
-            let Some(item) = (unsafe { (item as *mut VeloxSubmenuHandle).as_ref() }) else {
-                return false;
-            };
-            let submenu_ref = item.submenu.borrow();
-            if submenu.submenu.borrow().append(&*submenu_ref).is_ok() {
-                submenu.items.push(MenuItemKind::Submenu(submenu_ref.clone()));
-                true
-            } else {
-                false
+        window.set_inner_size(PhysicalSize::new(width as u32, height as u32));
+    });
+}
+
+/// Keep the drag rectangle of a `WM_SIZING` at the locked ratio so the window never overshoots
+#[cfg(target_os = "windows")]
+unsafe extern "system" fn aspect_ratio_subclass_proc(
+    hwnd: windows::Win32::Foundation::HWND,
+    msg: u32,
+    wparam: windows::Win32::Foundation::WPARAM,
+    lparam: windows::Win32::Foundation::LPARAM,
+    _subclass_id: usize,
+    _ref_data: usize,
+) -> windows::Win32::Foundation::LRESULT {
+    use tao::platform::windows::WindowExtWindows;
+    use windows::Win32::{
+        Foundation::{LRESULT, RECT},
+        UI::{
+            Shell::DefSubclassProc,
+            WindowsAndMessaging::{
+                GetClientRect, GetWindowRect, WMSZ_BOTTOM, WMSZ_BOTTOMLEFT, WMSZ_LEFT, WMSZ_TOP,
+                WMSZ_TOPLEFT, WMSZ_TOPRIGHT, WM_SIZING,
+            },
+        },
+    };
+
+    if msg == WM_SIZING {
+        if let Some(rect) = (lparam.0 as *mut RECT).as_mut() {
+            let lock = ASPECT_RATIOS.with(|ratios| {
+                ratios
+                    .borrow()
+                    .values()
+                    .find(|lock| (*lock.window).hwnd() == hwnd.0 as isize)
+                    .map(|lock| (&*lock.window, lock.ratio))
+            });
+            let mut window_rect = RECT::default();
+            let mut client_rect = RECT::default();
+            if let Some((window, ratio)) = lock.filter(|_| {
+                GetWindowRect(hwnd, &mut window_rect).is_ok()
+                    && GetClientRect(hwnd, &mut client_rect).is_ok()
+            }) {
+                // The drag rectangle includes the frame while the ratio applies to the client area.
+                let frame_width =
+                    (window_rect.right - window_rect.left) - (client_rect.right - client_rect.left);
+                let frame_height =
+                    (window_rect.bottom - window_rect.top) - (client_rect.bottom - client_rect.top);
+                let client = (
+                    f64::from(rect.right - rect.left - frame_width).max(1.0),
+                    f64::from(rect.bottom - rect.top - frame_height).max(1.0),
+                );
+                let edge = wparam.0 as u32;
+                let (min, max) = physical_size_limits(window.id(), window.scale_factor());
+                let (width, height) = aspect_constrained_size(
+                    client,
+                    ratio,
+                    edge == WMSZ_TOP || edge == WMSZ_BOTTOM,
+                    min,
+                    max,
+                );
+                let width = width.round() as i32 + frame_width;
+                let height = height.round() as i32 + frame_height;
+                if matches!(edge, WMSZ_LEFT | WMSZ_TOPLEFT | WMSZ_BOTTOMLEFT) {
+                    rect.left = rect.right - width;
+                } else {
+                    rect.right = rect.left + width;
+                }
+                if matches!(edge, WMSZ_TOP | WMSZ_TOPLEFT | WMSZ_TOPRIGHT) {
+                    rect.top = rect.bottom - height;
+                } else {
+                    rect.bottom = rect.top + height;
+                }
+                return LRESULT(1);
             }
         }
     }
+    DefSubclassProc(hwnd, msg, wparam, lparam)
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_submenu_prepend(
-    submenu: *mut VeloxSubmenuHandle,
-    kind: VeloxMenuItemKind,
-    item: *mut c_void,
-) -> bool {
-    let Some(submenu) = (unsafe { submenu.as_mut() }) else {
-        return false;
-    };
+#[cfg(target_os = "windows")]
+const ASPECT_RATIO_SUBCLASS_ID: usize = 0x7665_6c61;
 
-    match kind {
-        VeloxMenuItemKind::MenuItem => {
-            let Some(item) = (unsafe { (item as *mut VeloxMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            if submenu.submenu.borrow().prepend(&item.item).is_ok() {
-                submenu.items.insert(0, MenuItemKind::MenuItem(item.item.clone()));
-                true
-            } else {
-                false
-            }
-        }
-        VeloxMenuItemKind::Predefined => {
-            let Some(item) = (unsafe { (item as *mut VeloxPredefinedMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            if submenu.submenu.borrow().prepend(&item.item).is_ok() {
-                submenu.items.insert(0, MenuItemKind::Predefined(item.item.clone()));
-                true
-            } else {
-                false
-            }
-        }
-        VeloxMenuItemKind::Check => {
-            let Some(item) = (unsafe { (item as *mut VeloxCheckMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            if submenu.submenu.borrow().prepend(&item.item).is_ok() {
-                submenu.items.insert(0, MenuItemKind::Check(item.item.clone()));
-                true
-            } else {
-                false
-            }
-        }
-        VeloxMenuItemKind::Icon => {
-            let Some(item) = (unsafe { (item as *mut VeloxIconMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            if submenu.submenu.borrow().prepend(&item.item).is_ok() {
-                submenu.items.insert(0, MenuItemKind::Icon(item.item.clone()));
-                true
-            } else {
-                false
-            }
-        }
-        VeloxMenuItemKind::Submenu => {
-            let Some(item) = (unsafe { (item as *mut VeloxSubmenuHandle).as_ref() }) else {
-                return false;
-            };
-            let submenu_ref = item.submenu.borrow();
-            if submenu.submenu.borrow().prepend(&*submenu_ref).is_ok() {
-                submenu.items.insert(0, MenuItemKind::Submenu(submenu_ref.clone()));
-                true
-            } else {
-                false
-            }
-        }
-    }
-}
+const CONTENT_SIZE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+const CONTENT_SIZE_SCRIPT: &str =
+    "[document.documentElement.scrollWidth, document.documentElement.scrollHeight]";
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_submenu_insert(
-    submenu: *mut VeloxSubmenuHandle,
-    kind: VeloxMenuItemKind,
-    item: *mut c_void,
-    position: usize,
-) -> bool {
-    let Some(submenu) = (unsafe { submenu.as_mut() }) else {
-        return false;
-    };
-    let insert_index = position.min(submenu.items.len());
+struct ContentSizeObserver {
+    max_width: f64,
+    max_height: f64,
+    callback: unsafe extern "C" fn(width: f64, height: f64, user_data: *mut c_void),
+    user_data: *mut c_void,
+    last_size: Option<(f64, f64)>,
+    next_poll: Instant,
+}
 
-    match kind {
-        VeloxMenuItemKind::MenuItem => {
-            let Some(item) = (unsafe { (item as *mut VeloxMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            if submenu.submenu.borrow().insert(&item.item, position).is_ok() {
-                submenu.items.insert(insert_index, MenuItemKind::MenuItem(item.item.clone()));
-                true
-            } else {
-                false
-            }
-        }
-        VeloxMenuItemKind::Predefined => {
-            let Some(item) = (unsafe { (item as *mut VeloxPredefinedMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            if submenu.submenu.borrow().insert(&item.item, position).is_ok() {
-                submenu.items.insert(insert_index, MenuItemKind::Predefined(item.item.clone()));
-                true
-            } else {
-                false
-            }
-        }
-        VeloxMenuItemKind::Check => {
-            let Some(item) = (unsafe { (item as *mut VeloxCheckMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            if submenu.submenu.borrow().insert(&item.item, position).is_ok() {
-                submenu.items.insert(insert_index, MenuItemKind::Check(item.item.clone()));
-                true
-            } else {
-                false
-            }
-        }
-        VeloxMenuItemKind::Icon => {
-            let Some(item) = (unsafe { (item as *mut VeloxIconMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            if submenu.submenu.borrow().insert(&item.item, position).is_ok() {
-                submenu.items.insert(insert_index, MenuItemKind::Icon(item.item.clone()));
-                true
-            } else {
-                false
-            }
-        }
-        VeloxMenuItemKind::Submenu => {
-            let Some(item) = (unsafe { (item as *mut VeloxSubmenuHandle).as_ref() }) else {
-                return false;
-            };
-            let submenu_ref = item.submenu.borrow();
-            if submenu.submenu.borrow().insert(&*submenu_ref, position).is_ok() {
-                submenu.items.insert(insert_index, MenuItemKind::Submenu(submenu_ref.clone()));
-                true
-            } else {
-                false
-            }
-        }
+fn protocol_redirect_target(
+    response: &WryHttpResponse<Vec<u8>>,
+    base: &url::Url,
+) -> Option<url::Url> {
+    if !response.status().is_redirection() {
+        return None;
     }
+    let location = response.headers().get(LOCATION)?.to_str().ok()?;
+    base.join(location).ok()
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_submenu_remove(
-    submenu: *mut VeloxSubmenuHandle,
-    kind: VeloxMenuItemKind,
-    item: *mut c_void,
-) -> bool {
-    let Some(submenu) = (unsafe { submenu.as_mut() }) else {
-        return false;
-    };
-
-    match kind {
-        VeloxMenuItemKind::MenuItem => {
-            let Some(item) = (unsafe { (item as *mut VeloxMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            submenu.submenu.borrow().remove(&item.item).is_ok()
-        }
-        VeloxMenuItemKind::Predefined => {
-            let Some(item) = (unsafe { (item as *mut VeloxPredefinedMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            submenu.submenu.borrow().remove(&item.item).is_ok()
-        }
-        VeloxMenuItemKind::Check => {
-            let Some(item) = (unsafe { (item as *mut VeloxCheckMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            submenu.submenu.borrow().remove(&item.item).is_ok()
-        }
-        VeloxMenuItemKind::Icon => {
-            let Some(item) = (unsafe { (item as *mut VeloxIconMenuItemHandle).as_ref() }) else {
-                return false;
-            };
-            submenu.submenu.borrow().remove(&item.item).is_ok()
-        }
-        VeloxMenuItemKind::Submenu => {
-            let Some(item) = (unsafe { (item as *mut VeloxSubmenuHandle).as_ref() }) else {
-                return false;
-            };
-            let submenu_ref = item.submenu.borrow();
-            submenu.submenu.borrow().remove(&*submenu_ref).is_ok()
-        }
+/// WebKit does not follow custom scheme redirects, so they are replayed as a navigation
+fn follow_protocol_redirect(webview_id: String, location: String) {
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    {
+        let Some(proxy) = USER_EVENT_PROXY.with(|proxy| proxy.borrow().clone()) else {
+            return;
+        };
+        let task = PostedTask::new(move || {
+            let handle = WINDOW_WEBVIEWS.with(|webviews| {
+                webviews
+                    .borrow()
+                    .values()
+                    .flatten()
+                    .copied()
+                    .find(|&handle| {
+                        unsafe { handle.as_ref() }
+                            .is_some_and(|handle| handle.webview.id() == webview_id)
+                    })
+            });
+            if let Some(handle) = handle.and_then(|handle| unsafe { handle.as_ref() }) {
+                let _ = handle.webview.load_url(&location);
+            }
+        });
+        let _ = proxy.send_event(VeloxUserEvent::Task(task));
+        return;
     }
-}
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_submenu_remove_at(
-    submenu: *mut VeloxSubmenuHandle,
-    position: usize,
-) -> bool {
-    let Some(submenu) = (unsafe { submenu.as_mut() }) else {
-        return false;
-    };
-    submenu.submenu.borrow().remove_at(position).is_some()
+    #[allow(unreachable_code)]
+    {
+        let _ = (webview_id, location);
+    }
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_menu_item_new(
-    id: *const c_char,
-    title: *const c_char,
-    enabled: bool,
-    accelerator: *const c_char,
-) -> *mut VeloxMenuItemHandle {
-    guard_panic(|| {
-        let title = opt_cstring(title).unwrap_or_default();
-        let accelerator = accelerator_from_ptr(accelerator);
-        let item = if let Some(id) = opt_cstring(id) {
-            MenuItem::with_id(MenuId::new(id.clone()), title, enabled, accelerator)
-        } else {
-            MenuItem::new(title, enabled, accelerator)
+fn poll_content_size_observers() -> Option<Instant> {
+    let now = Instant::now();
+    let due: Vec<WindowId> = CONTENT_SIZE_OBSERVERS.with(|observers| {
+        observers
+            .borrow_mut()
+            .iter_mut()
+            .filter(|(_, observer)| observer.next_poll <= now)
+            .map(|(window_id, observer)| {
+                observer.next_poll = now + CONTENT_SIZE_POLL_INTERVAL;
+                *window_id
+            })
+            .collect()
+    });
+
+    for window_id in due {
+        let Some(proxy) = USER_EVENT_PROXY.with(|proxy| proxy.borrow().clone()) else {
+            break;
         };
-        let identifier = CString::new(item.id().as_ref()).expect("menu item id contains null byte");
-        Box::into_raw(Box::new(VeloxMenuItemHandle { item, identifier }))
+        let handle = WINDOW_WEBVIEWS.with(|webviews| {
+            webviews
+                .borrow()
+                .get(&window_id)
+                .and_then(|handles| handles.first().copied())
+        });
+        let Some(handle) = handle.and_then(|handle| unsafe { handle.as_ref() }) else {
+            continue;
+        };
+        let _ = handle
+            .webview
+            .evaluate_script_with_callback(CONTENT_SIZE_SCRIPT, move |result| {
+                if let Ok((width, height)) = serde_json::from_str::<(f64, f64)>(&result) {
+                    let _ = proxy.send_event(VeloxUserEvent::ContentSize(window_id, width, height));
+                }
+            });
+    }
+
+    CONTENT_SIZE_OBSERVERS.with(|observers| {
+        observers
+            .borrow()
+            .values()
+            .map(|observer| observer.next_poll)
+            .min()
     })
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_menu_item_free(item: *mut VeloxMenuItemHandle) {
-    if !item.is_null() {
-        unsafe { drop(Box::from_raw(item)) };
+fn deliver_content_size(window_id: WindowId, width: f64, height: f64) {
+    let pending = CONTENT_SIZE_OBSERVERS.with(|observers| {
+        let mut observers = observers.borrow_mut();
+        let observer = observers.get_mut(&window_id)?;
+        let size = (
+            width.min(observer.max_width),
+            height.min(observer.max_height),
+        );
+        if observer.last_size == Some(size) {
+            return None;
+        }
+        observer.last_size = Some(size);
+        Some((observer.callback, observer.user_data, size))
+    });
+    // The observer usually resizes the window, so it runs without the map borrowed.
+    if let Some((callback, user_data, (width, height))) = pending {
+        let _ = catch_unwind(AssertUnwindSafe(|| unsafe {
+            callback(width, height, user_data)
+        }));
     }
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_menu_item_set_enabled(
-    item: *mut VeloxMenuItemHandle,
-    enabled: bool,
-) -> bool {
-    let Some(item) = (unsafe { item.as_mut() }) else {
-        return false;
-    };
-    item.item.set_enabled(enabled);
-    true
+fn forget_window_state(window_id: WindowId) {
+    IME_STATE.with(|state| state.borrow_mut().remove(&window_id));
+    CURSOR_TRACKING.with(|tracking| tracking.borrow_mut().remove(&window_id));
+    DRAG_REGIONS.with(|regions| regions.borrow_mut().remove(&window_id));
+    ASPECT_RATIOS.with(|ratios| ratios.borrow_mut().remove(&window_id));
+    SIZE_LIMITS.with(|limits| limits.borrow_mut().remove(&window_id));
+    CONTENT_SIZE_OBSERVERS.with(|observers| observers.borrow_mut().remove(&window_id));
+    WINDOW_WEBVIEWS.with(|webviews| webviews.borrow_mut().remove(&window_id));
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 #[no_mangle]
-pub extern "C" fn velox_menu_item_is_enabled(item: *mut VeloxMenuItemHandle) -> bool {
-    guard_panic_bool(|| {
-        let Some(item) = (unsafe { item.as_ref() }) else {
-            return false;
-        };
-        item.item.is_enabled()
-    })
+pub extern "C" fn velox_runtime_wry_library_name() -> *const c_char {
+    cached_cstring(&LIBRARY_NAME, || "VeloxRuntimeWry".to_string())
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
+const VELOX_RUNTIME_WRY_FFI_ABI_VERSION: u32 = 3;
+
 #[no_mangle]
-pub extern "C" fn velox_menu_item_text(item: *mut VeloxMenuItemHandle) -> *const c_char {
-    guard_panic_value(|| {
-        let Some(item) = (unsafe { item.as_ref() }) else {
-            return ptr::null();
-        };
-        write_string_to_buffer(&TITLE_BUFFER, item.item.text())
-    })
+pub extern "C" fn velox_runtime_wry_ffi_abi_version() -> u32 {
+    VELOX_RUNTIME_WRY_FFI_ABI_VERSION
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 #[no_mangle]
-pub extern "C" fn velox_menu_item_set_text(
-    item: *mut VeloxMenuItemHandle,
-    title: *const c_char,
-) -> bool {
-    guard_panic_bool(|| {
-        let Some(item) = (unsafe { item.as_mut() }) else {
-            return false;
-        };
-        let text = opt_cstring(title).unwrap_or_default();
-        item.item.set_text(text);
-        true
-    })
+pub extern "C" fn velox_runtime_wry_crate_version() -> *const c_char {
+    cached_cstring(&RUNTIME_VERSION, || env!("CARGO_PKG_VERSION").to_string())
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 #[no_mangle]
-pub extern "C" fn velox_menu_item_set_accelerator(
-    item: *mut VeloxMenuItemHandle,
-    accelerator: *const c_char,
-) -> bool {
-    guard_panic_bool(|| {
-        let Some(item) = (unsafe { item.as_mut() }) else {
-            return false;
-        };
-        item.item
-            .set_accelerator(accelerator_from_ptr(accelerator))
-            .is_ok()
+pub extern "C" fn velox_runtime_wry_webview_version() -> *const c_char {
+    cached_cstring(&WEBVIEW_VERSION, || {
+        wry::webview_version().unwrap_or_default()
     })
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 #[no_mangle]
-pub extern "C" fn velox_menu_item_identifier(item: *mut VeloxMenuItemHandle) -> *const c_char {
-    let Some(item) = (unsafe { item.as_ref() }) else {
-        return ptr::null();
-    };
-    item.identifier.as_ptr()
-}
+pub extern "C" fn velox_event_loop_new() -> *mut VeloxEventLoop {
+    let event_loop = EventLoopBuilder::<VeloxUserEvent>::with_user_event().build();
+    let user_event_proxy = event_loop.create_proxy();
+    USER_EVENT_PROXY.with(|proxy| *proxy.borrow_mut() = Some(user_event_proxy));
 
-// MARK: - Icon Menu Item
+    let menu_proxy = event_loop.create_proxy();
+    MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+        let _ = menu_proxy.send_event(VeloxUserEvent::Menu(event.id().as_ref().to_string()));
+    }));
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_icon_menu_item_new(
-    id: *const c_char,
-    title: *const c_char,
-    enabled: bool,
-    accelerator: *const c_char,
-    native_icon: *const c_char,
-) -> *mut VeloxIconMenuItemHandle {
-    guard_panic(|| {
-        let title = opt_cstring(title).unwrap_or_default();
-        let accelerator = accelerator_from_ptr(accelerator);
-        let native_icon = native_icon_from_ptr(native_icon);
-        let item = if let Some(id) = opt_cstring(id) {
-            IconMenuItem::with_id_and_native_icon(
-                MenuId::new(id.clone()),
-                title,
-                enabled,
-                native_icon,
-                accelerator,
-            )
-        } else {
-            IconMenuItem::with_native_icon(title, enabled, native_icon, accelerator)
-        };
-        let identifier = CString::new(item.id().as_ref()).expect("icon menu item id contains null byte");
-        Box::into_raw(Box::new(VeloxIconMenuItemHandle { item, identifier }))
-    })
+    let tray_proxy = event_loop.create_proxy();
+    TrayIconEvent::set_event_handler(Some(move |event: TrayIconEvent| {
+        // tray-icon shows the popup right after this handler returns, so rebuild it here.
+        #[cfg(target_os = "windows")]
+        if let TrayIconEvent::Click {
+            id,
+            button_state: tray_icon::MouseButtonState::Down,
+            ..
+        } = &event
+        {
+            rebuild_tray_menu(id.as_ref());
+        }
+        let _ = tray_proxy.send_event(VeloxUserEvent::Tray(event.into()));
+    }));
+
+    let hotkey_proxy = event_loop.create_proxy();
+    GlobalHotKeyEvent::set_event_handler(Some(move |event: GlobalHotKeyEvent| {
+        if event.state != HotKeyState::Pressed {
+            return;
+        }
+        let id = HOTKEY_IDS
+            .get()
+            .and_then(|ids| ids.lock().ok()?.get(&event.id).cloned());
+        if let Some(id) = id {
+            let _ = hotkey_proxy.send_event(VeloxUserEvent::Hotkey(id));
+        }
+    }));
+
+    Box::into_raw(Box::new(VeloxEventLoop {
+        event_loop,
+        suspend_handler: Cell::new(None),
+        resume_handler: Cell::new(None),
+        accessibility_enabled: Cell::new(false),
+        event_filter: Cell::new(None),
+        hotkeys: RefCell::new(None),
+        id: NEXT_EVENT_LOOP_ID.fetch_add(1, Ordering::Relaxed),
+    }))
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 #[no_mangle]
-pub extern "C" fn velox_icon_menu_item_free(item: *mut VeloxIconMenuItemHandle) {
-    if !item.is_null() {
-        unsafe { drop(Box::from_raw(item)) };
+pub extern "C" fn velox_event_loop_free(event_loop: *mut VeloxEventLoop) {
+    if !event_loop.is_null() {
+        unsafe { drop(Box::from_raw(event_loop)) };
+        MenuEvent::set_event_handler::<fn(MenuEvent)>(None);
+        TrayIconEvent::set_event_handler::<fn(TrayIconEvent)>(None);
+        GlobalHotKeyEvent::set_event_handler::<fn(GlobalHotKeyEvent)>(None);
     }
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 #[no_mangle]
-pub extern "C" fn velox_icon_menu_item_identifier(item: *mut VeloxIconMenuItemHandle) -> *const c_char {
-    let Some(item) = (unsafe { item.as_ref() }) else {
-        return ptr::null();
-    };
-    item.identifier.as_ptr()
-}
+pub extern "C" fn velox_event_loop_create_proxy(
+    event_loop: *mut VeloxEventLoop,
+) -> *mut VeloxEventLoopProxyHandle {
+    if event_loop.is_null() {
+        return ptr::null_mut();
+    }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_icon_menu_item_text(item: *mut VeloxIconMenuItemHandle) -> *const c_char {
-    guard_panic_value(|| {
-        let Some(item) = (unsafe { item.as_ref() }) else {
-            return ptr::null();
-        };
-        write_string_to_buffer(&TITLE_BUFFER, item.item.text())
-    })
+    #[cfg(all(target_os = "macos", feature = "local-dev"))]
+    tao::platform::macos::force_app_state_launched_for_testing();
+
+    let event_loop = unsafe { &mut *event_loop };
+    let proxy = event_loop.event_loop.create_proxy();
+    Box::into_raw(Box::new(VeloxEventLoopProxyHandle {
+        proxy,
+        loop_id: event_loop.id,
+    }))
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 #[no_mangle]
-pub extern "C" fn velox_icon_menu_item_set_text(
-    item: *mut VeloxIconMenuItemHandle,
-    title: *const c_char,
+pub extern "C" fn velox_event_loop_proxy_request_exit(
+    proxy: *mut VeloxEventLoopProxyHandle,
 ) -> bool {
-    guard_panic_bool(|| {
-        let Some(item) = (unsafe { item.as_mut() }) else {
-            return false;
-        };
-        let text = opt_cstring(title).unwrap_or_default();
-        item.item.set_text(text);
-        true
-    })
+    if proxy.is_null() {
+        return false;
+    }
+
+    let proxy = unsafe { &mut *proxy };
+    proxy.proxy.send_event(VeloxUserEvent::Exit).is_ok()
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 #[no_mangle]
-pub extern "C" fn velox_icon_menu_item_is_enabled(item: *mut VeloxIconMenuItemHandle) -> bool {
-    guard_panic_bool(|| {
-        let Some(item) = (unsafe { item.as_ref() }) else {
-            return false;
-        };
-        item.item.is_enabled()
-    })
+pub extern "C" fn velox_event_loop_proxy_send_user_event(
+    proxy: *mut VeloxEventLoopProxyHandle,
+    payload: *const c_char,
+) -> bool {
+    if proxy.is_null() {
+        return false;
+    }
+
+    let proxy = unsafe { &mut *proxy };
+    let message = opt_cstring(payload).unwrap_or_default();
+    proxy
+        .proxy
+        .send_event(VeloxUserEvent::Custom(message))
+        .is_ok()
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
+/// Run `callback` on the event loop thread; safe to call from any thread
 #[no_mangle]
-pub extern "C" fn velox_icon_menu_item_set_enabled(
-    item: *mut VeloxIconMenuItemHandle,
-    enabled: bool,
+pub extern "C" fn velox_event_loop_post_task(
+    proxy: *mut VeloxEventLoopProxyHandle,
+    callback: Option<unsafe extern "C" fn(user_data: *mut c_void)>,
+    user_data: *mut c_void,
 ) -> bool {
-    let Some(item) = (unsafe { item.as_mut() }) else {
+    let (Some(proxy), Some(callback)) = (unsafe { proxy.as_ref() }, callback) else {
         return false;
     };
-    item.item.set_enabled(enabled);
-    true
+    // Raw pointers are not `Send`; the caller vouches for handing `user_data` across threads.
+    let user_data = user_data as usize;
+    let task = PostedTask::new(move || unsafe { callback(user_data as *mut c_void) });
+    proxy.proxy.send_event(VeloxUserEvent::Task(task)).is_ok()
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
+/// Call `callback` on the event loop thread after `interval_ms`; returns a timer id or 0
 #[no_mangle]
-pub extern "C" fn velox_icon_menu_item_set_accelerator(
-    item: *mut VeloxIconMenuItemHandle,
-    accelerator: *const c_char,
-) -> bool {
-    guard_panic_bool(|| {
-        let Some(item) = (unsafe { item.as_mut() }) else {
-            return false;
-        };
-        item.item
-            .set_accelerator(accelerator_from_ptr(accelerator))
-            .is_ok()
-    })
+pub extern "C" fn velox_event_loop_add_timer(
+    proxy: *mut VeloxEventLoopProxyHandle,
+    interval_ms: u64,
+    repeat: bool,
+    callback: Option<unsafe extern "C" fn(user_data: *mut c_void)>,
+    user_data: *mut c_void,
+) -> u64 {
+    let (Some(proxy), Some(callback)) = (unsafe { proxy.as_ref() }, callback) else {
+        return 0;
+    };
+    if repeat && interval_ms == 0 {
+        return 0;
+    }
+    let Some(scheduler) = timer_scheduler() else {
+        return 0;
+    };
+    let id = scheduler.next_id.fetch_add(1, Ordering::Relaxed);
+    let interval = Duration::from_millis(interval_ms);
+    let Ok(mut timers) = scheduler.timers.lock() else {
+        return 0;
+    };
+    timers.insert(
+        id,
+        TimerEntry {
+            proxy: proxy.proxy.clone(),
+            loop_id: proxy.loop_id,
+            interval,
+            repeat,
+            next: Some(Instant::now() + interval),
+            callback,
+            user_data: user_data as usize,
+        },
+    );
+    scheduler.wake.notify_one();
+    id
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
+/// Stop a timer added through a proxy of the same event loop
 #[no_mangle]
-pub extern "C" fn velox_icon_menu_item_set_native_icon(
-    item: *mut VeloxIconMenuItemHandle,
-    native_icon: *const c_char,
+pub extern "C" fn velox_event_loop_cancel_timer(
+    proxy: *mut VeloxEventLoopProxyHandle,
+    id: u64,
 ) -> bool {
-    let Some(item) = (unsafe { item.as_mut() }) else {
+    let Some(scheduler) = TIMER_SCHEDULER.get().and_then(Option::as_ref) else {
         return false;
     };
-    let icon = native_icon_from_ptr(native_icon);
-    item.item.set_native_icon(icon);
+    let Some(proxy) = (unsafe { proxy.as_ref() }) else {
+        return false;
+    };
+    let Ok(mut timers) = scheduler.timers.lock() else {
+        return false;
+    };
+    if timers.get(&id).map(|timer| timer.loop_id) != Some(proxy.loop_id) {
+        return false;
+    }
+    timers.remove(&id);
+    scheduler.wake.notify_one();
     true
 }
 
-// MARK: - Predefined Menu Item
-
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[derive(Default, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct VeloxAboutMetadata {
-    name: Option<String>,
-    version: Option<String>,
-    short_version: Option<String>,
-    authors: Option<Vec<String>>,
-    comments: Option<String>,
-    copyright: Option<String>,
-    license: Option<String>,
-    website: Option<String>,
-    website_label: Option<String>,
-    credits: Option<String>,
-    #[serde(default)]
-    icon: Option<serde_json::Value>,
-}
-
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-fn about_metadata_from_json(json: *const c_char) -> Option<AboutMetadata> {
-    let value = opt_cstring(json)?;
-    let parsed: VeloxAboutMetadata = serde_json::from_str(&value).ok()?;
-    let _ = parsed.icon;
-    Some(AboutMetadata {
-        name: parsed.name,
-        version: parsed.version,
-        short_version: parsed.short_version,
-        authors: parsed.authors,
-        comments: parsed.comments,
-        copyright: parsed.copyright,
-        license: parsed.license,
-        website: parsed.website,
-        website_label: parsed.website_label,
-        credits: parsed.credits,
-        icon: None,
-    })
+#[no_mangle]
+pub extern "C" fn velox_event_loop_proxy_free(proxy: *mut VeloxEventLoopProxyHandle) {
+    if !proxy.is_null() {
+        unsafe { drop(Box::from_raw(proxy)) };
+    }
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
+/// Write the scale factor of the primary monitor, usable before any window exists
 #[no_mangle]
-pub extern "C" fn velox_predefined_menu_item_new(
-    item_type: *const c_char,
-    text: *const c_char,
-    about_json: *const c_char,
-) -> *mut VeloxPredefinedMenuItemHandle {
-    guard_panic(|| {
-        let item_type = opt_cstring(item_type).unwrap_or_default();
-        let text = opt_cstring(text);
-        let item = match item_type.as_str() {
-            "Separator" => PredefinedMenuItem::separator(),
-            "Copy" => PredefinedMenuItem::copy(text.as_deref()),
-            "Cut" => PredefinedMenuItem::cut(text.as_deref()),
-            "Paste" => PredefinedMenuItem::paste(text.as_deref()),
-            "SelectAll" => PredefinedMenuItem::select_all(text.as_deref()),
-            "Undo" => PredefinedMenuItem::undo(text.as_deref()),
-            "Redo" => PredefinedMenuItem::redo(text.as_deref()),
-            "Minimize" => PredefinedMenuItem::minimize(text.as_deref()),
-            "Maximize" => PredefinedMenuItem::maximize(text.as_deref()),
-            "Fullscreen" => PredefinedMenuItem::fullscreen(text.as_deref()),
-            "Hide" => PredefinedMenuItem::hide(text.as_deref()),
-            "HideOthers" => PredefinedMenuItem::hide_others(text.as_deref()),
-            "ShowAll" => PredefinedMenuItem::show_all(text.as_deref()),
-            "CloseWindow" => PredefinedMenuItem::close_window(text.as_deref()),
-            "Quit" => PredefinedMenuItem::quit(text.as_deref()),
-            "Services" => PredefinedMenuItem::services(text.as_deref()),
-            "About" => {
-                let metadata = about_metadata_from_json(about_json);
-                PredefinedMenuItem::about(text.as_deref(), metadata)
-            }
-            "BringAllToFront" => PredefinedMenuItem::bring_all_to_front(text.as_deref()),
-            _ => PredefinedMenuItem::separator(),
-        };
-        let identifier = CString::new(item.id().as_ref())
-            .expect("predefined menu item id contains null byte");
-        Box::into_raw(Box::new(VeloxPredefinedMenuItemHandle { item, identifier }))
-    })
+pub extern "C" fn velox_event_loop_get_primary_monitor_scale(
+    event_loop: *mut VeloxEventLoop,
+    scale_factor: *mut f64,
+) -> bool {
+    if event_loop.is_null() || scale_factor.is_null() {
+        return false;
+    }
+
+    let event_loop = unsafe { &*event_loop };
+    let Some(monitor) = event_loop.event_loop.primary_monitor() else {
+        return false;
+    };
+    unsafe {
+        *scale_factor = monitor.scale_factor();
+    }
+    true
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
+/// Write the scale factor of the monitor containing the given point (physical pixels)
 #[no_mangle]
-pub extern "C" fn velox_predefined_menu_item_free(item: *mut VeloxPredefinedMenuItemHandle) {
-    if !item.is_null() {
-        unsafe { drop(Box::from_raw(item)) };
+pub extern "C" fn velox_event_loop_get_monitor_scale_at(
+    event_loop: *mut VeloxEventLoop,
+    x: f64,
+    y: f64,
+    scale_factor: *mut f64,
+) -> bool {
+    if event_loop.is_null() || scale_factor.is_null() {
+        return false;
+    }
+
+    let event_loop = unsafe { &*event_loop };
+    let Some(monitor) = event_loop.event_loop.monitor_from_point(x, y) else {
+        return false;
+    };
+    unsafe {
+        *scale_factor = monitor.scale_factor();
     }
+    true
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
+/// Primary monitor as JSON, usable before any window exists; null if none is detected
 #[no_mangle]
-pub extern "C" fn velox_predefined_menu_item_identifier(
-    item: *mut VeloxPredefinedMenuItemHandle,
+pub extern "C" fn velox_event_loop_get_primary_monitor(
+    event_loop: *mut VeloxEventLoop,
 ) -> *const c_char {
-    let Some(item) = (unsafe { item.as_ref() }) else {
+    let Some(event_loop) = (unsafe { event_loop.as_ref() }) else {
         return ptr::null();
     };
-    item.identifier.as_ptr()
+    match event_loop.event_loop.primary_monitor() {
+        Some(monitor) => write_json_to_buffer(&MONITOR_BUFFER, monitor_to_json(&monitor)),
+        None => ptr::null(),
+    }
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
+/// All connected monitors as a JSON array; null if none are detected
 #[no_mangle]
-pub extern "C" fn velox_predefined_menu_item_text(
-    item: *mut VeloxPredefinedMenuItemHandle,
+pub extern "C" fn velox_event_loop_get_available_monitors(
+    event_loop: *mut VeloxEventLoop,
 ) -> *const c_char {
-    guard_panic_value(|| {
-        let Some(item) = (unsafe { item.as_ref() }) else {
-            return ptr::null();
-        };
-        write_string_to_buffer(&TITLE_BUFFER, item.item.text())
-    })
-}
-
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_predefined_menu_item_set_text(
-    item: *mut VeloxPredefinedMenuItemHandle,
-    title: *const c_char,
-) -> bool {
-    guard_panic_bool(|| {
-        let Some(item) = (unsafe { item.as_mut() }) else {
-            return false;
-        };
-        let text = opt_cstring(title).unwrap_or_default();
-        item.item.set_text(text);
-        true
-    })
-}
-
-// MARK: - Separator Menu Item
-
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_separator_new() -> *mut VeloxSeparatorHandle {
-    guard_panic(|| {
-        let item = PredefinedMenuItem::separator();
-        let identifier = CString::new(item.id().as_ref()).expect("separator id contains null byte");
-        Box::into_raw(Box::new(VeloxSeparatorHandle { item, identifier }))
-    })
-}
-
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_separator_free(separator: *mut VeloxSeparatorHandle) {
-    if !separator.is_null() {
-        unsafe { drop(Box::from_raw(separator)) };
+    let Some(event_loop) = (unsafe { event_loop.as_ref() }) else {
+        return ptr::null();
+    };
+    let monitors: Vec<_> = event_loop
+        .event_loop
+        .available_monitors()
+        .map(|monitor| monitor_to_json(&monitor))
+        .collect();
+    if monitors.is_empty() {
+        return ptr::null();
     }
+    write_json_to_buffer(&MONITOR_LIST_BUFFER, serde_json::Value::Array(monitors))
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
+pub type VeloxLifecycleHandler = Option<unsafe extern "C" fn(user_data: *mut c_void)>;
+
+/// Called when the app is suspended, before the `suspended` event reaches the pump callback
 #[no_mangle]
-pub extern "C" fn velox_separator_identifier(separator: *mut VeloxSeparatorHandle) -> *const c_char {
-    let Some(separator) = (unsafe { separator.as_ref() }) else {
-        return ptr::null();
+pub extern "C" fn velox_event_loop_set_suspend_handler(
+    event_loop: *mut VeloxEventLoop,
+    handler: VeloxLifecycleHandler,
+    user_data: *mut c_void,
+) -> bool {
+    let Some(event_loop) = (unsafe { event_loop.as_ref() }) else {
+        return false;
     };
-    separator.identifier.as_ptr()
+    event_loop
+        .suspend_handler
+        .set(LifecycleCallback::new(handler, user_data));
+    true
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
+/// Called when the app is resumed, before the `resumed` event reaches the pump callback
 #[no_mangle]
-pub extern "C" fn velox_submenu_append_separator(
-    submenu: *mut VeloxSubmenuHandle,
-    separator: *mut VeloxSeparatorHandle,
+pub extern "C" fn velox_event_loop_set_resume_handler(
+    event_loop: *mut VeloxEventLoop,
+    handler: VeloxLifecycleHandler,
+    user_data: *mut c_void,
 ) -> bool {
-    let Some(submenu) = (unsafe { submenu.as_mut() }) else {
-        return false;
-    };
-    let Some(separator) = (unsafe { separator.as_ref() }) else {
+    let Some(event_loop) = (unsafe { event_loop.as_ref() }) else {
         return false;
     };
-    if submenu.submenu.borrow().append(&separator.item).is_ok() {
-        submenu.items.push(MenuItemKind::Predefined(separator.item.clone()));
-        true
-    } else {
-        false
-    }
+    event_loop
+        .resume_handler
+        .set(LifecycleCallback::new(handler, user_data));
+    true
 }
 
-// MARK: - Check Menu Item
-
-#[cfg(any(target_os = "macos", target_os = "linux"))]
+/// Toggle the app's accessibility mode, reported as an `accessibility-enabled` or `-disabled` event
 #[no_mangle]
-pub extern "C" fn velox_check_menu_item_new(
-    id: *const c_char,
-    title: *const c_char,
+pub extern "C" fn velox_event_loop_set_accessibility_enabled(
+    event_loop: *mut VeloxEventLoop,
     enabled: bool,
-    checked: bool,
-    accelerator: *const c_char,
-) -> *mut VeloxCheckMenuItemHandle {
-    guard_panic(|| {
-        let title = opt_cstring(title).unwrap_or_default();
-        let accelerator = accelerator_from_ptr(accelerator);
-        let item = if let Some(id) = opt_cstring(id) {
-            CheckMenuItem::with_id(MenuId::new(id.clone()), title, enabled, checked, accelerator)
-        } else {
-            CheckMenuItem::new(title, enabled, checked, accelerator)
-        };
-        let identifier = CString::new(item.id().as_ref()).expect("check menu item id contains null byte");
-        Box::into_raw(Box::new(VeloxCheckMenuItemHandle { item, identifier }))
-    })
-}
-
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_check_menu_item_free(item: *mut VeloxCheckMenuItemHandle) {
-    if !item.is_null() {
-        unsafe { drop(Box::from_raw(item)) };
+) -> bool {
+    let Some(event_loop) = (unsafe { event_loop.as_ref() }) else {
+        return false;
+    };
+    if event_loop.accessibility_enabled.replace(enabled) == enabled {
+        return true;
     }
+    event_loop
+        .event_loop
+        .create_proxy()
+        .send_event(VeloxUserEvent::Accessibility(enabled))
+        .is_ok()
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_check_menu_item_is_checked(item: *mut VeloxCheckMenuItemHandle) -> bool {
-    guard_panic_bool(|| {
-        let Some(item) = (unsafe { item.as_ref() }) else {
-            return false;
-        };
-        item.item.is_checked()
-    })
-}
-
-#[cfg(any(target_os = "macos", target_os = "linux"))]
+/// Only deliver events in the filter's categories to the pump callback
 #[no_mangle]
-pub extern "C" fn velox_check_menu_item_set_checked(
-    item: *mut VeloxCheckMenuItemHandle,
-    checked: bool,
+pub extern "C" fn velox_event_loop_set_filter(
+    event_loop: *mut VeloxEventLoop,
+    filter: VeloxEventFilter,
 ) -> bool {
-    let Some(item) = (unsafe { item.as_mut() }) else {
+    let Some(event_loop) = (unsafe { event_loop.as_ref() }) else {
         return false;
     };
-    item.item.set_checked(checked);
+    event_loop.event_filter.set(Some(filter));
     true
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_check_menu_item_is_enabled(item: *mut VeloxCheckMenuItemHandle) -> bool {
-    guard_panic_bool(|| {
-        let Some(item) = (unsafe { item.as_ref() }) else {
-            return false;
-        };
-        item.item.is_enabled()
-    })
+pub type VeloxMenuProvider =
+    Option<unsafe extern "C" fn(user_data: *mut c_void) -> *mut VeloxMenuBarHandle>;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VeloxPermissionState {
+    Granted = 0,
+    Denied = 1,
+    /// The platform allows the action without asking
+    NotRequired = 2,
+    Unsupported = 3,
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
+pub type VeloxPermissionCallback =
+    Option<unsafe extern "C" fn(state: VeloxPermissionState, user_data: *mut c_void)>;
+
+/// Report whether floating windows need consent (macOS only)
 #[no_mangle]
-pub extern "C" fn velox_check_menu_item_set_enabled(
-    item: *mut VeloxCheckMenuItemHandle,
-    enabled: bool,
+pub extern "C" fn velox_request_permission_always_on_top(
+    event_loop: *mut VeloxEventLoop,
+    callback: VeloxPermissionCallback,
+    user_data: *mut c_void,
 ) -> bool {
-    let Some(item) = (unsafe { item.as_mut() }) else {
+    if event_loop.is_null() {
         return false;
+    }
+    let state = if cfg!(target_os = "macos") {
+        VeloxPermissionState::NotRequired
+    } else {
+        VeloxPermissionState::Unsupported
     };
-    item.item.set_enabled(enabled);
-    true
-}
-
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_check_menu_item_text(item: *mut VeloxCheckMenuItemHandle) -> *const c_char {
-    guard_panic_value(|| {
-        let Some(item) = (unsafe { item.as_ref() }) else {
-            return ptr::null();
-        };
-        write_string_to_buffer(&TITLE_BUFFER, item.item.text())
-    })
+    if let Some(callback) = callback {
+        let _ = catch_unwind(AssertUnwindSafe(|| unsafe { callback(state, user_data) }));
+    }
+    state != VeloxPermissionState::Unsupported
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 #[no_mangle]
-pub extern "C" fn velox_check_menu_item_set_text(
-    item: *mut VeloxCheckMenuItemHandle,
-    title: *const c_char,
+pub extern "C" fn velox_event_loop_set_activation_policy(
+    event_loop: *mut VeloxEventLoop,
+    policy: VeloxActivationPolicy,
 ) -> bool {
-    guard_panic_bool(|| {
-        let Some(item) = (unsafe { item.as_mut() }) else {
+    #[cfg(target_os = "macos")]
+    {
+        if event_loop.is_null() {
             return false;
-        };
-        let text = opt_cstring(title).unwrap_or_default();
-        item.item.set_text(text);
+        }
+
+        let event_loop = unsafe { &mut *event_loop };
+        event_loop
+            .event_loop
+            .set_activation_policy_at_runtime(activation_policy_from_ffi(policy));
         true
-    })
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (event_loop, policy);
+        false
+    }
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 #[no_mangle]
-pub extern "C" fn velox_check_menu_item_set_accelerator(
-    item: *mut VeloxCheckMenuItemHandle,
-    accelerator: *const c_char,
+pub extern "C" fn velox_event_loop_set_dock_visibility(
+    event_loop: *mut VeloxEventLoop,
+    visible: bool,
 ) -> bool {
-    guard_panic_bool(|| {
-        let Some(item) = (unsafe { item.as_mut() }) else {
+    #[cfg(target_os = "macos")]
+    {
+        if event_loop.is_null() {
             return false;
-        };
-        item.item
-            .set_accelerator(accelerator_from_ptr(accelerator))
-            .is_ok()
-    })
-}
-
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_check_menu_item_identifier(item: *mut VeloxCheckMenuItemHandle) -> *const c_char {
-    let Some(item) = (unsafe { item.as_ref() }) else {
-        return ptr::null();
-    };
-    item.identifier.as_ptr()
-}
+        }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_submenu_append_check_item(
-    submenu: *mut VeloxSubmenuHandle,
-    item: *mut VeloxCheckMenuItemHandle,
-) -> bool {
-    let Some(submenu) = (unsafe { submenu.as_mut() }) else {
-        return false;
-    };
-    let Some(item) = (unsafe { item.as_ref() }) else {
-        return false;
-    };
-    if submenu.submenu.borrow().append(&item.item).is_ok() {
-        submenu.items.push(MenuItemKind::Check(item.item.clone()));
+        let event_loop = unsafe { &mut *event_loop };
+        event_loop.event_loop.set_dock_visibility(visible);
         true
-    } else {
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (event_loop, visible);
         false
     }
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 #[no_mangle]
-pub extern "C" fn velox_tray_new(config: *const VeloxTrayConfig) -> *mut VeloxTrayHandle {
-    guard_panic(|| {
-        let cfg = unsafe { config.as_ref() }.copied().unwrap_or_default();
-        let identifier = opt_cstring(cfg.identifier);
-        let title = opt_cstring(cfg.title);
-        let tooltip = opt_cstring(cfg.tooltip);
+pub extern "C" fn velox_event_loop_hide_application(event_loop: *mut VeloxEventLoop) -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        if event_loop.is_null() {
+            return false;
+        }
 
-        let mut builder = TrayIconBuilder::new();
-        if let Some(ref id) = identifier {
-            builder = builder.with_id(id.clone());
-        }
-        if let Some(ref title) = title {
-            builder = builder.with_title(title.clone());
-        }
-        if let Some(ref tooltip) = tooltip {
-            builder = builder.with_tooltip(tooltip.clone());
-        }
-        builder = builder.with_menu_on_left_click(cfg.show_menu_on_left_click);
+        let event_loop = unsafe { &mut *event_loop };
+        event_loop.event_loop.hide_application();
+        true
+    }
 
-        let tray = match builder.build() {
-            Ok(tray) => tray,
-            Err(_) => return ptr::null_mut(),
-        };
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = event_loop;
+        false
+    }
+}
 
-        if !cfg.visible {
-            let _ = tray.set_visible(false);
+#[no_mangle]
+pub extern "C" fn velox_event_loop_show_application(event_loop: *mut VeloxEventLoop) -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        if event_loop.is_null() {
+            return false;
         }
 
-        tray.set_show_menu_on_left_click(cfg.show_menu_on_left_click);
+        let event_loop = unsafe { &mut *event_loop };
+        event_loop.event_loop.show_application();
+        true
+    }
 
-        let identifier = CString::new(tray.id().as_ref())
-            .unwrap_or_else(|_| CString::new("velox-tray").expect("static string has no nulls"));
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = event_loop;
+        false
+    }
+}
 
-        Box::into_raw(Box::new(VeloxTrayHandle {
-            tray,
-            menu: None,
-            identifier,
-        }))
-    })
+fn accelerator_from_ptr(ptr: *const c_char) -> Option<Accelerator> {
+    opt_cstring(ptr)?.parse().ok()
 }
 
-#[cfg(target_os = "windows")]
-#[no_mangle]
-pub extern "C" fn velox_tray_new(_config: *const VeloxTrayConfig) -> *mut VeloxTrayHandle {
-    ptr::null_mut()
+fn native_icon_from_ptr(ptr: *const c_char) -> Option<NativeIcon> {
+    let value = opt_cstring(ptr)?;
+    let quoted = format!("\"{}\"", value);
+    serde_json::from_str(&quoted).ok()
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_tray_free(tray: *mut VeloxTrayHandle) {
-    if !tray.is_null() {
-        unsafe { drop(Box::from_raw(tray)) };
-    }
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub enum VeloxMenuItemKind {
+    MenuItem = 0,
+    Predefined = 1,
+    Check = 2,
+    Icon = 3,
+    Submenu = 4,
 }
 
-#[cfg(target_os = "windows")]
-#[no_mangle]
-pub extern "C" fn velox_tray_free(_tray: *mut VeloxTrayHandle) {}
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VeloxPredefinedMenuItemKind {
+    Separator = 0,
+    Copy = 1,
+    Cut = 2,
+    Paste = 3,
+    SelectAll = 4,
+    Undo = 5,
+    Redo = 6,
+    Minimize = 7,
+    Maximize = 8,
+    Fullscreen = 9,
+    Hide = 10,
+    HideOthers = 11,
+    ShowAll = 12,
+    CloseWindow = 13,
+    Quit = 14,
+    About = 15,
+    Services = 16,
+    BringAllToFront = 17,
+}
+
+impl VeloxPredefinedMenuItemKind {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "Separator" => Self::Separator,
+            "Copy" => Self::Copy,
+            "Cut" => Self::Cut,
+            "Paste" => Self::Paste,
+            "SelectAll" => Self::SelectAll,
+            "Undo" => Self::Undo,
+            "Redo" => Self::Redo,
+            "Minimize" => Self::Minimize,
+            "Maximize" => Self::Maximize,
+            "Fullscreen" => Self::Fullscreen,
+            "Hide" => Self::Hide,
+            "HideOthers" => Self::HideOthers,
+            "ShowAll" => Self::ShowAll,
+            "CloseWindow" => Self::CloseWindow,
+            "Quit" => Self::Quit,
+            "About" => Self::About,
+            "Services" => Self::Services,
+            "BringAllToFront" => Self::BringAllToFront,
+            _ => return None,
+        })
+    }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
-#[no_mangle]
-pub extern "C" fn velox_tray_identifier(tray: *mut VeloxTrayHandle) -> *const c_char {
-    let Some(tray) = (unsafe { tray.as_ref() }) else {
-        return ptr::null();
-    };
-    tray.identifier.as_ptr()
+    /// `text` overrides the platform's default title; `about` is only read for `About`.
+    fn build(self, text: Option<&str>, about: Option<AboutMetadata>) -> PredefinedMenuItem {
+        match self {
+            Self::Separator => PredefinedMenuItem::separator(),
+            Self::Copy => PredefinedMenuItem::copy(text),
+            Self::Cut => PredefinedMenuItem::cut(text),
+            Self::Paste => PredefinedMenuItem::paste(text),
+            Self::SelectAll => PredefinedMenuItem::select_all(text),
+            Self::Undo => PredefinedMenuItem::undo(text),
+            Self::Redo => PredefinedMenuItem::redo(text),
+            Self::Minimize => PredefinedMenuItem::minimize(text),
+            Self::Maximize => PredefinedMenuItem::maximize(text),
+            Self::Fullscreen => PredefinedMenuItem::fullscreen(text),
+            Self::Hide => PredefinedMenuItem::hide(text),
+            Self::HideOthers => PredefinedMenuItem::hide_others(text),
+            Self::ShowAll => PredefinedMenuItem::show_all(text),
+            Self::CloseWindow => PredefinedMenuItem::close_window(text),
+            Self::Quit => PredefinedMenuItem::quit(text),
+            Self::About => PredefinedMenuItem::about(text, about),
+            Self::Services => PredefinedMenuItem::services(text),
+            Self::BringAllToFront => PredefinedMenuItem::bring_all_to_front(text),
+        }
+    }
 }
 
-#[cfg(target_os = "windows")]
 #[no_mangle]
-pub extern "C" fn velox_tray_identifier(_tray: *mut VeloxTrayHandle) -> *const c_char {
-    ptr::null()
+pub extern "C" fn velox_menu_bar_new() -> *mut VeloxMenuBarHandle {
+    guard_panic(|| {
+        let menu = Menu::new();
+        let identifier = CString::new(menu.id().as_ref()).expect("menu id contains null byte");
+        Box::into_raw(Box::new(VeloxMenuBarHandle {
+            menu,
+            submenus: Vec::new(),
+            items: Vec::new(),
+            identifier,
+        }))
+    })
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 #[no_mangle]
-pub extern "C" fn velox_tray_set_title(tray: *mut VeloxTrayHandle, title: *const c_char) -> bool {
-    let Some(tray) = (unsafe { tray.as_mut() }) else {
-        return false;
-    };
-    let result_title = opt_cstring(title);
-    tray.tray.set_title(result_title.as_deref());
-    true
+pub extern "C" fn velox_menu_bar_new_with_id(id: *const c_char) -> *mut VeloxMenuBarHandle {
+    guard_panic(|| {
+        let identifier_string = opt_cstring(id).unwrap_or_default();
+        let menu = Menu::with_id(MenuId::new(identifier_string.clone()));
+        let identifier = CString::new(identifier_string).expect("menu id contains null byte");
+        Box::into_raw(Box::new(VeloxMenuBarHandle {
+            menu,
+            submenus: Vec::new(),
+            items: Vec::new(),
+            identifier,
+        }))
+    })
 }
 
-#[cfg(target_os = "windows")]
 #[no_mangle]
-pub extern "C" fn velox_tray_set_title(_tray: *mut VeloxTrayHandle, _title: *const c_char) -> bool {
-    false
+pub extern "C" fn velox_menu_bar_free(menu: *mut VeloxMenuBarHandle) {
+    if !menu.is_null() {
+        unsafe { drop(Box::from_raw(menu)) };
+    }
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 #[no_mangle]
-pub extern "C" fn velox_tray_set_tooltip(
-    tray: *mut VeloxTrayHandle,
-    tooltip: *const c_char,
-) -> bool {
-    let Some(tray) = (unsafe { tray.as_mut() }) else {
-        return false;
+pub extern "C" fn velox_menu_bar_identifier(menu: *mut VeloxMenuBarHandle) -> *const c_char {
+    let Some(menu) = (unsafe { menu.as_ref() }) else {
+        return ptr::null();
     };
-    let tooltip = opt_cstring(tooltip);
-    tray.tray.set_tooltip(tooltip.as_deref()).is_ok()
+    menu.identifier.as_ptr()
 }
 
-#[cfg(target_os = "windows")]
 #[no_mangle]
-pub extern "C" fn velox_tray_set_tooltip(
-    _tray: *mut VeloxTrayHandle,
-    _tooltip: *const c_char,
+pub extern "C" fn velox_menu_bar_append_submenu(
+    menu: *mut VeloxMenuBarHandle,
+    submenu: *mut VeloxSubmenuHandle,
 ) -> bool {
-    false
+    velox_menu_bar_append_submenu_checked(menu, submenu) == VeloxErrorCode::Success
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 #[no_mangle]
-pub extern "C" fn velox_tray_set_visible(tray: *mut VeloxTrayHandle, visible: bool) -> bool {
-    let Some(tray) = (unsafe { tray.as_mut() }) else {
-        return false;
+pub extern "C" fn velox_menu_bar_append_submenu_checked(
+    menu: *mut VeloxMenuBarHandle,
+    submenu: *mut VeloxSubmenuHandle,
+) -> VeloxErrorCode {
+    let Some(menu) = (unsafe { menu.as_mut() }) else {
+        return VeloxErrorCode::NullPointer;
+    };
+    let Some(submenu) = (unsafe { submenu.as_ref() }) else {
+        return VeloxErrorCode::NullPointer;
     };
-    tray.tray.set_visible(visible).is_ok()
-}
 
-#[cfg(target_os = "windows")]
-#[no_mangle]
-pub extern "C" fn velox_tray_set_visible(_tray: *mut VeloxTrayHandle, _visible: bool) -> bool {
-    false
+    let result = {
+        let submenu_ref = submenu.submenu.borrow();
+        menu.menu.append(&*submenu_ref)
+    };
+
+    let code = VeloxErrorCode::from_result(record_error(result, VeloxErrorCode::Unknown));
+    if code == VeloxErrorCode::Success {
+        menu.submenus.push(submenu.submenu.clone());
+        menu
+            .items
+            .push(MenuItemKind::Submenu(submenu.submenu.borrow().clone()));
+    }
+    code
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 #[no_mangle]
-pub extern "C" fn velox_tray_set_show_menu_on_left_click(
-    tray: *mut VeloxTrayHandle,
-    enable: bool,
+pub extern "C" fn velox_menu_bar_append(
+    menu: *mut VeloxMenuBarHandle,
+    kind: VeloxMenuItemKind,
+    item: *mut c_void,
 ) -> bool {
-    let Some(tray) = (unsafe { tray.as_mut() }) else {
+    let Some(menu) = (unsafe { menu.as_mut() }) else {
         return false;
     };
-    tray.tray.set_show_menu_on_left_click(enable);
-    true
-}
 
-#[cfg(target_os = "windows")]
-#[no_mangle]
-pub extern "C" fn velox_tray_set_show_menu_on_left_click(
-    _tray: *mut VeloxTrayHandle,
-    _enable: bool,
-) -> bool {
-    false
+    match kind {
+        VeloxMenuItemKind::MenuItem => {
+            let Some(item) = (unsafe { (item as *mut VeloxMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            if report_error(menu.menu.append(&item.item), VeloxErrorCode::Unknown).is_some() {
+                menu.items.push(MenuItemKind::MenuItem(item.item.clone()));
+                true
+            } else {
+                false
+            }
+        }
+        VeloxMenuItemKind::Predefined => {
+            let Some(item) = (unsafe { (item as *mut VeloxPredefinedMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            if report_error(menu.menu.append(&item.item), VeloxErrorCode::Unknown).is_some() {
+                menu.items.push(MenuItemKind::Predefined(item.item.clone()));
+                true
+            } else {
+                false
+            }
+        }
+        VeloxMenuItemKind::Check => {
+            let Some(item) = (unsafe { (item as *mut VeloxCheckMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            if report_error(menu.menu.append(&item.item), VeloxErrorCode::Unknown).is_some() {
+                menu.items.push(MenuItemKind::Check(item.item.clone()));
+                true
+            } else {
+                false
+            }
+        }
+        VeloxMenuItemKind::Icon => {
+            let Some(item) = (unsafe { (item as *mut VeloxIconMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            if report_error(menu.menu.append(&item.item), VeloxErrorCode::Unknown).is_some() {
+                menu.items.push(MenuItemKind::Icon(item.item.clone()));
+                true
+            } else {
+                false
+            }
+        }
+        VeloxMenuItemKind::Submenu => {
+            let Some(item) = (unsafe { (item as *mut VeloxSubmenuHandle).as_ref() }) else {
+                return false;
+            };
+            let submenu_ref = item.submenu.borrow();
+            if report_error(menu.menu.append(&*submenu_ref), VeloxErrorCode::Unknown).is_some() {
+                menu.submenus.push(item.submenu.clone());
+                menu.items.push(MenuItemKind::Submenu(submenu_ref.clone()));
+                true
+            } else {
+                false
+            }
+        }
+    }
 }
 
-#[cfg(any(target_os = "macos", target_os = "linux"))]
 #[no_mangle]
-pub extern "C" fn velox_tray_set_menu(
-    tray: *mut VeloxTrayHandle,
+pub extern "C" fn velox_menu_bar_prepend(
     menu: *mut VeloxMenuBarHandle,
+    kind: VeloxMenuItemKind,
+    item: *mut c_void,
 ) -> bool {
-    let Some(tray) = (unsafe { tray.as_mut() }) else {
+    let Some(menu) = (unsafe { menu.as_mut() }) else {
         return false;
     };
 
-    if menu.is_null() {
-        tray.tray
-            .set_menu(None::<Box<dyn tray_icon::menu::ContextMenu>>);
-        tray.menu = None;
-        return true;
-    }
-
-    let Some(menu_handle) = (unsafe { menu.as_ref() }) else {
-        return false;
-    };
-
-    let cloned_menu = menu_handle.menu.clone();
-    tray.tray.set_menu(Some(
-        Box::new(cloned_menu.clone()) as Box<dyn tray_icon::menu::ContextMenu>
-    ));
-    tray.menu = Some(cloned_menu);
-    true
-}
-
-#[cfg(target_os = "windows")]
-#[no_mangle]
-pub extern "C" fn velox_tray_set_menu(
-    _tray: *mut VeloxTrayHandle,
-    _menu: *mut VeloxMenuBarHandle,
-) -> bool {
-    false
-}
-
-#[no_mangle]
-pub extern "C" fn velox_event_loop_pump(
-    event_loop: *mut VeloxEventLoop,
-    callback: VeloxEventLoopCallback,
-    user_data: *mut c_void,
-) {
-    if event_loop.is_null() {
-        return;
-    }
-
-    let event_loop = unsafe { &mut *event_loop };
-    event_loop
-        .event_loop
-        .run_return(|event, _target, control_flow| {
-            if let Some(cb) = callback {
-                let description = serialize_event(&event);
-                if let Ok(c_description) = CString::new(description) {
-                    let desired_flow = cb(c_description.as_ptr(), user_data);
-                    match desired_flow {
-                        VeloxEventLoopControlFlow::Poll => *control_flow = ControlFlow::Poll,
-                        VeloxEventLoopControlFlow::Wait => *control_flow = ControlFlow::Wait,
-                        VeloxEventLoopControlFlow::Exit => *control_flow = ControlFlow::Exit,
-                    }
-                } else {
-                    *control_flow = ControlFlow::Exit;
-                }
+    match kind {
+        VeloxMenuItemKind::MenuItem => {
+            let Some(item) = (unsafe { (item as *mut VeloxMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            if report_error(menu.menu.prepend(&item.item), VeloxErrorCode::Unknown).is_some() {
+                menu.items.insert(0, MenuItemKind::MenuItem(item.item.clone()));
+                true
             } else {
-                *control_flow = ControlFlow::Exit;
+                false
             }
-
-            if matches!(event, Event::UserEvent(VeloxUserEvent::Exit)) {
-                *control_flow = ControlFlow::Exit;
+        }
+        VeloxMenuItemKind::Predefined => {
+            let Some(item) = (unsafe { (item as *mut VeloxPredefinedMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            if report_error(menu.menu.prepend(&item.item), VeloxErrorCode::Unknown).is_some() {
+                menu.items.insert(0, MenuItemKind::Predefined(item.item.clone()));
+                true
+            } else {
+                false
             }
-
-            if matches!(event, Event::LoopDestroyed) {
-                *control_flow = ControlFlow::Exit;
+        }
+        VeloxMenuItemKind::Check => {
+            let Some(item) = (unsafe { (item as *mut VeloxCheckMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            if report_error(menu.menu.prepend(&item.item), VeloxErrorCode::Unknown).is_some() {
+                menu.items.insert(0, MenuItemKind::Check(item.item.clone()));
+                true
+            } else {
+                false
             }
-        });
+        }
+        VeloxMenuItemKind::Icon => {
+            let Some(item) = (unsafe { (item as *mut VeloxIconMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            if report_error(menu.menu.prepend(&item.item), VeloxErrorCode::Unknown).is_some() {
+                menu.items.insert(0, MenuItemKind::Icon(item.item.clone()));
+                true
+            } else {
+                false
+            }
+        }
+        VeloxMenuItemKind::Submenu => {
+            let Some(item) = (unsafe { (item as *mut VeloxSubmenuHandle).as_ref() }) else {
+                return false;
+            };
+            let submenu_ref = item.submenu.borrow();
+            if report_error(menu.menu.prepend(&*submenu_ref), VeloxErrorCode::Unknown).is_some() {
+                menu.submenus.push(item.submenu.clone());
+                menu.items.insert(0, MenuItemKind::Submenu(submenu_ref.clone()));
+                true
+            } else {
+                false
+            }
+        }
+    }
 }
 
 #[no_mangle]
-pub extern "C" fn velox_window_build(
-    event_loop: *mut VeloxEventLoop,
-    config: *const VeloxWindowConfig,
-) -> *mut VeloxWindowHandle {
-    if event_loop.is_null() {
-        return ptr::null_mut();
-    }
-
-    let event_loop = unsafe { &mut *event_loop };
-    let cfg = unsafe { config.as_ref().copied().unwrap_or_default() };
-
-    let build_result = catch_unwind(AssertUnwindSafe(|| {
-        let mut builder = TaoWindowBuilder::new();
-
-        if let Some(title) = opt_cstring(cfg.title) {
-            builder = builder.with_title(title);
-        }
-
-        if let Some(parent) = unsafe { cfg.parent.as_ref() } {
-            builder = apply_parent_builder(builder, &parent.window);
-        }
+pub extern "C" fn velox_menu_bar_insert(
+    menu: *mut VeloxMenuBarHandle,
+    kind: VeloxMenuItemKind,
+    item: *mut c_void,
+    position: usize,
+) -> bool {
+    let Some(menu) = (unsafe { menu.as_mut() }) else {
+        return false;
+    };
 
-        if let Some(shadow) = opt_bool(cfg.has_shadow) {
-            #[cfg(target_os = "macos")]
+    let insert_index = position.min(menu.items.len());
+    match kind {
+        VeloxMenuItemKind::MenuItem => {
+            let Some(item) = (unsafe { (item as *mut VeloxMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            if report_error(
+                menu.menu.insert(&item.item, insert_index),
+                VeloxErrorCode::Unknown,
+            )
+            .is_some()
             {
-                builder = builder.with_has_shadow(shadow);
+                menu.items
+                    .insert(insert_index, MenuItemKind::MenuItem(item.item.clone()));
+                true
+            } else {
+                false
             }
-            #[cfg(target_os = "windows")]
+        }
+        VeloxMenuItemKind::Predefined => {
+            let Some(item) = (unsafe { (item as *mut VeloxPredefinedMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            if report_error(
+                menu.menu.insert(&item.item, insert_index),
+                VeloxErrorCode::Unknown,
+            )
+            .is_some()
             {
-                builder = builder.with_undecorated_shadow(shadow);
+                menu.items
+                    .insert(insert_index, MenuItemKind::Predefined(item.item.clone()));
+                true
+            } else {
+                false
             }
         }
-
-        #[cfg(target_os = "macos")]
-        {
-            if let Some(transparent) = opt_bool(cfg.titlebar_transparent) {
-                builder = builder.with_titlebar_transparent(transparent);
-            }
-            if let Some(hidden) = opt_bool(cfg.titlebar_hidden) {
-                builder = builder.with_titlebar_hidden(hidden);
-            }
-            if let Some(hidden_buttons) = opt_bool(cfg.titlebar_buttons_hidden) {
-                builder = builder.with_titlebar_buttons_hidden(hidden_buttons);
+        VeloxMenuItemKind::Check => {
+            let Some(item) = (unsafe { (item as *mut VeloxCheckMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            if report_error(
+                menu.menu.insert(&item.item, insert_index),
+                VeloxErrorCode::Unknown,
+            )
+            .is_some()
+            {
+                menu.items
+                    .insert(insert_index, MenuItemKind::Check(item.item.clone()));
+                true
+            } else {
+                false
             }
         }
-
-        if cfg.width > 0 && cfg.height > 0 {
-            builder =
-                builder.with_inner_size(LogicalSize::new(cfg.width as f64, cfg.height as f64));
+        VeloxMenuItemKind::Icon => {
+            let Some(item) = (unsafe { (item as *mut VeloxIconMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            if report_error(
+                menu.menu.insert(&item.item, insert_index),
+                VeloxErrorCode::Unknown,
+            )
+            .is_some()
+            {
+                menu.items
+                    .insert(insert_index, MenuItemKind::Icon(item.item.clone()));
+                true
+            } else {
+                false
+            }
         }
-
-        builder.build(&event_loop.event_loop)
-    }));
-
-    match build_result {
-        Ok(Ok(window)) => {
-            let id_string = format!("{:?}", window.id());
-            let identifier = CString::new(id_string).unwrap_or_else(|_| {
-                CString::new("velox-window").expect("static string has no nulls")
-            });
-            Box::into_raw(Box::new(VeloxWindowHandle { window, identifier }))
+        VeloxMenuItemKind::Submenu => {
+            let Some(item) = (unsafe { (item as *mut VeloxSubmenuHandle).as_ref() }) else {
+                return false;
+            };
+            let submenu_ref = item.submenu.borrow();
+            if report_error(
+                menu.menu.insert(&*submenu_ref, insert_index),
+                VeloxErrorCode::Unknown,
+            )
+            .is_some()
+            {
+                menu.submenus.push(item.submenu.clone());
+                menu.items.insert(insert_index, MenuItemKind::Submenu(submenu_ref.clone()));
+                true
+            } else {
+                false
+            }
         }
-        _ => ptr::null_mut(),
-    }
-}
-
-#[no_mangle]
-pub extern "C" fn velox_window_free(window: *mut VeloxWindowHandle) {
-    if !window.is_null() {
-        unsafe { drop(Box::from_raw(window)) };
-    }
-}
-
-#[no_mangle]
-pub extern "C" fn velox_window_identifier(window: *mut VeloxWindowHandle) -> *const c_char {
-    if window.is_null() {
-        return ptr::null();
     }
-
-    unsafe { &*window }.identifier.as_ptr()
 }
 
 #[no_mangle]
-pub extern "C" fn velox_window_set_title(
-    window: *mut VeloxWindowHandle,
-    title: *const c_char,
+pub extern "C" fn velox_menu_bar_remove(
+    menu: *mut VeloxMenuBarHandle,
+    kind: VeloxMenuItemKind,
+    item: *mut c_void,
 ) -> bool {
-    let Some(title) = opt_cstring(title) else {
+    let Some(menu) = (unsafe { menu.as_mut() }) else {
         return false;
     };
-    with_window(window, |w| {
-        w.set_title(&title);
-        true
-    })
-    .unwrap_or(false)
-}
 
-#[no_mangle]
-pub extern "C" fn velox_window_set_fullscreen(
-    window: *mut VeloxWindowHandle,
-    fullscreen: bool,
-) -> bool {
-    with_window(window, |w| {
-        if fullscreen {
-            w.set_fullscreen(Some(Fullscreen::Borderless(None)));
-        } else {
-            w.set_fullscreen(None);
+    match kind {
+        VeloxMenuItemKind::MenuItem => {
+            let Some(item) = (unsafe { (item as *mut VeloxMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            report_error(menu.menu.remove(&item.item), VeloxErrorCode::Unknown).is_some()
         }
-        true
-    })
-    .unwrap_or(false)
-}
-
-#[no_mangle]
-pub extern "C" fn velox_window_set_decorations(
-    window: *mut VeloxWindowHandle,
-    decorations: bool,
-) -> bool {
-    with_window(window, |w| {
-        w.set_decorations(decorations);
-        true
-    })
-    .unwrap_or(false)
-}
-
-#[no_mangle]
-pub extern "C" fn velox_window_set_shadow(window: *mut VeloxWindowHandle, shadow: bool) -> bool {
-    with_window(window, |w| {
-        #[cfg(target_os = "macos")]
-        {
-            w.set_has_shadow(shadow);
-            return true;
+        VeloxMenuItemKind::Predefined => {
+            let Some(item) = (unsafe { (item as *mut VeloxPredefinedMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            report_error(menu.menu.remove(&item.item), VeloxErrorCode::Unknown).is_some()
         }
-        #[cfg(target_os = "windows")]
-        {
-            w.set_undecorated_shadow(shadow);
-            return true;
+        VeloxMenuItemKind::Check => {
+            let Some(item) = (unsafe { (item as *mut VeloxCheckMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            report_error(menu.menu.remove(&item.item), VeloxErrorCode::Unknown).is_some()
         }
-        #[allow(unreachable_code)]
-        {
-            let _ = shadow;
-            false
+        VeloxMenuItemKind::Icon => {
+            let Some(item) = (unsafe { (item as *mut VeloxIconMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            report_error(menu.menu.remove(&item.item), VeloxErrorCode::Unknown).is_some()
         }
-    })
-    .unwrap_or(false)
+        VeloxMenuItemKind::Submenu => {
+            let Some(item) = (unsafe { (item as *mut VeloxSubmenuHandle).as_ref() }) else {
+                return false;
+            };
+            let submenu_ref = item.submenu.borrow();
+            report_error(menu.menu.remove(&*submenu_ref), VeloxErrorCode::Unknown).is_some()
+        }
+    }
 }
 
 #[no_mangle]
-pub extern "C" fn velox_window_set_always_on_bottom(
-    window: *mut VeloxWindowHandle,
-    on_bottom: bool,
+pub extern "C" fn velox_menu_bar_remove_at(
+    menu: *mut VeloxMenuBarHandle,
+    position: usize,
 ) -> bool {
-    with_window(window, |w| {
-        w.set_always_on_bottom(on_bottom);
-        true
-    })
-    .unwrap_or(false)
+    let Some(menu) = (unsafe { menu.as_mut() }) else {
+        return false;
+    };
+    let Some(removed) = menu.menu.remove_at(position) else {
+        return false;
+    };
+    menu.items.retain(|item| item.id() != removed.id());
+    true
 }
 
+/// Insert `submenu` at `index`, clamped to the end of the menu bar
 #[no_mangle]
-pub extern "C" fn velox_window_set_visible_on_all_workspaces(
-    window: *mut VeloxWindowHandle,
-    visible_on_all_workspaces: bool,
+pub extern "C" fn velox_menu_bar_insert_submenu_at_index(
+    menu: *mut VeloxMenuBarHandle,
+    submenu: *mut VeloxSubmenuHandle,
+    index: usize,
 ) -> bool {
-    with_window(window, |w| {
-        w.set_visible_on_all_workspaces(visible_on_all_workspaces);
-        true
-    })
-    .unwrap_or(false)
+    velox_menu_bar_insert(menu, VeloxMenuItemKind::Submenu, submenu.cast(), index)
 }
 
+/// Remove the top-level submenu whose menu id is `submenu_id`
 #[no_mangle]
-pub extern "C" fn velox_window_set_content_protected(
-    window: *mut VeloxWindowHandle,
-    protected: bool,
+pub extern "C" fn velox_menu_bar_remove_submenu(
+    menu: *mut VeloxMenuBarHandle,
+    submenu_id: *const c_char,
 ) -> bool {
-    with_window(window, |w| {
-        w.set_content_protection(protected);
-        true
-    })
-    .unwrap_or(false)
+    let Some(menu) = (unsafe { menu.as_mut() }) else {
+        return false;
+    };
+    let Some(submenu_id) = opt_cstring(submenu_id) else {
+        return false;
+    };
+    let Some(position) = menu.menu.items().iter().position(|item| {
+        matches!(item, MenuItemKind::Submenu(_)) && item.id().as_ref() == submenu_id
+    }) else {
+        return false;
+    };
+    if menu.menu.remove_at(position).is_none() {
+        return false;
+    }
+    menu.items.retain(|item| item.id().as_ref() != submenu_id);
+    menu.submenus
+        .retain(|submenu| submenu.borrow().id().as_ref() != submenu_id);
+    true
 }
 
+#[cfg(target_os = "macos")]
 #[no_mangle]
-pub extern "C" fn velox_window_set_resizable(
-    window: *mut VeloxWindowHandle,
-    resizable: bool,
-) -> bool {
-    with_window(window, |w| {
-        w.set_resizable(resizable);
-        true
-    })
-    .unwrap_or(false)
+pub extern "C" fn velox_menu_bar_set_app_menu(menu: *mut VeloxMenuBarHandle) -> bool {
+    let Some(menu) = (unsafe { menu.as_ref() }) else {
+        return false;
+    };
+    menu.menu.init_for_nsapp();
+    install_menu("app", Some(InstalledMenu::Menu(menu.menu.clone())));
+    true
 }
 
+#[cfg(target_os = "macos")]
 #[no_mangle]
-pub extern "C" fn velox_window_set_always_on_top(
+pub extern "C" fn velox_menu_bar_popup(
+    menu: *mut VeloxMenuBarHandle,
     window: *mut VeloxWindowHandle,
-    on_top: bool,
+    x: f64,
+    y: f64,
+    has_position: bool,
+    is_logical: bool,
 ) -> bool {
-    with_window(window, |w| {
-        w.set_always_on_top(on_top);
-        true
-    })
-    .unwrap_or(false)
-}
+    let Some(menu) = (unsafe { menu.as_ref() }) else {
+        return false;
+    };
+    let Some(window) = (unsafe { window.as_ref() }) else {
+        return false;
+    };
 
-#[no_mangle]
-pub extern "C" fn velox_window_set_visible(window: *mut VeloxWindowHandle, visible: bool) -> bool {
-    with_window(window, |w| {
-        let _ = w.set_visible(visible);
-        true
-    })
-    .unwrap_or(false)
-}
+    let position = if has_position {
+        if is_logical {
+            Some(muda::dpi::Position::Logical(LogicalPosition { x, y }))
+        } else {
+            let px = x.round() as i32;
+            let py = y.round() as i32;
+            Some(muda::dpi::Position::Physical(PhysicalPosition { x: px, y: py }))
+        }
+    } else {
+        None
+    };
 
-#[no_mangle]
-pub extern "C" fn velox_window_set_maximized(
-    window: *mut VeloxWindowHandle,
-    maximized: bool,
-) -> bool {
-    with_window(window, |w| {
-        w.set_maximized(maximized);
-        true
-    })
-    .unwrap_or(false)
+    install_menu("context", Some(InstalledMenu::Menu(menu.menu.clone())));
+    unsafe { menu.menu.show_context_menu_for_nsview(window.window.ns_view(), position) }
 }
 
+#[cfg(target_os = "linux")]
 #[no_mangle]
-pub extern "C" fn velox_window_set_minimized(
-    window: *mut VeloxWindowHandle,
-    minimized: bool,
-) -> bool {
-    with_window(window, |w| {
-        w.set_minimized(minimized);
-        true
-    })
-    .unwrap_or(false)
+pub extern "C" fn velox_menu_bar_set_app_menu(_menu: *mut VeloxMenuBarHandle) -> bool {
+    // No NSApp equivalent on Linux — menus are per-window via GTK
+    false
 }
 
+#[cfg(target_os = "linux")]
 #[no_mangle]
-pub extern "C" fn velox_window_set_skip_taskbar(
+pub extern "C" fn velox_menu_bar_popup(
+    menu: *mut VeloxMenuBarHandle,
     window: *mut VeloxWindowHandle,
-    skip: bool,
+    x: f64,
+    y: f64,
+    has_position: bool,
+    is_logical: bool,
 ) -> bool {
-    with_window(window, |w| {
-        #[cfg(target_os = "windows")]
-        {
-            use tao::platform::windows::WindowExtWindows;
-            return w.set_skip_taskbar(skip).is_ok();
-        }
+    let Some(menu) = (unsafe { menu.as_ref() }) else {
+        return false;
+    };
+    let Some(window) = (unsafe { window.as_ref() }) else {
+        return false;
+    };
 
-        #[cfg(any(
-            target_os = "linux",
-            target_os = "dragonfly",
-            target_os = "freebsd",
-            target_os = "netbsd",
-            target_os = "openbsd"
-        ))]
-        {
-            use tao::platform::unix::WindowExtUnix;
-            return w.set_skip_taskbar(skip).is_ok();
+    let position = if has_position {
+        if is_logical {
+            Some(muda::dpi::Position::Logical(LogicalPosition { x, y }))
+        } else {
+            let px = x.round() as i32;
+            let py = y.round() as i32;
+            Some(muda::dpi::Position::Physical(PhysicalPosition { x: px, y: py }))
         }
+    } else {
+        None
+    };
 
-        #[cfg(not(any(
-            target_os = "windows",
-            target_os = "linux",
-            target_os = "dragonfly",
-            target_os = "freebsd",
-            target_os = "netbsd",
-            target_os = "openbsd"
-        )))]
-        {
-            let _ = (w, skip);
-            return false;
-        }
-    })
-    .unwrap_or(false)
+    install_menu("context", Some(InstalledMenu::Menu(menu.menu.clone())));
+    unsafe { menu.menu.show_context_menu_for_gtk_window(window.window.gtk_window().as_ref(), position) }
 }
 
+#[cfg(target_os = "windows")]
 #[no_mangle]
-pub extern "C" fn velox_window_set_minimizable(
-    window: *mut VeloxWindowHandle,
-    minimizable: bool,
-) -> bool {
-    with_window(window, |w| {
-        w.set_minimizable(minimizable);
-        true
-    })
-    .unwrap_or(false)
+pub extern "C" fn velox_menu_bar_set_app_menu(_menu: *mut VeloxMenuBarHandle) -> bool {
+    // No NSApp equivalent on Windows — menu bars belong to a single HWND
+    false
 }
 
+#[cfg(target_os = "windows")]
 #[no_mangle]
-pub extern "C" fn velox_window_set_maximizable(
+pub extern "C" fn velox_menu_bar_popup(
+    menu: *mut VeloxMenuBarHandle,
     window: *mut VeloxWindowHandle,
-    maximizable: bool,
+    x: f64,
+    y: f64,
+    has_position: bool,
+    is_logical: bool,
 ) -> bool {
-    with_window(window, |w| {
-        w.set_maximizable(maximizable);
-        true
-    })
-    .unwrap_or(false)
+    let Some(menu) = (unsafe { menu.as_ref() }) else {
+        return false;
+    };
+    let Some(window) = (unsafe { window.as_ref() }) else {
+        return false;
+    };
+
+    let position = if has_position {
+        if is_logical {
+            Some(muda::dpi::Position::Logical(LogicalPosition { x, y }))
+        } else {
+            let px = x.round() as i32;
+            let py = y.round() as i32;
+            Some(muda::dpi::Position::Physical(PhysicalPosition {
+                x: px,
+                y: py,
+            }))
+        }
+    } else {
+        None
+    };
+
+    install_menu("context", Some(InstalledMenu::Menu(menu.menu.clone())));
+    unsafe {
+        menu.menu
+            .show_context_menu_for_hwnd(window.window.hwnd() as isize, position)
+    }
 }
 
+/// Show `menu` as a context menu at logical `x`/`y` within `window`
 #[no_mangle]
-pub extern "C" fn velox_window_set_closable(
+pub extern "C" fn velox_context_menu_show(
     window: *mut VeloxWindowHandle,
-    closable: bool,
+    menu: *mut VeloxMenuBarHandle,
+    x: f64,
+    y: f64,
 ) -> bool {
-    with_window(window, |w| {
-        w.set_closable(closable);
-        true
-    })
-    .unwrap_or(false)
+    velox_menu_bar_popup(menu, window, x, y, true, true)
 }
 
+
 #[no_mangle]
-pub extern "C" fn velox_window_set_background_color(
-    window: *mut VeloxWindowHandle,
-    color: *const VeloxColor,
-) -> bool {
-    let color = opt_color(color);
-    with_window(window, |w| {
-        w.set_background_color(color);
-        true
+pub extern "C" fn velox_submenu_new(
+    title: *const c_char,
+    enabled: bool,
+) -> *mut VeloxSubmenuHandle {
+    guard_panic(|| {
+        let title = opt_cstring(title).unwrap_or_default();
+        let submenu = Submenu::new(title, enabled);
+        let identifier =
+            CString::new(submenu.id().as_ref()).expect("submenu id contains null byte");
+        Box::into_raw(Box::new(VeloxSubmenuHandle {
+            submenu: Rc::new(RefCell::new(submenu)),
+            identifier,
+            items: Vec::new(),
+        }))
     })
-    .unwrap_or(false)
 }
 
 #[no_mangle]
-pub extern "C" fn velox_window_is_maximized(window: *mut VeloxWindowHandle) -> bool {
-    with_window(window, |w| w.is_maximized()).unwrap_or(false)
+pub extern "C" fn velox_submenu_new_with_id(
+    id: *const c_char,
+    title: *const c_char,
+    enabled: bool,
+) -> *mut VeloxSubmenuHandle {
+    guard_panic(|| {
+        let title = opt_cstring(title).unwrap_or_default();
+        let id_string = opt_cstring(id).unwrap_or_default();
+        let submenu = Submenu::with_id(MenuId::new(id_string.clone()), title, enabled);
+        let identifier = CString::new(id_string).expect("submenu id contains null byte");
+        Box::into_raw(Box::new(VeloxSubmenuHandle {
+            submenu: Rc::new(RefCell::new(submenu)),
+            identifier,
+            items: Vec::new(),
+        }))
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn velox_window_is_minimized(window: *mut VeloxWindowHandle) -> bool {
-    with_window(window, |w| w.is_minimized()).unwrap_or(false)
+pub extern "C" fn velox_submenu_free(submenu: *mut VeloxSubmenuHandle) {
+    if !submenu.is_null() {
+        unsafe { drop(Box::from_raw(submenu)) };
+    }
 }
 
 #[no_mangle]
-pub extern "C" fn velox_window_is_visible(window: *mut VeloxWindowHandle) -> bool {
-    with_window(window, |w| w.is_visible()).unwrap_or(false)
+pub extern "C" fn velox_submenu_identifier(submenu: *mut VeloxSubmenuHandle) -> *const c_char {
+    let Some(submenu) = (unsafe { submenu.as_ref() }) else {
+        return ptr::null();
+    };
+    submenu.identifier.as_ptr()
 }
 
 #[no_mangle]
-pub extern "C" fn velox_window_is_resizable(window: *mut VeloxWindowHandle) -> bool {
-    with_window(window, |w| w.is_resizable()).unwrap_or(false)
+pub extern "C" fn velox_submenu_text(submenu: *mut VeloxSubmenuHandle) -> *const c_char {
+    guard_panic_value(|| {
+        let Some(submenu) = (unsafe { submenu.as_ref() }) else {
+            return ptr::null();
+        };
+        write_string_to_buffer(&TITLE_BUFFER, submenu.submenu.borrow().text())
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn velox_window_is_decorated(window: *mut VeloxWindowHandle) -> bool {
-    with_window(window, |w| w.is_decorated()).unwrap_or(false)
+pub extern "C" fn velox_submenu_set_text(
+    submenu: *mut VeloxSubmenuHandle,
+    title: *const c_char,
+) -> bool {
+    guard_panic_bool(|| {
+        let Some(submenu) = (unsafe { submenu.as_mut() }) else {
+            return false;
+        };
+        let text = opt_cstring(title).unwrap_or_default();
+        submenu.submenu.borrow_mut().set_text(text);
+        true
+    })
 }
 
+/// Alias of `velox_submenu_text` named after the other title accessors
 #[no_mangle]
-pub extern "C" fn velox_window_is_always_on_top(window: *mut VeloxWindowHandle) -> bool {
-    with_window(window, |w| w.is_always_on_top()).unwrap_or(false)
+pub extern "C" fn velox_submenu_get_title(submenu: *mut VeloxSubmenuHandle) -> *const c_char {
+    velox_submenu_text(submenu)
 }
 
+/// Alias of `velox_submenu_set_text` named after the other title accessors
 #[no_mangle]
-pub extern "C" fn velox_window_is_minimizable(window: *mut VeloxWindowHandle) -> bool {
-    with_window(window, |w| w.is_minimizable()).unwrap_or(false)
+pub extern "C" fn velox_submenu_set_title(
+    submenu: *mut VeloxSubmenuHandle,
+    title: *const c_char,
+) -> bool {
+    velox_submenu_set_text(submenu, title)
 }
 
 #[no_mangle]
-pub extern "C" fn velox_window_is_maximizable(window: *mut VeloxWindowHandle) -> bool {
-    with_window(window, |w| w.is_maximizable()).unwrap_or(false)
+pub extern "C" fn velox_submenu_is_enabled(submenu: *mut VeloxSubmenuHandle) -> bool {
+    guard_panic_bool(|| {
+        let Some(submenu) = (unsafe { submenu.as_ref() }) else {
+            return false;
+        };
+        submenu.submenu.borrow().is_enabled()
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn velox_window_is_closable(window: *mut VeloxWindowHandle) -> bool {
-    with_window(window, |w| w.is_closable()).unwrap_or(false)
+pub extern "C" fn velox_submenu_set_enabled(
+    submenu: *mut VeloxSubmenuHandle,
+    enabled: bool,
+) -> bool {
+    let Some(submenu) = (unsafe { submenu.as_mut() }) else {
+        return false;
+    };
+    submenu.submenu.borrow_mut().set_enabled(enabled);
+    true
 }
 
 #[no_mangle]
-pub extern "C" fn velox_window_scale_factor(
-    window: *mut VeloxWindowHandle,
-    scale_factor: *mut f64,
+pub extern "C" fn velox_submenu_set_native_icon(
+    submenu: *mut VeloxSubmenuHandle,
+    native_icon: *const c_char,
 ) -> bool {
-    if scale_factor.is_null() {
+    let Some(submenu) = (unsafe { submenu.as_mut() }) else {
         return false;
-    }
-
-    with_window(window, |w| {
-        unsafe {
-            *scale_factor = w.scale_factor();
-        }
-        true
-    })
-    .unwrap_or(false)
+    };
+    let icon = native_icon_from_ptr(native_icon);
+    submenu.submenu.borrow_mut().set_native_icon(icon);
+    true
 }
 
-fn write_position(target: *mut VeloxPoint, position: LogicalPosition<f64>) {
-    unsafe {
-        (*target).x = position.x;
-        (*target).y = position.y;
-    }
+#[cfg(target_os = "macos")]
+#[no_mangle]
+pub extern "C" fn velox_submenu_set_as_windows_menu_for_nsapp(
+    submenu: *mut VeloxSubmenuHandle,
+) -> bool {
+    let Some(submenu) = (unsafe { submenu.as_ref() }) else {
+        return false;
+    };
+    submenu.submenu.borrow().set_as_windows_menu_for_nsapp();
+    true
 }
 
-fn write_size(target: *mut VeloxSize, size: LogicalSize<f64>) {
-    unsafe {
-        (*target).width = size.width;
-        (*target).height = size.height;
-    }
+#[cfg(not(target_os = "macos"))]
+#[no_mangle]
+pub extern "C" fn velox_submenu_set_as_windows_menu_for_nsapp(
+    _submenu: *mut VeloxSubmenuHandle,
+) -> bool {
+    false
 }
 
+#[cfg(target_os = "macos")]
 #[no_mangle]
-pub extern "C" fn velox_window_inner_position(
-    window: *mut VeloxWindowHandle,
-    position: *mut VeloxPoint,
+pub extern "C" fn velox_submenu_set_as_help_menu_for_nsapp(
+    submenu: *mut VeloxSubmenuHandle,
 ) -> bool {
-    if position.is_null() {
+    let Some(submenu) = (unsafe { submenu.as_ref() }) else {
         return false;
-    }
+    };
+    submenu.submenu.borrow().set_as_help_menu_for_nsapp();
+    true
+}
 
-    with_window(window, |w| match w.inner_position() {
-        Ok(pos) => {
-            write_position(position, pos.to_logical(w.scale_factor()));
-            true
-        }
-        Err(_) => false,
-    })
-    .unwrap_or(false)
+#[cfg(not(target_os = "macos"))]
+#[no_mangle]
+pub extern "C" fn velox_submenu_set_as_help_menu_for_nsapp(
+    _submenu: *mut VeloxSubmenuHandle,
+) -> bool {
+    false
 }
 
+#[cfg(target_os = "macos")]
 #[no_mangle]
-pub extern "C" fn velox_window_outer_position(
+pub extern "C" fn velox_submenu_popup(
+    submenu: *mut VeloxSubmenuHandle,
     window: *mut VeloxWindowHandle,
-    position: *mut VeloxPoint,
+    x: f64,
+    y: f64,
+    has_position: bool,
+    is_logical: bool,
 ) -> bool {
-    if position.is_null() {
+    let Some(submenu) = (unsafe { submenu.as_ref() }) else {
         return false;
-    }
+    };
+    let Some(window) = (unsafe { window.as_ref() }) else {
+        return false;
+    };
 
-    with_window(window, |w| match w.outer_position() {
-        Ok(pos) => {
-            write_position(position, pos.to_logical(w.scale_factor()));
-            true
+    let position = if has_position {
+        if is_logical {
+            Some(muda::dpi::Position::Logical(LogicalPosition { x, y }))
+        } else {
+            let px = x.round() as i32;
+            let py = y.round() as i32;
+            Some(muda::dpi::Position::Physical(PhysicalPosition { x: px, y: py }))
         }
-        Err(_) => false,
-    })
-    .unwrap_or(false)
+    } else {
+        None
+    };
+
+    let submenu = submenu.submenu.borrow();
+    install_menu("context", Some(InstalledMenu::Submenu(submenu.clone())));
+    unsafe { submenu.show_context_menu_for_nsview(window.window.ns_view(), position) }
 }
 
+#[cfg(target_os = "linux")]
 #[no_mangle]
-pub extern "C" fn velox_window_inner_size(
+pub extern "C" fn velox_submenu_popup(
+    submenu: *mut VeloxSubmenuHandle,
     window: *mut VeloxWindowHandle,
-    size: *mut VeloxSize,
+    x: f64,
+    y: f64,
+    has_position: bool,
+    is_logical: bool,
 ) -> bool {
-    if size.is_null() {
+    let Some(submenu) = (unsafe { submenu.as_ref() }) else {
         return false;
-    }
+    };
+    let Some(window) = (unsafe { window.as_ref() }) else {
+        return false;
+    };
 
-    with_window(window, |w| {
-        let inner = w.inner_size().to_logical::<f64>(w.scale_factor());
-        write_size(size, inner);
-        true
-    })
-    .unwrap_or(false)
+    let position = if has_position {
+        if is_logical {
+            Some(muda::dpi::Position::Logical(LogicalPosition { x, y }))
+        } else {
+            let px = x.round() as i32;
+            let py = y.round() as i32;
+            Some(muda::dpi::Position::Physical(PhysicalPosition { x: px, y: py }))
+        }
+    } else {
+        None
+    };
+
+    let submenu = submenu.submenu.borrow();
+    install_menu("context", Some(InstalledMenu::Submenu(submenu.clone())));
+    unsafe {
+        submenu.show_context_menu_for_gtk_window(window.window.gtk_window().as_ref(), position)
+    }
 }
 
+#[cfg(target_os = "windows")]
 #[no_mangle]
-pub extern "C" fn velox_window_outer_size(
+pub extern "C" fn velox_submenu_popup(
+    submenu: *mut VeloxSubmenuHandle,
     window: *mut VeloxWindowHandle,
-    size: *mut VeloxSize,
+    x: f64,
+    y: f64,
+    has_position: bool,
+    is_logical: bool,
 ) -> bool {
-    if size.is_null() {
+    let Some(submenu) = (unsafe { submenu.as_ref() }) else {
         return false;
-    }
+    };
+    let Some(window) = (unsafe { window.as_ref() }) else {
+        return false;
+    };
 
-    with_window(window, |w| {
-        let outer = w.outer_size().to_logical::<f64>(w.scale_factor());
-        write_size(size, outer);
-        true
-    })
-    .unwrap_or(false)
-}
+    let position = if has_position {
+        if is_logical {
+            Some(muda::dpi::Position::Logical(LogicalPosition { x, y }))
+        } else {
+            let px = x.round() as i32;
+            let py = y.round() as i32;
+            Some(muda::dpi::Position::Physical(PhysicalPosition {
+                x: px,
+                y: py,
+            }))
+        }
+    } else {
+        None
+    };
 
-#[no_mangle]
-pub extern "C" fn velox_window_title(window: *mut VeloxWindowHandle) -> *const c_char {
-    with_window(window, |w| {
-        let title = w.title();
-        write_string_to_buffer(&TITLE_BUFFER, title)
-    })
-    .unwrap_or(ptr::null())
+    let submenu = submenu.submenu.borrow();
+    install_menu("context", Some(InstalledMenu::Submenu(submenu.clone())));
+    unsafe { submenu.show_context_menu_for_hwnd(window.window.hwnd() as isize, position) }
 }
 
 #[no_mangle]
-pub extern "C" fn velox_window_is_fullscreen(window: *mut VeloxWindowHandle) -> bool {
-    with_window(window, |w| w.fullscreen().is_some()).unwrap_or(false)
+pub extern "C" fn velox_submenu_append_item(
+    submenu: *mut VeloxSubmenuHandle,
+    item: *mut VeloxMenuItemHandle,
+) -> bool {
+    velox_submenu_append_item_checked(submenu, item) == VeloxErrorCode::Success
 }
 
 #[no_mangle]
-pub extern "C" fn velox_window_is_focused(window: *mut VeloxWindowHandle) -> bool {
-    with_window(window, |w| w.is_focused()).unwrap_or(false)
+pub extern "C" fn velox_submenu_append_item_checked(
+    submenu: *mut VeloxSubmenuHandle,
+    item: *mut VeloxMenuItemHandle,
+) -> VeloxErrorCode {
+    let Some(submenu) = (unsafe { submenu.as_mut() }) else {
+        return VeloxErrorCode::NullPointer;
+    };
+    let Some(item) = (unsafe { item.as_ref() }) else {
+        return VeloxErrorCode::NullPointer;
+    };
+
+    let result = submenu.submenu.borrow().append(&item.item);
+    let code = VeloxErrorCode::from_result(record_error(result, VeloxErrorCode::Unknown));
+    if code == VeloxErrorCode::Success {
+        submenu.items.push(MenuItemKind::MenuItem(item.item.clone()));
+    }
+    code
 }
 
 #[no_mangle]
-pub extern "C" fn velox_window_cursor_position(
-    window: *mut VeloxWindowHandle,
-    point: *mut VeloxPoint,
+pub extern "C" fn velox_submenu_append(
+    submenu: *mut VeloxSubmenuHandle,
+    kind: VeloxMenuItemKind,
+    item: *mut c_void,
 ) -> bool {
-    if point.is_null() {
+    let Some(submenu) = (unsafe { submenu.as_mut() }) else {
         return false;
-    }
+    };
 
-    with_window(window, |w| match w.cursor_position() {
-        Ok(position) => {
-            unsafe {
-                (*point).x = position.x;
-                (*point).y = position.y;
+    match kind {
+        VeloxMenuItemKind::MenuItem => {
+            let Some(item) = (unsafe { (item as *mut VeloxMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            if report_error(
+                submenu.submenu.borrow().append(&item.item),
+                VeloxErrorCode::Unknown,
+            )
+            .is_some()
+            {
+                submenu
+                    .items
+                    .push(MenuItemKind::MenuItem(item.item.clone()));
+                true
+            } else {
+                false
             }
-            true
         }
-        Err(_) => false,
-    })
-    .unwrap_or(false)
-}
-
-#[no_mangle]
-pub extern "C" fn velox_window_current_monitor(window: *mut VeloxWindowHandle) -> *const c_char {
-    with_window(window, |w| {
-        if let Some(monitor) = w.current_monitor() {
-            write_json_to_buffer(&MONITOR_BUFFER, monitor_to_json(&monitor))
-        } else {
-            ptr::null()
+        VeloxMenuItemKind::Predefined => {
+            let Some(item) = (unsafe { (item as *mut VeloxPredefinedMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            if report_error(
+                submenu.submenu.borrow().append(&item.item),
+                VeloxErrorCode::Unknown,
+            )
+            .is_some()
+            {
+                submenu
+                    .items
+                    .push(MenuItemKind::Predefined(item.item.clone()));
+                true
+            } else {
+                false
+            }
         }
-    })
-    .unwrap_or(ptr::null())
-}
-
-#[no_mangle]
-pub extern "C" fn velox_window_primary_monitor(window: *mut VeloxWindowHandle) -> *const c_char {
-    with_window(window, |w| {
-        if let Some(monitor) = w.primary_monitor() {
-            write_json_to_buffer(&MONITOR_BUFFER, monitor_to_json(&monitor))
-        } else {
-            ptr::null()
+        VeloxMenuItemKind::Check => {
+            let Some(item) = (unsafe { (item as *mut VeloxCheckMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            if report_error(
+                submenu.submenu.borrow().append(&item.item),
+                VeloxErrorCode::Unknown,
+            )
+            .is_some()
+            {
+                submenu.items.push(MenuItemKind::Check(item.item.clone()));
+                true
+            } else {
+                false
+            }
         }
-    })
-    .unwrap_or(ptr::null())
-}
-
-#[no_mangle]
-pub extern "C" fn velox_window_available_monitors(window: *mut VeloxWindowHandle) -> *const c_char {
-    with_window(window, |w| {
-        let monitors: Vec<_> = w
-            .available_monitors()
-            .map(|monitor| monitor_to_json(&monitor))
-            .collect();
-        write_json_to_buffer(&MONITOR_LIST_BUFFER, serde_json::Value::Array(monitors))
-    })
-    .unwrap_or(ptr::null())
-}
-
-#[no_mangle]
-pub extern "C" fn velox_window_monitor_from_point(
-    window: *mut VeloxWindowHandle,
-    point: VeloxPoint,
-) -> *const c_char {
-    with_window(window, |w| {
-        if let Some(monitor) = w.monitor_from_point(point.x, point.y) {
-            write_json_to_buffer(&MONITOR_BUFFER, monitor_to_json(&monitor))
-        } else {
-            ptr::null()
+        VeloxMenuItemKind::Icon => {
+            let Some(item) = (unsafe { (item as *mut VeloxIconMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            if report_error(
+                submenu.submenu.borrow().append(&item.item),
+                VeloxErrorCode::Unknown,
+            )
+            .is_some()
+            {
+                submenu.items.push(MenuItemKind::Icon(item.item.clone()));
+                true
+            } else {
+                false
+            }
         }
-    })
-    .unwrap_or(ptr::null())
-}
-
-#[no_mangle]
-pub extern "C" fn velox_window_set_theme(
-    window: *mut VeloxWindowHandle,
-    theme: VeloxWindowTheme,
-) -> bool {
-    let theme = theme_from_ffi(theme);
-    with_window(window, |w| {
-        w.set_theme(theme);
-        true
-    })
-    .unwrap_or(false)
-}
-
-#[no_mangle]
-pub extern "C" fn velox_window_focus(window: *mut VeloxWindowHandle) -> bool {
-    with_window(window, |w| {
-        w.set_focus();
-        true
-    })
-    .unwrap_or(false)
+        VeloxMenuItemKind::Submenu => {
+            let Some(item) = (unsafe { (item as *mut VeloxSubmenuHandle).as_ref() }) else {
+                return false;
+            };
+            let submenu_ref = item.submenu.borrow();
+            if report_error(
+                submenu.submenu.borrow().append(&*submenu_ref),
+                VeloxErrorCode::Unknown,
+            )
+            .is_some()
+            {
+                submenu
+                    .items
+                    .push(MenuItemKind::Submenu(submenu_ref.clone()));
+                true
+            } else {
+                false
+            }
+        }
+    }
 }
 
 #[no_mangle]
-pub extern "C" fn velox_window_set_focusable(
-    window: *mut VeloxWindowHandle,
-    focusable: bool,
+pub extern "C" fn velox_submenu_prepend(
+    submenu: *mut VeloxSubmenuHandle,
+    kind: VeloxMenuItemKind,
+    item: *mut c_void,
 ) -> bool {
-    with_window(window, |w| {
-        w.set_focusable(focusable);
-        true
-    })
-    .unwrap_or(false)
-}
-
-#[no_mangle]
-pub extern "C" fn velox_window_request_redraw(window: *mut VeloxWindowHandle) -> bool {
-    with_window(window, |w| {
-        w.request_redraw();
-        true
-    })
-    .unwrap_or(false)
-}
+    let Some(submenu) = (unsafe { submenu.as_mut() }) else {
+        return false;
+    };
 
-#[no_mangle]
-pub extern "C" fn velox_window_set_size(
-    window: *mut VeloxWindowHandle,
-    width: f64,
-    height: f64,
-) -> bool {
-    with_window(window, |w| {
-        w.set_inner_size(LogicalSize::new(width, height));
-        true
-    })
-    .unwrap_or(false)
+    match kind {
+        VeloxMenuItemKind::MenuItem => {
+            let Some(item) = (unsafe { (item as *mut VeloxMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            if report_error(
+                submenu.submenu.borrow().prepend(&item.item),
+                VeloxErrorCode::Unknown,
+            )
+            .is_some()
+            {
+                submenu
+                    .items
+                    .insert(0, MenuItemKind::MenuItem(item.item.clone()));
+                true
+            } else {
+                false
+            }
+        }
+        VeloxMenuItemKind::Predefined => {
+            let Some(item) = (unsafe { (item as *mut VeloxPredefinedMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            if report_error(
+                submenu.submenu.borrow().prepend(&item.item),
+                VeloxErrorCode::Unknown,
+            )
+            .is_some()
+            {
+                submenu
+                    .items
+                    .insert(0, MenuItemKind::Predefined(item.item.clone()));
+                true
+            } else {
+                false
+            }
+        }
+        VeloxMenuItemKind::Check => {
+            let Some(item) = (unsafe { (item as *mut VeloxCheckMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            if report_error(
+                submenu.submenu.borrow().prepend(&item.item),
+                VeloxErrorCode::Unknown,
+            )
+            .is_some()
+            {
+                submenu
+                    .items
+                    .insert(0, MenuItemKind::Check(item.item.clone()));
+                true
+            } else {
+                false
+            }
+        }
+        VeloxMenuItemKind::Icon => {
+            let Some(item) = (unsafe { (item as *mut VeloxIconMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            if report_error(
+                submenu.submenu.borrow().prepend(&item.item),
+                VeloxErrorCode::Unknown,
+            )
+            .is_some()
+            {
+                submenu
+                    .items
+                    .insert(0, MenuItemKind::Icon(item.item.clone()));
+                true
+            } else {
+                false
+            }
+        }
+        VeloxMenuItemKind::Submenu => {
+            let Some(item) = (unsafe { (item as *mut VeloxSubmenuHandle).as_ref() }) else {
+                return false;
+            };
+            let submenu_ref = item.submenu.borrow();
+            if report_error(
+                submenu.submenu.borrow().prepend(&*submenu_ref),
+                VeloxErrorCode::Unknown,
+            )
+            .is_some()
+            {
+                submenu
+                    .items
+                    .insert(0, MenuItemKind::Submenu(submenu_ref.clone()));
+                true
+            } else {
+                false
+            }
+        }
+    }
 }
 
 #[no_mangle]
-pub extern "C" fn velox_window_set_position(
-    window: *mut VeloxWindowHandle,
-    x: f64,
-    y: f64,
+pub extern "C" fn velox_submenu_insert(
+    submenu: *mut VeloxSubmenuHandle,
+    kind: VeloxMenuItemKind,
+    item: *mut c_void,
+    position: usize,
 ) -> bool {
-    with_window(window, |w| {
-        w.set_outer_position(LogicalPosition::new(x, y));
-        true
-    })
-    .unwrap_or(false)
+    let Some(submenu) = (unsafe { submenu.as_mut() }) else {
+        return false;
+    };
+    let insert_index = position.min(submenu.items.len());
+
+    match kind {
+        VeloxMenuItemKind::MenuItem => {
+            let Some(item) = (unsafe { (item as *mut VeloxMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            if report_error(
+                submenu.submenu.borrow().insert(&item.item, insert_index),
+                VeloxErrorCode::Unknown,
+            )
+            .is_some()
+            {
+                submenu
+                    .items
+                    .insert(insert_index, MenuItemKind::MenuItem(item.item.clone()));
+                true
+            } else {
+                false
+            }
+        }
+        VeloxMenuItemKind::Predefined => {
+            let Some(item) = (unsafe { (item as *mut VeloxPredefinedMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            if report_error(
+                submenu.submenu.borrow().insert(&item.item, insert_index),
+                VeloxErrorCode::Unknown,
+            )
+            .is_some()
+            {
+                submenu
+                    .items
+                    .insert(insert_index, MenuItemKind::Predefined(item.item.clone()));
+                true
+            } else {
+                false
+            }
+        }
+        VeloxMenuItemKind::Check => {
+            let Some(item) = (unsafe { (item as *mut VeloxCheckMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            if report_error(
+                submenu.submenu.borrow().insert(&item.item, insert_index),
+                VeloxErrorCode::Unknown,
+            )
+            .is_some()
+            {
+                submenu
+                    .items
+                    .insert(insert_index, MenuItemKind::Check(item.item.clone()));
+                true
+            } else {
+                false
+            }
+        }
+        VeloxMenuItemKind::Icon => {
+            let Some(item) = (unsafe { (item as *mut VeloxIconMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            if report_error(
+                submenu.submenu.borrow().insert(&item.item, insert_index),
+                VeloxErrorCode::Unknown,
+            )
+            .is_some()
+            {
+                submenu
+                    .items
+                    .insert(insert_index, MenuItemKind::Icon(item.item.clone()));
+                true
+            } else {
+                false
+            }
+        }
+        VeloxMenuItemKind::Submenu => {
+            let Some(item) = (unsafe { (item as *mut VeloxSubmenuHandle).as_ref() }) else {
+                return false;
+            };
+            let submenu_ref = item.submenu.borrow();
+            if report_error(
+                submenu.submenu.borrow().insert(&*submenu_ref, insert_index),
+                VeloxErrorCode::Unknown,
+            )
+            .is_some()
+            {
+                submenu
+                    .items
+                    .insert(insert_index, MenuItemKind::Submenu(submenu_ref.clone()));
+                true
+            } else {
+                false
+            }
+        }
+    }
 }
 
+/// Insert `item` at `index`, clamped to the end of the submenu
 #[no_mangle]
-pub extern "C" fn velox_window_set_min_size(
-    window: *mut VeloxWindowHandle,
-    width: f64,
-    height: f64,
+pub extern "C" fn velox_submenu_insert_item_at_index(
+    submenu: *mut VeloxSubmenuHandle,
+    item: *mut VeloxMenuItemHandle,
+    index: usize,
 ) -> bool {
-    with_window(window, |w| {
-        let size: Option<Size> = if width > 0.0 && height > 0.0 {
-            Some(Size::Logical(LogicalSize::new(width, height)))
-        } else {
-            None
-        };
-        w.set_min_inner_size(size);
-        true
-    })
-    .unwrap_or(false)
+    velox_submenu_insert(submenu, VeloxMenuItemKind::MenuItem, item.cast(), index)
 }
 
+/// Insert a check `item` at `index`, clamped to the end of the submenu
 #[no_mangle]
-pub extern "C" fn velox_window_set_max_size(
-    window: *mut VeloxWindowHandle,
-    width: f64,
-    height: f64,
+pub extern "C" fn velox_submenu_insert_check_item_at_index(
+    submenu: *mut VeloxSubmenuHandle,
+    item: *mut VeloxCheckMenuItemHandle,
+    index: usize,
 ) -> bool {
-    with_window(window, |w| {
-        let size: Option<Size> = if width > 0.0 && height > 0.0 {
-            Some(Size::Logical(LogicalSize::new(width, height)))
-        } else {
-            None
-        };
-        w.set_max_inner_size(size);
-        true
-    })
-    .unwrap_or(false)
+    velox_submenu_insert(submenu, VeloxMenuItemKind::Check, item.cast(), index)
 }
 
+/// Insert `separator` at `index`, clamped to the end of the submenu
 #[no_mangle]
-pub extern "C" fn velox_window_request_user_attention(
-    window: *mut VeloxWindowHandle,
-    attention_type: VeloxUserAttentionType,
+pub extern "C" fn velox_submenu_insert_separator_at_index(
+    submenu: *mut VeloxSubmenuHandle,
+    separator: *mut VeloxSeparatorHandle,
+    index: usize,
 ) -> bool {
-    let attention = tao_user_attention_from_ffi(attention_type);
-    with_window(window, |w| {
-        w.request_user_attention(Some(attention));
+    let Some(submenu) = (unsafe { submenu.as_mut() }) else {
+        return false;
+    };
+    let Some(separator) = (unsafe { separator.as_ref() }) else {
+        return false;
+    };
+    let index = index.min(submenu.items.len());
+    if report_error(
+        submenu.submenu.borrow().insert(&separator.item, index),
+        VeloxErrorCode::Unknown,
+    )
+    .is_some()
+    {
+        submenu
+            .items
+            .insert(index, MenuItemKind::Predefined(separator.item.clone()));
         true
-    })
-    .unwrap_or(false)
+    } else {
+        false
+    }
 }
 
 #[no_mangle]
-pub extern "C" fn velox_window_clear_user_attention(window: *mut VeloxWindowHandle) -> bool {
-    with_window(window, |w| {
-        w.request_user_attention(None);
-        true
-    })
-    .unwrap_or(false)
+pub extern "C" fn velox_submenu_remove(
+    submenu: *mut VeloxSubmenuHandle,
+    kind: VeloxMenuItemKind,
+    item: *mut c_void,
+) -> bool {
+    let Some(submenu) = (unsafe { submenu.as_mut() }) else {
+        return false;
+    };
+
+    match kind {
+        VeloxMenuItemKind::MenuItem => {
+            let Some(item) = (unsafe { (item as *mut VeloxMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            report_error(
+                submenu.submenu.borrow().remove(&item.item),
+                VeloxErrorCode::Unknown,
+            )
+            .is_some()
+        }
+        VeloxMenuItemKind::Predefined => {
+            let Some(item) = (unsafe { (item as *mut VeloxPredefinedMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            report_error(
+                submenu.submenu.borrow().remove(&item.item),
+                VeloxErrorCode::Unknown,
+            )
+            .is_some()
+        }
+        VeloxMenuItemKind::Check => {
+            let Some(item) = (unsafe { (item as *mut VeloxCheckMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            report_error(
+                submenu.submenu.borrow().remove(&item.item),
+                VeloxErrorCode::Unknown,
+            )
+            .is_some()
+        }
+        VeloxMenuItemKind::Icon => {
+            let Some(item) = (unsafe { (item as *mut VeloxIconMenuItemHandle).as_ref() }) else {
+                return false;
+            };
+            report_error(
+                submenu.submenu.borrow().remove(&item.item),
+                VeloxErrorCode::Unknown,
+            )
+            .is_some()
+        }
+        VeloxMenuItemKind::Submenu => {
+            let Some(item) = (unsafe { (item as *mut VeloxSubmenuHandle).as_ref() }) else {
+                return false;
+            };
+            let submenu_ref = item.submenu.borrow();
+            report_error(
+                submenu.submenu.borrow().remove(&*submenu_ref),
+                VeloxErrorCode::Unknown,
+            )
+            .is_some()
+        }
+    }
 }
 
 #[no_mangle]
-pub extern "C" fn velox_window_set_cursor_grab(window: *mut VeloxWindowHandle, grab: bool) -> bool {
-    with_window(window, |w| w.set_cursor_grab(grab).is_ok()).unwrap_or(false)
+pub extern "C" fn velox_submenu_remove_at(
+    submenu: *mut VeloxSubmenuHandle,
+    position: usize,
+) -> bool {
+    let Some(submenu) = (unsafe { submenu.as_mut() }) else {
+        return false;
+    };
+    let Some(removed) = submenu.submenu.borrow().remove_at(position) else {
+        return false;
+    };
+    submenu.items.retain(|item| item.id() != removed.id());
+    true
 }
 
+/// Remove the item whose menu id is `item_id` from the submenu
 #[no_mangle]
-pub extern "C" fn velox_window_set_cursor_visible(
-    window: *mut VeloxWindowHandle,
-    visible: bool,
+pub extern "C" fn velox_submenu_remove_item(
+    submenu: *mut VeloxSubmenuHandle,
+    item_id: *const c_char,
 ) -> bool {
-    with_window(window, |w| {
-        w.set_cursor_visible(visible);
-        true
-    })
-    .unwrap_or(false)
+    let Some(submenu) = (unsafe { submenu.as_mut() }) else {
+        return false;
+    };
+    let Some(item_id) = opt_cstring(item_id) else {
+        return false;
+    };
+    let native = submenu.submenu.borrow();
+    let Some(position) = native
+        .items()
+        .iter()
+        .position(|item| item.id().as_ref() == item_id)
+    else {
+        return false;
+    };
+    if native.remove_at(position).is_none() {
+        return false;
+    }
+    drop(native);
+    submenu.items.retain(|item| item.id().as_ref() != item_id);
+    true
 }
 
 #[no_mangle]
-pub extern "C" fn velox_window_set_cursor_position(
-    window: *mut VeloxWindowHandle,
-    x: f64,
-    y: f64,
-) -> bool {
-    with_window(window, |w| {
-        w.set_cursor_position(LogicalPosition::new(x, y)).is_ok()
+pub extern "C" fn velox_menu_item_new(
+    id: *const c_char,
+    title: *const c_char,
+    enabled: bool,
+    accelerator: *const c_char,
+) -> *mut VeloxMenuItemHandle {
+    guard_panic(|| {
+        let title = opt_cstring(title).unwrap_or_default();
+        let accelerator = accelerator_from_ptr(accelerator);
+        let item = if let Some(id) = opt_cstring(id) {
+            MenuItem::with_id(MenuId::new(id.clone()), title, enabled, accelerator)
+        } else {
+            MenuItem::new(title, enabled, accelerator)
+        };
+        let identifier = CString::new(item.id().as_ref()).expect("menu item id contains null byte");
+        Box::into_raw(Box::new(VeloxMenuItemHandle { item, identifier }))
     })
-    .unwrap_or(false)
 }
 
 #[no_mangle]
-pub extern "C" fn velox_window_set_ignore_cursor_events(
-    window: *mut VeloxWindowHandle,
-    ignore: bool,
+pub extern "C" fn velox_menu_item_free(item: *mut VeloxMenuItemHandle) {
+    if !item.is_null() {
+        unsafe { drop(Box::from_raw(item)) };
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn velox_menu_item_set_enabled(
+    item: *mut VeloxMenuItemHandle,
+    enabled: bool,
 ) -> bool {
-    with_window(window, |w| w.set_ignore_cursor_events(ignore).is_ok()).unwrap_or(false)
+    let Some(item) = (unsafe { item.as_mut() }) else {
+        return false;
+    };
+    item.item.set_enabled(enabled);
+    true
 }
 
 #[no_mangle]
-pub extern "C" fn velox_window_start_dragging(window: *mut VeloxWindowHandle) -> bool {
-    with_window(window, |w| w.drag_window().is_ok()).unwrap_or(false)
+pub extern "C" fn velox_menu_item_is_enabled(item: *mut VeloxMenuItemHandle) -> bool {
+    guard_panic_bool(|| {
+        let Some(item) = (unsafe { item.as_ref() }) else {
+            return false;
+        };
+        item.item.is_enabled()
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn velox_window_start_resize_dragging(
-    window: *mut VeloxWindowHandle,
-    direction: VeloxResizeDirection,
-) -> bool {
-    let tao_direction = tao_resize_direction_from_ffi(direction);
-    with_window(window, |w| w.drag_resize_window(tao_direction).is_ok()).unwrap_or(false)
+pub extern "C" fn velox_menu_item_text(item: *mut VeloxMenuItemHandle) -> *const c_char {
+    guard_panic_value(|| {
+        let Some(item) = (unsafe { item.as_ref() }) else {
+            return ptr::null();
+        };
+        write_string_to_buffer(&TITLE_BUFFER, item.item.text())
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn velox_webview_build(
-    window: *mut VeloxWindowHandle,
-    config: *const VeloxWebviewConfig,
-) -> *mut VeloxWebviewHandle {
-    if window.is_null() {
-        return ptr::null_mut();
-    }
+pub extern "C" fn velox_menu_item_set_text(
+    item: *mut VeloxMenuItemHandle,
+    title: *const c_char,
+) -> bool {
+    guard_panic_bool(|| {
+        let Some(item) = (unsafe { item.as_mut() }) else {
+            return false;
+        };
+        let text = opt_cstring(title).unwrap_or_default();
+        item.item.set_text(text);
+        true
+    })
+}
 
-    let cfg = unsafe { config.as_ref().copied().unwrap_or_default() };
-    let url = opt_cstring(cfg.url);
-    let proxy_url = opt_cstring(cfg.proxy_url);
-    let data_directory = opt_cstring(cfg.data_directory);
+#[no_mangle]
+pub extern "C" fn velox_menu_item_set_accelerator(
+    item: *mut VeloxMenuItemHandle,
+    accelerator: *const c_char,
+) -> bool {
+    guard_panic_bool(|| {
+        let Some(item) = (unsafe { item.as_mut() }) else {
+            return false;
+        };
+        report_error(
+            item.item.set_accelerator(accelerator_from_ptr(accelerator)),
+            VeloxErrorCode::InvalidArgument,
+        )
+        .is_some()
+    })
+}
 
-    let ffi_protocols: Vec<(
-        String,
-        unsafe extern "C" fn(
-            *const VeloxCustomProtocolRequest,
-            *mut VeloxCustomProtocolResponse,
-            *mut c_void,
-        ) -> bool,
-        *mut c_void,
-    )> = if cfg.custom_protocols.count > 0 && !cfg.custom_protocols.protocols.is_null() {
-        unsafe {
-            std::slice::from_raw_parts(cfg.custom_protocols.protocols, cfg.custom_protocols.count)
-        }
-        .iter()
-        .filter_map(|definition| {
-            let handler = definition.handler?;
-            let scheme = opt_cstring(definition.scheme)?;
-            Some((scheme, handler, definition.user_data))
-        })
-        .collect()
-    } else {
-        Vec::new()
+#[no_mangle]
+pub extern "C" fn velox_menu_item_identifier(item: *mut VeloxMenuItemHandle) -> *const c_char {
+    let Some(item) = (unsafe { item.as_ref() }) else {
+        return ptr::null();
     };
+    item.identifier.as_ptr()
+}
 
-    with_window(window, |w| {
-        let mut web_context = data_directory
-            .as_ref()
-            .map(|path| WebContext::new(Some(PathBuf::from(path))));
-        let mut builder = if let Some(context) = web_context.as_mut() {
-            WebViewBuilder::new_with_web_context(context)
+// MARK: - Icon Menu Item
+
+#[no_mangle]
+pub extern "C" fn velox_icon_menu_item_new(
+    id: *const c_char,
+    title: *const c_char,
+    enabled: bool,
+    accelerator: *const c_char,
+    native_icon: *const c_char,
+) -> *mut VeloxIconMenuItemHandle {
+    guard_panic(|| {
+        let title = opt_cstring(title).unwrap_or_default();
+        let accelerator = accelerator_from_ptr(accelerator);
+        let native_icon = native_icon_from_ptr(native_icon);
+        let item = if let Some(id) = opt_cstring(id) {
+            IconMenuItem::with_id_and_native_icon(
+                MenuId::new(id.clone()),
+                title,
+                enabled,
+                native_icon,
+                accelerator,
+            )
         } else {
-            WebViewBuilder::new()
+            IconMenuItem::with_native_icon(title, enabled, native_icon, accelerator)
         };
+        let identifier = CString::new(item.id().as_ref()).expect("icon menu item id contains null byte");
+        Box::into_raw(Box::new(VeloxIconMenuItemHandle { item, identifier }))
+    })
+}
 
-        if let Some(url) = url.as_ref() {
-            builder = builder.with_url(url.clone());
-        }
-
-        builder = builder.with_devtools(cfg.devtools);
-
-        if let Some(accept_first_mouse) = opt_bool(cfg.accept_first_mouse) {
-            builder = builder.with_accept_first_mouse(accept_first_mouse);
-        }
+#[no_mangle]
+pub extern "C" fn velox_icon_menu_item_free(item: *mut VeloxIconMenuItemHandle) {
+    if !item.is_null() {
+        unsafe { drop(Box::from_raw(item)) };
+    }
+}
 
-        if let Some(incognito) = opt_bool(cfg.incognito) {
-            builder = builder.with_incognito(incognito);
-        }
+#[no_mangle]
+pub extern "C" fn velox_icon_menu_item_identifier(item: *mut VeloxIconMenuItemHandle) -> *const c_char {
+    let Some(item) = (unsafe { item.as_ref() }) else {
+        return ptr::null();
+    };
+    item.identifier.as_ptr()
+}
 
-        if opt_bool(cfg.javascript_disabled).unwrap_or(false) {
-            builder = builder.with_javascript_disabled();
-        }
+#[no_mangle]
+pub extern "C" fn velox_icon_menu_item_text(item: *mut VeloxIconMenuItemHandle) -> *const c_char {
+    guard_panic_value(|| {
+        let Some(item) = (unsafe { item.as_ref() }) else {
+            return ptr::null();
+        };
+        write_string_to_buffer(&TITLE_BUFFER, item.item.text())
+    })
+}
 
-        if let Some(policy) = background_throttling_from_flag(cfg.background_throttling) {
-            builder = builder.with_background_throttling(policy);
-        }
+#[no_mangle]
+pub extern "C" fn velox_icon_menu_item_set_text(
+    item: *mut VeloxIconMenuItemHandle,
+    title: *const c_char,
+) -> bool {
+    guard_panic_bool(|| {
+        let Some(item) = (unsafe { item.as_mut() }) else {
+            return false;
+        };
+        let text = opt_cstring(title).unwrap_or_default();
+        item.item.set_text(text);
+        true
+    })
+}
 
-        if let Some(proxy_config) = parse_proxy_config(proxy_url) {
-            builder = builder.with_proxy_config(proxy_config);
-        }
+#[no_mangle]
+pub extern "C" fn velox_icon_menu_item_is_enabled(item: *mut VeloxIconMenuItemHandle) -> bool {
+    guard_panic_bool(|| {
+        let Some(item) = (unsafe { item.as_ref() }) else {
+            return false;
+        };
+        item.item.is_enabled()
+    })
+}
 
-        #[cfg(target_os = "windows")]
-        if let Some(style) = scroll_bar_style_from_flag(cfg.scroll_bar_style) {
-            builder = builder.with_scroll_bar_style(style);
-        }
+#[no_mangle]
+pub extern "C" fn velox_icon_menu_item_set_enabled(
+    item: *mut VeloxIconMenuItemHandle,
+    enabled: bool,
+) -> bool {
+    let Some(item) = (unsafe { item.as_mut() }) else {
+        return false;
+    };
+    item.item.set_enabled(enabled);
+    true
+}
 
-        for (scheme, handler, user_data) in ffi_protocols.iter().cloned() {
+#[no_mangle]
+pub extern "C" fn velox_icon_menu_item_set_accelerator(
+    item: *mut VeloxIconMenuItemHandle,
+    accelerator: *const c_char,
+) -> bool {
+    guard_panic_bool(|| {
+        let Some(item) = (unsafe { item.as_mut() }) else {
+            return false;
+        };
+        report_error(
+            item.item.set_accelerator(accelerator_from_ptr(accelerator)),
+            VeloxErrorCode::InvalidArgument,
+        )
+        .is_some()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn velox_icon_menu_item_set_native_icon(
+    item: *mut VeloxIconMenuItemHandle,
+    native_icon: *const c_char,
+) -> bool {
+    let Some(item) = (unsafe { item.as_mut() }) else {
+        return false;
+    };
+    let icon = native_icon_from_ptr(native_icon);
+    item.item.set_native_icon(icon);
+    true
+}
+
+// MARK: - Predefined Menu Item
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VeloxAboutMetadata {
+    name: Option<String>,
+    version: Option<String>,
+    short_version: Option<String>,
+    authors: Option<Vec<String>>,
+    comments: Option<String>,
+    copyright: Option<String>,
+    license: Option<String>,
+    website: Option<String>,
+    website_label: Option<String>,
+    credits: Option<String>,
+    #[serde(default)]
+    icon: Option<serde_json::Value>,
+}
+
+fn about_metadata_from_json(json: *const c_char) -> Option<AboutMetadata> {
+    let value = opt_cstring(json)?;
+    let parsed: VeloxAboutMetadata = serde_json::from_str(&value).ok()?;
+    let _ = parsed.icon;
+    Some(AboutMetadata {
+        name: parsed.name,
+        version: parsed.version,
+        short_version: parsed.short_version,
+        authors: parsed.authors,
+        comments: parsed.comments,
+        copyright: parsed.copyright,
+        license: parsed.license,
+        website: parsed.website,
+        website_label: parsed.website_label,
+        credits: parsed.credits,
+        icon: None,
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn velox_predefined_menu_item_new(
+    item_type: *const c_char,
+    text: *const c_char,
+    about_json: *const c_char,
+) -> *mut VeloxPredefinedMenuItemHandle {
+    guard_panic(|| {
+        let item_type = opt_cstring(item_type).unwrap_or_default();
+        let text = opt_cstring(text);
+        let kind = VeloxPredefinedMenuItemKind::from_name(&item_type)
+            .unwrap_or(VeloxPredefinedMenuItemKind::Separator);
+        let item = kind.build(text.as_deref(), about_metadata_from_json(about_json));
+        let identifier = CString::new(item.id().as_ref())
+            .expect("predefined menu item id contains null byte");
+        Box::into_raw(Box::new(VeloxPredefinedMenuItemHandle { item, identifier }))
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn velox_predefined_menu_item_free(item: *mut VeloxPredefinedMenuItemHandle) {
+    if !item.is_null() {
+        unsafe { drop(Box::from_raw(item)) };
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn velox_predefined_menu_item_identifier(
+    item: *mut VeloxPredefinedMenuItemHandle,
+) -> *const c_char {
+    let Some(item) = (unsafe { item.as_ref() }) else {
+        return ptr::null();
+    };
+    item.identifier.as_ptr()
+}
+
+#[no_mangle]
+pub extern "C" fn velox_predefined_menu_item_text(
+    item: *mut VeloxPredefinedMenuItemHandle,
+) -> *const c_char {
+    guard_panic_value(|| {
+        let Some(item) = (unsafe { item.as_ref() }) else {
+            return ptr::null();
+        };
+        write_string_to_buffer(&TITLE_BUFFER, item.item.text())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn velox_predefined_menu_item_set_text(
+    item: *mut VeloxPredefinedMenuItemHandle,
+    title: *const c_char,
+) -> bool {
+    guard_panic_bool(|| {
+        let Some(item) = (unsafe { item.as_mut() }) else {
+            return false;
+        };
+        let text = opt_cstring(title).unwrap_or_default();
+        item.item.set_text(text);
+        true
+    })
+}
+
+// MARK: - Separator Menu Item
+
+#[no_mangle]
+pub extern "C" fn velox_separator_new() -> *mut VeloxSeparatorHandle {
+    guard_panic(|| {
+        let item = PredefinedMenuItem::separator();
+        let identifier = CString::new(item.id().as_ref()).expect("separator id contains null byte");
+        Box::into_raw(Box::new(VeloxSeparatorHandle { item, identifier }))
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn velox_separator_free(separator: *mut VeloxSeparatorHandle) {
+    if !separator.is_null() {
+        unsafe { drop(Box::from_raw(separator)) };
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn velox_separator_identifier(separator: *mut VeloxSeparatorHandle) -> *const c_char {
+    let Some(separator) = (unsafe { separator.as_ref() }) else {
+        return ptr::null();
+    };
+    separator.identifier.as_ptr()
+}
+
+/// Append a standard item of `kind` with its platform default title
+#[no_mangle]
+pub extern "C" fn velox_submenu_append_predefined(
+    submenu: *mut VeloxSubmenuHandle,
+    kind: VeloxPredefinedMenuItemKind,
+) -> bool {
+    guard_panic_bool(|| {
+        let Some(submenu) = (unsafe { submenu.as_mut() }) else {
+            return false;
+        };
+        let item = kind.build(None, None);
+        if report_error(
+            submenu.submenu.borrow().append(&item),
+            VeloxErrorCode::Unknown,
+        )
+        .is_some()
+        {
+            submenu.items.push(MenuItemKind::Predefined(item));
+            true
+        } else {
+            false
+        }
+    })
+}
+
+/// Append `separator` to the submenu; the submenu keeps its own clone of the item
+#[no_mangle]
+pub extern "C" fn velox_submenu_append_separator(
+    submenu: *mut VeloxSubmenuHandle,
+    separator: *mut VeloxSeparatorHandle,
+) -> bool {
+    let Some(submenu) = (unsafe { submenu.as_mut() }) else {
+        return false;
+    };
+    let Some(separator) = (unsafe { separator.as_ref() }) else {
+        return false;
+    };
+    if report_error(
+        submenu.submenu.borrow().append(&separator.item),
+        VeloxErrorCode::Unknown,
+    )
+    .is_some()
+    {
+        submenu
+            .items
+            .push(MenuItemKind::Predefined(separator.item.clone()));
+        true
+    } else {
+        false
+    }
+}
+
+/// Append a new separator without going through a separator handle
+#[no_mangle]
+pub extern "C" fn velox_submenu_append_new_separator(submenu: *mut VeloxSubmenuHandle) -> bool {
+    guard_panic_bool(|| {
+        let Some(submenu) = (unsafe { submenu.as_mut() }) else {
+            return false;
+        };
+        let item = PredefinedMenuItem::separator();
+        if report_error(
+            submenu.submenu.borrow().append(&item),
+            VeloxErrorCode::Unknown,
+        )
+        .is_some()
+        {
+            submenu.items.push(MenuItemKind::Predefined(item));
+            true
+        } else {
+            false
+        }
+    })
+}
+
+// MARK: - Check Menu Item
+
+#[no_mangle]
+pub extern "C" fn velox_check_menu_item_new(
+    id: *const c_char,
+    title: *const c_char,
+    enabled: bool,
+    checked: bool,
+    accelerator: *const c_char,
+) -> *mut VeloxCheckMenuItemHandle {
+    guard_panic(|| {
+        let title = opt_cstring(title).unwrap_or_default();
+        let accelerator = accelerator_from_ptr(accelerator);
+        let item = if let Some(id) = opt_cstring(id) {
+            CheckMenuItem::with_id(MenuId::new(id.clone()), title, enabled, checked, accelerator)
+        } else {
+            CheckMenuItem::new(title, enabled, checked, accelerator)
+        };
+        let identifier = CString::new(item.id().as_ref()).expect("check menu item id contains null byte");
+        Box::into_raw(Box::new(VeloxCheckMenuItemHandle { item, identifier }))
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn velox_check_menu_item_free(item: *mut VeloxCheckMenuItemHandle) {
+    if !item.is_null() {
+        unsafe { drop(Box::from_raw(item)) };
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn velox_check_menu_item_is_checked(item: *mut VeloxCheckMenuItemHandle) -> bool {
+    guard_panic_bool(|| {
+        let Some(item) = (unsafe { item.as_ref() }) else {
+            return false;
+        };
+        item.item.is_checked()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn velox_check_menu_item_set_checked(
+    item: *mut VeloxCheckMenuItemHandle,
+    checked: bool,
+) -> bool {
+    let Some(item) = (unsafe { item.as_mut() }) else {
+        return false;
+    };
+    item.item.set_checked(checked);
+    true
+}
+
+#[no_mangle]
+pub extern "C" fn velox_check_menu_item_is_enabled(item: *mut VeloxCheckMenuItemHandle) -> bool {
+    guard_panic_bool(|| {
+        let Some(item) = (unsafe { item.as_ref() }) else {
+            return false;
+        };
+        item.item.is_enabled()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn velox_check_menu_item_set_enabled(
+    item: *mut VeloxCheckMenuItemHandle,
+    enabled: bool,
+) -> bool {
+    let Some(item) = (unsafe { item.as_mut() }) else {
+        return false;
+    };
+    item.item.set_enabled(enabled);
+    true
+}
+
+#[no_mangle]
+pub extern "C" fn velox_check_menu_item_text(item: *mut VeloxCheckMenuItemHandle) -> *const c_char {
+    guard_panic_value(|| {
+        let Some(item) = (unsafe { item.as_ref() }) else {
+            return ptr::null();
+        };
+        write_string_to_buffer(&TITLE_BUFFER, item.item.text())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn velox_check_menu_item_set_text(
+    item: *mut VeloxCheckMenuItemHandle,
+    title: *const c_char,
+) -> bool {
+    guard_panic_bool(|| {
+        let Some(item) = (unsafe { item.as_mut() }) else {
+            return false;
+        };
+        let text = opt_cstring(title).unwrap_or_default();
+        item.item.set_text(text);
+        true
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn velox_check_menu_item_set_accelerator(
+    item: *mut VeloxCheckMenuItemHandle,
+    accelerator: *const c_char,
+) -> bool {
+    guard_panic_bool(|| {
+        let Some(item) = (unsafe { item.as_mut() }) else {
+            return false;
+        };
+        report_error(
+            item.item.set_accelerator(accelerator_from_ptr(accelerator)),
+            VeloxErrorCode::InvalidArgument,
+        )
+        .is_some()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn velox_check_menu_item_identifier(item: *mut VeloxCheckMenuItemHandle) -> *const c_char {
+    let Some(item) = (unsafe { item.as_ref() }) else {
+        return ptr::null();
+    };
+    item.identifier.as_ptr()
+}
+
+#[no_mangle]
+pub extern "C" fn velox_submenu_append_check_item(
+    submenu: *mut VeloxSubmenuHandle,
+    item: *mut VeloxCheckMenuItemHandle,
+) -> bool {
+    let Some(submenu) = (unsafe { submenu.as_mut() }) else {
+        return false;
+    };
+    let Some(item) = (unsafe { item.as_ref() }) else {
+        return false;
+    };
+    if report_error(
+        submenu.submenu.borrow().append(&item.item),
+        VeloxErrorCode::Unknown,
+    )
+    .is_some()
+    {
+        submenu.items.push(MenuItemKind::Check(item.item.clone()));
+        true
+    } else {
+        false
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn velox_tray_new(config: *const VeloxTrayConfig) -> *mut VeloxTrayHandle {
+    guard_panic(|| {
+        let cfg = unsafe { config.as_ref() }.copied().unwrap_or_default();
+        let identifier = opt_cstring(cfg.identifier);
+        let title = opt_cstring(cfg.title);
+        let tooltip = opt_cstring(cfg.tooltip);
+
+        let mut builder = TrayIconBuilder::new();
+        if let Some(ref id) = identifier {
+            builder = builder.with_id(id.clone());
+        }
+        if let Some(ref title) = title {
+            builder = builder.with_title(title.clone());
+        }
+        if let Some(ref tooltip) = tooltip {
+            builder = builder.with_tooltip(tooltip.clone());
+        }
+        builder = builder.with_menu_on_left_click(cfg.show_menu_on_left_click);
+        if let Some(icon) =
+            tray_icon_from_rgba(cfg.icon_rgba, cfg.icon_rgba_width, cfg.icon_rgba_height)
+                .or_else(|| tray_icon_from_path(cfg.icon_path))
+        {
+            builder = builder.with_icon(icon);
+        }
+
+        let Some(tray) = report_error(builder.build(), VeloxErrorCode::Unknown) else {
+            return ptr::null_mut();
+        };
+
+        if !cfg.visible {
+            let _ = tray.set_visible(false);
+        }
+
+        tray.set_show_menu_on_left_click(cfg.show_menu_on_left_click);
+
+        let identifier = CString::new(tray.id().as_ref())
+            .unwrap_or_else(|_| CString::new("velox-tray").expect("static string has no nulls"));
+
+        Box::into_raw(Box::new(VeloxTrayHandle {
+            tray,
+            menu: None,
+            identifier,
+            #[cfg(target_os = "macos")]
+            menu_delegate: None,
+        }))
+    })
+}
+
+/// Like `velox_tray_new`, storing the tray in `tray` (null on failure)
+#[no_mangle]
+pub extern "C" fn velox_tray_new_checked(
+    config: *const VeloxTrayConfig,
+    tray: *mut *mut VeloxTrayHandle,
+) -> VeloxErrorCode {
+    if tray.is_null() {
+        return VeloxErrorCode::NullPointer;
+    }
+    let handle = velox_tray_new(config);
+    unsafe { *tray = handle };
+    if handle.is_null() {
+        velox_last_error_code()
+    } else {
+        VeloxErrorCode::Success
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn velox_tray_free(tray: *mut VeloxTrayHandle) {
+    if !tray.is_null() {
+        let tray = unsafe { Box::from_raw(tray) };
+        install_menu(format!("tray:{}", tray.tray.id().as_ref()), None);
+        #[cfg(target_os = "windows")]
+        TRAY_MENU_PROVIDERS
+            .with(|providers| providers.borrow_mut().remove(tray.tray.id().as_ref()));
+        drop(tray);
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn velox_tray_identifier(tray: *mut VeloxTrayHandle) -> *const c_char {
+    let Some(tray) = (unsafe { tray.as_ref() }) else {
+        return ptr::null();
+    };
+    tray.identifier.as_ptr()
+}
+
+/// Titles are not shown next to tray icons on Windows.
+#[no_mangle]
+pub extern "C" fn velox_tray_set_title(tray: *mut VeloxTrayHandle, title: *const c_char) -> bool {
+    let Some(tray) = (unsafe { tray.as_mut() }) else {
+        return false;
+    };
+    let result_title = opt_cstring(title);
+    tray.tray.set_title(result_title.as_deref());
+    true
+}
+
+#[no_mangle]
+pub extern "C" fn velox_tray_set_tooltip(
+    tray: *mut VeloxTrayHandle,
+    tooltip: *const c_char,
+) -> bool {
+    let Some(tray) = (unsafe { tray.as_mut() }) else {
+        return false;
+    };
+    let tooltip = opt_cstring(tooltip);
+    report_error(
+        tray.tray.set_tooltip(tooltip.as_deref()),
+        VeloxErrorCode::Unknown,
+    )
+    .is_some()
+}
+
+#[no_mangle]
+pub extern "C" fn velox_tray_set_visible(tray: *mut VeloxTrayHandle, visible: bool) -> bool {
+    let Some(tray) = (unsafe { tray.as_mut() }) else {
+        return false;
+    };
+    report_error(tray.tray.set_visible(visible), VeloxErrorCode::Unknown).is_some()
+}
+
+fn tray_icon_from_rgba(rgba: *const u8, width: u32, height: u32) -> Option<tray_icon::Icon> {
+    if rgba.is_null() {
+        return None;
+    }
+    let len = width as usize * height as usize * 4;
+    let rgba = unsafe { std::slice::from_raw_parts(rgba, len) }.to_vec();
+    report_error(
+        tray_icon::Icon::from_rgba(rgba, width, height),
+        VeloxErrorCode::InvalidArgument,
+    )
+}
+
+fn tray_icon_from_path(path: *const c_char) -> Option<tray_icon::Icon> {
+    let image =
+        report_error(image::open(opt_cstring(path)?), VeloxErrorCode::IoError)?.into_rgba8();
+    let (width, height) = image.dimensions();
+    report_error(
+        tray_icon::Icon::from_rgba(image.into_raw(), width, height),
+        VeloxErrorCode::InvalidArgument,
+    )
+}
+
+/// `icon` is `None` when loading it failed, which has already been recorded.
+fn set_tray_icon(tray: *mut VeloxTrayHandle, icon: Option<tray_icon::Icon>) -> VeloxErrorCode {
+    let Some(tray) = (unsafe { tray.as_mut() }) else {
+        return VeloxErrorCode::NullPointer;
+    };
+    let Some(icon) = icon else {
+        return VeloxErrorCode::InvalidArgument;
+    };
+    VeloxErrorCode::from_result(record_error(
+        tray.tray.set_icon(Some(icon)),
+        VeloxErrorCode::Unknown,
+    ))
+}
+
+#[no_mangle]
+pub extern "C" fn velox_tray_set_icon_from_rgba(
+    tray: *mut VeloxTrayHandle,
+    rgba: *const u8,
+    width: u32,
+    height: u32,
+) -> bool {
+    velox_tray_set_icon_from_rgba_checked(tray, rgba, width, height) == VeloxErrorCode::Success
+}
+
+#[no_mangle]
+pub extern "C" fn velox_tray_set_icon_from_rgba_checked(
+    tray: *mut VeloxTrayHandle,
+    rgba: *const u8,
+    width: u32,
+    height: u32,
+) -> VeloxErrorCode {
+    if rgba.is_null() {
+        return VeloxErrorCode::NullPointer;
+    }
+    set_tray_icon(tray, tray_icon_from_rgba(rgba, width, height))
+}
+
+/// Draw the tray icon as a template image tinted for the menu bar appearance (macOS only)
+#[no_mangle]
+pub extern "C" fn velox_tray_set_icon_as_template(
+    tray: *mut VeloxTrayHandle,
+    is_template: bool,
+) -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        let Some(tray) = (unsafe { tray.as_mut() }) else {
+            return false;
+        };
+        tray.tray.set_icon_as_template(is_template);
+        return true;
+    }
+    #[allow(unreachable_code)]
+    {
+        let _ = (tray, is_template);
+        false
+    }
+}
+
+/// Set the tray icon from a PNG or ICO file
+#[no_mangle]
+pub extern "C" fn velox_tray_set_icon_from_path(
+    tray: *mut VeloxTrayHandle,
+    path: *const c_char,
+) -> bool {
+    velox_tray_set_icon_from_path_checked(tray, path) == VeloxErrorCode::Success
+}
+
+#[no_mangle]
+pub extern "C" fn velox_tray_set_icon_from_path_checked(
+    tray: *mut VeloxTrayHandle,
+    path: *const c_char,
+) -> VeloxErrorCode {
+    if path.is_null() {
+        return VeloxErrorCode::NullPointer;
+    }
+    set_tray_icon(tray, tray_icon_from_path(path))
+}
+
+#[no_mangle]
+pub extern "C" fn velox_tray_set_show_menu_on_left_click(
+    tray: *mut VeloxTrayHandle,
+    enable: bool,
+) -> bool {
+    let Some(tray) = (unsafe { tray.as_mut() }) else {
+        return false;
+    };
+    tray.tray.set_show_menu_on_left_click(enable);
+    true
+}
+
+/// 3x5 glyphs for the digits and `+`, one row per byte with the leftmost pixel in bit 2.
+#[cfg(target_os = "windows")]
+const BADGE_GLYPHS: [(char, [u8; 5]); 11] = [
+    ('0', [0b111, 0b101, 0b101, 0b101, 0b111]),
+    ('1', [0b010, 0b110, 0b010, 0b010, 0b111]),
+    ('2', [0b111, 0b001, 0b111, 0b100, 0b111]),
+    ('3', [0b111, 0b001, 0b111, 0b001, 0b111]),
+    ('4', [0b101, 0b101, 0b111, 0b001, 0b001]),
+    ('5', [0b111, 0b100, 0b111, 0b001, 0b111]),
+    ('6', [0b111, 0b100, 0b111, 0b101, 0b111]),
+    ('7', [0b111, 0b001, 0b010, 0b010, 0b010]),
+    ('8', [0b111, 0b101, 0b111, 0b101, 0b111]),
+    ('9', [0b111, 0b101, 0b111, 0b001, 0b111]),
+    ('+', [0b000, 0b010, 0b111, 0b010, 0b000]),
+];
+
+#[cfg(target_os = "windows")]
+fn render_badge_icon(count: i32) -> Option<Vec<u8>> {
+    const SIZE: usize = 16;
+    let label = if count > 99 {
+        "99+".to_string()
+    } else {
+        count.to_string()
+    };
+    let scale = if label.len() == 1 { 2 } else { 1 };
+    let text_width = (label.len() * 4 - 1) * scale;
+    let origin_x = (SIZE - text_width) / 2;
+    let origin_y = (SIZE - 5 * scale) / 2;
+
+    let mut rgba = vec![0u8; SIZE * SIZE * 4];
+    let center = (SIZE as f32 - 1.0) / 2.0;
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let (dx, dy) = (x as f32 - center, y as f32 - center);
+            if dx * dx + dy * dy <= (SIZE as f32 / 2.0).powi(2) {
+                rgba[(y * SIZE + x) * 4..][..4].copy_from_slice(&[0xE0, 0x2B, 0x2B, 0xFF]);
+            }
+        }
+    }
+    for (index, ch) in label.chars().enumerate() {
+        let (_, rows) = BADGE_GLYPHS.iter().find(|(glyph, _)| *glyph == ch)?;
+        for (row, bits) in rows.iter().enumerate() {
+            for column in 0..3 {
+                if bits & (0b100 >> column) == 0 {
+                    continue;
+                }
+                for sy in 0..scale {
+                    for sx in 0..scale {
+                        let x = origin_x + (index * 4 + column) * scale + sx;
+                        let y = origin_y + row * scale + sy;
+                        rgba[(y * SIZE + x) * 4..][..4].copy_from_slice(&[0xFF; 4]);
+                    }
+                }
+            }
+        }
+    }
+    Some(rgba)
+}
+
+#[cfg(target_os = "windows")]
+fn current_thread_main_window() -> Option<HWND> {
+    use windows::Win32::Foundation::{BOOL, LPARAM};
+    use windows::Win32::System::Threading::GetCurrentThreadId;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        EnumThreadWindows, GetWindow, IsWindowVisible, GW_OWNER,
+    };
+
+    unsafe extern "system" fn visit(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let found = &mut *(lparam.0 as *mut Option<HWND>);
+        let owned = GetWindow(hwnd, GW_OWNER).is_ok_and(|owner| !owner.is_invalid());
+        if IsWindowVisible(hwnd).as_bool() && !owned {
+            *found = Some(hwnd);
+            return BOOL(0);
+        }
+        BOOL(1)
+    }
+
+    let mut found = None;
+    let _ = unsafe {
+        EnumThreadWindows(
+            GetCurrentThreadId(),
+            Some(visit),
+            LPARAM(&mut found as *mut Option<HWND> as isize),
+        )
+    };
+    found
+}
+
+/// Show `count` on the dock icon or taskbar button; 0 or less clears it (not on Linux)
+#[no_mangle]
+pub extern "C" fn velox_tray_set_badge_count(tray: *mut VeloxTrayHandle, count: i32) -> bool {
+    if tray.is_null() {
+        return false;
+    }
+    let count = count.max(0);
+    #[cfg(target_os = "macos")]
+    {
+        use objc2::{class, msg_send, rc::Retained, runtime::AnyObject};
+        use objc2_foundation::NSString;
+
+        return unsafe {
+            let app: Option<Retained<AnyObject>> =
+                msg_send![class!(NSApplication), sharedApplication];
+            let Some(app) = app else {
+                return false;
+            };
+            let tile: Option<Retained<AnyObject>> = msg_send![&*app, dockTile];
+            let Some(tile) = tile else {
+                return false;
+            };
+            let label = (count > 0).then(|| NSString::from_str(&count.to_string()));
+            let _: () = msg_send![&*tile, setBadgeLabel: label.as_deref()];
+            true
+        };
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let Some(hwnd) = current_thread_main_window() else {
+            return false;
+        };
+        if count == 0 {
+            return set_taskbar_overlay(hwnd, None, "");
+        }
+        let Some(rgba) = render_badge_icon(count) else {
+            return false;
+        };
+        return set_taskbar_overlay(hwnd, Some((&rgba, 16, 16)), &count.to_string());
+    }
+    #[allow(unreachable_code)]
+    {
+        let _ = count;
+        false
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn velox_tray_set_menu(
+    tray: *mut VeloxTrayHandle,
+    menu: *mut VeloxMenuBarHandle,
+) -> bool {
+    let Some(tray) = (unsafe { tray.as_mut() }) else {
+        return false;
+    };
+
+    if menu.is_null() {
+        tray.tray
+            .set_menu(None::<Box<dyn tray_icon::menu::ContextMenu>>);
+        tray.menu = None;
+        install_menu(format!("tray:{}", tray.tray.id().as_ref()), None);
+        #[cfg(target_os = "macos")]
+        {
+            tray.menu_delegate = None;
+        }
+        #[cfg(target_os = "windows")]
+        TRAY_MENU_PROVIDERS
+            .with(|providers| providers.borrow_mut().remove(tray.tray.id().as_ref()));
+        return true;
+    }
+
+    let Some(menu_handle) = (unsafe { menu.as_ref() }) else {
+        return false;
+    };
+
+    let cloned_menu = menu_handle.menu.clone();
+    tray.tray.set_menu(Some(
+        Box::new(cloned_menu.clone()) as Box<dyn tray_icon::menu::ContextMenu>
+    ));
+    install_menu(
+        format!("tray:{}", tray.tray.id().as_ref()),
+        Some(InstalledMenu::Menu(cloned_menu.clone())),
+    );
+    tray.menu = Some(cloned_menu);
+    #[cfg(target_os = "macos")]
+    {
+        tray.menu_delegate = None;
+    }
+    #[cfg(target_os = "windows")]
+    TRAY_MENU_PROVIDERS.with(|providers| providers.borrow_mut().remove(tray.tray.id().as_ref()));
+    true
+}
+
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+struct TrayMenuProvider {
+    provider: unsafe extern "C" fn(user_data: *mut c_void) -> *mut VeloxMenuBarHandle,
+    user_data: *mut c_void,
+    /// Handle whose native items are currently shown in the tray menu
+    current: Cell<*mut VeloxMenuBarHandle>,
+}
+
+#[cfg(target_os = "macos")]
+impl TrayMenuProvider {
+    fn rebuild(&self, menu: &objc2::runtime::AnyObject) {
+        use objc2::{msg_send, rc::Retained, runtime::AnyObject};
+
+        let provider = self.provider;
+        let user_data = self.user_data;
+        let handle = catch_unwind(AssertUnwindSafe(|| unsafe { provider(user_data) }))
+            .unwrap_or(ptr::null_mut());
+        let Some(source) = (unsafe { handle.as_ref() }) else {
+            return;
+        };
+
+        // The status item keeps showing the NSMenu it was given, so move the new items into it.
+        let source = unsafe { &*(source.menu.ns_menu() as *const AnyObject) };
+        unsafe {
+            let _: () = msg_send![menu, removeAllItems];
+            loop {
+                let count: isize = msg_send![source, numberOfItems];
+                if count == 0 {
+                    break;
+                }
+                let item: Retained<AnyObject> = msg_send![source, itemAtIndex: 0isize];
+                let _: () = msg_send![source, removeItemAtIndex: 0isize];
+                let _: () = msg_send![menu, addItem: &*item];
+            }
+        }
+
+        // Item actions resolve through the handle that built them, so the previous handle is
+        // only released once its items have been taken off screen.
+        let previous = self.current.replace(handle);
+        velox_menu_bar_free(previous);
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl TrayMenuProvider {
+    fn rebuild(&self, menu: &TrayMenu) {
+        let provider = self.provider;
+        let user_data = self.user_data;
+        let handle = catch_unwind(AssertUnwindSafe(|| unsafe { provider(user_data) }))
+            .unwrap_or(ptr::null_mut());
+        let Some(source) = (unsafe { handle.as_ref() }) else {
+            return;
+        };
+
+        // The tray keeps showing the HMENU it was given, so move the new items into it.
+        while menu.remove_at(0).is_some() {}
+        while let Some(item) = source.menu.remove_at(0) {
+            let _ = match &item {
+                MenuItemKind::MenuItem(item) => menu.append(item),
+                MenuItemKind::Submenu(item) => menu.append(item),
+                MenuItemKind::Predefined(item) => menu.append(item),
+                MenuItemKind::Check(item) => menu.append(item),
+                MenuItemKind::Icon(item) => menu.append(item),
+            };
+        }
+
+        let previous = self.current.replace(handle);
+        velox_menu_bar_free(previous);
+    }
+}
+
+/// Rebuild the menu of the tray `id` if it has a provider; runs before the popup is shown
+#[cfg(target_os = "windows")]
+fn rebuild_tray_menu(id: &str) {
+    let entry = TRAY_MENU_PROVIDERS.with(|providers| providers.borrow().get(id).cloned());
+    if let Some((menu, provider)) = entry {
+        provider.rebuild(&menu);
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+impl Drop for TrayMenuProvider {
+    fn drop(&mut self) {
+        velox_menu_bar_free(self.current.get());
+    }
+}
+
+#[cfg(target_os = "macos")]
+objc2::define_class!(
+    #[unsafe(super(objc2_foundation::NSObject))]
+    #[name = "VeloxTrayMenuDelegate"]
+    #[ivars = TrayMenuProvider]
+    struct VeloxTrayMenuDelegate;
+
+    impl VeloxTrayMenuDelegate {
+        #[unsafe(method(menuWillOpen:))]
+        fn menu_will_open(&self, menu: &objc2::runtime::AnyObject) {
+            self.ivars().rebuild(menu);
+        }
+    }
+);
+
+/// Rebuild the tray menu from `provider` before it opens; the tray owns each menu (macOS and Windows)
+#[cfg(target_os = "macos")]
+#[no_mangle]
+pub extern "C" fn velox_tray_set_menu_provider(
+    tray: *mut VeloxTrayHandle,
+    provider: VeloxMenuProvider,
+    user_data: *mut c_void,
+) -> bool {
+    use objc2::{msg_send, runtime::AnyObject, AllocAnyThread};
+
+    if tray.is_null() {
+        return false;
+    }
+    let Some(provider) = provider else {
+        return velox_tray_set_menu(tray, ptr::null_mut());
+    };
+
+    // The status item only opens a menu that already has items, so build one up front.
+    let initial = catch_unwind(AssertUnwindSafe(|| unsafe { provider(user_data) }))
+        .unwrap_or(ptr::null_mut());
+    if initial.is_null() || !velox_tray_set_menu(tray, initial) {
+        velox_menu_bar_free(initial);
+        return false;
+    }
+
+    let tray_handle = unsafe { &mut *tray };
+    let Some(menu) = tray_handle.menu.as_ref() else {
+        velox_menu_bar_free(initial);
+        return false;
+    };
+    let menu = unsafe { &*(menu.ns_menu() as *const AnyObject) };
+    let delegate = VeloxTrayMenuDelegate::alloc().set_ivars(TrayMenuProvider {
+        provider,
+        user_data,
+        current: Cell::new(initial),
+    });
+    let delegate: objc2::rc::Retained<VeloxTrayMenuDelegate> =
+        unsafe { msg_send![super(delegate), init] };
+    let _: () = unsafe { msg_send![menu, setDelegate: &*delegate] };
+    tray_handle.menu_delegate = Some(delegate);
+    true
+}
+
+#[cfg(target_os = "windows")]
+#[no_mangle]
+pub extern "C" fn velox_tray_set_menu_provider(
+    tray: *mut VeloxTrayHandle,
+    provider: VeloxMenuProvider,
+    user_data: *mut c_void,
+) -> bool {
+    if tray.is_null() {
+        return false;
+    }
+    let Some(provider) = provider else {
+        return velox_tray_set_menu(tray, ptr::null_mut());
+    };
+
+    // Built up front so the first click has items while later clicks rebuild in place.
+    let initial = catch_unwind(AssertUnwindSafe(|| unsafe { provider(user_data) }))
+        .unwrap_or(ptr::null_mut());
+    if initial.is_null() || !velox_tray_set_menu(tray, initial) {
+        velox_menu_bar_free(initial);
+        return false;
+    }
+
+    let tray_handle = unsafe { &*tray };
+    let Some(menu) = tray_handle.menu.clone() else {
+        velox_menu_bar_free(initial);
+        return false;
+    };
+    let provider = Rc::new(TrayMenuProvider {
+        provider,
+        user_data,
+        current: Cell::new(initial),
+    });
+    TRAY_MENU_PROVIDERS.with(|providers| {
+        providers
+            .borrow_mut()
+            .insert(tray_handle.tray.id().as_ref().to_string(), (menu, provider))
+    });
+    true
+}
+
+#[cfg(target_os = "linux")]
+#[no_mangle]
+pub extern "C" fn velox_tray_set_menu_provider(
+    _tray: *mut VeloxTrayHandle,
+    _provider: VeloxMenuProvider,
+    _user_data: *mut c_void,
+) -> bool {
+    false
+}
+
+#[no_mangle]
+pub extern "C" fn velox_event_loop_pump(
+    event_loop: *mut VeloxEventLoop,
+    callback: VeloxEventLoopCallback,
+    user_data: *mut c_void,
+) {
+    if event_loop.is_null() {
+        return;
+    }
+
+    let event_loop = unsafe { &mut *event_loop };
+    run_pump(event_loop, callback, user_data, None, None);
+}
+
+/// Pump events for at most `timeout_ms`; true if the timeout ended the pump
+#[no_mangle]
+pub extern "C" fn velox_event_loop_pump_with_timeout(
+    event_loop: *mut VeloxEventLoop,
+    callback: VeloxEventLoopCallback,
+    user_data: *mut c_void,
+    timeout_ms: u64,
+) -> bool {
+    let Some(event_loop) = (unsafe { event_loop.as_mut() }) else {
+        return false;
+    };
+    let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+    run_pump(event_loop, callback, user_data, None, Some(deadline))
+}
+
+/// Returns whether `deadline` ended the run
+fn run_pump(
+    event_loop: &mut VeloxEventLoop,
+    callback: VeloxEventLoopCallback,
+    user_data: *mut c_void,
+    cancelled: Option<&AtomicBool>,
+    deadline: Option<Instant>,
+) -> bool {
+    let suspend_handler = &event_loop.suspend_handler;
+    let resume_handler = &event_loop.resume_handler;
+    let event_filter = &event_loop.event_filter;
+    let mut timed_out = false;
+    event_loop
+        .event_loop
+        .run_return(|event, _target, control_flow| {
+            if cancelled.is_some_and(|flag| flag.load(Ordering::SeqCst)) {
+                *control_flow = ControlFlow::Exit;
+                return;
+            }
+            let expired = || deadline.is_some_and(|deadline| Instant::now() >= deadline);
+            if matches!(event, Event::UserEvent(VeloxUserEvent::Wake)) {
+                if expired() {
+                    timed_out = true;
+                    *control_flow = ControlFlow::Exit;
+                }
+                return;
+            }
+
+            let lifecycle = match event {
+                Event::Suspended => suspend_handler.get(),
+                Event::Resumed => resume_handler.get(),
+                _ => None,
+            };
+            if let Some(lifecycle) = lifecycle {
+                lifecycle.invoke();
+            }
+
+            if let Event::WindowEvent {
+                window_id, event, ..
+            } = &event
+            {
+                match event {
+                    TaoWindowEvent::CursorMoved { position, .. } => {
+                        update_cursor_tracking(*window_id, *position);
+                        update_drag_region_cursor(*window_id, *position);
+                    }
+                    TaoWindowEvent::MouseInput {
+                        state: ElementState::Pressed,
+                        button: MouseButton::Left,
+                        ..
+                    } => {
+                        start_region_drag(*window_id, None);
+                    }
+                    TaoWindowEvent::CursorLeft { .. } => reset_cursor_tracking(*window_id),
+                    TaoWindowEvent::Resized(size) => enforce_aspect_ratio(*window_id, *size),
+                    _ => {}
+                }
+            }
+
+            if let Event::UserEvent(VeloxUserEvent::ContentSize(window_id, width, height)) = &event
+            {
+                deliver_content_size(*window_id, *width, *height);
+            }
+            if let Event::UserEvent(VeloxUserEvent::Task(task)) = &event {
+                task.run();
+            }
+            if let Event::UserEvent(VeloxUserEvent::Timer(id)) = &event {
+                dispatch_timer(*id);
+            }
+            let content_size_deadline = [poll_content_size_observers(), poll_offscreen_painters()]
+                .into_iter()
+                .flatten()
+                .min();
+
+            let filtered_out = event_filter
+                .get()
+                .is_some_and(|filter| !filter.allows(&event));
+            if filtered_out {
+                // Leave the control flow from the last delivered event in place.
+            } else if let Some(cb) = callback {
+                let description = serialize_event(&event);
+                if let Ok(c_description) = CString::new(description) {
+                    let desired_flow = cb(c_description.as_ptr(), user_data);
+                    match desired_flow {
+                        VeloxEventLoopControlFlow::Poll => *control_flow = ControlFlow::Poll,
+                        VeloxEventLoopControlFlow::Wait => *control_flow = ControlFlow::Wait,
+                        VeloxEventLoopControlFlow::Exit => *control_flow = ControlFlow::Exit,
+                    }
+                } else {
+                    *control_flow = ControlFlow::Exit;
+                }
+            } else {
+                *control_flow = ControlFlow::Exit;
+            }
+
+            // Keep waking up while content size observers or offscreen painters are registered.
+            if let (ControlFlow::Wait, Some(deadline)) = (*control_flow, content_size_deadline) {
+                *control_flow = ControlFlow::WaitUntil(deadline);
+            }
+
+            // Wake up in time to honour the pump timeout.
+            if let Some(deadline) = deadline {
+                match *control_flow {
+                    ControlFlow::Wait => *control_flow = ControlFlow::WaitUntil(deadline),
+                    ControlFlow::WaitUntil(wake) if wake > deadline => {
+                        *control_flow = ControlFlow::WaitUntil(deadline)
+                    }
+                    _ => {}
+                }
+            }
+
+            if matches!(event, Event::UserEvent(VeloxUserEvent::Exit)) {
+                *control_flow = ControlFlow::Exit;
+            }
+
+            if matches!(event, Event::LoopDestroyed) {
+                *control_flow = ControlFlow::Exit;
+            }
+
+            // Checked last so the event that crosses the deadline is still handled and delivered.
+            if !matches!(*control_flow, ControlFlow::Exit) && expired() {
+                timed_out = true;
+                *control_flow = ControlFlow::Exit;
+            }
+        });
+    timed_out
+}
+
+/// Prepare a pump that another thread can cancel; run it with `velox_event_loop_pump_async_run`
+#[no_mangle]
+pub extern "C" fn velox_event_loop_pump_async_start(
+    event_loop: *mut VeloxEventLoop,
+    callback: VeloxEventLoopCallback,
+    user_data: *mut c_void,
+) -> *mut VeloxEventLoopPumpHandle {
+    if event_loop.is_null() {
+        return ptr::null_mut();
+    }
+
+    let proxy = unsafe { &*event_loop }.event_loop.create_proxy();
+    Box::into_raw(Box::new(VeloxEventLoopPumpHandle {
+        event_loop,
+        callback,
+        user_data,
+        cancelled: AtomicBool::new(false),
+        proxy,
+    }))
+}
+
+/// Run the pump on the event loop thread until it exits or is cancelled
+#[no_mangle]
+pub extern "C" fn velox_event_loop_pump_async_run(handle: *mut VeloxEventLoopPumpHandle) {
+    let Some(handle) = (unsafe { handle.as_ref() }) else {
+        return;
+    };
+    let Some(event_loop) = (unsafe { handle.event_loop.as_mut() }) else {
+        return;
+    };
+    run_pump(
+        event_loop,
+        handle.callback,
+        handle.user_data,
+        Some(&handle.cancelled),
+        None,
+    );
+}
+
+/// Ask a running pump to exit; safe to call from any thread
+#[no_mangle]
+pub extern "C" fn velox_event_loop_pump_async_cancel(
+    handle: *mut VeloxEventLoopPumpHandle,
+) -> bool {
+    let Some(handle) = (unsafe { handle.as_ref() }) else {
+        return false;
+    };
+    handle.cancelled.store(true, Ordering::SeqCst);
+    // Wake the loop in case it is blocked waiting for events.
+    let _ = handle.proxy.send_event(VeloxUserEvent::Wake);
+    true
+}
+
+#[no_mangle]
+pub extern "C" fn velox_event_loop_pump_async_free(handle: *mut VeloxEventLoopPumpHandle) {
+    if !handle.is_null() {
+        unsafe { drop(Box::from_raw(handle)) };
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_build(
+    event_loop: *mut VeloxEventLoop,
+    config: *const VeloxWindowConfig,
+) -> *mut VeloxWindowHandle {
+    if event_loop.is_null() {
+        return ptr::null_mut();
+    }
+
+    let event_loop = unsafe { &mut *event_loop };
+    let cfg = unsafe { config.as_ref().copied().unwrap_or_default() };
+    build_window(event_loop, cfg, true)
+}
+
+fn build_window(
+    event_loop: &VeloxEventLoop,
+    cfg: VeloxWindowConfig,
+    visible: bool,
+) -> *mut VeloxWindowHandle {
+    let build_result = catch_unwind(AssertUnwindSafe(|| {
+        let mut builder = TaoWindowBuilder::new().with_visible(visible);
+
+        if let Some(title) = opt_cstring(cfg.title) {
+            builder = builder.with_title(title);
+        }
+
+        if let Some(parent) = unsafe { cfg.parent.as_ref() } {
+            builder = apply_parent_builder(builder, &parent.window);
+        }
+
+        if let Some(shadow) = opt_bool(cfg.has_shadow) {
+            #[cfg(target_os = "macos")]
+            {
+                builder = builder.with_has_shadow(shadow);
+            }
+            #[cfg(target_os = "windows")]
+            {
+                builder = builder.with_undecorated_shadow(shadow);
+            }
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            if let Some(transparent) = opt_bool(cfg.titlebar_transparent) {
+                builder = builder.with_titlebar_transparent(transparent);
+            }
+            if let Some(hidden) = opt_bool(cfg.titlebar_hidden) {
+                builder = builder.with_titlebar_hidden(hidden);
+            }
+            if let Some(hidden_buttons) = opt_bool(cfg.titlebar_buttons_hidden) {
+                builder = builder.with_titlebar_buttons_hidden(hidden_buttons);
+            }
+        }
+
+        if cfg.width > 0 && cfg.height > 0 {
+            builder =
+                builder.with_inner_size(LogicalSize::new(cfg.width as f64, cfg.height as f64));
+        }
+
+        if cfg.has_position {
+            builder = builder.with_position(LogicalPosition::new(cfg.x, cfg.y));
+        }
+        if cfg.min_width > 0.0 && cfg.min_height > 0.0 {
+            builder = builder.with_min_inner_size(LogicalSize::new(cfg.min_width, cfg.min_height));
+        }
+        if cfg.max_width > 0.0 && cfg.max_height > 0.0 {
+            builder = builder.with_max_inner_size(LogicalSize::new(cfg.max_width, cfg.max_height));
+        }
+
+        if let Some(decorations) = opt_bool(cfg.decorations) {
+            builder = builder.with_decorations(decorations);
+        }
+        if let Some(resizable) = opt_bool(cfg.resizable) {
+            builder = builder.with_resizable(resizable);
+        }
+        builder = builder
+            .with_transparent(cfg.transparent)
+            .with_always_on_top(cfg.always_on_top)
+            .with_theme(theme_from_ffi(cfg.theme));
+
+        if cfg.skip_taskbar {
+            #[cfg(target_os = "windows")]
+            {
+                builder = builder.with_skip_taskbar(true);
+            }
+            #[cfg(target_os = "linux")]
+            {
+                use tao::platform::unix::WindowBuilderExtUnix;
+                builder = builder.with_skip_taskbar(true);
+            }
+        }
+
+        builder.build(&event_loop.event_loop)
+    }));
+
+    match build_result {
+        Ok(Ok(window)) => {
+            let min = (cfg.min_width > 0.0 && cfg.min_height > 0.0)
+                .then(|| LogicalSize::new(cfg.min_width, cfg.min_height));
+            let max = (cfg.max_width > 0.0 && cfg.max_height > 0.0)
+                .then(|| LogicalSize::new(cfg.max_width, cfg.max_height));
+            if min.is_some() || max.is_some() {
+                update_size_limits(window.id(), |limits| *limits = SizeLimits { min, max });
+            }
+            let id_string = format!("{:?}", window.id());
+            let identifier = CString::new(id_string).unwrap_or_else(|_| {
+                CString::new("velox-window").expect("static string has no nulls")
+            });
+            Box::into_raw(Box::new(VeloxWindowHandle {
+                window,
+                identifier,
+                #[cfg(target_os = "macos")]
+                observers: RefCell::new(HashMap::new()),
+            }))
+        }
+        Ok(Err(error)) => {
+            set_last_error(VeloxErrorCode::WindowCreationFailed, &error.to_string());
+            ptr::null_mut()
+        }
+        Err(payload) => {
+            report_panic(payload);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Build a window with every property applied before it is shown
+#[no_mangle]
+pub extern "C" fn velox_window_build_v2(
+    event_loop: *mut VeloxEventLoop,
+    config: *const VeloxWindowConfigV2,
+) -> *mut VeloxWindowHandle {
+    if event_loop.is_null() {
+        return ptr::null_mut();
+    }
+
+    let event_loop = unsafe { &mut *event_loop };
+    let cfg = unsafe { config.as_ref().copied().unwrap_or_default() };
+    let window = build_window(event_loop, cfg.base, false);
+    if window.is_null() {
+        return window;
+    }
+
+    if !cfg.focusable {
+        velox_window_set_focusable(window, false);
+    }
+    if cfg.level != VeloxWindowLevel::Normal {
+        velox_window_set_level(window, cfg.level);
+    }
+    if !cfg.icon_rgba.is_null() {
+        velox_window_set_icon_from_rgba(
+            window,
+            cfg.icon_rgba,
+            cfg.icon_rgba_width,
+            cfg.icon_rgba_height,
+        );
+    } else if !cfg.icon_path.is_null() {
+        velox_window_set_icon_from_path(window, cfg.icon_path);
+    }
+    if cfg.fullsize_content_view {
+        velox_window_set_fullsize_content_view(window, true);
+    }
+    if cfg.vibrancy != VeloxVibrancy::Disabled {
+        velox_window_set_vibrancy(window, cfg.vibrancy);
+    }
+    if cfg.visible {
+        velox_window_set_visible(window, true);
+    }
+
+    window
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_free(window: *mut VeloxWindowHandle) {
+    if !window.is_null() {
+        #[cfg(target_os = "macos")]
+        {
+            let address = unsafe { &*window }.window.ns_window() as usize;
+            APPEARS_INACTIVE.with(|windows| windows.borrow_mut().remove(&address));
+        }
+        forget_window_state(unsafe { &*window }.window.id());
+        unsafe { drop(Box::from_raw(window)) };
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_identifier(window: *mut VeloxWindowHandle) -> *const c_char {
+    if window.is_null() {
+        return ptr::null();
+    }
+
+    unsafe { &*window }.identifier.as_ptr()
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_title(
+    window: *mut VeloxWindowHandle,
+    title: *const c_char,
+) -> bool {
+    let Some(title) = opt_cstring(title) else {
+        return false;
+    };
+    with_window(window, |w| {
+        w.set_title(&title);
+        true
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_fullscreen(
+    window: *mut VeloxWindowHandle,
+    fullscreen: bool,
+) -> bool {
+    with_window(window, |w| {
+        if fullscreen {
+            w.set_fullscreen(Some(Fullscreen::Borderless(None)));
+        } else {
+            w.set_fullscreen(None);
+        }
+        true
+    })
+    .unwrap_or(false)
+}
+
+/// Animate fullscreen transitions unless `duration_ms` is 0 (macOS only)
+#[no_mangle]
+pub extern "C" fn velox_window_set_fullscreen_animation_duration(
+    window: *mut VeloxWindowHandle,
+    duration_ms: u32,
+) -> bool {
+    with_window(window, |w| {
+        #[cfg(target_os = "macos")]
+        {
+            use objc2::{msg_send, runtime::AnyObject};
+
+            const NS_WINDOW_ANIMATION_BEHAVIOR_NONE: isize = 2;
+            const NS_WINDOW_ANIMATION_BEHAVIOR_DOCUMENT_WINDOW: isize = 3;
+
+            let ns_window = w.ns_window() as *const AnyObject;
+            if ns_window.is_null() {
+                return false;
+            }
+            let behavior = if duration_ms == 0 {
+                NS_WINDOW_ANIMATION_BEHAVIOR_NONE
+            } else {
+                NS_WINDOW_ANIMATION_BEHAVIOR_DOCUMENT_WINDOW
+            };
+            let _: () = unsafe { msg_send![&*ns_window, setAnimationBehavior: behavior] };
+            return true;
+        }
+        #[allow(unreachable_code)]
+        {
+            let _ = (w, duration_ms);
+            false
+        }
+    })
+    .unwrap_or(false)
+}
+
+/// Called when the window is about to enter fullscreen (macOS only); pass NULL to remove
+#[no_mangle]
+pub extern "C" fn velox_window_set_fullscreen_will_enter_handler(
+    window: *mut VeloxWindowHandle,
+    handler: VeloxWindowNotificationHandler,
+    user_data: *mut c_void,
+) -> bool {
+    let Some(handle) = (unsafe { window.as_ref() }) else {
+        return false;
+    };
+    #[cfg(target_os = "macos")]
+    {
+        return observe_window_notification(
+            handle,
+            "NSWindowWillEnterFullScreenNotification",
+            handler,
+            user_data,
+        );
+    }
+    #[allow(unreachable_code)]
+    {
+        let _ = (handle, handler, user_data);
+        false
+    }
+}
+
+/// Called once the window has finished entering fullscreen (macOS only); pass NULL to remove
+#[no_mangle]
+pub extern "C" fn velox_window_set_fullscreen_did_enter_handler(
+    window: *mut VeloxWindowHandle,
+    handler: VeloxWindowNotificationHandler,
+    user_data: *mut c_void,
+) -> bool {
+    let Some(handle) = (unsafe { window.as_ref() }) else {
+        return false;
+    };
+    #[cfg(target_os = "macos")]
+    {
+        return observe_window_notification(
+            handle,
+            "NSWindowDidEnterFullScreenNotification",
+            handler,
+            user_data,
+        );
+    }
+    #[allow(unreachable_code)]
+    {
+        let _ = (handle, handler, user_data);
+        false
+    }
+}
+
+/// Opt the window's backing layer into extended dynamic range (HDR) content (macOS only)
+#[no_mangle]
+pub extern "C" fn velox_window_set_allows_hdr(
+    window: *mut VeloxWindowHandle,
+    enabled: bool,
+) -> bool {
+    with_window(window, |w| {
+        #[cfg(target_os = "macos")]
+        {
+            use objc2::{msg_send, runtime::AnyObject, sel};
+
+            let view = w.ns_view() as *const AnyObject;
+            if view.is_null() {
+                return false;
+            }
+            let view = unsafe { &*view };
+            let _: () = unsafe { msg_send![view, setWantsLayer: true] };
+            let layer: *mut AnyObject = unsafe { msg_send![view, layer] };
+            let Some(layer) = (unsafe { layer.as_ref() }) else {
+                return false;
+            };
+            // Only Metal-backed layers understand EDR; other layer kinds cannot show HDR.
+            let supported: bool = unsafe {
+                msg_send![layer, respondsToSelector: sel!(setWantsExtendedDynamicRangeContent:)]
+            };
+            if !supported {
+                return false;
+            }
+            let _: () = unsafe { msg_send![layer, setWantsExtendedDynamicRangeContent: enabled] };
+            return true;
+        }
+        #[allow(unreachable_code)]
+        {
+            let _ = (w, enabled);
+            false
+        }
+    })
+    .unwrap_or(false)
+}
+
+#[cfg(target_os = "macos")]
+objc2::define_class!(
+    #[unsafe(super(objc2_foundation::NSObject))]
+    #[name = "VeloxTouchBarTarget"]
+    struct VeloxTouchBarTarget;
+
+    impl VeloxTouchBarTarget {
+        #[unsafe(method(veloxTouchBarItemActivated:))]
+        fn item_activated(&self, sender: Option<&objc2::runtime::AnyObject>) {
+            dispatch_touch_bar_action(sender);
+        }
+    }
+);
+
+#[cfg(target_os = "macos")]
+impl VeloxTouchBarTarget {
+    fn shared() -> objc2::rc::Retained<Self> {
+        use objc2::{msg_send, AllocAnyThread};
+
+        TOUCH_BAR_TARGET.with(|target| {
+            target
+                .borrow_mut()
+                .get_or_insert_with(|| {
+                    let this = Self::alloc().set_ivars(());
+                    unsafe { msg_send![super(this), init] }
+                })
+                .clone()
+        })
+    }
+}
+
+/// Touch Bar controls report their identifier as a menu event.
+#[cfg(target_os = "macos")]
+fn dispatch_touch_bar_action(sender: Option<&objc2::runtime::AnyObject>) {
+    use objc2::{msg_send, rc::Retained};
+    use objc2_foundation::NSString;
+
+    let Some(sender) = sender else {
+        return;
+    };
+    let identifier: Option<Retained<NSString>> = unsafe { msg_send![sender, identifier] };
+    let Some(identifier) = identifier else {
+        return;
+    };
+    USER_EVENT_PROXY.with(|proxy| {
+        if let Some(proxy) = proxy.borrow().as_ref() {
+            let _ = proxy.send_event(VeloxUserEvent::Menu(identifier.to_string()));
+        }
+    });
+}
+
+/// Replace the window's Touch Bar with the given items (macOS only)
+#[no_mangle]
+pub extern "C" fn velox_window_set_touchbar(
+    window: *mut VeloxWindowHandle,
+    items: *const VeloxTouchBarItem,
+    count: usize,
+) -> bool {
+    let items = if items.is_null() || count == 0 {
+        &[][..]
+    } else {
+        unsafe { std::slice::from_raw_parts(items, count) }
+    };
+    with_window(window, |w| {
+        #[cfg(target_os = "macos")]
+        {
+            use objc2::{
+                class, msg_send,
+                rc::{Allocated, Retained},
+                runtime::AnyObject,
+                sel,
+            };
+            use objc2_foundation::NSString;
+
+            let ns_window = w.ns_window() as *const AnyObject;
+            if ns_window.is_null() {
+                return false;
+            }
+
+            let target = VeloxTouchBarTarget::shared();
+            unsafe {
+                let identifiers: Retained<AnyObject> = msg_send![class!(NSMutableArray), array];
+                let templates: Retained<AnyObject> = msg_send![class!(NSMutableSet), set];
+
+                for item in items {
+                    if item.kind == VeloxTouchBarItemKind::Spacer {
+                        let spacer = NSString::from_str("NSTouchBarItemIdentifierFlexibleSpace");
+                        let _: () = msg_send![&*identifiers, addObject: &*spacer];
+                        continue;
+                    }
+                    let Some(identifier) = opt_cstring(item.identifier) else {
+                        continue;
+                    };
+                    let identifier = NSString::from_str(&identifier);
+                    let title = NSString::from_str(&opt_cstring(item.title).unwrap_or_default());
+
+                    let view: Retained<AnyObject> = match item.kind {
+                        VeloxTouchBarItemKind::Button => msg_send![
+                            class!(NSButton),
+                            buttonWithTitle: &*title,
+                            target: &*target,
+                            action: sel!(veloxTouchBarItemActivated:)
+                        ],
+                        VeloxTouchBarItemKind::Label => {
+                            msg_send![class!(NSTextField), labelWithString: &*title]
+                        }
+                        VeloxTouchBarItemKind::Slider => msg_send![
+                            class!(NSSlider),
+                            sliderWithTarget: &*target,
+                            action: sel!(veloxTouchBarItemActivated:)
+                        ],
+                        VeloxTouchBarItemKind::Spacer => unreachable!(),
+                    };
+                    let _: () = msg_send![&*view, setIdentifier: &*identifier];
+
+                    let allocated: Allocated<AnyObject> =
+                        msg_send![class!(NSCustomTouchBarItem), alloc];
+                    let touch_bar_item: Retained<AnyObject> =
+                        msg_send![allocated, initWithIdentifier: &*identifier];
+                    let _: () = msg_send![&*touch_bar_item, setView: &*view];
+                    let _: () = msg_send![&*templates, addObject: &*touch_bar_item];
+                    let _: () = msg_send![&*identifiers, addObject: &*identifier];
+                }
+
+                let touch_bar: Retained<AnyObject> = msg_send![class!(NSTouchBar), new];
+                let _: () = msg_send![&*touch_bar, setDefaultItemIdentifiers: &*identifiers];
+                let _: () = msg_send![&*touch_bar, setTemplateItems: &*templates];
+                let _: () = msg_send![&*ns_window, setTouchBar: &*touch_bar];
+            }
+            return true;
+        }
+        #[allow(unreachable_code)]
+        {
+            let _ = (w, items);
+            false
+        }
+    })
+    .unwrap_or(false)
+}
+
+#[cfg(feature = "debug-overlay")]
+const DEBUG_OVERLAY_COLORS: [(u8, u8, u8); 4] =
+    [(255, 59, 48), (52, 199, 89), (0, 122, 255), (255, 149, 0)];
+
+#[cfg(all(feature = "debug-overlay", target_os = "macos"))]
+#[repr(C)]
+struct CGColor {
+    _private: [u8; 0],
+}
+
+#[cfg(all(feature = "debug-overlay", target_os = "macos"))]
+unsafe impl objc2::encode::RefEncode for CGColor {
+    const ENCODING_REF: objc2::encode::Encoding =
+        objc2::encode::Encoding::Pointer(&objc2::encode::Encoding::Struct("CGColor", &[]));
+}
+
+/// Bordered layer on top of the webview's own layer, which keeps it out of hit testing
+#[cfg(all(feature = "debug-overlay", target_os = "macos"))]
+type DebugOutline = objc2::rc::Retained<objc2::runtime::AnyObject>;
+
+/// Edge windows stacked above the WebView2 host
+#[cfg(all(feature = "debug-overlay", target_os = "windows"))]
+type DebugOutline = Vec<isize>;
+
+#[cfg(all(feature = "debug-overlay", target_os = "macos"))]
+fn add_debug_outline(
+    _window: &Window,
+    handle: &VeloxWebviewHandle,
+    (red, green, blue): (u8, u8, u8),
+) -> Option<DebugOutline> {
+    use objc2::{class, msg_send, rc::Retained, runtime::AnyObject};
+    use objc2_foundation::{NSPoint, NSRect, NSSize};
+    use wry::WebViewExtMacOS;
+
+    // kCALayerWidthSizable | kCALayerHeightSizable
+    const CA_LAYER_AUTORESIZE: u32 = (1 << 1) | (1 << 4);
+
+    // WebKit reports logical bounds, so the scale factor is never applied.
+    let size: LogicalSize<f64> = handle.webview.bounds().ok()?.size.to_logical(1.0);
+    let webview = handle.webview.webview();
+    let object: &AnyObject = &webview;
+    unsafe {
+        let _: () = msg_send![object, setWantsLayer: true];
+        let host: *mut AnyObject = msg_send![object, layer];
+        let host = host.as_ref()?;
+        let layer: Retained<AnyObject> = msg_send![class!(CALayer), layer];
+        let frame = NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(size.width, size.height));
+        let color: *mut AnyObject = msg_send![
+            class!(NSColor),
+            colorWithSRGBRed: f64::from(red) / 255.0,
+            green: f64::from(green) / 255.0,
+            blue: f64::from(blue) / 255.0,
+            alpha: 1.0f64
+        ];
+        let cg_color: *mut CGColor = msg_send![color, CGColor];
+        let _: () = msg_send![&*layer, setFrame: frame];
+        let _: () = msg_send![&*layer, setAutoresizingMask: CA_LAYER_AUTORESIZE];
+        let _: () = msg_send![&*layer, setBorderWidth: 2.0f64];
+        let _: () = msg_send![&*layer, setBorderColor: cg_color];
+        let _: () = msg_send![&*layer, setZPosition: 1000.0f64];
+        let _: () = msg_send![host, addSublayer: &*layer];
+        Some(layer)
+    }
+}
+
+#[cfg(all(feature = "debug-overlay", target_os = "macos"))]
+fn remove_debug_outline(outline: DebugOutline) {
+    let _: () = unsafe { objc2::msg_send![&*outline, removeFromSuperlayer] };
+}
+
+#[cfg(all(feature = "debug-overlay", target_os = "windows"))]
+fn add_debug_outline(
+    window: &Window,
+    handle: &VeloxWebviewHandle,
+    (red, green, blue): (u8, u8, u8),
+) -> Option<DebugOutline> {
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::{COLORREF, HINSTANCE, LPARAM, LRESULT, WPARAM};
+    use windows::Win32::Graphics::Gdi::{CreateSolidBrush, DeleteObject};
+    use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        CreateWindowExW, DefWindowProcW, RegisterClassW, SetWindowPos, HMENU, HWND_TOP,
+        SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOSIZE, WINDOW_EX_STYLE, WNDCLASSW, WS_CHILD, WS_VISIBLE,
+    };
+
+    const THICKNESS: i32 = 2;
+
+    unsafe extern "system" fn window_proc(
+        hwnd: HWND,
+        message: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> LRESULT {
+        DefWindowProcW(hwnd, message, wparam, lparam)
+    }
+
+    let bounds = handle.webview.bounds().ok()?;
+    let position: PhysicalPosition<i32> = bounds.position.to_physical(window.scale_factor());
+    let size: PhysicalSize<i32> = bounds.size.to_physical(window.scale_factor());
+    // The class brush paints the edges, so each colour gets its own class.
+    let class_name = format!("VeloxDebugOutline{red:02x}{green:02x}{blue:02x}")
+        .encode_utf16()
+        .chain(Some(0))
+        .collect::<Vec<u16>>();
+
+    unsafe {
+        let instance: HINSTANCE = GetModuleHandleW(None).ok()?.into();
+        let brush = CreateSolidBrush(COLORREF(
+            u32::from(red) | (u32::from(green) << 8) | (u32::from(blue) << 16),
+        ));
+        let class = WNDCLASSW {
+            lpfnWndProc: Some(window_proc),
+            hInstance: instance,
+            hbrBackground: brush,
+            lpszClassName: PCWSTR(class_name.as_ptr()),
+            ..Default::default()
+        };
+        // An earlier outline already registered this colour, so its brush is the one in use.
+        if RegisterClassW(&class) == 0 {
+            let _ = DeleteObject(brush);
+        }
+
+        let parent = HWND(window.hwnd() as _);
+        let edges = [
+            (position.x, position.y, size.width, THICKNESS),
+            (
+                position.x,
+                position.y + size.height - THICKNESS,
+                size.width,
+                THICKNESS,
+            ),
+            (position.x, position.y, THICKNESS, size.height),
+            (
+                position.x + size.width - THICKNESS,
+                position.y,
+                THICKNESS,
+                size.height,
+            ),
+        ];
+        let mut outline = Vec::with_capacity(edges.len());
+        for (x, y, width, height) in edges {
+            let Ok(edge) = CreateWindowExW(
+                WINDOW_EX_STYLE::default(),
+                PCWSTR(class_name.as_ptr()),
+                PCWSTR::null(),
+                WS_CHILD | WS_VISIBLE,
+                x,
+                y,
+                width,
+                height,
+                parent,
+                HMENU::default(),
+                instance,
+                None,
+            ) else {
+                continue;
+            };
+            let _ = SetWindowPos(
+                edge,
+                HWND_TOP,
+                0,
+                0,
+                0,
+                0,
+                SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+            );
+            outline.push(edge.0 as isize);
+        }
+        Some(outline)
+    }
+}
+
+#[cfg(all(feature = "debug-overlay", target_os = "windows"))]
+fn remove_debug_outline(outline: DebugOutline) {
+    use windows::Win32::UI::WindowsAndMessaging::DestroyWindow;
+
+    for edge in outline {
+        let _ = unsafe { DestroyWindow(HWND(edge as _)) };
+    }
+}
+
+/// Outline each webview in the window from inside its page and, on macOS and Windows, natively
+#[cfg(feature = "debug-overlay")]
+#[no_mangle]
+pub extern "C" fn velox_window_set_debug_overlay(
+    window: *mut VeloxWindowHandle,
+    enabled: bool,
+) -> bool {
+    let Some(window_id) = with_window(window, |w| w.id()) else {
+        return false;
+    };
+    let handles = WINDOW_WEBVIEWS.with(|webviews| {
+        webviews
+            .borrow()
+            .get(&window_id)
+            .cloned()
+            .unwrap_or_default()
+    });
+
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    {
+        let previous = DEBUG_OUTLINES.with(|outlines| outlines.borrow_mut().remove(&window_id));
+        previous
+            .into_iter()
+            .flatten()
+            .for_each(remove_debug_outline);
+        if enabled {
+            let added = with_window(window, |w| {
+                handles
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, &handle)| {
+                        let handle = unsafe { handle.as_ref() }?;
+                        let color = DEBUG_OVERLAY_COLORS[index % DEBUG_OVERLAY_COLORS.len()];
+                        add_debug_outline(w, handle, color)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+            DEBUG_OUTLINES.with(|outlines| outlines.borrow_mut().insert(window_id, added));
+        }
+    }
+
+    // Apply to every webview even if one of them fails.
+    handles
+        .iter()
+        .enumerate()
+        .fold(true, |ok, (index, &handle)| {
+            let Some(handle) = (unsafe { handle.as_ref() }) else {
+                return false;
+            };
+            let script = if enabled {
+                let (red, green, blue) = DEBUG_OVERLAY_COLORS[index % DEBUG_OVERLAY_COLORS.len()];
+                format!(
+                    "(function () {{ var o = document.getElementById('__velox_debug_overlay'); \
+                     if (!o) {{ o = document.createElement('div'); o.id = '__velox_debug_overlay'; \
+                     document.documentElement.appendChild(o); }} \
+                     o.style.cssText = 'position:fixed;inset:0;pointer-events:none;\
+                     z-index:2147483647;box-shadow:inset 0 0 0 2px rgb({red},{green},{blue});'; }})();"
+                )
+            } else {
+                "document.getElementById('__velox_debug_overlay')?.remove();".to_string()
+            };
+            report_error(handle.webview.evaluate_script(&script), VeloxErrorCode::Unknown).is_some() && ok
+        })
+}
+
+#[cfg(target_os = "macos")]
+type IsKeyWindowImp = unsafe extern "C-unwind" fn(
+    &objc2::runtime::AnyObject,
+    objc2::runtime::Sel,
+) -> objc2::runtime::Bool;
+
+#[cfg(target_os = "macos")]
+static ORIGINAL_IS_KEY_WINDOW: OnceLock<IsKeyWindowImp> = OnceLock::new();
+
+#[cfg(target_os = "macos")]
+unsafe extern "C-unwind" fn velox_is_key_window(
+    this: &objc2::runtime::AnyObject,
+    sel: objc2::runtime::Sel,
+) -> objc2::runtime::Bool {
+    let address = this as *const objc2::runtime::AnyObject as usize;
+    if APPEARS_INACTIVE.with(|windows| windows.borrow().contains(&address)) {
+        return objc2::runtime::Bool::NO;
+    }
+    match ORIGINAL_IS_KEY_WINDOW.get() {
+        Some(original) => original(this, sel),
+        None => objc2::runtime::Bool::NO,
+    }
+}
+
+/// AppKit draws the title bar and controls from `isKeyWindow`, so answer it per window.
+#[cfg(target_os = "macos")]
+fn install_is_key_window_override(window: &objc2::runtime::AnyObject) -> bool {
+    use objc2::{runtime::Imp, sel};
+
+    if ORIGINAL_IS_KEY_WINDOW.get().is_some() {
+        return true;
+    }
+    // Override on tao's own subclass; the inherited `NSWindow` method is shared by every window.
+    let class = window.class();
+    if class.name() != c"TaoWindow" {
+        return false;
+    }
+    let Some(method) = class.instance_method(sel!(isKeyWindow)) else {
+        return false;
+    };
+    let original = method.implementation();
+    let replacement: Imp = unsafe { std::mem::transmute(velox_is_key_window as IsKeyWindowImp) };
+    let added = unsafe {
+        objc2::ffi::class_addMethod(
+            class as *const objc2::runtime::AnyClass as *mut objc2::runtime::AnyClass,
+            sel!(isKeyWindow),
+            replacement,
+            objc2::ffi::method_getTypeEncoding(method),
+        )
+    };
+    if !added.as_bool() {
+        return false;
+    }
+    let _ =
+        ORIGINAL_IS_KEY_WINDOW.set(unsafe { std::mem::transmute::<Imp, IsKeyWindowImp>(original) });
+    true
+}
+
+/// Draw the window as inactive (dimmed title bar and controls) even while it is key (macOS only)
+#[no_mangle]
+pub extern "C" fn velox_window_set_appears_inactive(
+    window: *mut VeloxWindowHandle,
+    inactive: bool,
+) -> bool {
+    with_window(window, |w| {
+        #[cfg(target_os = "macos")]
+        {
+            use objc2::{msg_send, runtime::AnyObject};
+
+            let ns_window = w.ns_window() as *const AnyObject;
+            if ns_window.is_null() {
+                return false;
+            }
+            let ns_window = unsafe { &*ns_window };
+            if !install_is_key_window_override(ns_window) {
+                return false;
+            }
+            let address = ns_window as *const AnyObject as usize;
+            APPEARS_INACTIVE.with(|windows| {
+                let mut windows = windows.borrow_mut();
+                if inactive {
+                    windows.insert(address);
+                } else {
+                    windows.remove(&address);
+                }
+            });
+            // Redraw the frame view too, which owns the title bar and traffic lights.
+            unsafe {
+                let content_view: *mut AnyObject = msg_send![ns_window, contentView];
+                if !content_view.is_null() {
+                    let frame_view: *mut AnyObject = msg_send![content_view, superview];
+                    if !frame_view.is_null() {
+                        let _: () = msg_send![frame_view, setNeedsDisplay: true];
+                    }
+                }
+                let _: () = msg_send![ns_window, setViewsNeedDisplay: true];
+            }
+            return true;
+        }
+        #[allow(unreachable_code)]
+        {
+            let _ = (w, inactive);
+            false
+        }
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_decorations(
+    window: *mut VeloxWindowHandle,
+    decorations: bool,
+) -> bool {
+    with_window(window, |w| {
+        w.set_decorations(decorations);
+        true
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_shadow(window: *mut VeloxWindowHandle, shadow: bool) -> bool {
+    with_window(window, |w| {
+        #[cfg(target_os = "macos")]
+        {
+            w.set_has_shadow(shadow);
+            return true;
+        }
+        #[cfg(target_os = "windows")]
+        {
+            w.set_undecorated_shadow(shadow);
+            return true;
+        }
+        #[allow(unreachable_code)]
+        {
+            let _ = shadow;
+            false
+        }
+    })
+    .unwrap_or(false)
+}
+
+/// Let content show through the title bar (macOS only)
+#[no_mangle]
+pub extern "C" fn velox_window_set_titlebar_transparent(
+    window: *mut VeloxWindowHandle,
+    transparent: bool,
+) -> bool {
+    with_window(window, |w| {
+        #[cfg(target_os = "macos")]
+        {
+            w.set_titlebar_transparent(transparent);
+            return true;
+        }
+        #[allow(unreachable_code)]
+        {
+            let _ = (w, transparent);
+            false
+        }
+    })
+    .unwrap_or(false)
+}
+
+/// Show or hide the title text while keeping the title bar (macOS only)
+#[no_mangle]
+pub extern "C" fn velox_window_set_title_visible(
+    window: *mut VeloxWindowHandle,
+    visible: bool,
+) -> bool {
+    with_window(window, |w| {
+        #[cfg(target_os = "macos")]
+        {
+            use objc2::{msg_send, runtime::AnyObject};
+
+            const NS_WINDOW_TITLE_VISIBLE: isize = 0;
+            const NS_WINDOW_TITLE_HIDDEN: isize = 1;
+
+            let ns_window = w.ns_window() as *const AnyObject;
+            if ns_window.is_null() {
+                return false;
+            }
+            let visibility = if visible {
+                NS_WINDOW_TITLE_VISIBLE
+            } else {
+                NS_WINDOW_TITLE_HIDDEN
+            };
+            let _: () = unsafe { msg_send![&*ns_window, setTitleVisibility: visibility] };
+            return true;
+        }
+        #[allow(unreachable_code)]
+        {
+            let _ = (w, visible);
+            false
+        }
+    })
+    .unwrap_or(false)
+}
+
+/// Extend the content view underneath the title bar (macOS only)
+#[no_mangle]
+pub extern "C" fn velox_window_set_fullsize_content_view(
+    window: *mut VeloxWindowHandle,
+    enabled: bool,
+) -> bool {
+    with_window(window, |w| {
+        #[cfg(target_os = "macos")]
+        {
+            w.set_fullsize_content_view(enabled);
+            return true;
+        }
+        #[allow(unreachable_code)]
+        {
+            let _ = (w, enabled);
+            false
+        }
+    })
+    .unwrap_or(false)
+}
+
+/// Move the close, minimize and zoom buttons to a logical offset from the top-left (macOS only)
+#[no_mangle]
+pub extern "C" fn velox_window_set_traffic_lights_offset(
+    window: *mut VeloxWindowHandle,
+    x: f64,
+    y: f64,
+) -> bool {
+    with_window(window, |w| {
+        #[cfg(target_os = "macos")]
+        {
+            w.set_traffic_light_inset(LogicalPosition::new(x, y));
+            return true;
+        }
+        #[allow(unreachable_code)]
+        {
+            let _ = (w, x, y);
+            false
+        }
+    })
+    .unwrap_or(false)
+}
+
+/// Mark the window as having unsaved changes (macOS only)
+#[no_mangle]
+pub extern "C" fn velox_window_set_document_edited(
+    window: *mut VeloxWindowHandle,
+    edited: bool,
+) -> bool {
+    with_window(window, |w| {
+        #[cfg(target_os = "macos")]
+        {
+            w.set_is_document_edited(edited);
+            return true;
+        }
+        #[allow(unreachable_code)]
+        {
+            let _ = (w, edited);
+            false
+        }
+    })
+    .unwrap_or(false)
+}
+
+/// Show the file's proxy icon in the title bar; null clears it (macOS only)
+#[no_mangle]
+pub extern "C" fn velox_window_set_represented_filename(
+    window: *mut VeloxWindowHandle,
+    path: *const c_char,
+) -> bool {
+    let path = opt_cstring(path).unwrap_or_default();
+    with_window(window, |w| {
+        #[cfg(target_os = "macos")]
+        {
+            use objc2::{msg_send, runtime::AnyObject};
+            use objc2_foundation::NSString;
+
+            let ns_window = w.ns_window() as *const AnyObject;
+            if ns_window.is_null() {
+                return false;
+            }
+            let filename = NSString::from_str(&path);
+            let _: () = unsafe { msg_send![&*ns_window, setRepresentedFilename: &*filename] };
+            return true;
+        }
+        #[allow(unreachable_code)]
+        {
+            let _ = (w, path);
+            false
+        }
+    })
+    .unwrap_or(false)
+}
+
+/// Set the window vibrancy material (macOS only)
+#[no_mangle]
+pub extern "C" fn velox_window_set_vibrancy(
+    window: *mut VeloxWindowHandle,
+    vibrancy: VeloxVibrancy,
+) -> bool {
+    with_window(window, |w| {
+        #[cfg(target_os = "macos")]
+        {
+            use objc2::{
+                class, msg_send,
+                rc::{Allocated, Retained},
+                runtime::AnyObject,
+            };
+            use objc2_foundation::{NSRect, NSString};
+
+            const NS_VISUAL_EFFECT_BLENDING_MODE_BEHIND_WINDOW: isize = 0;
+            const NS_VISUAL_EFFECT_STATE_ACTIVE: isize = 1;
+            const NS_VIEW_WIDTH_SIZABLE: usize = 2;
+            const NS_VIEW_HEIGHT_SIZABLE: usize = 16;
+            const NS_WINDOW_BELOW: isize = -1;
+
+            let material: isize = match vibrancy {
+                VeloxVibrancy::Disabled => 0,
+                VeloxVibrancy::Titlebar => 3,
+                VeloxVibrancy::Menu => 5,
+                VeloxVibrancy::Popover => 6,
+                VeloxVibrancy::Sidebar => 7,
+                VeloxVibrancy::HeaderView => 10,
+                VeloxVibrancy::Sheet => 11,
+                VeloxVibrancy::WindowBackground => 12,
+                VeloxVibrancy::HudWindow => 13,
+                VeloxVibrancy::FullScreenUI => 15,
+                VeloxVibrancy::ToolTip => 17,
+                VeloxVibrancy::ContentBackground => 18,
+                VeloxVibrancy::UnderWindowBackground => 21,
+                VeloxVibrancy::UnderPageBackground => 22,
+            };
+
+            let content_view = w.ns_view() as *const AnyObject;
+            if content_view.is_null() {
+                return false;
+            }
+            let content_view = unsafe { &*content_view };
+            let identifier = NSString::from_str("VeloxVibrancyView");
+
+            unsafe {
+                let subviews: Retained<AnyObject> = msg_send![content_view, subviews];
+                let count: usize = msg_send![&*subviews, count];
+                for index in (0..count).rev() {
+                    let view: *mut AnyObject = msg_send![&*subviews, objectAtIndex: index];
+                    let view_identifier: *mut AnyObject = msg_send![view, identifier];
+                    if view_identifier.is_null() {
+                        continue;
+                    }
+                    let matches: bool = msg_send![view_identifier, isEqualToString: &*identifier];
+                    if matches {
+                        let _: () = msg_send![view, removeFromSuperview];
+                    }
+                }
+
+                if vibrancy == VeloxVibrancy::Disabled {
+                    return true;
+                }
+
+                let bounds: NSRect = msg_send![content_view, bounds];
+                let allocated: Allocated<AnyObject> = msg_send![class!(NSVisualEffectView), alloc];
+                let effect_view: Option<Retained<AnyObject>> =
+                    msg_send![allocated, initWithFrame: bounds];
+                let Some(effect_view) = effect_view else {
+                    return false;
+                };
+                let _: () = msg_send![&*effect_view, setMaterial: material];
+                let _: () = msg_send![
+                    &*effect_view,
+                    setBlendingMode: NS_VISUAL_EFFECT_BLENDING_MODE_BEHIND_WINDOW
+                ];
+                let _: () = msg_send![&*effect_view, setState: NS_VISUAL_EFFECT_STATE_ACTIVE];
+                let _: () = msg_send![
+                    &*effect_view,
+                    setAutoresizingMask: NS_VIEW_WIDTH_SIZABLE | NS_VIEW_HEIGHT_SIZABLE
+                ];
+                let _: () = msg_send![&*effect_view, setIdentifier: &*identifier];
+                let _: () = msg_send![
+                    content_view,
+                    addSubview: &*effect_view,
+                    positioned: NS_WINDOW_BELOW,
+                    relativeTo: ptr::null_mut::<AnyObject>()
+                ];
+            }
+            return true;
+        }
+        #[allow(unreachable_code)]
+        {
+            let _ = (w, vibrancy);
+            false
+        }
+    })
+    .unwrap_or(false)
+}
+
+/// Control how the window behaves in Spaces, Exposé and the window cycle (macOS only)
+#[no_mangle]
+pub extern "C" fn velox_window_set_collection_behavior(
+    window: *mut VeloxWindowHandle,
+    behavior: VeloxCollectionBehavior,
+) -> bool {
+    with_window(window, |w| {
+        #[cfg(target_os = "macos")]
+        {
+            use objc2::{msg_send, runtime::AnyObject};
+
+            let ns_window = w.ns_window() as *const AnyObject;
+            if ns_window.is_null() {
+                return false;
+            }
+            // In `NSWindowCollectionBehavior` bit order, starting at bit 0.
+            let flags = [
+                behavior.can_join_all_spaces,
+                behavior.move_to_active_space,
+                behavior.managed,
+                behavior.transient,
+                behavior.stationary,
+                behavior.participates_in_cycle,
+                behavior.ignores_cycle,
+            ];
+            let mask = flags
+                .iter()
+                .enumerate()
+                .filter(|(_, &set)| set)
+                .fold(0usize, |mask, (bit, _)| mask | 1 << bit);
+            let _: () = unsafe { msg_send![&*ns_window, setCollectionBehavior: mask] };
+            return true;
+        }
+        #[allow(unreachable_code)]
+        {
+            let _ = (w, behavior);
+            false
+        }
+    })
+    .unwrap_or(false)
+}
+
+fn window_safe_area_insets(w: &Window) -> VeloxEdgeInsets {
+    #[cfg(target_os = "macos")]
+    {
+        use objc2::{msg_send, runtime::AnyObject};
+        use objc2_foundation::NSEdgeInsets;
+
+        let content_view = w.ns_view() as *const AnyObject;
+        if content_view.is_null() {
+            return VeloxEdgeInsets::default();
+        }
+        let insets: NSEdgeInsets = unsafe { msg_send![&*content_view, safeAreaInsets] };
+        return VeloxEdgeInsets {
+            top: insets.top,
+            right: insets.right,
+            bottom: insets.bottom,
+            left: insets.left,
+        };
+    }
+    #[allow(unreachable_code)]
+    {
+        let _ = w;
+        VeloxEdgeInsets::default()
+    }
+}
+
+/// Read the safe area insets in logical pixels; zero outside macOS
+#[no_mangle]
+pub extern "C" fn velox_window_get_safe_area_insets(
+    window: *mut VeloxWindowHandle,
+    insets: *mut VeloxEdgeInsets,
+) -> bool {
+    let Some(insets) = (unsafe { insets.as_mut() }) else {
+        return false;
+    };
+    with_window(window, |w| {
+        *insets = window_safe_area_insets(w);
+        true
+    })
+    .unwrap_or(false)
+}
+
+/// Publish the safe area insets as `--velox-safe-area-inset-*` CSS variables
+#[no_mangle]
+pub extern "C" fn velox_window_apply_safe_area_insets(window: *mut VeloxWindowHandle) -> bool {
+    let Some((window_id, insets)) = with_window(window, |w| (w.id(), window_safe_area_insets(w)))
+    else {
+        return false;
+    };
+    let script = format!(
+        "(function () {{ var s = document.documentElement.style; \
+         s.setProperty('--velox-safe-area-inset-top', '{}px'); \
+         s.setProperty('--velox-safe-area-inset-right', '{}px'); \
+         s.setProperty('--velox-safe-area-inset-bottom', '{}px'); \
+         s.setProperty('--velox-safe-area-inset-left', '{}px'); }})();",
+        insets.top, insets.right, insets.bottom, insets.left
+    );
+    let handles = WINDOW_WEBVIEWS.with(|webviews| {
+        webviews
+            .borrow()
+            .get(&window_id)
+            .cloned()
+            .unwrap_or_default()
+    });
+    handles.iter().fold(true, |ok, &handle| {
+        let Some(handle) = (unsafe { handle.as_ref() }) else {
+            return false;
+        };
+        report_error(
+            handle.webview.evaluate_script(&script),
+            VeloxErrorCode::Unknown,
+        )
+        .is_some()
+            && ok
+    })
+}
+
+/// Let the user drag the window from anywhere in its content area (macOS only)
+#[no_mangle]
+pub extern "C" fn velox_window_set_movable_by_window_background(
+    window: *mut VeloxWindowHandle,
+    movable: bool,
+) -> bool {
+    with_window(window, |w| {
+        #[cfg(target_os = "macos")]
+        {
+            use objc2::{msg_send, runtime::AnyObject};
+
+            let ns_window = w.ns_window() as *const AnyObject;
+            if ns_window.is_null() {
+                return false;
+            }
+            let _: () = unsafe { msg_send![&*ns_window, setMovableByWindowBackground: movable] };
+            return true;
+        }
+        #[allow(unreachable_code)]
+        {
+            let _ = (w, movable);
+            false
+        }
+    })
+    .unwrap_or(false)
+}
+
+/// Set the label announced by screen readers (VoiceOver / Narrator)
+#[no_mangle]
+pub extern "C" fn velox_window_set_accessibility_label(
+    window: *mut VeloxWindowHandle,
+    label: *const c_char,
+) -> bool {
+    let Some(label) = opt_cstring(label) else {
+        return false;
+    };
+    with_window(window, |w| {
+        #[cfg(target_os = "macos")]
+        {
+            use objc2::{msg_send, runtime::AnyObject};
+            use objc2_foundation::NSString;
+
+            let view = w.ns_view() as *const AnyObject;
+            if view.is_null() {
+                return false;
+            }
+            let label = NSString::from_str(&label);
+            let _: () = unsafe { msg_send![&*view, setAccessibilityLabel: &*label] };
+            return true;
+        }
+        #[cfg(target_os = "windows")]
+        {
+            use windows::core::HSTRING;
+            use windows::Win32::UI::WindowsAndMessaging::SetWindowTextW;
+
+            let hwnd = HWND(w.hwnd() as _);
+            let result = unsafe { SetWindowTextW(hwnd, &HSTRING::from(label.as_str())) };
+            return report_error(result, VeloxErrorCode::Unknown).is_some();
+        }
+        #[allow(unreachable_code)]
+        {
+            let _ = (w, label);
+            false
+        }
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_always_on_bottom(
+    window: *mut VeloxWindowHandle,
+    on_bottom: bool,
+) -> bool {
+    with_window(window, |w| {
+        w.set_always_on_bottom(on_bottom);
+        true
+    })
+    .unwrap_or(false)
+}
+
+#[cfg(target_os = "macos")]
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGWindowLevelForKey(key: i32) -> i32;
+}
+
+/// Move the window to a stacking level; only desktop, normal and floating outside macOS
+#[no_mangle]
+pub extern "C" fn velox_window_set_level(
+    window: *mut VeloxWindowHandle,
+    level: VeloxWindowLevel,
+) -> bool {
+    with_window(window, |w| {
+        #[cfg(target_os = "macos")]
+        {
+            use objc2::{msg_send, runtime::AnyObject};
+
+            const CG_DESKTOP_WINDOW_LEVEL_KEY: i32 = 2;
+            const NS_NORMAL_WINDOW_LEVEL: isize = 0;
+            const NS_FLOATING_WINDOW_LEVEL: isize = 3;
+            const NS_POP_UP_MENU_WINDOW_LEVEL: isize = 101;
+            const NS_SCREEN_SAVER_WINDOW_LEVEL: isize = 1000;
+
+            let ns_window = w.ns_window() as *const AnyObject;
+            if ns_window.is_null() {
+                return false;
+            }
+            let level = match level {
+                VeloxWindowLevel::Desktop => unsafe {
+                    CGWindowLevelForKey(CG_DESKTOP_WINDOW_LEVEL_KEY) as isize
+                },
+                VeloxWindowLevel::Normal => NS_NORMAL_WINDOW_LEVEL,
+                VeloxWindowLevel::Floating => NS_FLOATING_WINDOW_LEVEL,
+                VeloxWindowLevel::AboveScreenSaver => NS_SCREEN_SAVER_WINDOW_LEVEL + 1,
+                VeloxWindowLevel::PopupMenu => NS_POP_UP_MENU_WINDOW_LEVEL,
+            };
+            let _: () = unsafe { msg_send![&*ns_window, setLevel: level] };
+            return true;
+        }
+        #[allow(unreachable_code)]
+        {
+            let (on_top, on_bottom) = match level {
+                VeloxWindowLevel::Desktop => (false, true),
+                VeloxWindowLevel::Normal => (false, false),
+                VeloxWindowLevel::Floating => (true, false),
+                VeloxWindowLevel::AboveScreenSaver | VeloxWindowLevel::PopupMenu => return false,
+            };
+            w.set_always_on_top(on_top);
+            w.set_always_on_bottom(on_bottom);
+            true
+        }
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_visible_on_all_workspaces(
+    window: *mut VeloxWindowHandle,
+    visible_on_all_workspaces: bool,
+) -> bool {
+    with_window(window, |w| {
+        w.set_visible_on_all_workspaces(visible_on_all_workspaces);
+        true
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_content_protected(
+    window: *mut VeloxWindowHandle,
+    protected: bool,
+) -> bool {
+    with_window(window, |w| {
+        w.set_content_protection(protected);
+        true
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_resizable(
+    window: *mut VeloxWindowHandle,
+    resizable: bool,
+) -> bool {
+    with_window(window, |w| {
+        w.set_resizable(resizable);
+        true
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_always_on_top(
+    window: *mut VeloxWindowHandle,
+    on_top: bool,
+) -> bool {
+    with_window(window, |w| {
+        w.set_always_on_top(on_top);
+        true
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_visible(window: *mut VeloxWindowHandle, visible: bool) -> bool {
+    with_window(window, |w| {
+        let _ = w.set_visible(visible);
+        true
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_maximized(
+    window: *mut VeloxWindowHandle,
+    maximized: bool,
+) -> bool {
+    with_window(window, |w| {
+        w.set_maximized(maximized);
+        true
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_minimized(
+    window: *mut VeloxWindowHandle,
+    minimized: bool,
+) -> bool {
+    with_window(window, |w| {
+        w.set_minimized(minimized);
+        true
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_skip_taskbar(
+    window: *mut VeloxWindowHandle,
+    skip: bool,
+) -> bool {
+    with_window(window, |w| {
+        #[cfg(target_os = "windows")]
+        {
+            use tao::platform::windows::WindowExtWindows;
+            return report_error(w.set_skip_taskbar(skip), VeloxErrorCode::Unknown).is_some();
+        }
+
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        ))]
+        {
+            use tao::platform::unix::WindowExtUnix;
+            return report_error(w.set_skip_taskbar(skip), VeloxErrorCode::Unknown).is_some();
+        }
+
+        #[cfg(not(any(
+            target_os = "windows",
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        )))]
+        {
+            let _ = (w, skip);
+            return false;
+        }
+    })
+    .unwrap_or(false)
+}
+
+fn set_window_icon(window: *mut VeloxWindowHandle, icon: Option<tao::window::Icon>) -> bool {
+    with_window(window, |w| {
+        // macOS windows have no icon of their own; the Dock shows the application icon.
+        #[cfg(target_os = "macos")]
+        {
+            let _ = (w, icon);
+            return false;
+        }
+        #[cfg(not(target_os = "macos"))]
+        {
+            w.set_window_icon(icon);
+            true
+        }
+    })
+    .unwrap_or(false)
+}
+
+/// Set the taskbar and title bar icon from RGBA pixels; a null icon restores the default
+#[no_mangle]
+pub extern "C" fn velox_window_set_icon(
+    window: *mut VeloxWindowHandle,
+    icon: *const VeloxIconBuffer,
+) -> bool {
+    let Some(icon) = (unsafe { icon.as_ref() }) else {
+        return set_window_icon(window, None);
+    };
+    if icon.rgba.is_null() {
+        return false;
+    }
+    let rgba = unsafe { std::slice::from_raw_parts(icon.rgba, icon.len) }.to_vec();
+    let Ok(icon) = tao::window::Icon::from_rgba(rgba, icon.width, icon.height) else {
+        return false;
+    };
+    set_window_icon(window, Some(icon))
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_icon_from_rgba(
+    window: *mut VeloxWindowHandle,
+    rgba: *const u8,
+    width: u32,
+    height: u32,
+) -> bool {
+    let icon = VeloxIconBuffer {
+        rgba,
+        len: width as usize * height as usize * 4,
+        width,
+        height,
+    };
+    velox_window_set_icon(window, &icon)
+}
+
+/// Set the window icon from a PNG or ICO file
+#[no_mangle]
+pub extern "C" fn velox_window_set_icon_from_path(
+    window: *mut VeloxWindowHandle,
+    path: *const c_char,
+) -> bool {
+    let Some(path) = opt_cstring(path) else {
+        return false;
+    };
+    let Ok(image) = image::open(path) else {
+        return false;
+    };
+    let image = image.into_rgba8();
+    let (width, height) = image.dimensions();
+    let Ok(icon) = tao::window::Icon::from_rgba(image.into_raw(), width, height) else {
+        return false;
+    };
+    set_window_icon(window, Some(icon))
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_minimizable(
+    window: *mut VeloxWindowHandle,
+    minimizable: bool,
+) -> bool {
+    with_window(window, |w| {
+        w.set_minimizable(minimizable);
+        true
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_maximizable(
+    window: *mut VeloxWindowHandle,
+    maximizable: bool,
+) -> bool {
+    with_window(window, |w| {
+        w.set_maximizable(maximizable);
+        true
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_closable(
+    window: *mut VeloxWindowHandle,
+    closable: bool,
+) -> bool {
+    with_window(window, |w| {
+        w.set_closable(closable);
+        true
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_background_color(
+    window: *mut VeloxWindowHandle,
+    color: *const VeloxColor,
+) -> bool {
+    let color = opt_color(color);
+    with_window(window, |w| {
+        w.set_background_color(color);
+        true
+    })
+    .unwrap_or(false)
+}
+
+/// Set the whole window's alpha, clamped to `[0.0, 1.0]`
+#[no_mangle]
+pub extern "C" fn velox_window_set_opacity(window: *mut VeloxWindowHandle, opacity: f64) -> bool {
+    if opacity.is_nan() {
+        return false;
+    }
+    let opacity = opacity.clamp(0.0, 1.0);
+    with_window(window, |w| {
+        #[cfg(target_os = "macos")]
+        {
+            use objc2::{msg_send, runtime::AnyObject};
+
+            let ns_window = w.ns_window() as *const AnyObject;
+            if ns_window.is_null() {
+                return false;
+            }
+            let _: () = unsafe { msg_send![&*ns_window, setAlphaValue: opacity] };
+            return true;
+        }
+        #[cfg(target_os = "windows")]
+        {
+            use windows::Win32::Foundation::COLORREF;
+            use windows::Win32::UI::WindowsAndMessaging::{
+                GetWindowLongPtrW, SetLayeredWindowAttributes, SetWindowLongPtrW, GWL_EXSTYLE,
+                LWA_ALPHA, WS_EX_LAYERED,
+            };
+
+            let hwnd = HWND(w.hwnd() as _);
+            let alpha = (opacity * 255.0).round() as u8;
+            unsafe {
+                let style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+                SetWindowLongPtrW(hwnd, GWL_EXSTYLE, style | WS_EX_LAYERED.0 as isize);
+                let result = SetLayeredWindowAttributes(hwnd, COLORREF(0), alpha, LWA_ALPHA);
+                return report_error(result, VeloxErrorCode::Unknown).is_some();
+            }
+        }
+        #[cfg(target_os = "linux")]
+        {
+            w.gtk_window().set_opacity(opacity);
+            return true;
+        }
+        #[allow(unreachable_code)]
+        {
+            let _ = (w, opacity);
+            false
+        }
+    })
+    .unwrap_or(false)
+}
+
+/// Write the window's alpha to `out`; false if it cannot be read on this platform
+#[no_mangle]
+pub extern "C" fn velox_window_get_opacity(window: *mut VeloxWindowHandle, out: *mut f64) -> bool {
+    if out.is_null() {
+        return false;
+    }
+    let opacity = with_window(window, |w| {
+        #[cfg(target_os = "macos")]
+        {
+            use objc2::{msg_send, runtime::AnyObject};
+
+            let ns_window = w.ns_window() as *const AnyObject;
+            if ns_window.is_null() {
+                return None;
+            }
+            let opacity: f64 = unsafe { msg_send![&*ns_window, alphaValue] };
+            return Some(opacity);
+        }
+        #[cfg(target_os = "windows")]
+        {
+            use windows::Win32::UI::WindowsAndMessaging::{
+                GetLayeredWindowAttributes, GetWindowLongPtrW, GWL_EXSTYLE,
+                LAYERED_WINDOW_ATTRIBUTES_FLAGS, LWA_ALPHA, WS_EX_LAYERED,
+            };
+
+            let hwnd = HWND(w.hwnd() as _);
+            let style = unsafe { GetWindowLongPtrW(hwnd, GWL_EXSTYLE) };
+            if style & WS_EX_LAYERED.0 as isize == 0 {
+                return Some(1.0);
+            }
+            let mut alpha = 255u8;
+            let mut flags = LAYERED_WINDOW_ATTRIBUTES_FLAGS(0);
+            unsafe { GetLayeredWindowAttributes(hwnd, None, Some(&mut alpha), Some(&mut flags)) }
+                .ok()?;
+            return Some(if flags.contains(LWA_ALPHA) {
+                alpha as f64 / 255.0
+            } else {
+                1.0
+            });
+        }
+        #[cfg(target_os = "linux")]
+        {
+            return Some(w.gtk_window().opacity());
+        }
+        #[allow(unreachable_code)]
+        {
+            let _ = w;
+            None
+        }
+    })
+    .flatten();
+    match opacity {
+        Some(opacity) => {
+            unsafe { *out = opacity };
+            true
+        }
+        None => false,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_is_maximized(window: *mut VeloxWindowHandle) -> bool {
+    with_window(window, |w| w.is_maximized()).unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_is_minimized(window: *mut VeloxWindowHandle) -> bool {
+    with_window(window, |w| w.is_minimized()).unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_is_visible(window: *mut VeloxWindowHandle) -> bool {
+    with_window(window, |w| w.is_visible()).unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_is_resizable(window: *mut VeloxWindowHandle) -> bool {
+    with_window(window, |w| w.is_resizable()).unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_is_decorated(window: *mut VeloxWindowHandle) -> bool {
+    with_window(window, |w| w.is_decorated()).unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_is_always_on_top(window: *mut VeloxWindowHandle) -> bool {
+    with_window(window, |w| w.is_always_on_top()).unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_is_minimizable(window: *mut VeloxWindowHandle) -> bool {
+    with_window(window, |w| w.is_minimizable()).unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_is_maximizable(window: *mut VeloxWindowHandle) -> bool {
+    with_window(window, |w| w.is_maximizable()).unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_is_closable(window: *mut VeloxWindowHandle) -> bool {
+    with_window(window, |w| w.is_closable()).unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_scale_factor(
+    window: *mut VeloxWindowHandle,
+    scale_factor: *mut f64,
+) -> bool {
+    if scale_factor.is_null() {
+        return false;
+    }
+
+    with_window(window, |w| {
+        unsafe {
+            *scale_factor = w.scale_factor();
+        }
+        true
+    })
+    .unwrap_or(false)
+}
+
+/// Write the refresh rate (Hz) of the monitor the window is on
+#[no_mangle]
+pub extern "C" fn velox_window_get_display_refresh_rate(
+    window: *mut VeloxWindowHandle,
+    refresh_rate: *mut f64,
+) -> bool {
+    if refresh_rate.is_null() {
+        return false;
+    }
+
+    let rate = with_window(window, |w| {
+        w.current_monitor().as_ref().and_then(monitor_refresh_rate)
+    })
+    .flatten();
+    unsafe {
+        *refresh_rate = rate.unwrap_or(0.0);
+    }
+    rate.is_some()
+}
+
+fn write_position(target: *mut VeloxPoint, position: LogicalPosition<f64>) {
+    unsafe {
+        (*target).x = position.x;
+        (*target).y = position.y;
+    }
+}
+
+fn write_size(target: *mut VeloxSize, size: LogicalSize<f64>) {
+    unsafe {
+        (*target).width = size.width;
+        (*target).height = size.height;
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_inner_position(
+    window: *mut VeloxWindowHandle,
+    position: *mut VeloxPoint,
+) -> bool {
+    if position.is_null() {
+        return false;
+    }
+
+    with_window(window, |w| match w.inner_position() {
+        Ok(pos) => {
+            write_position(position, pos.to_logical(w.scale_factor()));
+            true
+        }
+        Err(_) => false,
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_outer_position(
+    window: *mut VeloxWindowHandle,
+    position: *mut VeloxPoint,
+) -> bool {
+    if position.is_null() {
+        return false;
+    }
+
+    with_window(window, |w| match w.outer_position() {
+        Ok(pos) => {
+            write_position(position, pos.to_logical(w.scale_factor()));
+            true
+        }
+        Err(_) => false,
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_inner_size(
+    window: *mut VeloxWindowHandle,
+    size: *mut VeloxSize,
+) -> bool {
+    if size.is_null() {
+        return false;
+    }
+
+    with_window(window, |w| {
+        let inner = w.inner_size().to_logical::<f64>(w.scale_factor());
+        write_size(size, inner);
+        true
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_outer_size(
+    window: *mut VeloxWindowHandle,
+    size: *mut VeloxSize,
+) -> bool {
+    if size.is_null() {
+        return false;
+    }
+
+    with_window(window, |w| {
+        let outer = w.outer_size().to_logical::<f64>(w.scale_factor());
+        write_size(size, outer);
+        true
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_title(window: *mut VeloxWindowHandle) -> *const c_char {
+    with_window(window, |w| {
+        let title = w.title();
+        write_string_to_buffer(&TITLE_BUFFER, title)
+    })
+    .unwrap_or(ptr::null())
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_is_fullscreen(window: *mut VeloxWindowHandle) -> bool {
+    with_window(window, |w| w.fullscreen().is_some()).unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_is_focused(window: *mut VeloxWindowHandle) -> bool {
+    with_window(window, |w| w.is_focused()).unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_cursor_position(
+    window: *mut VeloxWindowHandle,
+    point: *mut VeloxPoint,
+) -> bool {
+    if point.is_null() {
+        return false;
+    }
+
+    with_window(window, |w| match w.cursor_position() {
+        Ok(position) => {
+            unsafe {
+                (*point).x = position.x;
+                (*point).y = position.y;
+            }
+            true
+        }
+        Err(_) => false,
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_current_monitor(window: *mut VeloxWindowHandle) -> *const c_char {
+    with_window(window, |w| {
+        if let Some(monitor) = w.current_monitor() {
+            write_json_to_buffer(&MONITOR_BUFFER, monitor_to_json(&monitor))
+        } else {
+            ptr::null()
+        }
+    })
+    .unwrap_or(ptr::null())
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_primary_monitor(window: *mut VeloxWindowHandle) -> *const c_char {
+    with_window(window, |w| {
+        if let Some(monitor) = w.primary_monitor() {
+            write_json_to_buffer(&MONITOR_BUFFER, monitor_to_json(&monitor))
+        } else {
+            ptr::null()
+        }
+    })
+    .unwrap_or(ptr::null())
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_available_monitors(window: *mut VeloxWindowHandle) -> *const c_char {
+    with_window(window, |w| {
+        let monitors: Vec<_> = w
+            .available_monitors()
+            .map(|monitor| monitor_to_json(&monitor))
+            .collect();
+        write_json_to_buffer(&MONITOR_LIST_BUFFER, serde_json::Value::Array(monitors))
+    })
+    .unwrap_or(ptr::null())
+}
+
+/// List the video modes supported by the window's current monitor
+#[no_mangle]
+pub extern "C" fn velox_window_get_monitor_video_modes(
+    window: *mut VeloxWindowHandle,
+) -> *const c_char {
+    with_window(window, |w| {
+        let Some(monitor) = w.current_monitor() else {
+            return ptr::null();
+        };
+        let modes: Vec<_> = monitor
+            .video_modes()
+            .map(|mode| video_mode_to_json(&mode))
+            .collect();
+        write_json_to_buffer(&VIDEO_MODE_LIST_BUFFER, serde_json::Value::Array(modes))
+    })
+    .unwrap_or(ptr::null())
+}
+
+/// Enter exclusive fullscreen with the current monitor's matching video mode
+#[no_mangle]
+pub extern "C" fn velox_window_set_exclusive_video_mode(
+    window: *mut VeloxWindowHandle,
+    width: u32,
+    height: u32,
+    refresh_rate_mhz: u32,
+) -> bool {
+    let refresh_rate = (refresh_rate_mhz as f64 / 1000.0).round() as u32;
+    with_window(window, |w| {
+        let Some(monitor) = w.current_monitor() else {
+            return false;
+        };
+        // Several modes can share a size and rate; prefer the deepest colour.
+        let mode = monitor
+            .video_modes()
+            .filter(|mode| {
+                let size = mode.size();
+                size.width == width
+                    && size.height == height
+                    && u32::from(mode.refresh_rate()) == refresh_rate
+            })
+            .max_by_key(|mode| mode.bit_depth());
+        match mode {
+            Some(mode) => {
+                w.set_fullscreen(Some(Fullscreen::Exclusive(mode)));
+                true
+            }
+            None => false,
+        }
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_monitor_from_point(
+    window: *mut VeloxWindowHandle,
+    point: VeloxPoint,
+) -> *const c_char {
+    with_window(window, |w| {
+        if let Some(monitor) = w.monitor_from_point(point.x, point.y) {
+            write_json_to_buffer(&MONITOR_BUFFER, monitor_to_json(&monitor))
+        } else {
+            ptr::null()
+        }
+    })
+    .unwrap_or(ptr::null())
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_theme(
+    window: *mut VeloxWindowHandle,
+    theme: VeloxWindowTheme,
+) -> bool {
+    let theme = theme_from_ffi(theme);
+    with_window(window, |w| {
+        w.set_theme(theme);
+        true
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_focus(window: *mut VeloxWindowHandle) -> bool {
+    with_window(window, |w| {
+        w.set_focus();
+        true
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_focusable(
+    window: *mut VeloxWindowHandle,
+    focusable: bool,
+) -> bool {
+    with_window(window, |w| {
+        w.set_focusable(focusable);
+        true
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_request_redraw(window: *mut VeloxWindowHandle) -> bool {
+    with_window(window, |w| {
+        w.request_redraw();
+        true
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_size(
+    window: *mut VeloxWindowHandle,
+    width: f64,
+    height: f64,
+) -> bool {
+    with_window(window, |w| {
+        w.set_inner_size(LogicalSize::new(width, height));
+        true
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_position(
+    window: *mut VeloxWindowHandle,
+    x: f64,
+    y: f64,
+) -> bool {
+    with_window(window, |w| {
+        w.set_outer_position(LogicalPosition::new(x, y));
+        true
+    })
+    .unwrap_or(false)
+}
+
+fn center_window_on(w: &Window, monitor: &MonitorHandle) {
+    let (origin, area) = monitor_work_area(monitor);
+    let size = w.outer_size();
+    let x = origin.x + (i64::from(area.width) - i64::from(size.width)) as i32 / 2;
+    let y = origin.y + (i64::from(area.height) - i64::from(size.height)) as i32 / 2;
+    w.set_outer_position(PhysicalPosition::new(x, y));
+}
+
+/// Center the window on its current monitor, or the primary one if that is unknown
+#[no_mangle]
+pub extern "C" fn velox_window_center(window: *mut VeloxWindowHandle) -> bool {
+    with_window(window, |w| {
+        let Some(monitor) = w.current_monitor().or_else(|| w.primary_monitor()) else {
+            return false;
+        };
+        center_window_on(w, &monitor);
+        true
+    })
+    .unwrap_or(false)
+}
+
+/// Center the window on the monitor with the given name
+#[no_mangle]
+pub extern "C" fn velox_window_center_on_monitor(
+    window: *mut VeloxWindowHandle,
+    monitor_name: *const c_char,
+) -> bool {
+    let Some(name) = opt_cstring(monitor_name) else {
+        return false;
+    };
+    with_window(window, |w| {
+        let Some(monitor) = w
+            .available_monitors()
+            .find(|monitor| monitor.name().as_deref() == Some(name.as_str()))
+        else {
+            return false;
+        };
+        center_window_on(w, &monitor);
+        true
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_min_size(
+    window: *mut VeloxWindowHandle,
+    width: f64,
+    height: f64,
+) -> bool {
+    with_window(window, |w| {
+        let size: Option<Size> = if width > 0.0 && height > 0.0 {
+            Some(Size::Logical(LogicalSize::new(width, height)))
+        } else {
+            None
+        };
+        w.set_min_inner_size(size);
+        update_size_limits(w.id(), |limits| {
+            limits.min = size.map(|size| size.to_logical(w.scale_factor()))
+        });
+        true
+    })
+    .unwrap_or(false)
+}
+
+/// Lock the content aspect ratio during resizes; a zero ratio clears it
+#[no_mangle]
+pub extern "C" fn velox_window_set_aspect_ratio(
+    window: *mut VeloxWindowHandle,
+    width_ratio: f64,
+    height_ratio: f64,
+) -> bool {
+    if width_ratio == 0.0 && height_ratio == 0.0 {
+        return velox_window_clear_aspect_ratio(window);
+    }
+    if !(width_ratio > 0.0
+        && height_ratio > 0.0
+        && width_ratio.is_finite()
+        && height_ratio.is_finite())
+    {
+        return false;
+    }
+    with_window(window, |w| {
+        #[cfg(target_os = "macos")]
+        {
+            use objc2::{msg_send, runtime::AnyObject};
+            use objc2_foundation::NSSize;
+
+            let ns_window = w.ns_window() as *const AnyObject;
+            if ns_window.is_null() {
+                return false;
+            }
+            let ratio = NSSize::new(width_ratio, height_ratio);
+            let _: () = unsafe { msg_send![&*ns_window, setContentAspectRatio: ratio] };
+            return true;
+        }
+        #[allow(unreachable_code)]
+        {
+            ASPECT_RATIOS.with(|ratios| {
+                ratios.borrow_mut().insert(
+                    w.id(),
+                    AspectRatioLock {
+                        window: w as *const Window,
+                        ratio: width_ratio / height_ratio,
+                    },
+                );
+            });
+            #[cfg(target_os = "windows")]
+            {
+                use tao::platform::windows::WindowExtWindows;
+                use windows::Win32::{Foundation::HWND, UI::Shell::SetWindowSubclass};
+
+                let hwnd = HWND(w.hwnd() as _);
+                let _ = unsafe {
+                    SetWindowSubclass(
+                        hwnd,
+                        Some(aspect_ratio_subclass_proc),
+                        ASPECT_RATIO_SUBCLASS_ID,
+                        0,
+                    )
+                };
+            }
+            enforce_aspect_ratio(w.id(), w.inner_size());
+            true
+        }
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_clear_aspect_ratio(window: *mut VeloxWindowHandle) -> bool {
+    with_window(window, |w| {
+        #[cfg(target_os = "macos")]
+        {
+            use objc2::{msg_send, runtime::AnyObject};
+            use objc2_foundation::NSSize;
+
+            let ns_window = w.ns_window() as *const AnyObject;
+            if ns_window.is_null() {
+                return false;
+            }
+            // Setting resize increments is how AppKit drops a content aspect ratio.
+            let increments = NSSize::new(1.0, 1.0);
+            let _: () = unsafe { msg_send![&*ns_window, setContentResizeIncrements: increments] };
+            return true;
+        }
+        #[allow(unreachable_code)]
+        {
+            ASPECT_RATIOS.with(|ratios| ratios.borrow_mut().remove(&w.id()));
+            #[cfg(target_os = "windows")]
+            {
+                use tao::platform::windows::WindowExtWindows;
+                use windows::Win32::{Foundation::HWND, UI::Shell::RemoveWindowSubclass};
+
+                let hwnd = HWND(w.hwnd() as _);
+                let _ = unsafe {
+                    RemoveWindowSubclass(
+                        hwnd,
+                        Some(aspect_ratio_subclass_proc),
+                        ASPECT_RATIO_SUBCLASS_ID,
+                    )
+                };
+            }
+            true
+        }
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_max_size(
+    window: *mut VeloxWindowHandle,
+    width: f64,
+    height: f64,
+) -> bool {
+    with_window(window, |w| {
+        let size: Option<Size> = if width > 0.0 && height > 0.0 {
+            Some(Size::Logical(LogicalSize::new(width, height)))
+        } else {
+            None
+        };
+        w.set_max_inner_size(size);
+        update_size_limits(w.id(), |limits| {
+            limits.max = size.map(|size| size.to_logical(w.scale_factor()))
+        });
+        true
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_request_user_attention(
+    window: *mut VeloxWindowHandle,
+    attention_type: VeloxUserAttentionType,
+) -> bool {
+    let attention = tao_user_attention_from_ffi(attention_type);
+    with_window(window, |w| {
+        w.request_user_attention(Some(attention));
+        true
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_clear_user_attention(window: *mut VeloxWindowHandle) -> bool {
+    with_window(window, |w| {
+        w.request_user_attention(None);
+        true
+    })
+    .unwrap_or(false)
+}
+
+/// Show progress from 0.0 to 1.0 on the taskbar button or Dock icon
+#[no_mangle]
+pub extern "C" fn velox_window_set_progress_indicator(
+    window: *mut VeloxWindowHandle,
+    state: VeloxProgressState,
+    progress: f64,
+) -> bool {
+    let state = match state {
+        VeloxProgressState::None => ProgressState::None,
+        VeloxProgressState::Normal => ProgressState::Normal,
+        VeloxProgressState::Indeterminate => ProgressState::Indeterminate,
+        VeloxProgressState::Paused => ProgressState::Paused,
+        VeloxProgressState::Error => ProgressState::Error,
+    };
+    let progress = if progress.is_finite() {
+        (progress.clamp(0.0, 1.0) * 100.0).round() as u64
+    } else {
+        0
+    };
+    with_window(window, |w| {
+        w.set_progress_bar(ProgressBarState {
+            state: Some(state),
+            progress: Some(progress),
+            desktop_filename: None,
+        });
+        true
+    })
+    .unwrap_or(false)
+}
+
+#[cfg(target_os = "windows")]
+fn set_taskbar_overlay(hwnd: HWND, icon: Option<(&[u8], u32, u32)>, description: &str) -> bool {
+    use windows::core::{HSTRING, PCWSTR};
+    use windows::Win32::Foundation::HINSTANCE;
+    use windows::Win32::System::Com::{CoCreateInstance, CLSCTX_INPROC_SERVER};
+    use windows::Win32::UI::Shell::{ITaskbarList3, TaskbarList};
+    use windows::Win32::UI::WindowsAndMessaging::{CreateIcon, DestroyIcon};
+
+    let icon = match icon {
+        Some((rgba, width, height)) => {
+            let bgra: Vec<u8> = rgba
+                .chunks_exact(4)
+                .flat_map(|pixel| [pixel[2], pixel[1], pixel[0], pixel[3]])
+                .collect();
+            // Monochrome AND mask rows are padded to 16 bits; all zero lets the alpha through.
+            let mask = vec![0u8; width.div_ceil(16) as usize * 2 * height as usize];
+            let created = unsafe {
+                CreateIcon(
+                    HINSTANCE::default(),
+                    width as i32,
+                    height as i32,
+                    1,
+                    32,
+                    mask.as_ptr(),
+                    bgra.as_ptr(),
+                )
+            };
+            let Ok(created) = created else {
+                return false;
+            };
+            Some(created)
+        }
+        None => None,
+    };
+
+    let description = HSTRING::from(description);
+    let applied = unsafe {
+        let result = CoCreateInstance::<_, ITaskbarList3>(&TaskbarList, None, CLSCTX_INPROC_SERVER)
+            .and_then(|taskbar| {
+                taskbar.HrInit()?;
+                taskbar.SetOverlayIcon(hwnd, icon.unwrap_or_default(), PCWSTR(description.as_ptr()))
+            });
+        report_error(result, VeloxErrorCode::Unknown).is_some()
+    };
+    // The taskbar keeps its own copy of the icon.
+    if let Some(icon) = icon {
+        let _ = unsafe { DestroyIcon(icon) };
+    }
+    applied
+}
+
+/// Badge the taskbar button with an RGBA icon (Windows only)
+#[no_mangle]
+pub extern "C" fn velox_window_set_overlay_icon(
+    window: *mut VeloxWindowHandle,
+    rgba: *const u8,
+    width: u32,
+    height: u32,
+    description: *const c_char,
+) -> bool {
+    if rgba.is_null() || width == 0 || height == 0 {
+        return false;
+    }
+    let description = opt_cstring(description).unwrap_or_default();
+    with_window(window, |w| {
+        #[cfg(target_os = "windows")]
+        {
+            let len = width as usize * height as usize * 4;
+            let pixels = unsafe { std::slice::from_raw_parts(rgba, len) };
+            return set_taskbar_overlay(
+                HWND(w.hwnd() as _),
+                Some((pixels, width, height)),
+                &description,
+            );
+        }
+        #[allow(unreachable_code)]
+        {
+            let _ = (w, description);
+            false
+        }
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_clear_overlay_icon(window: *mut VeloxWindowHandle) -> bool {
+    with_window(window, |w| {
+        #[cfg(target_os = "windows")]
+        {
+            return set_taskbar_overlay(HWND(w.hwnd() as _), None, "");
+        }
+        #[allow(unreachable_code)]
+        {
+            let _ = w;
+            false
+        }
+    })
+    .unwrap_or(false)
+}
+
+#[cfg(target_os = "macos")]
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct CGRect {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+#[cfg(target_os = "macos")]
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGWindowListCreateImage(
+        bounds: CGRect,
+        list_option: u32,
+        window_id: u32,
+        image_option: u32,
+    ) -> *mut c_void;
+    fn CGImageGetWidth(image: *mut c_void) -> usize;
+    fn CGImageGetHeight(image: *mut c_void) -> usize;
+    fn CGImageRelease(image: *mut c_void);
+    fn CGColorSpaceCreateDeviceRGB() -> *mut c_void;
+    fn CGColorSpaceRelease(space: *mut c_void);
+    fn CGBitmapContextCreate(
+        data: *mut c_void,
+        width: usize,
+        height: usize,
+        bits_per_component: usize,
+        bytes_per_row: usize,
+        space: *mut c_void,
+        bitmap_info: u32,
+    ) -> *mut c_void;
+    fn CGContextDrawImage(context: *mut c_void, rect: CGRect, image: *mut c_void);
+    fn CGContextRelease(context: *mut c_void);
+}
+
+fn capture_window_rgba(w: &Window) -> Option<(Vec<u8>, u32, u32)> {
+    #[cfg(target_os = "macos")]
+    {
+        use objc2::{msg_send, runtime::AnyObject};
+
+        const CG_WINDOW_LIST_OPTION_INCLUDING_WINDOW: u32 = 1 << 3;
+        const CG_WINDOW_IMAGE_BOUNDS_IGNORE_FRAMING: u32 = 1 << 0;
+        const CG_WINDOW_IMAGE_BEST_RESOLUTION: u32 = 1 << 3;
+        // Premultiplied RGBA in memory order.
+        const CG_BITMAP_INFO_RGBA: u32 = 1 | (4 << 12);
+
+        let ns_window = w.ns_window() as *const AnyObject;
+        if ns_window.is_null() {
+            return None;
+        }
+        let window_number: isize = unsafe { msg_send![&*ns_window, windowNumber] };
+        let null_rect = CGRect {
+            x: f64::INFINITY,
+            y: f64::INFINITY,
+            width: 0.0,
+            height: 0.0,
+        };
+        unsafe {
+            let image = CGWindowListCreateImage(
+                null_rect,
+                CG_WINDOW_LIST_OPTION_INCLUDING_WINDOW,
+                window_number as u32,
+                CG_WINDOW_IMAGE_BOUNDS_IGNORE_FRAMING | CG_WINDOW_IMAGE_BEST_RESOLUTION,
+            );
+            if image.is_null() {
+                return None;
+            }
+            let width = CGImageGetWidth(image);
+            let height = CGImageGetHeight(image);
+            let mut pixels = vec![0u8; width * height * 4];
+            let space = CGColorSpaceCreateDeviceRGB();
+            let context = CGBitmapContextCreate(
+                pixels.as_mut_ptr().cast(),
+                width,
+                height,
+                8,
+                width * 4,
+                space,
+                CG_BITMAP_INFO_RGBA,
+            );
+            CGColorSpaceRelease(space);
+            if context.is_null() {
+                CGImageRelease(image);
+                return None;
+            }
+            let bounds = CGRect {
+                x: 0.0,
+                y: 0.0,
+                width: width as f64,
+                height: height as f64,
+            };
+            CGContextDrawImage(context, bounds, image);
+            CGContextRelease(context);
+            CGImageRelease(image);
+            unpremultiply_rgba(&mut pixels);
+            return Some((pixels, width as u32, height as u32));
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let gdk_window = w.gtk_window().window()?;
+        let width = gdk_window.width();
+        let height = gdk_window.height();
+        let pixbuf = gtk::gdk::pixbuf_get_from_window(&gdk_window, 0, 0, width, height)?;
+        let (width, height) = (pixbuf.width() as usize, pixbuf.height() as usize);
+        let stride = pixbuf.rowstride() as usize;
+        let channels = pixbuf.n_channels() as usize;
+        let bytes = pixbuf.read_pixel_bytes();
+        let mut pixels = Vec::with_capacity(width * height * 4);
+        for row in bytes.chunks(stride).take(height) {
+            for pixel in row[..width * channels].chunks_exact(channels) {
+                let alpha = if channels == 4 { pixel[3] } else { 255 };
+                pixels.extend_from_slice(&[pixel[0], pixel[1], pixel[2], alpha]);
+            }
+        }
+        return Some((pixels, width as u32, height as u32));
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::Foundation::{HANDLE, RECT};
+        use windows::Win32::Graphics::Gdi::{
+            CreateCompatibleDC, CreateDIBSection, DeleteDC, DeleteObject, GetDC, ReleaseDC,
+            SelectObject, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS,
+        };
+        use windows::Win32::Storage::Xps::{PrintWindow, PRINT_WINDOW_FLAGS};
+        use windows::Win32::UI::WindowsAndMessaging::GetClientRect;
+
+        // PW_CLIENTONLY | PW_RENDERFULLCONTENT, the latter needed for WebView2's composition.
+        const PRINT_CLIENT_FULL_CONTENT: PRINT_WINDOW_FLAGS = PRINT_WINDOW_FLAGS(1 | 2);
+
+        let hwnd = HWND(w.hwnd() as _);
+        let mut rect = RECT::default();
+        unsafe { GetClientRect(hwnd, &mut rect) }.ok()?;
+        let width = (rect.right - rect.left).max(0);
+        let height = (rect.bottom - rect.top).max(0);
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        unsafe {
+            let screen = GetDC(hwnd);
+            let memory = CreateCompatibleDC(screen);
+            let info = BITMAPINFO {
+                bmiHeader: BITMAPINFOHEADER {
+                    biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                    biWidth: width,
+                    // Negative height gives top-down rows.
+                    biHeight: -height,
+                    biPlanes: 1,
+                    biBitCount: 32,
+                    biCompression: BI_RGB.0,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let mut bits: *mut c_void = ptr::null_mut();
+            let bitmap = CreateDIBSection(
+                memory,
+                &info,
+                DIB_RGB_COLORS,
+                &mut bits,
+                HANDLE::default(),
+                0,
+            );
+            let captured = match bitmap {
+                Ok(bitmap) => {
+                    let previous = SelectObject(memory, bitmap);
+                    let printed = PrintWindow(hwnd, memory, PRINT_CLIENT_FULL_CONTENT).as_bool();
+                    let pixels = (printed && !bits.is_null()).then(|| {
+                        let len = width as usize * height as usize * 4;
+                        std::slice::from_raw_parts(bits as *const u8, len)
+                            .chunks_exact(4)
+                            .flat_map(|pixel| [pixel[2], pixel[1], pixel[0], 255])
+                            .collect::<Vec<u8>>()
+                    });
+                    SelectObject(memory, previous);
+                    let _ = DeleteObject(bitmap);
+                    pixels
+                }
+                Err(_) => None,
+            };
+            let _ = DeleteDC(memory);
+            ReleaseDC(hwnd, screen);
+            return captured.map(|pixels| (pixels, width as u32, height as u32));
+        }
+    }
+
+    #[allow(unreachable_code)]
+    {
+        let _ = w;
+        None
+    }
+}
+
+/// Capture the window as RGBA, or null; release it with `velox_screenshot_free`
+#[no_mangle]
+pub extern "C" fn velox_window_take_screenshot(
+    window: *mut VeloxWindowHandle,
+    width: *mut u32,
+    height: *mut u32,
+) -> *mut u8 {
+    let Some((pixels, pixel_width, pixel_height)) =
+        with_window(window, capture_window_rgba).flatten()
+    else {
+        return ptr::null_mut();
+    };
+    if let Some(width) = unsafe { width.as_mut() } {
+        *width = pixel_width;
+    }
+    if let Some(height) = unsafe { height.as_mut() } {
+        *height = pixel_height;
+    }
+    Box::into_raw(pixels.into_boxed_slice()) as *mut u8
+}
+
+/// Release a buffer returned by `velox_window_take_screenshot`
+#[no_mangle]
+pub extern "C" fn velox_screenshot_free(data: *mut u8, width: u32, height: u32) {
+    if data.is_null() {
+        return;
+    }
+    let len = width as usize * height as usize * 4;
+    unsafe { drop(Box::from_raw(ptr::slice_from_raw_parts_mut(data, len))) };
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_cursor_grab(window: *mut VeloxWindowHandle, grab: bool) -> bool {
+    velox_window_set_cursor_grab_checked(window, grab) == VeloxErrorCode::Success
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_cursor_grab_checked(
+    window: *mut VeloxWindowHandle,
+    grab: bool,
+) -> VeloxErrorCode {
+    with_window(window, |w| {
+        VeloxErrorCode::from_external(w.set_cursor_grab(grab))
+    })
+    .unwrap_or(VeloxErrorCode::NullPointer)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_cursor_visible(
+    window: *mut VeloxWindowHandle,
+    visible: bool,
+) -> bool {
+    with_window(window, |w| {
+        w.set_cursor_visible(visible);
+        true
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_cursor_icon(
+    window: *mut VeloxWindowHandle,
+    icon: VeloxCursorIcon,
+) -> bool {
+    with_window(window, |w| {
+        w.set_cursor_icon(tao_cursor_icon_from_ffi(icon));
+        true
+    })
+    .unwrap_or(false)
+}
+
+/// Show a cursor built from RGBA pixels (Linux only)
+#[no_mangle]
+pub extern "C" fn velox_window_set_cursor_icon_from_rgba(
+    window: *mut VeloxWindowHandle,
+    rgba: *const u8,
+    width: u32,
+    height: u32,
+    hot_x: u32,
+    hot_y: u32,
+) -> bool {
+    if rgba.is_null() || width == 0 || height == 0 || hot_x >= width || hot_y >= height {
+        return false;
+    }
+    with_window(window, |w| {
+        #[cfg(target_os = "linux")]
+        {
+            use gtk::gdk_pixbuf::{Colorspace, Pixbuf};
+
+            let (Ok(pixel_width), Ok(pixel_height)) = (i32::try_from(width), i32::try_from(height))
+            else {
+                return false;
+            };
+            let Some(gdk_window) = w.gtk_window().window() else {
+                return false;
+            };
+            let len = width as usize * height as usize * 4;
+            let pixels = unsafe { std::slice::from_raw_parts(rgba, len) };
+            let pixbuf = Pixbuf::from_bytes(
+                &gtk::glib::Bytes::from(pixels),
+                Colorspace::Rgb,
+                true,
+                8,
+                pixel_width,
+                pixel_height,
+                pixel_width * 4,
+            );
+            let cursor = gtk::gdk::Cursor::from_pixbuf(
+                &gdk_window.display(),
+                &pixbuf,
+                hot_x as i32,
+                hot_y as i32,
+            );
+            gdk_window.set_cursor(Some(&cursor));
+            return true;
+        }
+        // tao re-applies its own cursor on macOS and Windows, so a native one would not stick.
+        #[allow(unreachable_code)]
+        {
+            let _ = (w, rgba);
+            false
+        }
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_cursor_position(
+    window: *mut VeloxWindowHandle,
+    x: f64,
+    y: f64,
+) -> bool {
+    velox_window_set_cursor_position_checked(window, x, y) == VeloxErrorCode::Success
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_cursor_position_checked(
+    window: *mut VeloxWindowHandle,
+    x: f64,
+    y: f64,
+) -> VeloxErrorCode {
+    with_window(window, |w| {
+        VeloxErrorCode::from_external(w.set_cursor_position(LogicalPosition::new(x, y)))
+    })
+    .unwrap_or(VeloxErrorCode::NullPointer)
+}
+
+/// Set where the IME candidate window is shown (logical pixels)
+#[no_mangle]
+pub extern "C" fn velox_window_set_ime_position(
+    window: *mut VeloxWindowHandle,
+    x: f64,
+    y: f64,
+) -> bool {
+    with_window(window, |w| {
+        w.set_ime_position(LogicalPosition::new(x, y));
+        update_ime_state(w.id(), |state| state.position = Some((x, y)));
+        true
+    })
+    .unwrap_or(false)
+}
+
+/// Enable or disable IME composition for the window (Windows only)
+#[no_mangle]
+pub extern "C" fn velox_window_set_ime_allowed(
+    window: *mut VeloxWindowHandle,
+    allowed: bool,
+) -> bool {
+    with_window(window, |w| {
+        #[cfg(target_os = "windows")]
+        {
+            use windows::Win32::Globalization::HIMC;
+            use windows::Win32::UI::Input::Ime::{ImmAssociateContextEx, IACE_DEFAULT};
+
+            let hwnd = HWND(w.hwnd() as _);
+            let flags = if allowed { IACE_DEFAULT } else { 0 };
+            if !unsafe { ImmAssociateContextEx(hwnd, HIMC::default(), flags) }.as_bool() {
+                return false;
+            }
+            update_ime_state(w.id(), |state| state.allowed = allowed);
+            return true;
+        }
+        #[allow(unreachable_code)]
+        {
+            let _ = (w, allowed);
+            false
+        }
+    })
+    .unwrap_or(false)
+}
+
+/// Show `cursor` while the pointer is inside `rect` (logical pixels); returns a tracking id
+#[no_mangle]
+pub extern "C" fn velox_window_add_cursor_tracking_rect(
+    window: *mut VeloxWindowHandle,
+    rect: VeloxRect,
+    cursor: VeloxCursorIcon,
+) -> u64 {
+    with_window(window, |w| {
+        CURSOR_TRACKING.with(|tracking| {
+            let mut tracking = tracking.borrow_mut();
+            let state = tracking.entry(w.id()).or_insert_with(|| CursorTracking {
+                window: w as *const Window,
+                rects: Vec::new(),
+                next_id: 1,
+                active: None,
+            });
+            let id = state.next_id;
+            state.next_id += 1;
+            state.rects.push(CursorTrackingRect {
+                id,
+                rect,
+                cursor: tao_cursor_icon_from_ffi(cursor),
+            });
+            id
+        })
+    })
+    .unwrap_or(0)
+}
+
+pub type VeloxSizeCallback =
+    Option<unsafe extern "C" fn(width: f64, height: f64, user_data: *mut c_void)>;
+
+/// Observe the content size of the window's first webview; null removes the observer
+#[no_mangle]
+pub extern "C" fn velox_window_set_content_size_observer(
+    window: *mut VeloxWindowHandle,
+    max_width: f64,
+    max_height: f64,
+    observer: VeloxSizeCallback,
+    user_data: *mut c_void,
+) -> bool {
+    if !(max_width > 0.0 && max_height > 0.0) {
+        return false;
+    }
+    with_window(window, |w| {
+        CONTENT_SIZE_OBSERVERS.with(|observers| {
+            let mut observers = observers.borrow_mut();
+            match observer {
+                Some(callback) => {
+                    observers.insert(
+                        w.id(),
+                        ContentSizeObserver {
+                            max_width,
+                            max_height,
+                            callback,
+                            user_data,
+                            last_size: None,
+                            next_poll: Instant::now(),
+                        },
+                    );
+                }
+                None => {
+                    observers.remove(&w.id());
+                }
+            }
+        });
+        true
+    })
+    .unwrap_or(false)
+}
+
+/// Remove a cursor tracking rect previously added to the window
+#[no_mangle]
+pub extern "C" fn velox_window_remove_cursor_tracking_rect(
+    window: *mut VeloxWindowHandle,
+    id: u64,
+) -> bool {
+    with_window(window, |w| {
+        CURSOR_TRACKING.with(|tracking| {
+            let mut tracking = tracking.borrow_mut();
+            let Some(state) = tracking.get_mut(&w.id()) else {
+                return false;
+            };
+            let before = state.rects.len();
+            state.rects.retain(|entry| entry.id != id);
+            if state.rects.len() == before {
+                return false;
+            }
+            if state.active == Some(id) {
+                w.set_cursor_icon(CursorIcon::Default);
+                state.active = None;
+            }
+            true
+        })
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_ignore_cursor_events(
+    window: *mut VeloxWindowHandle,
+    ignore: bool,
+) -> bool {
+    velox_window_set_ignore_cursor_events_checked(window, ignore) == VeloxErrorCode::Success
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_set_ignore_cursor_events_checked(
+    window: *mut VeloxWindowHandle,
+    ignore: bool,
+) -> VeloxErrorCode {
+    with_window(window, |w| {
+        VeloxErrorCode::from_external(w.set_ignore_cursor_events(ignore))
+    })
+    .unwrap_or(VeloxErrorCode::NullPointer)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_start_dragging(window: *mut VeloxWindowHandle) -> bool {
+    velox_window_start_dragging_checked(window) == VeloxErrorCode::Success
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_start_dragging_checked(
+    window: *mut VeloxWindowHandle,
+) -> VeloxErrorCode {
+    with_window(window, |w| VeloxErrorCode::from_external(w.drag_window()))
+        .unwrap_or(VeloxErrorCode::NullPointer)
+}
+
+fn set_window_drag_rects(
+    window: *mut VeloxWindowHandle,
+    regions: *const VeloxRect,
+    count: usize,
+    no_drag: bool,
+) -> bool {
+    if regions.is_null() && count > 0 {
+        return false;
+    }
+    let rects = if count == 0 {
+        Vec::new()
+    } else {
+        unsafe { std::slice::from_raw_parts(regions, count) }.to_vec()
+    };
+    with_window(window, |w| {
+        DRAG_REGIONS.with(|state| {
+            let mut state = state.borrow_mut();
+            let entry = state.entry(w.id()).or_insert_with(|| DragRegions {
+                window: w as *const Window,
+                drag: Vec::new(),
+                no_drag: Vec::new(),
+                cursor: None,
+            });
+            if no_drag {
+                entry.no_drag = rects;
+            } else {
+                entry.drag = rects;
+            }
+            if entry.drag.is_empty() && entry.no_drag.is_empty() {
+                state.remove(&w.id());
+            }
+        });
+        inject_drag_region_bridge(w.id());
+        true
+    })
+    .unwrap_or(false)
+}
+
+/// Replace the window's drag regions in logical pixels; zero regions clears them
+#[no_mangle]
+pub extern "C" fn velox_window_set_drag_regions(
+    window: *mut VeloxWindowHandle,
+    regions: *const VeloxRect,
+    count: usize,
+) -> bool {
+    set_window_drag_rects(window, regions, count, false)
+}
+
+/// Replace the areas carved out of the drag regions, such as buttons in a custom title bar
+#[no_mangle]
+pub extern "C" fn velox_window_set_no_drag_regions(
+    window: *mut VeloxWindowHandle,
+    regions: *const VeloxRect,
+    count: usize,
+) -> bool {
+    set_window_drag_rects(window, regions, count, true)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_window_start_resize_dragging(
+    window: *mut VeloxWindowHandle,
+    direction: VeloxResizeDirection,
+) -> bool {
+    let tao_direction = tao_resize_direction_from_ffi(direction);
+    with_window(window, |w| {
+        report_error(w.drag_resize_window(tao_direction), VeloxErrorCode::Unknown).is_some()
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_webview_build(
+    window: *mut VeloxWindowHandle,
+    config: *const VeloxWebviewConfig,
+) -> *mut VeloxWebviewHandle {
+    if window.is_null() {
+        return ptr::null_mut();
+    }
+
+    let cfg = unsafe { config.as_ref().copied().unwrap_or_default() };
+    let url = opt_cstring(cfg.url);
+    let proxy_url = opt_cstring(cfg.proxy_url);
+    let data_directory = match data_directory_from_ffi(cfg.data_directory) {
+        Ok(data_directory) => data_directory,
+        Err(message) => {
+            set_last_error(VeloxErrorCode::InvalidArgument, &message);
+            return ptr::null_mut();
+        }
+    };
+    let initialization_script = opt_cstring(cfg.initialization_script);
+    let user_agent = opt_cstring(cfg.user_agent);
+    let csp = opt_cstring(cfg.csp).filter(|policy| !policy.is_empty());
+
+    let ffi_protocols: Vec<(
+        String,
+        unsafe extern "C" fn(
+            *const VeloxCustomProtocolRequest,
+            *mut VeloxCustomProtocolResponse,
+            *mut c_void,
+        ) -> bool,
+        *mut c_void,
+    )> = if cfg.custom_protocols.count > 0 && !cfg.custom_protocols.protocols.is_null() {
+        unsafe {
+            std::slice::from_raw_parts(cfg.custom_protocols.protocols, cfg.custom_protocols.count)
+        }
+        .iter()
+        .filter_map(|definition| {
+            let handler = definition.handler?;
+            let scheme = opt_cstring(definition.scheme)?;
+            Some((scheme, handler, definition.user_data))
+        })
+        .collect()
+    } else {
+        Vec::new()
+    };
+
+    let shared_user_data: Vec<(*mut c_void, VeloxCustomProtocolUserDataRelease)> =
+        if cfg.custom_protocols.count > 0 && !cfg.custom_protocols.protocols.is_null() {
+            unsafe {
+                std::slice::from_raw_parts(
+                    cfg.custom_protocols.protocols,
+                    cfg.custom_protocols.count,
+                )
+            }
+            .iter()
+            .filter(|definition| {
+                definition.copy_user_data
+                    && definition.handler.is_some()
+                    && !definition.user_data.is_null()
+            })
+            .map(|definition| (definition.user_data, definition.release_user_data))
+            .collect()
+        } else {
+            Vec::new()
+        };
+
+    with_window(window, |w| {
+        let mut web_context = data_directory
+            .as_ref()
+            .map(|path| WebContext::new(Some(path.clone())));
+        let mut builder = if let Some(context) = web_context.as_mut() {
+            WebViewBuilder::new_with_web_context(context)
+        } else {
+            WebViewBuilder::new()
+        };
+
+        if let Some(url) = url.as_ref() {
+            builder = builder.with_url(url.clone());
+        }
+
+        builder = builder.with_devtools(cfg.devtools);
+
+        if let Some(user_agent) = user_agent.as_ref() {
+            builder = builder.with_user_agent(user_agent.clone());
+        }
+
+        if cfg.transparent {
+            builder = builder.with_transparent(true);
+        }
+
+        let mut background_color = (255, 255, 255, 255);
+        if cfg.has_background_color {
+            let color = cfg.background_color;
+            background_color = (color.red, color.green, color.blue, color.alpha);
+            builder = builder.with_background_color(background_color);
+        }
+
+        if let Some(accept_first_mouse) = opt_bool(cfg.accept_first_mouse) {
+            builder = builder.with_accept_first_mouse(accept_first_mouse);
+        }
+
+        if let Some(incognito) = opt_bool(cfg.incognito) {
+            builder = builder.with_incognito(incognito);
+        }
+
+        if opt_bool(cfg.javascript_disabled).unwrap_or(false) {
+            builder = builder.with_javascript_disabled();
+        }
+
+        if let Some(policy) = background_throttling_from_flag(cfg.background_throttling) {
+            builder = builder.with_background_throttling(policy);
+        }
+
+        if let Some(proxy_config) = parse_proxy_config(proxy_url) {
+            builder = builder.with_proxy_config(proxy_config);
+        }
+
+        #[cfg(target_os = "windows")]
+        if let Some(style) = scroll_bar_style_from_flag(cfg.scroll_bar_style) {
+            builder = builder.with_scroll_bar_style(style);
+        }
+
+        if cfg.script_error_handler.is_some() {
+            builder = builder.with_initialization_script(SCRIPT_ERROR_BRIDGE);
+        }
+
+        builder = builder.with_initialization_script(RECV_BRIDGE);
+
+        if let Some(policy) = csp.as_deref() {
+            builder = builder.with_initialization_script(csp_meta_script(policy));
+        }
+
+        if let Some(script) = initialization_script.as_ref() {
+            builder = builder.with_initialization_script(script.clone());
+        }
+
+        let document_title = Rc::new(DocumentTitle::default());
+        let title_state = Rc::clone(&document_title);
+        builder = builder.with_document_title_changed_handler(move |title| {
+            *title_state.title.borrow_mut() = title.clone();
+            let webview_id = title_state.webview_id.borrow().clone();
+            USER_EVENT_PROXY.with(|proxy| {
+                if let Some(proxy) = proxy.borrow().as_ref() {
+                    let _ = proxy.send_event(VeloxUserEvent::TitleChanged { webview_id, title });
+                }
+            });
+        });
+
+        // wry accepts a single IPC handler, so script errors and app messages share it.
+        let ipc_handler: Rc<Cell<Option<IpcCallback>>> = Rc::new(Cell::new(None));
+        let script_error_handler = cfg.script_error_handler;
+        let script_error_user_data = cfg.script_error_user_data;
+        let app_ipc_handler = Rc::clone(&ipc_handler);
+        let window_id = w.id();
+        builder = builder
+            .with_initialization_script(IPC_BRIDGE)
+            .with_ipc_handler(move |request| {
+                if let Some(position) = parse_drag_region_press(request.body(), drag_region_token())
+                {
+                    start_region_drag(window_id, Some(position));
+                    return;
+                }
+                if let Some(error) = parse_script_error(request.body()) {
+                    if let Some(handler) = script_error_handler {
+                        let message = CString::new(error.message)
+                            .unwrap_or_else(|_| CString::new("").expect("empty string"));
+                        let source = CString::new(error.source)
+                            .unwrap_or_else(|_| CString::new("").expect("empty string"));
+                        let _ = catch_unwind(AssertUnwindSafe(|| unsafe {
+                            handler(
+                                message.as_ptr(),
+                                source.as_ptr(),
+                                error.line,
+                                script_error_user_data,
+                            )
+                        }));
+                    }
+                    return;
+                }
+                if let Some(callback) = app_ipc_handler.get() {
+                    callback.invoke(request.body());
+                }
+            });
+
+        // Each new document needs the drag bridge again while the window has drag regions,
+        // and the page styles set so far.
+        let page_styles = Rc::new(PageStyles::default());
+        let load_styles = Rc::clone(&page_styles);
+        builder = builder.with_on_page_load_handler(move |event, _url| {
+            if matches!(event, PageLoadEvent::Finished) {
+                inject_drag_region_bridge(window_id);
+                reapply_page_styles(window_id, &load_styles);
+            }
+        });
+
+        let navigation_handler: Rc<Cell<Option<NavigationCallback>>> = Rc::new(Cell::new(None));
+        let app_navigation_handler = Rc::clone(&navigation_handler);
+        let navigating = Cell::new(false);
+        builder = builder.with_navigation_handler(move |url| {
+            let Some(callback) = app_navigation_handler.get() else {
+                return true;
+            };
+            // A handler that navigates synchronously must not be asked about its own navigation.
+            if navigating.replace(true) {
+                return true;
+            }
+            let allowed = callback.allows(&url);
+            navigating.set(false);
+            allowed
+        });
+
+        // Without a registered handler downloads go to the platform's suggested destination.
+        let download_handler: Rc<Cell<Option<VeloxDownloadConfig>>> = Rc::new(Cell::new(None));
+        let started_downloads = Rc::clone(&download_handler);
+        let completed_downloads = Rc::clone(&download_handler);
+        builder = builder
+            .with_download_started_handler(move |url, destination| {
+                let Some(config) = started_downloads.get() else {
+                    return true;
+                };
+                let Some(started) = config.started_handler else {
+                    return true;
+                };
+                let suggested = destination
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                let (Ok(url), Ok(suggested)) = (CString::new(url), CString::new(suggested)) else {
+                    return false;
+                };
+                let path = catch_unwind(AssertUnwindSafe(|| unsafe {
+                    started(url.as_ptr(), suggested.as_ptr(), config.user_data)
+                }))
+                .unwrap_or(ptr::null());
+                let Some(path) = opt_cstring(path) else {
+                    return false;
+                };
+                *destination = PathBuf::from(path);
+                true
+            })
+            .with_download_completed_handler(move |_url, path, success| {
+                let Some(config) = completed_downloads.get() else {
+                    return;
+                };
+                let Some(completed) = config.completed_handler else {
+                    return;
+                };
+                let path = path.and_then(|path| CString::new(path.to_string_lossy().as_ref()).ok());
+                let _ = catch_unwind(AssertUnwindSafe(|| unsafe {
+                    completed(
+                        path.as_ref().map_or(ptr::null(), |path| path.as_ptr()),
+                        success,
+                        config.user_data,
+                    )
+                }));
+            });
+
+        let new_window_handler: Arc<Mutex<Option<NewWindowCallback>>> = Arc::default();
+        let app_new_window_handler = Arc::clone(&new_window_handler);
+        builder = builder.with_new_window_req_handler(move |url, _features| {
+            // Without a handler keep each platform's behaviour from before the hook existed:
+            // WebView2 opens its own popup, WebKit ignores the request.
+            let fallback = if cfg!(target_os = "windows") {
+                NewWindowResponse::Allow
+            } else {
+                NewWindowResponse::Deny
+            };
+            let callback = app_new_window_handler
+                .lock()
+                .ok()
+                .and_then(|callback| *callback);
+            let Some(callback) = callback else {
+                return fallback;
+            };
+            let Ok(url) = CString::new(url) else {
+                return fallback;
+            };
+            let handled = catch_unwind(AssertUnwindSafe(|| unsafe {
+                (callback.handler)(url.as_ptr(), callback.user_data as *mut c_void)
+            }))
+            .unwrap_or(false);
+            if handled {
+                NewWindowResponse::Deny
+            } else {
+                NewWindowResponse::Allow
+            }
+        });
+
+        let mut protocol_handlers = HashMap::new();
+        for (scheme, handler, user_data) in ffi_protocols.iter().cloned() {
+            let slot = Arc::new(Mutex::new(ProtocolHandler {
+                handler: Some(handler),
+                user_data: user_data as usize,
+            }));
+            protocol_handlers.insert(scheme.clone(), Arc::clone(&slot));
             builder = builder.with_asynchronous_custom_protocol(
                 scheme.clone(),
                 move |webview_id, request, responder| {
@@ -4015,272 +10874,2152 @@ pub extern "C" fn velox_webview_build(
                     let uri_string = parts.uri.to_string();
                     let method_string = parts.method.as_str().to_string();
                     let headers_map = parts.headers;
+                    let accepts_html = headers_map
+                        .get(ACCEPT)
+                        .and_then(|accept| accept.to_str().ok())
+                        .is_some_and(|accept| accept.contains("text/html"));
+                    let redirect_base = url::Url::parse(&uri_string).ok();
+
+                    let url_cstring = match CString::new(uri_string) {
+                        Ok(value) => value,
+                        Err(_) => {
+                            let _ = responder.respond(
+                                WryHttpResponse::builder()
+                                    .status(StatusCode::BAD_REQUEST)
+                                    .body(Vec::new())
+                                    .unwrap(),
+                            );
+                            return;
+                        }
+                    };
+
+                    let method_cstring = match CString::new(method_string) {
+                        Ok(value) => value,
+                        Err(_) => {
+                            let _ = responder.respond(
+                                WryHttpResponse::builder()
+                                    .status(StatusCode::BAD_REQUEST)
+                                    .body(Vec::new())
+                                    .unwrap(),
+                            );
+                            return;
+                        }
+                    };
+
+                    let webview_id_string = format!("{webview_id}");
+                    let webview_id_cstring = CString::new(webview_id_string)
+                        .unwrap_or_else(|_| CString::new("").expect("empty string"));
+
+                    let mut header_storage: Vec<CString> = Vec::new();
+                    let mut header_pairs: Vec<VeloxCustomProtocolHeader> = Vec::new();
+                    for (name, value) in headers_map.iter() {
+                        let name_str = name.as_str();
+                        let value_str = match value.to_str() {
+                            Ok(v) => v,
+                            Err(_) => continue,
+                        };
+
+                        let Ok(name_cstring) = CString::new(name_str) else {
+                            continue;
+                        };
+                        let Ok(value_cstring) = CString::new(value_str) else {
+                            continue;
+                        };
+
+                        header_pairs.push(VeloxCustomProtocolHeader {
+                            name: name_cstring.as_ptr(),
+                            value: value_cstring.as_ptr(),
+                        });
+                        header_storage.push(name_cstring);
+                        header_storage.push(value_cstring);
+                    }
+
+                    let headers_list = VeloxCustomProtocolHeaderList {
+                        headers: if header_pairs.is_empty() {
+                            ptr::null()
+                        } else {
+                            header_pairs.as_ptr()
+                        },
+                        count: header_pairs.len(),
+                    };
+
+                    let body_buffer = VeloxCustomProtocolBuffer {
+                        ptr: body_vec.as_ptr(),
+                        len: body_vec.len(),
+                    };
+
+                    let ffi_request = VeloxCustomProtocolRequest {
+                        url: url_cstring.as_ptr(),
+                        method: method_cstring.as_ptr(),
+                        headers: headers_list,
+                        body: body_buffer,
+                        webview_id: webview_id_cstring.as_ptr(),
+                    };
+
+                    let mut ffi_response = VeloxCustomProtocolResponse::default();
+                    // Copy the handler out so a replacement from inside the callback cannot deadlock.
+                    let current = *slot.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                    let handled = match current.handler {
+                        Some(handler) => catch_unwind(AssertUnwindSafe(|| unsafe {
+                            handler(
+                                &ffi_request,
+                                &mut ffi_response,
+                                current.user_data as *mut c_void,
+                            )
+                        }))
+                        .unwrap_or(false),
+                        None => false,
+                    };
+
+                    if !handled {
+                        let _ = responder.respond(
+                            WryHttpResponse::builder()
+                                .status(StatusCode::NOT_FOUND)
+                                .body(Vec::new())
+                                .unwrap(),
+                        );
+                        return;
+                    }
+
+                    let status = if ffi_response.status == 0 {
+                        StatusCode::OK
+                    } else {
+                        StatusCode::from_u16(ffi_response.status).unwrap_or(StatusCode::OK)
+                    };
+
+                    let mut builder = WryHttpResponse::builder().status(status);
+
+                    if !ffi_response.mime_type.is_null() {
+                        if let Ok(mime) = unsafe { CStr::from_ptr(ffi_response.mime_type) }.to_str()
+                        {
+                            if let Ok(value) = HeaderValue::from_str(mime) {
+                                builder = builder.header(CONTENT_TYPE, value);
+                            }
+                        }
+                    }
+
+                    if ffi_response.headers.count > 0 && !ffi_response.headers.headers.is_null() {
+                        let header_slice = unsafe {
+                            std::slice::from_raw_parts(
+                                ffi_response.headers.headers,
+                                ffi_response.headers.count,
+                            )
+                        };
+                        for header in header_slice {
+                            if header.name.is_null() || header.value.is_null() {
+                                continue;
+                            }
+                            let Ok(name_str) = unsafe { CStr::from_ptr(header.name) }.to_str()
+                            else {
+                                continue;
+                            };
+                            let Ok(value_str) = unsafe { CStr::from_ptr(header.value) }.to_str()
+                            else {
+                                continue;
+                            };
+                            let Ok(name) = HeaderName::from_bytes(name_str.as_bytes()) else {
+                                continue;
+                            };
+                            let Ok(value) = HeaderValue::from_str(value_str) else {
+                                continue;
+                            };
+                            builder = builder.header(name, value);
+                        }
+                    }
+
+                    let body = if ffi_response.body.len > 0 && !ffi_response.body.ptr.is_null() {
+                        unsafe {
+                            std::slice::from_raw_parts(ffi_response.body.ptr, ffi_response.body.len)
+                        }
+                        .to_vec()
+                    } else {
+                        Vec::new()
+                    };
+
+                    let response = builder.body(body).unwrap_or_else(|_| {
+                        WryHttpResponse::builder()
+                            .status(StatusCode::INTERNAL_SERVER_ERROR)
+                            .body(Vec::new())
+                            .unwrap()
+                    });
+
+                    let redirect = redirect_base
+                        .as_ref()
+                        .filter(|_| accepts_html)
+                        .and_then(|base| protocol_redirect_target(&response, base));
+
+                    let _ = responder.respond(response);
+
+                    if let Some(target) = redirect {
+                        follow_protocol_redirect(webview_id.to_string(), target.into());
+                    }
+
+                    if let Some(free) = ffi_response.free {
+                        unsafe { free(ffi_response.user_data) };
+                    }
+                },
+            );
+        }
+
+        // Build as child webview if requested, otherwise as full-window webview
+        let built = if cfg.is_child {
+            let bounds = Rect {
+                position: LogicalPosition::new(cfg.x, cfg.y).into(),
+                size: LogicalSize::new(cfg.width, cfg.height).into(),
+            };
+            builder = builder.with_bounds(bounds);
+            builder.build_as_child(w)
+        } else {
+            builder.build(w)
+        };
+        report_error(built, VeloxErrorCode::WebviewCreationFailed).map(|webview| {
+            *document_title.webview_id.borrow_mut() = webview.id().to_string();
+            for &(user_data, release) in &shared_user_data {
+                retain_protocol_user_data(user_data, release);
+            }
+            let handle = Box::into_raw(Box::new(VeloxWebviewHandle {
+                webview,
+                context: web_context,
+                page_styles,
+                background_color: Cell::new(background_color),
+                ipc_handler,
+                navigation_handler,
+                download_handler,
+                new_window_handler,
+                document_title,
+                shared_user_data: shared_user_data
+                    .iter()
+                    .map(|&(user_data, _)| user_data)
+                    .collect(),
+                protocol_handlers,
+                #[cfg(any(target_os = "linux", target_os = "windows"))]
+                media_capture_policy: RefCell::new(None),
+                #[cfg(target_os = "macos")]
+                media_capture_delegate: RefCell::new(None),
+                is_child: cfg.is_child,
+                incognito: opt_bool(cfg.incognito).unwrap_or(false),
+                #[cfg(target_os = "windows")]
+                parent_hwnd: w.hwnd() as isize,
+                #[cfg(target_os = "windows")]
+                default_user_agent: RefCell::new(None),
+                offscreen_host: None,
+            }));
+            WINDOW_WEBVIEWS.with(|webviews| {
+                webviews
+                    .borrow_mut()
+                    .entry(w.id())
+                    .or_default()
+                    .push(handle as *const VeloxWebviewHandle)
+            });
+            handle
+        })
+    })
+    .flatten()
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Like `velox_webview_build`, storing the webview in `webview` (null on failure)
+#[no_mangle]
+pub extern "C" fn velox_webview_build_checked(
+    window: *mut VeloxWindowHandle,
+    config: *const VeloxWebviewConfig,
+    webview: *mut *mut VeloxWebviewHandle,
+) -> VeloxErrorCode {
+    if window.is_null() || webview.is_null() {
+        return VeloxErrorCode::NullPointer;
+    }
+    let handle = velox_webview_build(window, config);
+    unsafe { *webview = handle };
+    if handle.is_null() {
+        velox_last_error_code()
+    } else {
+        VeloxErrorCode::Success
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn velox_webview_free(webview: *mut VeloxWebviewHandle) {
+    if !webview.is_null() {
+        WINDOW_WEBVIEWS.with(|webviews| {
+            for handles in webviews.borrow_mut().values_mut() {
+                handles.retain(|&handle| handle != webview as *const VeloxWebviewHandle);
+            }
+        });
+        OFFSCREEN_PAINTERS.with(|painters| painters.borrow_mut().remove(&(webview as usize)));
+        let mut handle = unsafe { Box::from_raw(webview) };
+        let shared_user_data = std::mem::take(&mut handle.shared_user_data);
+        #[cfg(target_os = "macos")]
+        {
+            use wry::WebViewExtMacOS;
+
+            let address = objc2::rc::Retained::as_ptr(&handle.webview.webview()) as usize;
+            ACCEPT_FIRST_MOUSE.with(|overrides| overrides.borrow_mut().remove(&address));
+        }
+        if let Some(host) = handle.offscreen_host.as_ref() {
+            forget_window_state(host.window.id());
+        }
+        // Tear down the webview first so no protocol handler can observe released data.
+        drop(handle);
+        for user_data in shared_user_data {
+            release_protocol_user_data(user_data);
+        }
+    }
+}
+
+/// Take an extra reference on a definition's reference-counted `user_data`
+#[no_mangle]
+pub extern "C" fn velox_custom_protocol_definition_retain(
+    definition: *const VeloxCustomProtocolDefinition,
+) -> bool {
+    let Some(definition) = (unsafe { definition.as_ref() }) else {
+        return false;
+    };
+    if !definition.copy_user_data || definition.user_data.is_null() {
+        return false;
+    }
+    retain_protocol_user_data(definition.user_data, definition.release_user_data);
+    true
+}
+
+/// Drop a reference taken with `velox_custom_protocol_definition_retain`
+#[no_mangle]
+pub extern "C" fn velox_custom_protocol_definition_release(
+    definition: *const VeloxCustomProtocolDefinition,
+) -> bool {
+    let Some(definition) = (unsafe { definition.as_ref() }) else {
+        return false;
+    };
+    if !definition.copy_user_data || definition.user_data.is_null() {
+        return false;
+    }
+    release_protocol_user_data(definition.user_data)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_webview_identifier(webview: *mut VeloxWebviewHandle) -> *const c_char {
+    with_webview(webview, |view| {
+        let id_string = format!("{}", view.id());
+        let cstring = CString::new(id_string).unwrap_or_else(|_| CString::new("").unwrap());
+        cstring.into_raw() as *const c_char
+    })
+    .unwrap_or(ptr::null())
+}
+
+#[no_mangle]
+pub extern "C" fn velox_webview_navigate(
+    webview: *mut VeloxWebviewHandle,
+    url: *const c_char,
+) -> bool {
+    velox_webview_navigate_checked(webview, url) == VeloxErrorCode::Success
+}
+
+#[no_mangle]
+pub extern "C" fn velox_webview_navigate_checked(
+    webview: *mut VeloxWebviewHandle,
+    url: *const c_char,
+) -> VeloxErrorCode {
+    let Some(url_str) = opt_cstring(url) else {
+        return VeloxErrorCode::NullPointer;
+    };
+    let parsed_url = match record_error(Url::parse(&url_str), VeloxErrorCode::InvalidArgument) {
+        Ok(url) => url,
+        Err(code) => return code,
+    };
+    with_webview(webview, |view| {
+        VeloxErrorCode::from_result(record_error(
+            view.load_url(parsed_url.as_str()),
+            VeloxErrorCode::Unknown,
+        ))
+    })
+    .unwrap_or(VeloxErrorCode::NullPointer)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_webview_reload(webview: *mut VeloxWebviewHandle) -> bool {
+    velox_webview_reload_checked(webview) == VeloxErrorCode::Success
+}
+
+#[no_mangle]
+pub extern "C" fn velox_webview_reload_checked(webview: *mut VeloxWebviewHandle) -> VeloxErrorCode {
+    with_webview(webview, |view| {
+        VeloxErrorCode::from_result(record_error(view.reload(), VeloxErrorCode::Unknown))
+    })
+    .unwrap_or(VeloxErrorCode::NullPointer)
+}
+
+/// Toggle the webview background transparency
+#[no_mangle]
+pub extern "C" fn velox_webview_set_transparent(
+    webview: *mut VeloxWebviewHandle,
+    transparent: bool,
+) -> bool {
+    let Some(handle) = (unsafe { webview.as_ref() }) else {
+        return false;
+    };
+    let view = &handle.webview;
+    #[cfg(target_os = "macos")]
+    {
+        #[cfg(feature = "macos-private-api")]
+        {
+            use objc2::{msg_send, runtime::AnyObject};
+            use objc2_foundation::{NSNumber, NSString};
+            use wry::WebViewExtMacOS;
+
+            let webview = view.webview();
+            let object: &AnyObject = &webview;
+            let draws_background = NSNumber::new_bool(!transparent);
+            let key = NSString::from_str("drawsBackground");
+            let _: () = unsafe { msg_send![object, setValue: &*draws_background, forKey: &*key] };
+            return true;
+        }
+        #[allow(unreachable_code)]
+        {
+            let _ = (view, transparent);
+            false
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let color = if transparent {
+            (0, 0, 0, 0)
+        } else {
+            handle.background_color.get()
+        };
+        report_error(view.set_background_color(color), VeloxErrorCode::Unknown).is_some()
+    }
+}
+
+/// Set the fill shown before content paints; a null color restores the default opaque white
+#[no_mangle]
+pub extern "C" fn velox_webview_set_background_color(
+    webview: *mut VeloxWebviewHandle,
+    color: *const VeloxColor,
+) -> bool {
+    let color = opt_color(color).unwrap_or((255, 255, 255, 255));
+    let Some(handle) = (unsafe { webview.as_ref() }) else {
+        return false;
+    };
+    handle.background_color.set(color);
+    report_error(
+        handle.webview.set_background_color(color),
+        VeloxErrorCode::Unknown,
+    )
+    .is_some()
+}
+
+/// Puts `tag` at the start of `<head>`, creating the head if needed
+fn with_base_tag(html: &str, tag: &str) -> String {
+    let lower = html.to_ascii_lowercase();
+    let tag_end = |name: &str, from: usize| {
+        let mut search = from;
+        while let Some(offset) = lower[search..].find(name) {
+            let start = search + offset;
+            let next = lower.as_bytes().get(start + name.len()).copied();
+            if matches!(next, Some(b'>' | b' ' | b'\t' | b'\n' | b'\r' | b'/')) {
+                return lower[start..].find('>').map(|close| start + close + 1);
+            }
+            search = start + name.len();
+        }
+        None
+    };
+    if let Some(end) = tag_end("<head", 0) {
+        return format!("{}{tag}{}", &html[..end], &html[end..]);
+    }
+    let mut end = 0;
+    if lower.trim_start().starts_with("<!doctype") {
+        end = lower.find('>').map_or(0, |close| close + 1);
+    }
+    if let Some(html_end) = tag_end("<html", end) {
+        end = html_end;
+    }
+    format!("{}<head>{tag}</head>{}", &html[..end], &html[end..])
+}
+
+/// Display an HTML string; relative URLs resolve against `base_url` when it is given
+#[no_mangle]
+pub extern "C" fn velox_webview_load_html(
+    webview: *mut VeloxWebviewHandle,
+    html: *const c_char,
+    base_url: *const c_char,
+) -> bool {
+    let Some(html) = opt_cstring(html) else {
+        return false;
+    };
+    let base_url = match opt_cstring(base_url).map(|base| url::Url::parse(&base)) {
+        Some(Ok(base)) => Some(base),
+        Some(Err(_)) => return false,
+        None => None,
+    };
+    with_webview(webview, |view| {
+        let Some(base_url) = base_url else {
+            return report_error(view.load_html(&html), VeloxErrorCode::Unknown).is_some();
+        };
+        #[cfg(target_os = "macos")]
+        {
+            use objc2::{msg_send, rc::Retained, runtime::AnyObject};
+            use objc2_foundation::{NSString, NSURL};
+            use wry::WebViewExtMacOS;
+
+            let webview = view.webview();
+            let object: &AnyObject = &webview;
+            let html = NSString::from_str(&html);
+            let base = NSURL::URLWithString(&NSString::from_str(base_url.as_str()));
+            let navigation: Option<Retained<AnyObject>> =
+                unsafe { msg_send![object, loadHTMLString: &*html, baseURL: base.as_deref()] };
+            return navigation.is_some();
+        }
+        #[cfg(target_os = "linux")]
+        {
+            use webkit2gtk::WebViewExt;
+            use wry::WebViewExtUnix;
+
+            view.webview().load_html(&html, Some(base_url.as_str()));
+            return true;
+        }
+        // WebView2 has no base URL for string content, so declare one in the document.
+        // Serialized URLs percent-encode quotes, so the attribute cannot be broken out of.
+        #[allow(unreachable_code)]
+        {
+            report_error(
+                view.load_html(&with_base_tag(
+                    &html,
+                    &format!("<base href=\"{base_url}\">"),
+                )),
+                VeloxErrorCode::Unknown,
+            )
+            .is_some()
+        }
+    })
+    .unwrap_or(false)
+}
+
+/// Swap the handler of a registered `scheme`; null answers every request with a 404
+#[no_mangle]
+pub extern "C" fn velox_webview_replace_protocol_handler(
+    webview: *mut VeloxWebviewHandle,
+    scheme: *const c_char,
+    handler: VeloxCustomProtocolHandler,
+    user_data: *mut c_void,
+) -> bool {
+    let (Some(handle), Some(scheme)) = (unsafe { webview.as_ref() }, opt_cstring(scheme)) else {
+        return false;
+    };
+    let Some(slot) = handle.protocol_handlers.get(&scheme) else {
+        return false;
+    };
+    *slot.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = ProtocolHandler {
+        handler,
+        user_data: user_data as usize,
+    };
+    true
+}
+
+/// Returns the current URL; valid until the next call on the same thread
+#[no_mangle]
+pub extern "C" fn velox_webview_get_url(webview: *mut VeloxWebviewHandle) -> *const c_char {
+    with_webview(webview, |view| match view.url() {
+        Ok(url) if !url.is_empty() => write_string_to_buffer(&URL_BUFFER, url),
+        _ => ptr::null(),
+    })
+    .unwrap_or(ptr::null())
+}
+
+/// Document title, valid until the next title query on the same thread
+#[no_mangle]
+pub extern "C" fn velox_webview_get_title(webview: *mut VeloxWebviewHandle) -> *const c_char {
+    let Some(handle) = (unsafe { webview.as_ref() }) else {
+        return ptr::null();
+    };
+    let title = handle.document_title.title.borrow().clone();
+    write_string_to_buffer(&TITLE_BUFFER, title)
+}
+
+/// Open the web inspector; needs a debug build or the `devtools` feature
+#[no_mangle]
+pub extern "C" fn velox_webview_open_devtools(webview: *mut VeloxWebviewHandle) -> bool {
+    #[cfg(any(debug_assertions, feature = "devtools"))]
+    {
+        with_webview(webview, |view| {
+            view.open_devtools();
+            true
+        })
+        .unwrap_or(false)
+    }
+    #[cfg(not(any(debug_assertions, feature = "devtools")))]
+    {
+        let _ = webview;
+        false
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn velox_webview_close_devtools(webview: *mut VeloxWebviewHandle) -> bool {
+    #[cfg(any(debug_assertions, feature = "devtools"))]
+    {
+        with_webview(webview, |view| {
+            view.close_devtools();
+            true
+        })
+        .unwrap_or(false)
+    }
+    #[cfg(not(any(debug_assertions, feature = "devtools")))]
+    {
+        let _ = webview;
+        false
+    }
+}
+
+/// Whether the webview was built in private browsing mode
+#[no_mangle]
+pub extern "C" fn velox_webview_is_incognito(webview: *mut VeloxWebviewHandle) -> bool {
+    unsafe { webview.as_ref() }.is_some_and(|handle| handle.incognito)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_webview_is_devtools_open(webview: *mut VeloxWebviewHandle) -> bool {
+    #[cfg(any(debug_assertions, feature = "devtools"))]
+    {
+        with_webview(webview, |view| view.is_devtools_open()).unwrap_or(false)
+    }
+    #[cfg(not(any(debug_assertions, feature = "devtools")))]
+    {
+        let _ = webview;
+        false
+    }
+}
+
+fn webview_history_step(view: &WebView, forward: bool) -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        use objc2::{msg_send, rc::Retained, runtime::AnyObject};
+        use wry::WebViewExtMacOS;
+
+        let webview = view.webview();
+        let object: &AnyObject = &webview;
+        let navigation: Option<Retained<AnyObject>> = if forward {
+            unsafe { msg_send![object, goForward] }
+        } else {
+            unsafe { msg_send![object, goBack] }
+        };
+        return navigation.is_some();
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use webkit2gtk::WebViewExt;
+        use wry::WebViewExtUnix;
+
+        let webview = view.webview();
+        if forward {
+            webview.go_forward();
+        } else {
+            webview.go_back();
+        }
+        return true;
+    }
+    // WebView2 history is not exposed by wry, so fall back to the DOM.
+    #[allow(unreachable_code)]
+    {
+        let script = if forward {
+            "history.forward();"
+        } else {
+            "history.back();"
+        };
+        report_error(view.evaluate_script(script), VeloxErrorCode::Unknown).is_some()
+    }
+}
+
+fn webview_can_step(view: &WebView, forward: bool) -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        use objc2::{msg_send, runtime::AnyObject};
+        use wry::WebViewExtMacOS;
+
+        let webview = view.webview();
+        let object: &AnyObject = &webview;
+        return if forward {
+            unsafe { msg_send![object, canGoForward] }
+        } else {
+            unsafe { msg_send![object, canGoBack] }
+        };
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use webkit2gtk::WebViewExt;
+        use wry::WebViewExtUnix;
+
+        let webview = view.webview();
+        return if forward {
+            webview.can_go_forward()
+        } else {
+            webview.can_go_back()
+        };
+    }
+    #[allow(unreachable_code)]
+    {
+        let _ = (view, forward);
+        false
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn velox_webview_navigate_back(webview: *mut VeloxWebviewHandle) -> bool {
+    with_webview(webview, |view| webview_history_step(view, false)).unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_webview_navigate_forward(webview: *mut VeloxWebviewHandle) -> bool {
+    with_webview(webview, |view| webview_history_step(view, true)).unwrap_or(false)
+}
+
+/// Always false on Windows, where the history is not queryable
+#[no_mangle]
+pub extern "C" fn velox_webview_can_go_back(webview: *mut VeloxWebviewHandle) -> bool {
+    with_webview(webview, |view| webview_can_step(view, false)).unwrap_or(false)
+}
+
+/// Always false on Windows, where the history is not queryable
+#[no_mangle]
+pub extern "C" fn velox_webview_can_go_forward(webview: *mut VeloxWebviewHandle) -> bool {
+    with_webview(webview, |view| webview_can_step(view, true)).unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_webview_evaluate_script(
+    webview: *mut VeloxWebviewHandle,
+    script: *const c_char,
+) -> bool {
+    velox_webview_evaluate_script_checked(webview, script) == VeloxErrorCode::Success
+}
+
+#[no_mangle]
+pub extern "C" fn velox_webview_evaluate_script_checked(
+    webview: *mut VeloxWebviewHandle,
+    script: *const c_char,
+) -> VeloxErrorCode {
+    let Some(script) = opt_cstring(script) else {
+        return VeloxErrorCode::NullPointer;
+    };
+    with_webview(webview, |view| {
+        VeloxErrorCode::from_result(record_error(
+            view.evaluate_script(&script),
+            VeloxErrorCode::Unknown,
+        ))
+    })
+    .unwrap_or(VeloxErrorCode::NullPointer)
+}
+
+/// Dispatch a JSON `payload` to the page as a `CustomEvent` named `event_name`
+#[no_mangle]
+pub extern "C" fn velox_webview_post_message(
+    webview: *mut VeloxWebviewHandle,
+    event_name: *const c_char,
+    payload: *const c_char,
+) -> bool {
+    let (Some(event_name), Some(payload)) = (opt_cstring(event_name), opt_cstring(payload)) else {
+        set_last_error(
+            VeloxErrorCode::NullPointer,
+            "event name and payload are required",
+        );
+        return false;
+    };
+    let Some(payload) = report_error(
+        serde_json::from_str::<serde_json::Value>(&payload),
+        VeloxErrorCode::InvalidArgument,
+    ) else {
+        return false;
+    };
+    let script = format!(
+        "window.__velox_recv__ && window.__velox_recv__({}, {});",
+        serde_json::Value::String(event_name),
+        payload
+    );
+    with_webview(webview, |view| {
+        report_error(view.evaluate_script(&script), VeloxErrorCode::Unknown).is_some()
+    })
+    .unwrap_or(false)
+}
+
+#[cfg(target_os = "macos")]
+type AcceptsFirstMouseImp = unsafe extern "C-unwind" fn(
+    &objc2::runtime::AnyObject,
+    objc2::runtime::Sel,
+    *mut objc2::runtime::AnyObject,
+) -> objc2::runtime::Bool;
+
+/// wry's own `acceptsFirstMouse:`, which answers from the builder flag
+#[cfg(target_os = "macos")]
+static ORIGINAL_ACCEPTS_FIRST_MOUSE: OnceLock<AcceptsFirstMouseImp> = OnceLock::new();
+
+#[cfg(target_os = "macos")]
+unsafe extern "C-unwind" fn velox_accepts_first_mouse(
+    this: &objc2::runtime::AnyObject,
+    sel: objc2::runtime::Sel,
+    event: *mut objc2::runtime::AnyObject,
+) -> objc2::runtime::Bool {
+    let address = this as *const objc2::runtime::AnyObject as usize;
+    if let Some(accept) =
+        ACCEPT_FIRST_MOUSE.with(|overrides| overrides.borrow().get(&address).copied())
+    {
+        return objc2::runtime::Bool::new(accept);
+    }
+    match ORIGINAL_ACCEPTS_FIRST_MOUSE.get() {
+        Some(original) => original(this, sel, event),
+        None => objc2::runtime::Bool::NO,
+    }
+}
+
+/// wry fixes `acceptsFirstMouse:` at build time, so route it through the override table.
+#[cfg(target_os = "macos")]
+fn install_accept_first_mouse_override(webview: &objc2::runtime::AnyObject) -> bool {
+    use objc2::{runtime::Imp, sel};
+
+    if ORIGINAL_ACCEPTS_FIRST_MOUSE.get().is_some() {
+        return true;
+    }
+    let Some(method) = webview.class().instance_method(sel!(acceptsFirstMouse:)) else {
+        return false;
+    };
+    let replacement: Imp =
+        unsafe { std::mem::transmute(velox_accepts_first_mouse as AcceptsFirstMouseImp) };
+    let original = unsafe { method.set_implementation(replacement) };
+    let _ = ORIGINAL_ACCEPTS_FIRST_MOUSE
+        .set(unsafe { std::mem::transmute::<Imp, AcceptsFirstMouseImp>(original) });
+    true
+}
 
-                    let url_cstring = match CString::new(uri_string) {
-                        Ok(value) => value,
-                        Err(_) => {
-                            let _ = responder.respond(
-                                WryHttpResponse::builder()
-                                    .status(StatusCode::BAD_REQUEST)
-                                    .body(Vec::new())
-                                    .unwrap(),
-                            );
-                            return;
-                        }
-                    };
+/// Let a click on an inactive window reach the page instead of only activating it (macOS only)
+#[no_mangle]
+pub extern "C" fn velox_webview_set_accept_first_mouse(
+    webview: *mut VeloxWebviewHandle,
+    accept: bool,
+) -> bool {
+    with_webview(webview, |view| {
+        #[cfg(target_os = "macos")]
+        {
+            use objc2::runtime::AnyObject;
+            use wry::WebViewExtMacOS;
+
+            let webview = view.webview();
+            let object: &AnyObject = &webview;
+            if !install_accept_first_mouse_override(object) {
+                return false;
+            }
+            let address = object as *const AnyObject as usize;
+            ACCEPT_FIRST_MOUSE.with(|overrides| overrides.borrow_mut().insert(address, accept));
+            return true;
+        }
+        #[allow(unreachable_code)]
+        {
+            let _ = (view, accept);
+            false
+        }
+    })
+    .unwrap_or(false)
+}
+
+pub type VeloxIpcHandler =
+    Option<unsafe extern "C" fn(message: *const c_char, user_data: *mut c_void)>;
+
+/// Receive the raw payload of every `window.ipc.postMessage` call; a null handler removes it
+#[no_mangle]
+pub extern "C" fn velox_webview_set_ipc_handler(
+    webview: *mut VeloxWebviewHandle,
+    handler: VeloxIpcHandler,
+    user_data: *mut c_void,
+) -> bool {
+    let Some(handle) = (unsafe { webview.as_ref() }) else {
+        return false;
+    };
+    handle
+        .ipc_handler
+        .set(handler.map(|handler| IpcCallback { handler, user_data }));
+    true
+}
+
+pub type VeloxNavigationHandler =
+    Option<unsafe extern "C" fn(url: *const c_char, user_data: *mut c_void) -> bool>;
+
+/// Decide every navigation; a null handler allows all and a panicking one denies
+#[no_mangle]
+pub extern "C" fn velox_webview_set_navigation_handler(
+    webview: *mut VeloxWebviewHandle,
+    handler: VeloxNavigationHandler,
+    user_data: *mut c_void,
+) -> bool {
+    let Some(handle) = (unsafe { webview.as_ref() }) else {
+        return false;
+    };
+    handle
+        .navigation_handler
+        .set(handler.map(|handler| NavigationCallback { handler, user_data }));
+    true
+}
+
+/// Intercept downloads; a null config restores saving to the suggested destination
+#[no_mangle]
+pub extern "C" fn velox_webview_set_download_handler(
+    webview: *mut VeloxWebviewHandle,
+    config: *const VeloxDownloadConfig,
+) -> bool {
+    let Some(handle) = (unsafe { webview.as_ref() }) else {
+        return false;
+    };
+    handle
+        .download_handler
+        .set(unsafe { config.as_ref() }.copied());
+    true
+}
+
+/// Handle new-window requests; return true once the app has opened the URL itself
+#[no_mangle]
+pub extern "C" fn velox_webview_set_new_window_handler(
+    webview: *mut VeloxWebviewHandle,
+    handler: VeloxNavigationHandler,
+    user_data: *mut c_void,
+) -> bool {
+    let Some(handle) = (unsafe { webview.as_ref() }) else {
+        return false;
+    };
+    let Ok(mut slot) = handle.new_window_handler.lock() else {
+        return false;
+    };
+    *slot = handler.map(|handler| NewWindowCallback {
+        handler,
+        user_data: user_data as usize,
+    });
+    true
+}
+
+pub type VeloxScriptResultCallback =
+    Option<unsafe extern "C" fn(result: *const c_char, user_data: *mut c_void)>;
+
+static SCRIPT_ERROR_MARKER: AtomicU64 = AtomicU64::new(0);
+
+/// Evaluate `script` and pass its JSON result, or `{"error": message}` if it threw, to `callback`
+#[no_mangle]
+pub extern "C" fn velox_webview_evaluate_script_with_callback(
+    webview: *mut VeloxWebviewHandle,
+    script: *const c_char,
+    callback: VeloxScriptResultCallback,
+    user_data: *mut c_void,
+) -> bool {
+    let Some(script) = opt_cstring(script) else {
+        return false;
+    };
+    let Some(callback) = callback else {
+        return false;
+    };
+    // A try statement completes with its block's value, so the script runs as written, without
+    // eval (which a CSP may forbid). A per-call key marks the catch branch's value as an error.
+    let marker = format!(
+        "__velox_script_error_{}_{}",
+        std::process::id(),
+        SCRIPT_ERROR_MARKER.fetch_add(1, Ordering::Relaxed)
+    );
+    let wrapped = format!(
+        "try {{\n{script}\n}} catch (e) {{ ({{ {marker}: String(e && e.message !== undefined ? e.message : e) }}); }}"
+    );
+    // The callback runs on the main thread, so the pointer never actually crosses threads.
+    let user_data = user_data as usize;
+    with_webview(webview, |view| {
+        let evaluated = view.evaluate_script_with_callback(&wrapped, move |result| {
+            let result = CString::new(script_result(result, &marker))
+                .unwrap_or_else(|_| CString::new("null").expect("static string"));
+            let _ = catch_unwind(AssertUnwindSafe(|| unsafe {
+                callback(result.as_ptr(), user_data as *mut c_void)
+            }));
+        });
+        report_error(evaluated, VeloxErrorCode::Unknown).is_some()
+    })
+    .unwrap_or(false)
+}
+
+/// Maps an evaluation result to its JSON value, or `{"error": message}` when the script threw.
+fn script_result(result: String, marker: &str) -> String {
+    if result.is_empty() {
+        return "null".to_string();
+    }
+    match serde_json::from_str::<serde_json::Value>(&result) {
+        Ok(serde_json::Value::Object(mut object)) if object.len() == 1 => {
+            match object.remove(marker) {
+                Some(message) => json!({ "error": message }).to_string(),
+                None => result,
+            }
+        }
+        _ => result,
+    }
+}
+
+/// Give the webview keyboard focus
+#[no_mangle]
+pub extern "C" fn velox_webview_focus(webview: *mut VeloxWebviewHandle) -> bool {
+    with_webview(webview, |view| {
+        report_error(view.focus(), VeloxErrorCode::Unknown).is_some()
+    })
+    .unwrap_or(false)
+}
+
+/// Hand keyboard focus back to the parent window, away from the webview
+#[no_mangle]
+pub extern "C" fn velox_webview_blur(webview: *mut VeloxWebviewHandle) -> bool {
+    with_webview(webview, |view| {
+        report_error(view.focus_parent(), VeloxErrorCode::Unknown).is_some()
+    })
+    .unwrap_or(false)
+}
+
+/// Move keyboard focus to the DOM element with the given id
+#[no_mangle]
+pub extern "C" fn velox_webview_focus_element_by_id(
+    webview: *mut VeloxWebviewHandle,
+    element_id: *const c_char,
+) -> bool {
+    let Some(element_id) = opt_cstring(element_id) else {
+        return false;
+    };
+    // Serialize as a JSON string literal so quotes and line terminators are escaped.
+    let Ok(literal) = serde_json::to_string(&element_id) else {
+        return false;
+    };
+    let script = format!("document.getElementById({literal})?.focus();");
+    with_webview(webview, |view| {
+        report_error(view.evaluate_script(&script), VeloxErrorCode::Unknown).is_some()
+    })
+    .unwrap_or(false)
+}
+
+/// Remove keyboard focus from the currently focused DOM element
+#[no_mangle]
+pub extern "C" fn velox_webview_blur_active_element(webview: *mut VeloxWebviewHandle) -> bool {
+    with_webview(webview, |view| {
+        report_error(
+            view.evaluate_script("document.activeElement?.blur();"),
+            VeloxErrorCode::Unknown,
+        )
+        .is_some()
+    })
+    .unwrap_or(false)
+}
+
+/// Dispatch a synthetic DOM mouse event at the given client coordinates
+#[no_mangle]
+pub extern "C" fn velox_webview_fire_mouse_event(
+    webview: *mut VeloxWebviewHandle,
+    event_type: *const c_char,
+    x: f64,
+    y: f64,
+    button: u8,
+    modifiers: VeloxModifiers,
+) -> bool {
+    let Some(event_type) = opt_cstring(event_type) else {
+        return false;
+    };
+    if !matches!(
+        event_type.as_str(),
+        "mousedown" | "mouseup" | "mousemove" | "click" | "dblclick"
+    ) {
+        return false;
+    }
+    if !x.is_finite() || !y.is_finite() || x < 0.0 || y < 0.0 {
+        return false;
+    }
+    let VeloxModifiers {
+        shift,
+        control,
+        alt,
+        meta,
+    } = modifiers;
+    let script = format!(
+        "(document.elementFromPoint({x}, {y}) || document).dispatchEvent(new MouseEvent(\"{event_type}\", \
+         {{bubbles: true, cancelable: true, view: window, clientX: {x}, clientY: {y}, button: {button}, \
+         shiftKey: {shift}, ctrlKey: {control}, altKey: {alt}, metaKey: {meta}}}));"
+    );
+    with_webview(webview, |view| {
+        report_error(view.evaluate_script(&script), VeloxErrorCode::Unknown).is_some()
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_webview_set_zoom(
+    webview: *mut VeloxWebviewHandle,
+    scale_factor: f64,
+) -> bool {
+    with_webview(webview, |view| {
+        report_error(view.zoom(scale_factor), VeloxErrorCode::Unknown).is_some()
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_webview_show(webview: *mut VeloxWebviewHandle) -> bool {
+    with_webview(webview, |view| {
+        report_error(view.set_visible(true), VeloxErrorCode::Unknown).is_some()
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_webview_hide(webview: *mut VeloxWebviewHandle) -> bool {
+    with_webview(webview, |view| {
+        report_error(view.set_visible(false), VeloxErrorCode::Unknown).is_some()
+    })
+    .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn velox_webview_clear_browsing_data(webview: *mut VeloxWebviewHandle) -> bool {
+    with_webview(webview, |view| {
+        report_error(view.clear_all_browsing_data(), VeloxErrorCode::Unknown).is_some()
+    })
+    .unwrap_or(false)
+}
+
+#[derive(Clone, Copy)]
+enum WebsiteDataKind {
+    Cookies,
+    LocalStorage,
+    Cache,
+}
+
+fn clear_website_data(view: &WebView, kind: WebsiteDataKind) -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        use block2::RcBlock;
+        use objc2::{msg_send, rc::Retained, runtime::AnyObject};
+        use objc2_foundation::{NSDate, NSSet, NSString};
+        use wry::WebViewExtMacOS;
+
+        let types: &[&str] = match kind {
+            WebsiteDataKind::Cookies => &["WKWebsiteDataTypeCookies"],
+            WebsiteDataKind::LocalStorage => &["WKWebsiteDataTypeLocalStorage"],
+            WebsiteDataKind::Cache => &[
+                "WKWebsiteDataTypeDiskCache",
+                "WKWebsiteDataTypeMemoryCache",
+                "WKWebsiteDataTypeFetchCache",
+            ],
+        };
+        let types: Vec<_> = types.iter().map(|name| NSString::from_str(name)).collect();
+        let types = NSSet::from_retained_slice(&types);
+
+        let webview = view.webview();
+        let object: &AnyObject = &webview;
+        let configuration: Option<Retained<AnyObject>> =
+            unsafe { msg_send![object, configuration] };
+        let Some(configuration) = configuration else {
+            return false;
+        };
+        let store: Option<Retained<AnyObject>> =
+            unsafe { msg_send![&*configuration, websiteDataStore] };
+        let Some(store) = store else {
+            return false;
+        };
+        let since = NSDate::dateWithTimeIntervalSince1970(0.0);
+        let handler = RcBlock::new(|| {});
+        let _: () = unsafe {
+            msg_send![
+                &*store,
+                removeDataOfTypes: &*types,
+                modifiedSince: &*since,
+                completionHandler: &*handler
+            ]
+        };
+        return true;
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        use webkit2gtk::{WebContextExt, WebViewExt, WebsiteDataManagerExt, WebsiteDataTypes};
+        use wry::WebViewExtUnix;
+
+        let types = match kind {
+            WebsiteDataKind::Cookies => WebsiteDataTypes::COOKIES,
+            WebsiteDataKind::LocalStorage => WebsiteDataTypes::LOCAL_STORAGE,
+            WebsiteDataKind::Cache => WebsiteDataTypes::DISK_CACHE | WebsiteDataTypes::MEMORY_CACHE,
+        };
+        let Some(manager) = view
+            .webview()
+            .context()
+            .and_then(|context| context.website_data_manager())
+        else {
+            return false;
+        };
+        manager.clear(
+            types,
+            gtk::glib::TimeSpan::from_seconds(0),
+            None::<&webkit2gtk::gio::Cancellable>,
+            |_| {},
+        );
+        return true;
+    }
+
+    // WebView2 only exposes per-kind clearing through profile APIs wry does not surface.
+    #[allow(unreachable_code)]
+    {
+        let _ = kind;
+        report_error(view.clear_all_browsing_data(), VeloxErrorCode::Unknown).is_some()
+    }
+}
+
+/// Remove stored cookies (Windows clears all browsing data instead)
+#[no_mangle]
+pub extern "C" fn velox_webview_clear_cookies(webview: *mut VeloxWebviewHandle) -> bool {
+    with_webview(webview, |view| {
+        clear_website_data(view, WebsiteDataKind::Cookies)
+    })
+    .unwrap_or(false)
+}
+
+/// Remove local storage (Windows clears all browsing data instead)
+#[no_mangle]
+pub extern "C" fn velox_webview_clear_local_storage(webview: *mut VeloxWebviewHandle) -> bool {
+    with_webview(webview, |view| {
+        clear_website_data(view, WebsiteDataKind::LocalStorage)
+    })
+    .unwrap_or(false)
+}
+
+/// Remove the disk and memory caches (Windows clears all browsing data instead)
+#[no_mangle]
+pub extern "C" fn velox_webview_clear_cache(webview: *mut VeloxWebviewHandle) -> bool {
+    with_webview(webview, |view| {
+        clear_website_data(view, WebsiteDataKind::Cache)
+    })
+    .unwrap_or(false)
+}
+
+/// Scale only the page text; re-applied on each page load
+#[no_mangle]
+pub extern "C" fn velox_webview_set_text_zoom(
+    webview: *mut VeloxWebviewHandle,
+    level: f64,
+) -> bool {
+    let Some(handle) = (unsafe { webview.as_ref() }) else {
+        return false;
+    };
+    if !level.is_finite() || level <= 0.0 {
+        return false;
+    }
+    if report_error(
+        handle.webview.evaluate_script(&text_zoom_script(level)),
+        VeloxErrorCode::Unknown,
+    )
+    .is_none()
+    {
+        return false;
+    }
+    handle.page_styles.text_zoom.set(level);
+    true
+}
+
+/// Write the text zoom level last applied with `velox_webview_set_text_zoom`
+#[no_mangle]
+pub extern "C" fn velox_webview_get_text_zoom(
+    webview: *mut VeloxWebviewHandle,
+    level: *mut f64,
+) -> bool {
+    if level.is_null() {
+        return false;
+    }
+    let Some(handle) = (unsafe { webview.as_ref() }) else {
+        return false;
+    };
+    unsafe {
+        *level = handle.page_styles.text_zoom.get();
+    }
+    true
+}
+
+fn webview_session_state(view: &WebView) -> Option<Vec<u8>> {
+    #[cfg(target_os = "macos")]
+    {
+        use objc2::{msg_send, rc::Retained, runtime::AnyObject, sel, ClassType};
+        use objc2_foundation::NSData;
+        use wry::WebViewExtMacOS;
+
+        let webview = view.webview();
+        let object: &AnyObject = &webview;
+        // `interactionState` was added in macOS 12.
+        let supported: bool =
+            unsafe { msg_send![object, respondsToSelector: sel!(interactionState)] };
+        if !supported {
+            return None;
+        }
+        let state: Option<Retained<AnyObject>> = unsafe { msg_send![object, interactionState] };
+        let state = state?;
+        let is_data: bool = unsafe { msg_send![&*state, isKindOfClass: NSData::class()] };
+        if !is_data {
+            return None;
+        }
+        let data = unsafe { Retained::cast_unchecked::<NSData>(state) };
+        return Some(data.to_vec());
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        use webkit2gtk::WebViewExt;
+        use wry::WebViewExtUnix;
+
+        let bytes = view.webview().session_state()?.serialize()?;
+        return Some(bytes.to_vec());
+    }
+
+    #[allow(unreachable_code)]
+    {
+        let _ = view;
+        None
+    }
+}
+
+/// Serialize back/forward history and page state; the bytes are passed to `callback`
+#[no_mangle]
+pub extern "C" fn velox_webview_export_session_state(
+    webview: *mut VeloxWebviewHandle,
+    callback: VeloxBytesCallback,
+    user_data: *mut c_void,
+) -> bool {
+    let Some(callback) = callback else {
+        return false;
+    };
+    let Some(state) = with_webview(webview, webview_session_state).flatten() else {
+        return false;
+    };
+    let _ = catch_unwind(AssertUnwindSafe(|| unsafe {
+        callback(state.as_ptr(), state.len(), user_data)
+    }));
+    true
+}
 
-                    let method_cstring = match CString::new(method_string) {
-                        Ok(value) => value,
-                        Err(_) => {
-                            let _ = responder.respond(
-                                WryHttpResponse::builder()
-                                    .status(StatusCode::BAD_REQUEST)
-                                    .body(Vec::new())
-                                    .unwrap(),
-                            );
-                            return;
-                        }
-                    };
+/// Restore state produced by `velox_webview_export_session_state`
+#[no_mangle]
+pub extern "C" fn velox_webview_import_session_state(
+    webview: *mut VeloxWebviewHandle,
+    data: *const u8,
+    len: usize,
+) -> bool {
+    if data.is_null() || len == 0 {
+        return false;
+    }
+    let bytes = unsafe { std::slice::from_raw_parts(data, len) };
+    with_webview(webview, |view| {
+        #[cfg(target_os = "macos")]
+        {
+            use objc2::{msg_send, runtime::AnyObject, sel};
+            use objc2_foundation::NSData;
+            use wry::WebViewExtMacOS;
+
+            let webview = view.webview();
+            let object: &AnyObject = &webview;
+            let supported: bool =
+                unsafe { msg_send![object, respondsToSelector: sel!(setInteractionState:)] };
+            if !supported {
+                return false;
+            }
+            let state = NSData::with_bytes(bytes);
+            let _: () = unsafe { msg_send![object, setInteractionState: &*state] };
+            return true;
+        }
+        #[cfg(target_os = "linux")]
+        {
+            use webkit2gtk::{WebViewExt, WebViewSessionState};
+            use wry::WebViewExtUnix;
 
-                    let webview_id_string = format!("{webview_id}");
-                    let webview_id_cstring = CString::new(webview_id_string)
-                        .unwrap_or_else(|_| CString::new("").expect("empty string"));
+            let state = WebViewSessionState::new(&gtk::glib::Bytes::from(bytes));
+            view.webview().restore_session_state(&state);
+            return true;
+        }
+        #[allow(unreachable_code)]
+        {
+            let _ = (view, bytes);
+            false
+        }
+    })
+    .unwrap_or(false)
+}
 
-                    let mut header_storage: Vec<CString> = Vec::new();
-                    let mut header_pairs: Vec<VeloxCustomProtocolHeader> = Vec::new();
-                    for (name, value) in headers_map.iter() {
-                        let name_str = name.as_str();
-                        let value_str = match value.to_str() {
-                            Ok(v) => v,
-                            Err(_) => continue,
-                        };
+/// Override the User-Agent string; null restores the platform default
+#[no_mangle]
+pub extern "C" fn velox_webview_set_user_agent(
+    webview: *mut VeloxWebviewHandle,
+    user_agent: *const c_char,
+) -> bool {
+    let user_agent = opt_cstring(user_agent);
+    with_webview(webview, |view| {
+        #[cfg(target_os = "macos")]
+        {
+            use objc2::{msg_send, runtime::AnyObject};
+            use objc2_foundation::NSString;
+            use wry::WebViewExtMacOS;
+
+            let webview = view.webview();
+            let object: &AnyObject = &webview;
+            let user_agent = user_agent.as_deref().map(NSString::from_str);
+            let _: () = unsafe { msg_send![object, setCustomUserAgent: user_agent.as_deref()] };
+            return true;
+        }
+        #[cfg(target_os = "linux")]
+        {
+            use webkit2gtk::{SettingsExt, WebViewExt};
+            use wry::WebViewExtUnix;
 
-                        let Ok(name_cstring) = CString::new(name_str) else {
-                            continue;
-                        };
-                        let Ok(value_cstring) = CString::new(value_str) else {
-                            continue;
-                        };
+            let Some(settings) = view.webview().settings() else {
+                return false;
+            };
+            settings.set_user_agent(user_agent.as_deref());
+            return true;
+        }
+        #[cfg(target_os = "windows")]
+        {
+            use webview2_com::{
+                CoTaskMemPWSTR, Microsoft::Web::WebView2::Win32::ICoreWebView2Settings2,
+            };
+            use windows_core::{Interface, HSTRING, PWSTR};
+            use wry::WebViewExtWindows;
 
-                        header_pairs.push(VeloxCustomProtocolHeader {
-                            name: name_cstring.as_ptr(),
-                            value: value_cstring.as_ptr(),
-                        });
-                        header_storage.push(name_cstring);
-                        header_storage.push(value_cstring);
-                    }
+            let settings = unsafe { view.webview().Settings() }
+                .and_then(|settings| settings.cast::<ICoreWebView2Settings2>());
+            let Some(settings) = report_error(settings, VeloxErrorCode::PlatformUnsupported) else {
+                return false;
+            };
+            // WebView2 has no way back to its own User-Agent, so remember it before overriding.
+            let handle = unsafe { &*webview };
+            let mut default = handle.default_user_agent.borrow_mut();
+            if default.is_none() {
+                let mut current = PWSTR::null();
+                if report_error(
+                    unsafe { settings.UserAgent(&mut current) },
+                    VeloxErrorCode::Unknown,
+                )
+                .is_none()
+                {
+                    return false;
+                }
+                *default = Some(CoTaskMemPWSTR::from(current).to_string());
+            }
+            let user_agent = user_agent
+                .as_deref()
+                .or(default.as_deref())
+                .unwrap_or_default();
+            let updated = unsafe { settings.SetUserAgent(&HSTRING::from(user_agent)) };
+            return report_error(updated, VeloxErrorCode::Unknown).is_some();
+        }
+        #[allow(unreachable_code)]
+        {
+            let _ = (view, user_agent);
+            false
+        }
+    })
+    .unwrap_or(false)
+}
 
-                    let headers_list = VeloxCustomProtocolHeaderList {
-                        headers: if header_pairs.is_empty() {
-                            ptr::null()
-                        } else {
-                            header_pairs.as_ptr()
-                        },
-                        count: header_pairs.len(),
-                    };
+/// Run `script` at document start of later page loads
+#[no_mangle]
+pub extern "C" fn velox_webview_add_initialization_script(
+    webview: *mut VeloxWebviewHandle,
+    script: *const c_char,
+) -> bool {
+    let Some(script) = opt_cstring(script) else {
+        return false;
+    };
+    with_webview(webview, |view| {
+        #[cfg(target_os = "macos")]
+        {
+            use objc2::{
+                msg_send,
+                rc::{Allocated, Retained},
+                runtime::{AnyClass, AnyObject},
+            };
+            use objc2_foundation::NSString;
+            use wry::WebViewExtMacOS;
 
-                    let body_buffer = VeloxCustomProtocolBuffer {
-                        ptr: body_vec.as_ptr(),
-                        len: body_vec.len(),
-                    };
+            let Some(class) = AnyClass::get(c"WKUserScript") else {
+                return false;
+            };
+            let webview = view.webview();
+            let object: &AnyObject = &webview;
+            let source = NSString::from_str(&script);
+            unsafe {
+                let configuration: Retained<AnyObject> = msg_send![object, configuration];
+                let controller: Retained<AnyObject> =
+                    msg_send![&*configuration, userContentController];
+                let allocated: Allocated<AnyObject> = msg_send![class, alloc];
+                // WKUserScriptInjectionTimeAtDocumentStart
+                let user_script: Retained<AnyObject> = msg_send![
+                    allocated,
+                    initWithSource: &*source,
+                    injectionTime: 0isize,
+                    forMainFrameOnly: true
+                ];
+                let _: () = msg_send![&*controller, addUserScript: &*user_script];
+            }
+            return true;
+        }
+        #[cfg(target_os = "linux")]
+        {
+            use webkit2gtk::{
+                UserContentInjectedFrames, UserContentManagerExt, UserScript,
+                UserScriptInjectionTime, WebViewExt,
+            };
+            use wry::WebViewExtUnix;
 
-                    let ffi_request = VeloxCustomProtocolRequest {
-                        url: url_cstring.as_ptr(),
-                        method: method_cstring.as_ptr(),
-                        headers: headers_list,
-                        body: body_buffer,
-                        webview_id: webview_id_cstring.as_ptr(),
-                    };
+            let Some(manager) = view.webview().user_content_manager() else {
+                return false;
+            };
+            manager.add_script(&UserScript::new(
+                &script,
+                UserContentInjectedFrames::TopFrame,
+                UserScriptInjectionTime::Start,
+                &[],
+                &[],
+            ));
+            return true;
+        }
+        #[cfg(target_os = "windows")]
+        {
+            use webview2_com::AddScriptToExecuteOnDocumentCreatedCompletedHandler;
+            use windows_core::HSTRING;
+            use wry::WebViewExtWindows;
+
+            let handler = AddScriptToExecuteOnDocumentCreatedCompletedHandler::create(Box::new(
+                |_, _| Ok(()),
+            ));
+            let added = unsafe {
+                view.webview()
+                    .AddScriptToExecuteOnDocumentCreated(&HSTRING::from(script.as_str()), &handler)
+            };
+            return report_error(added, VeloxErrorCode::Unknown).is_some();
+        }
+        #[allow(unreachable_code)]
+        {
+            let _ = (view, script);
+            false
+        }
+    })
+    .unwrap_or(false)
+}
 
-                    let mut ffi_response = VeloxCustomProtocolResponse::default();
-                    let handled = match catch_unwind(AssertUnwindSafe(|| unsafe {
-                        handler(&ffi_request, &mut ffi_response, user_data)
-                    })) {
-                        Ok(result) => result,
-                        Err(_) => false,
-                    };
+pub type VeloxSnapshotCallback =
+    Option<unsafe extern "C" fn(data: *const u8, width: u32, height: u32, user_data: *mut c_void)>;
 
-                    if !handled {
-                        let _ = responder.respond(
-                            WryHttpResponse::builder()
-                                .status(StatusCode::NOT_FOUND)
-                                .body(Vec::new())
-                                .unwrap(),
-                        );
-                        return;
-                    }
+/// Snapshot buffers handed to callers, keyed by address until `velox_snapshot_free`.
+static SNAPSHOT_BUFFERS: OnceLock<Mutex<HashMap<usize, Box<[u8]>>>> = OnceLock::new();
 
-                    let status = if ffi_response.status == 0 {
-                        StatusCode::OK
-                    } else {
-                        StatusCode::from_u16(ffi_response.status).unwrap_or(StatusCode::OK)
-                    };
+fn deliver_snapshot(
+    snapshot: Option<(Vec<u8>, u32, u32)>,
+    callback: unsafe extern "C" fn(*const u8, u32, u32, *mut c_void),
+    user_data: *mut c_void,
+) {
+    let (data, width, height) = match snapshot {
+        Some((pixels, width, height)) => {
+            let pixels = pixels.into_boxed_slice();
+            let data = pixels.as_ptr();
+            let Ok(mut buffers) = SNAPSHOT_BUFFERS.get_or_init(Default::default).lock() else {
+                return;
+            };
+            buffers.insert(data as usize, pixels);
+            (data, width, height)
+        }
+        None => (ptr::null(), 0, 0),
+    };
+    let _ = catch_unwind(AssertUnwindSafe(|| unsafe {
+        callback(data, width, height, user_data)
+    }));
+}
 
-                    let mut builder = WryHttpResponse::builder().status(status);
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn unpremultiply_rgba(pixels: &mut [u8]) {
+    for pixel in pixels.chunks_exact_mut(4) {
+        let alpha = u32::from(pixel[3]);
+        if alpha == 0 || alpha == 255 {
+            continue;
+        }
+        for channel in &mut pixel[..3] {
+            *channel = ((u32::from(*channel) * 255 + alpha / 2) / alpha).min(255) as u8;
+        }
+    }
+}
 
-                    if !ffi_response.mime_type.is_null() {
-                        if let Ok(mime) = unsafe { CStr::from_ptr(ffi_response.mime_type) }.to_str()
-                        {
-                            if let Ok(value) = HeaderValue::from_str(mime) {
-                                builder = builder.header(CONTENT_TYPE, value);
-                            }
-                        }
-                    }
+#[cfg(target_os = "macos")]
+fn ns_image_rgba(image: &objc2::runtime::AnyObject) -> Option<(Vec<u8>, u32, u32)> {
+    use objc2::{
+        class, msg_send,
+        rc::{Allocated, Retained},
+        runtime::AnyObject,
+    };
+    use objc2_foundation::{NSPoint, NSRect, NSSize, NSString};
 
-                    if ffi_response.headers.count > 0 && !ffi_response.headers.headers.is_null() {
-                        let header_slice = unsafe {
-                            std::slice::from_raw_parts(
-                                ffi_response.headers.headers,
-                                ffi_response.headers.count,
-                            )
-                        };
-                        for header in header_slice {
-                            if header.name.is_null() || header.value.is_null() {
-                                continue;
-                            }
-                            let Ok(name_str) = unsafe { CStr::from_ptr(header.name) }.to_str()
-                            else {
-                                continue;
-                            };
-                            let Ok(value_str) = unsafe { CStr::from_ptr(header.value) }.to_str()
-                            else {
-                                continue;
-                            };
-                            let Ok(name) = HeaderName::from_bytes(name_str.as_bytes()) else {
-                                continue;
-                            };
-                            let Ok(value) = HeaderValue::from_str(value_str) else {
-                                continue;
-                            };
-                            builder = builder.header(name, value);
-                        }
-                    }
+    unsafe {
+        let tiff: Option<Retained<AnyObject>> = msg_send![image, TIFFRepresentation];
+        let source: Option<Retained<AnyObject>> =
+            msg_send![class!(NSBitmapImageRep), imageRepWithData: &*tiff?];
+        let source = source?;
+        let width: isize = msg_send![&*source, pixelsWide];
+        let height: isize = msg_send![&*source, pixelsHigh];
+        if width <= 0 || height <= 0 {
+            return None;
+        }
 
-                    let body = if ffi_response.body.len > 0 && !ffi_response.body.ptr.is_null() {
-                        unsafe {
-                            std::slice::from_raw_parts(ffi_response.body.ptr, ffi_response.body.len)
-                        }
-                        .to_vec()
-                    } else {
-                        Vec::new()
-                    };
+        // Redraw into a bitmap with a known layout: 8-bit RGBA, alpha last, rows top first.
+        let color_space = NSString::from_str("NSDeviceRGBColorSpace");
+        let allocated: Allocated<AnyObject> = msg_send![class!(NSBitmapImageRep), alloc];
+        let target: Option<Retained<AnyObject>> = msg_send![
+            allocated,
+            initWithBitmapDataPlanes: ptr::null_mut::<*mut u8>(),
+            pixelsWide: width,
+            pixelsHigh: height,
+            bitsPerSample: 8isize,
+            samplesPerPixel: 4isize,
+            hasAlpha: true,
+            isPlanar: false,
+            colorSpaceName: &*color_space,
+            bytesPerRow: width * 4,
+            bitsPerPixel: 32isize
+        ];
+        let target = target?;
+        let context: Option<Retained<AnyObject>> = msg_send![
+            class!(NSGraphicsContext),
+            graphicsContextWithBitmapImageRep: &*target
+        ];
+        let context = context?;
+        let _: () = msg_send![class!(NSGraphicsContext), saveGraphicsState];
+        let _: () = msg_send![class!(NSGraphicsContext), setCurrentContext: &*context];
+        let bounds = NSRect::new(
+            NSPoint::new(0.0, 0.0),
+            NSSize::new(width as f64, height as f64),
+        );
+        let _: bool = msg_send![&*source, drawInRect: bounds];
+        let _: () = msg_send![class!(NSGraphicsContext), restoreGraphicsState];
+
+        let data: *mut u8 = msg_send![&*target, bitmapData];
+        if data.is_null() {
+            return None;
+        }
+        let mut pixels = std::slice::from_raw_parts(data, (width * height * 4) as usize).to_vec();
+        unpremultiply_rgba(&mut pixels);
+        Some((pixels, width as u32, height as u32))
+    }
+}
 
-                    let response = builder.body(body).unwrap_or_else(|_| {
-                        WryHttpResponse::builder()
-                            .status(StatusCode::INTERNAL_SERVER_ERROR)
-                            .body(Vec::new())
-                            .unwrap()
-                    });
+#[cfg(target_os = "linux")]
+fn cairo_surface_rgba(surface: &gtk::cairo::ImageSurface) -> Option<(Vec<u8>, u32, u32)> {
+    use gtk::cairo::Format;
 
-                    let _ = responder.respond(response);
+    let has_alpha = match surface.format() {
+        Format::ARgb32 => true,
+        Format::Rgb24 => false,
+        _ => return None,
+    };
+    let width = usize::try_from(surface.width()).ok()?;
+    let height = usize::try_from(surface.height()).ok()?;
+    let stride = usize::try_from(surface.stride()).ok()?;
+    let mut pixels = Vec::with_capacity(width * height * 4);
+    surface
+        .with_data(|data| {
+            for row in data.chunks(stride).take(height) {
+                for pixel in row[..width * 4].chunks_exact(4) {
+                    let argb = u32::from_ne_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]);
+                    let alpha = if has_alpha { (argb >> 24) as u8 } else { 255 };
+                    pixels.extend_from_slice(&[
+                        (argb >> 16) as u8,
+                        (argb >> 8) as u8,
+                        argb as u8,
+                        alpha,
+                    ]);
+                }
+            }
+        })
+        .ok()?;
+    unpremultiply_rgba(&mut pixels);
+    Some((pixels, width as u32, height as u32))
+}
 
-                    if let Some(free) = ffi_response.free {
-                        unsafe { free(ffi_response.user_data) };
-                    }
+/// Capture the webview as RGBA; the callback's buffer is freed with `velox_snapshot_free`
+#[no_mangle]
+pub extern "C" fn velox_webview_take_snapshot(
+    webview: *mut VeloxWebviewHandle,
+    callback: VeloxSnapshotCallback,
+    user_data: *mut c_void,
+) -> bool {
+    let Some(callback) = callback else {
+        return false;
+    };
+    with_webview(webview, |view| {
+        #[cfg(target_os = "macos")]
+        {
+            use block2::RcBlock;
+            use objc2::{msg_send, runtime::AnyObject};
+            use wry::WebViewExtMacOS;
+
+            let webview = view.webview();
+            let object: &AnyObject = &webview;
+            let block = RcBlock::new(move |image: *mut AnyObject, _error: *mut AnyObject| {
+                let snapshot = unsafe { image.as_ref() }.and_then(ns_image_rgba);
+                deliver_snapshot(snapshot, callback, user_data);
+            });
+            let _: () = unsafe {
+                msg_send![
+                    object,
+                    takeSnapshotWithConfiguration: ptr::null_mut::<AnyObject>(),
+                    completionHandler: &*block
+                ]
+            };
+            return true;
+        }
+        #[cfg(target_os = "linux")]
+        {
+            use webkit2gtk::{SnapshotOptions, SnapshotRegion, WebViewExt};
+            use wry::WebViewExtUnix;
+
+            view.webview().snapshot(
+                SnapshotRegion::Visible,
+                SnapshotOptions::NONE,
+                None::<&webkit2gtk::gio::Cancellable>,
+                move |result| {
+                    let snapshot = result
+                        .ok()
+                        .and_then(|surface| gtk::cairo::ImageSurface::try_from(surface).ok())
+                        .and_then(|surface| cairo_surface_rgba(&surface));
+                    deliver_snapshot(snapshot, callback, user_data);
                 },
             );
+            return true;
         }
-
-        // Build as child webview if requested, otherwise as full-window webview
-        if cfg.is_child {
-            let bounds = Rect {
-                position: LogicalPosition::new(cfg.x, cfg.y).into(),
-                size: LogicalSize::new(cfg.width, cfg.height).into(),
-            };
-            builder = builder.with_bounds(bounds);
-            builder
-                .build_as_child(w)
-                .ok()
-                .map(|webview| {
-                    Box::into_raw(Box::new(VeloxWebviewHandle {
-                        webview,
-                        context: web_context,
-                    }))
-                })
-        } else {
-            builder
-                .build(w)
-                .ok()
-                .map(|webview| {
-                    Box::into_raw(Box::new(VeloxWebviewHandle {
-                        webview,
-                        context: web_context,
-                    }))
-                })
+        #[allow(unreachable_code)]
+        {
+            let _ = (view, user_data);
+            false
         }
     })
-    .flatten()
-    .unwrap_or(ptr::null_mut())
+    .unwrap_or(false)
 }
 
-#[no_mangle]
-pub extern "C" fn velox_webview_free(webview: *mut VeloxWebviewHandle) {
-    if !webview.is_null() {
-        unsafe { drop(Box::from_raw(webview)) };
+const OFFSCREEN_PAINT_INTERVAL: Duration = Duration::from_millis(100);
+
+struct OffscreenPainter {
+    callback:
+        unsafe extern "C" fn(data: *const u8, width: u32, height: u32, user_data: *mut c_void),
+    user_data: *mut c_void,
+    next_poll: Instant,
+}
+
+fn poll_offscreen_painters() -> Option<Instant> {
+    let now = Instant::now();
+    let due: Vec<_> = OFFSCREEN_PAINTERS.with(|painters| {
+        painters
+            .borrow_mut()
+            .iter_mut()
+            .filter(|(_, painter)| painter.next_poll <= now)
+            .map(|(&handle, painter)| {
+                painter.next_poll = now + OFFSCREEN_PAINT_INTERVAL;
+                (handle, painter.callback, painter.user_data)
+            })
+            .collect()
+    });
+
+    for (handle, callback, user_data) in due {
+        velox_webview_take_snapshot(handle as *mut VeloxWebviewHandle, Some(callback), user_data);
     }
+
+    OFFSCREEN_PAINTERS.with(|painters| {
+        painters
+            .borrow()
+            .values()
+            .map(|painter| painter.next_poll)
+            .min()
+    })
 }
 
+/// Build a webview in a hidden host window, rendered through snapshots (not on Windows)
 #[no_mangle]
-pub extern "C" fn velox_webview_identifier(webview: *mut VeloxWebviewHandle) -> *const c_char {
-    with_webview(webview, |view| {
-        let id_string = format!("{}", view.id());
-        let cstring = CString::new(id_string).unwrap_or_else(|_| CString::new("").unwrap());
-        cstring.into_raw() as *const c_char
-    })
-    .unwrap_or(ptr::null())
+pub extern "C" fn velox_webview_build_offscreen(
+    event_loop: *mut VeloxEventLoop,
+    config: *const VeloxWebviewConfig,
+    width: u32,
+    height: u32,
+) -> *mut VeloxWebviewHandle {
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    {
+        if width == 0 || height == 0 {
+            return ptr::null_mut();
+        }
+        let window_config = VeloxWindowConfigV2 {
+            base: VeloxWindowConfig {
+                width,
+                height,
+                decorations: 0,
+                resizable: 0,
+                skip_taskbar: true,
+                ..Default::default()
+            },
+            visible: false,
+            focusable: false,
+            ..Default::default()
+        };
+        let window = velox_window_build_v2(event_loop, &window_config);
+        if window.is_null() {
+            return ptr::null_mut();
+        }
+        let mut webview_config = unsafe { config.as_ref().copied().unwrap_or_default() };
+        webview_config.is_child = false;
+        if webview_config.background_throttling == -1 {
+            webview_config.background_throttling = 0;
+        }
+        let webview = velox_webview_build(window, &webview_config);
+        let Some(handle) = (unsafe { webview.as_mut() }) else {
+            velox_window_free(window);
+            return ptr::null_mut();
+        };
+        handle.offscreen_host = Some(unsafe { Box::from_raw(window) });
+        return webview;
+    }
+
+    #[allow(unreachable_code)]
+    {
+        let _ = (event_loop, config, width, height);
+        ptr::null_mut()
+    }
 }
 
+/// Receive offscreen frames as RGBA; a null callback stops them
 #[no_mangle]
-pub extern "C" fn velox_webview_navigate(
+pub extern "C" fn velox_webview_set_offscreen_paint_callback(
     webview: *mut VeloxWebviewHandle,
-    url: *const c_char,
+    callback: VeloxSnapshotCallback,
+    user_data: *mut c_void,
 ) -> bool {
-    let Some(url_str) = opt_cstring(url) else {
+    let Some(handle) = (unsafe { webview.as_ref() }) else {
         return false;
     };
-    let Ok(parsed_url) = Url::parse(&url_str) else {
+    if handle.offscreen_host.is_none() {
         return false;
-    };
-    with_webview(webview, |view| view.load_url(parsed_url.as_str()).is_ok()).unwrap_or(false)
+    }
+    OFFSCREEN_PAINTERS.with(|painters| {
+        let mut painters = painters.borrow_mut();
+        match callback {
+            Some(callback) => {
+                painters.insert(
+                    webview as usize,
+                    OffscreenPainter {
+                        callback,
+                        user_data,
+                        next_poll: Instant::now(),
+                    },
+                );
+            }
+            None => {
+                painters.remove(&(webview as usize));
+            }
+        }
+    });
+    true
 }
 
+/// Release a buffer passed to a snapshot callback
 #[no_mangle]
-pub extern "C" fn velox_webview_reload(webview: *mut VeloxWebviewHandle) -> bool {
-    with_webview(webview, |view| view.reload().is_ok()).unwrap_or(false)
+pub extern "C" fn velox_snapshot_free(data: *mut u8) {
+    if data.is_null() {
+        return;
+    }
+    if let Some(buffers) = SNAPSHOT_BUFFERS.get() {
+        if let Ok(mut buffers) = buffers.lock() {
+            buffers.remove(&(data as usize));
+        }
+    }
+}
+
+fn style_element_script(element_id: &str, css: Option<&str>) -> String {
+    let id = serde_json::to_string(element_id).unwrap_or_else(|_| "\"\"".into());
+    match css.and_then(|css| serde_json::to_string(css).ok()) {
+        Some(css) => format!(
+            "(function () {{ var s = document.getElementById({id}); \
+             if (!s) {{ s = document.createElement('style'); s.id = {id}; \
+             (document.head || document.documentElement).appendChild(s); }} \
+             s.textContent = {css}; }})();"
+        ),
+        None => format!("document.getElementById({id})?.remove();"),
+    }
 }
 
+/// Browsers ignore CSP `<meta>` tags outside `<head>`, so wait for the parser to create it.
+fn csp_meta_script(policy: &str) -> String {
+    let policy = serde_json::to_string(policy).unwrap_or_else(|_| "\"\"".into());
+    format!(
+        "(function () {{ function apply() {{ var head = document.head; \
+         if (!head) return false; var meta = document.createElement('meta'); \
+         meta.httpEquiv = 'Content-Security-Policy'; meta.content = {policy}; \
+         head.insertBefore(meta, head.firstChild); return true; }} \
+         if (!apply()) {{ new MutationObserver(function (_, observer) {{ \
+         if (apply()) observer.disconnect(); }}) \
+         .observe(document, {{ childList: true, subtree: true }}); }} }})();"
+    )
+}
+
+/// Add a Content-Security-Policy `<meta>` tag to the current page only
 #[no_mangle]
-pub extern "C" fn velox_webview_evaluate_script(
+pub extern "C" fn velox_webview_set_content_security_policy(
     webview: *mut VeloxWebviewHandle,
-    script: *const c_char,
+    policy: *const c_char,
 ) -> bool {
-    let Some(script) = opt_cstring(script) else {
+    let Some(policy) = opt_cstring(policy).filter(|policy| !policy.is_empty()) else {
         return false;
     };
-    with_webview(webview, |view| view.evaluate_script(&script).is_ok()).unwrap_or(false)
+    with_webview(webview, |view| {
+        report_error(
+            view.evaluate_script(&csp_meta_script(&policy)),
+            VeloxErrorCode::Unknown,
+        )
+        .is_some()
+    })
+    .unwrap_or(false)
 }
 
+const SCROLL_INDICATOR_STYLE_ID: &str = "__velox_scroll_indicator_style";
+
+/// Restyle or hide the scrollbars; re-applied on each page load
 #[no_mangle]
-pub extern "C" fn velox_webview_set_zoom(
+pub extern "C" fn velox_webview_set_scroll_indicator_style(
     webview: *mut VeloxWebviewHandle,
-    scale_factor: f64,
+    style: VeloxScrollIndicatorStyle,
 ) -> bool {
-    with_webview(webview, |view| view.zoom(scale_factor).is_ok()).unwrap_or(false)
+    let css = match style {
+        VeloxScrollIndicatorStyle::Default => None,
+        VeloxScrollIndicatorStyle::Dark => Some(
+            "::-webkit-scrollbar { width: 8px; height: 8px; background: transparent; } \
+             ::-webkit-scrollbar-thumb { background: rgba(0, 0, 0, 0.5); border-radius: 4px; }",
+        ),
+        VeloxScrollIndicatorStyle::Light => Some(
+            "::-webkit-scrollbar { width: 8px; height: 8px; background: transparent; } \
+             ::-webkit-scrollbar-thumb { background: rgba(255, 255, 255, 0.6); border-radius: 4px; }",
+        ),
+        VeloxScrollIndicatorStyle::Hidden => Some(
+            "::-webkit-scrollbar { display: none; } html { scrollbar-width: none; }",
+        ),
+    };
+    let Some(handle) = (unsafe { webview.as_ref() }) else {
+        return false;
+    };
+    let script = style_element_script(SCROLL_INDICATOR_STYLE_ID, css);
+    if report_error(
+        handle.webview.evaluate_script(&script),
+        VeloxErrorCode::Unknown,
+    )
+    .is_none()
+    {
+        return false;
+    }
+    handle.page_styles.scroll_indicator_css.set(css);
+    true
 }
 
+/// Inject a stylesheet; a non-null `id` replaces the earlier one with that id
 #[no_mangle]
-pub extern "C" fn velox_webview_show(webview: *mut VeloxWebviewHandle) -> bool {
-    with_webview(webview, |view| view.set_visible(true).is_ok()).unwrap_or(false)
+pub extern "C" fn velox_webview_inject_css(
+    webview: *mut VeloxWebviewHandle,
+    css: *const c_char,
+    id: *const c_char,
+) -> bool {
+    let Some(css) = opt_cstring(css) else {
+        return false;
+    };
+    let script = match opt_cstring(id) {
+        Some(id) => style_element_script(&id, Some(&css)),
+        None => {
+            let css = serde_json::to_string(&css).unwrap_or_else(|_| "\"\"".into());
+            format!(
+                "(function () {{ var s = document.createElement('style'); s.textContent = {css}; \
+                 (document.head || document.documentElement).appendChild(s); }})();"
+            )
+        }
+    };
+    with_webview(webview, |view| {
+        report_error(view.evaluate_script(&script), VeloxErrorCode::Unknown).is_some()
+    })
+    .unwrap_or(false)
 }
 
-#[no_mangle]
-pub extern "C" fn velox_webview_hide(webview: *mut VeloxWebviewHandle) -> bool {
-    with_webview(webview, |view| view.set_visible(false).is_ok()).unwrap_or(false)
+#[cfg(target_os = "macos")]
+struct MediaCaptureDelegateState {
+    /// wry's UI delegate, which grants every media request and handles everything else
+    wry_delegate: objc2::rc::Retained<objc2::runtime::AnyObject>,
+    policy: Cell<VeloxMediaCapturePolicy>,
 }
 
+#[cfg(target_os = "macos")]
+objc2::define_class!(
+    #[unsafe(super(objc2_foundation::NSObject))]
+    #[name = "VeloxMediaCaptureDelegate"]
+    #[ivars = MediaCaptureDelegateState]
+    struct VeloxMediaCaptureDelegate;
+
+    impl VeloxMediaCaptureDelegate {
+        #[unsafe(method(respondsToSelector:))]
+        fn responds_to_selector(&self, selector: objc2::runtime::Sel) -> bool {
+            use objc2::msg_send;
+
+            let own: bool = unsafe { msg_send![super(self), respondsToSelector: selector] };
+            own || unsafe { msg_send![&*self.ivars().wry_delegate, respondsToSelector: selector] }
+        }
+
+        #[unsafe(method(forwardingTargetForSelector:))]
+        fn forwarding_target(
+            &self,
+            _selector: objc2::runtime::Sel,
+        ) -> *mut objc2::runtime::AnyObject {
+            objc2::rc::Retained::as_ptr(&self.ivars().wry_delegate) as *mut _
+        }
+
+        #[unsafe(method(webView:requestMediaCapturePermissionForOrigin:initiatedByFrame:type:decisionHandler:))]
+        fn request_media_capture_permission(
+            &self,
+            _webview: *mut objc2::runtime::AnyObject,
+            _origin: *mut objc2::runtime::AnyObject,
+            _frame: *mut objc2::runtime::AnyObject,
+            _capture_type: isize,
+            decision_handler: &block2::DynBlock<dyn Fn(isize)>,
+        ) {
+            const WK_PERMISSION_DECISION_PROMPT: isize = 0;
+            const WK_PERMISSION_DECISION_GRANT: isize = 1;
+            const WK_PERMISSION_DECISION_DENY: isize = 2;
+
+            let decision = match self.ivars().policy.get() {
+                VeloxMediaCapturePolicy::PromptUser => WK_PERMISSION_DECISION_PROMPT,
+                VeloxMediaCapturePolicy::Allow => WK_PERMISSION_DECISION_GRANT,
+                VeloxMediaCapturePolicy::Deny => WK_PERMISSION_DECISION_DENY,
+            };
+            decision_handler.call((decision,));
+        }
+    }
+);
+
+/// Decide how camera and microphone requests from page content are answered
 #[no_mangle]
-pub extern "C" fn velox_webview_clear_browsing_data(webview: *mut VeloxWebviewHandle) -> bool {
-    with_webview(webview, |view| view.clear_all_browsing_data().is_ok()).unwrap_or(false)
+pub extern "C" fn velox_webview_set_media_capture_policy(
+    webview: *mut VeloxWebviewHandle,
+    policy: VeloxMediaCapturePolicy,
+) -> bool {
+    let Some(handle) = (unsafe { webview.as_ref() }) else {
+        return false;
+    };
+    #[cfg(target_os = "macos")]
+    {
+        use objc2::{msg_send, rc::Retained, runtime::AnyObject, AllocAnyThread};
+        use wry::WebViewExtMacOS;
+
+        let mut slot = handle.media_capture_delegate.borrow_mut();
+        if let Some(current) = slot.as_ref() {
+            current.ivars().policy.set(policy);
+            return true;
+        }
+
+        // Wrap wry's delegate per webview rather than patching its class for every webview.
+        let webview = handle.webview.webview();
+        let wry_delegate: Option<Retained<AnyObject>> = unsafe { msg_send![&*webview, UIDelegate] };
+        let Some(wry_delegate) = wry_delegate else {
+            return false;
+        };
+        let delegate = VeloxMediaCaptureDelegate::alloc().set_ivars(MediaCaptureDelegateState {
+            wry_delegate,
+            policy: Cell::new(policy),
+        });
+        let delegate: Retained<VeloxMediaCaptureDelegate> =
+            unsafe { msg_send![super(delegate), init] };
+        // The UI delegate is weak, so the handle keeps the wrapper alive.
+        let _: () = unsafe { msg_send![&*webview, setUIDelegate: &*delegate] };
+        *slot = Some(delegate);
+        return true;
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use webview2_com::{
+            Microsoft::Web::WebView2::Win32::{
+                COREWEBVIEW2_PERMISSION_KIND, COREWEBVIEW2_PERMISSION_KIND_CAMERA,
+                COREWEBVIEW2_PERMISSION_KIND_MICROPHONE, COREWEBVIEW2_PERMISSION_STATE_ALLOW,
+                COREWEBVIEW2_PERMISSION_STATE_DENY,
+            },
+            PermissionRequestedEventHandler,
+        };
+        use wry::WebViewExtWindows;
+
+        fn with_token<T: Default, R>(register: impl FnOnce(*mut T) -> R) -> R {
+            let mut token = T::default();
+            register(&mut token)
+        }
+
+        let mut slot = handle.media_capture_policy.borrow_mut();
+        if let Some(current) = slot.as_ref() {
+            current.set(policy);
+            return true;
+        }
+
+        let current = Rc::new(Cell::new(policy));
+        let shared = Rc::clone(&current);
+        let handler = PermissionRequestedEventHandler::create(Box::new(move |_, args| {
+            let Some(args) = args else {
+                return Ok(());
+            };
+            let mut kind = COREWEBVIEW2_PERMISSION_KIND::default();
+            unsafe { args.PermissionKind(&mut kind)? };
+            if kind != COREWEBVIEW2_PERMISSION_KIND_CAMERA
+                && kind != COREWEBVIEW2_PERMISSION_KIND_MICROPHONE
+            {
+                return Ok(());
+            }
+            match shared.get() {
+                VeloxMediaCapturePolicy::Allow => unsafe {
+                    args.SetState(COREWEBVIEW2_PERMISSION_STATE_ALLOW)
+                },
+                VeloxMediaCapturePolicy::Deny => unsafe {
+                    args.SetState(COREWEBVIEW2_PERMISSION_STATE_DENY)
+                },
+                // Leave the default state so WebView2 shows its own prompt.
+                VeloxMediaCapturePolicy::PromptUser => Ok(()),
+            }
+        }));
+        let webview = handle.webview.webview();
+        let registered =
+            with_token(|token| unsafe { webview.add_PermissionRequested(&handler, token) });
+        if report_error(registered, VeloxErrorCode::Unknown).is_none() {
+            return false;
+        }
+        *slot = Some(current);
+        return true;
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use webkit2gtk::{PermissionRequestExt, UserMediaPermissionRequest, WebViewExt};
+        use wry::WebViewExtUnix;
+
+        let mut slot = handle.media_capture_policy.borrow_mut();
+        if let Some(current) = slot.as_ref() {
+            current.set(policy);
+            return true;
+        }
+
+        let current = Rc::new(Cell::new(policy));
+        let shared = Rc::clone(&current);
+        handle
+            .webview
+            .webview()
+            .connect_permission_request(move |_, request| {
+                if !request.is::<UserMediaPermissionRequest>() {
+                    return false;
+                }
+                match shared.get() {
+                    VeloxMediaCapturePolicy::Allow => request.allow(),
+                    VeloxMediaCapturePolicy::Deny => request.deny(),
+                    // Fall through to WebKit's default handling.
+                    VeloxMediaCapturePolicy::PromptUser => return false,
+                }
+                true
+            });
+        *slot = Some(current);
+        return true;
+    }
+    #[allow(unreachable_code)]
+    {
+        let _ = (handle, policy);
+        false
+    }
 }
 
 /// Set the bounds of a child webview
@@ -4297,7 +13036,105 @@ pub extern "C" fn velox_webview_set_bounds(
             position: LogicalPosition::new(x, y).into(),
             size: LogicalSize::new(width, height).into(),
         };
-        view.set_bounds(bounds).is_ok()
+        report_error(view.set_bounds(bounds), VeloxErrorCode::Unknown).is_some()
+    })
+    .unwrap_or(false)
+}
+
+/// Read the logical bounds of a child webview relative to its window
+#[no_mangle]
+pub extern "C" fn velox_webview_get_bounds(
+    webview: *mut VeloxWebviewHandle,
+    x: *mut f64,
+    y: *mut f64,
+    width: *mut f64,
+    height: *mut f64,
+) -> bool {
+    let Some(handle) = (unsafe { webview.as_ref() }) else {
+        return false;
+    };
+    if !handle.is_child {
+        return false;
+    }
+    let Ok(bounds) = handle.webview.bounds() else {
+        return false;
+    };
+
+    #[cfg(target_os = "windows")]
+    let scale_factor = {
+        use windows::Win32::UI::HiDpi::GetDpiForWindow;
+
+        match unsafe { GetDpiForWindow(HWND(handle.parent_hwnd as _)) } {
+            0 => 1.0,
+            dpi => f64::from(dpi) / 96.0,
+        }
+    };
+    // WebKit reports logical bounds already, so the factor is never applied there.
+    #[cfg(not(target_os = "windows"))]
+    let scale_factor = 1.0;
+
+    let position: LogicalPosition<f64> = bounds.position.to_logical(scale_factor);
+    let size: LogicalSize<f64> = bounds.size.to_logical(scale_factor);
+    for (out, value) in [
+        (x, position.x),
+        (y, position.y),
+        (width, size.width),
+        (height, size.height),
+    ] {
+        if let Some(out) = unsafe { out.as_mut() } {
+            *out = value;
+        }
+    }
+    true
+}
+
+/// Turns an origin such as `https://api.example.com` into a WebKit URL pattern.
+#[cfg(target_os = "linux")]
+fn cors_allowlist_pattern(origin: &str) -> String {
+    if origin.ends_with('*') {
+        origin.to_string()
+    } else {
+        format!("{}/*", origin.trim_end_matches('/'))
+    }
+}
+
+/// Control which origins may be requested cross-site from the webview (Linux only)
+#[no_mangle]
+pub extern "C" fn velox_webview_set_cors_policy(
+    webview: *mut VeloxWebviewHandle,
+    policy: VeloxCorsPolicy,
+    allowed_origins: *const *const c_char,
+    count: usize,
+) -> bool {
+    let origins = cstring_array(allowed_origins, count);
+    with_webview(webview, |view| {
+        #[cfg(target_os = "linux")]
+        {
+            use webkit2gtk::WebViewExt;
+            use wry::WebViewExtUnix;
+
+            let patterns: Vec<String> = match policy {
+                VeloxCorsPolicy::Default => Vec::new(),
+                VeloxCorsPolicy::Disabled => vec!["*://*/*".to_string()],
+                VeloxCorsPolicy::AllowSpecificOrigins => {
+                    if origins.is_empty() {
+                        return false;
+                    }
+                    origins
+                        .iter()
+                        .map(|origin| cors_allowlist_pattern(origin))
+                        .collect()
+                }
+            };
+            let patterns: Vec<&str> = patterns.iter().map(String::as_str).collect();
+            view.webview().set_cors_allowlist(&patterns);
+            return true;
+        }
+        #[allow(unreachable_code)]
+        {
+            let _ = (view, policy, origins);
+            false
+        }
     })
     .unwrap_or(false)
 }
@@ -4347,16 +13184,42 @@ fn serialize_event(event: &Event<VeloxUserEvent>) -> String {
             "window_id": format!("{window_id:?}"),
         }),
         Event::UserEvent(VeloxUserEvent::Exit) => json!({ "type": "user-exit" }),
+        Event::UserEvent(VeloxUserEvent::Wake) => json!({ "type": "wake" }),
         Event::UserEvent(VeloxUserEvent::Custom(payload)) => json!({
             "type": "user-event",
             "payload": payload,
         }),
-        #[cfg(any(target_os = "macos", target_os = "linux"))]
-        Event::UserEvent(VeloxUserEvent::Menu(menu_id)) => json!({
-            "type": "menu-event",
-            "menu_id": menu_id,
+        Event::UserEvent(VeloxUserEvent::Task(_)) => json!({ "type": "task" }),
+        Event::UserEvent(VeloxUserEvent::Timer(id)) => json!({ "type": "timer", "timer_id": id }),
+        Event::UserEvent(VeloxUserEvent::NotificationActivated(action_id)) => json!({
+            "type": "notification-activated",
+            "action_id": action_id,
+        }),
+        Event::UserEvent(VeloxUserEvent::Hotkey(id)) => json!({ "type": "hotkey", "id": id }),
+        Event::UserEvent(VeloxUserEvent::Accessibility(enabled)) => json!({
+            "type": if *enabled { "accessibility-enabled" } else { "accessibility-disabled" },
         }),
-        #[cfg(any(target_os = "macos", target_os = "linux"))]
+        Event::UserEvent(VeloxUserEvent::TitleChanged { webview_id, title }) => json!({
+            "type": "title-changed",
+            "webview_id": webview_id,
+            "title": title,
+        }),
+        Event::UserEvent(VeloxUserEvent::ContentSize(window_id, width, height)) => json!({
+            "type": "window-content-size",
+            "window_id": format!("{window_id:?}"),
+            "width": width,
+            "height": height,
+        }),
+        Event::UserEvent(VeloxUserEvent::Menu(menu_id)) => {
+            let mut payload = Map::new();
+            payload.insert("type".into(), json!("menu-event"));
+            payload.insert("menu_id".into(), json!(menu_id));
+            let checked = installed_check_menu_item(menu_id).map(|item| item.is_checked());
+            if let Some(checked) = checked {
+                payload.insert("checked".into(), json!(checked));
+            }
+            serde_json::Value::Object(payload)
+        }
         Event::UserEvent(VeloxUserEvent::Tray(event)) => {
             let mut payload = Map::new();
             payload.insert("type".into(), json!("tray-event"));
@@ -4470,11 +13333,18 @@ fn serialize_event(event: &Event<VeloxUserEvent>) -> String {
                 "location": format!("{:?}", key_event.location),
                 "is_synthetic": is_synthetic,
             }),
-            TaoWindowEvent::ReceivedImeText(text) => json!({
-                "type": "window-ime-text",
-                "window_id": format!("{window_id:?}"),
-                "text": text,
-            }),
+            TaoWindowEvent::ReceivedImeText(text) => {
+                let ime = IME_STATE
+                    .with(|state| state.borrow().get(window_id).copied())
+                    .unwrap_or_default();
+                json!({
+                    "type": "window-ime-text",
+                    "window_id": format!("{window_id:?}"),
+                    "text": text,
+                    "ime_allowed": ime.allowed,
+                    "ime_position": ime.position.map(|(x, y)| EventPosition { x, y }),
+                })
+            }
             TaoWindowEvent::ModifiersChanged(modifiers) => json!({
                 "type": "window-modifiers-changed",
                 "window_id": format!("{window_id:?}"),
@@ -4577,3 +13447,233 @@ fn serialize_event(event: &Event<VeloxUserEvent>) -> String {
 
     serde_json::to_string(&value).unwrap_or_else(|_| "{}".into())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn current_video_mode_matches_size_and_rate() {
+        let size = PhysicalSize::new(1920, 1080);
+        let modes = [
+            (PhysicalSize::new(1280, 720), 144, 32),
+            (size, 60, 24),
+            (size, 60, 32),
+            (size, 120, 32),
+        ];
+        let pick = |refresh_hz| current_video_mode(&modes, |mode| *mode, size, refresh_hz);
+        assert_eq!(pick(Some(60)), Some(&modes[2]));
+        assert_eq!(pick(None), Some(&modes[3]));
+        assert_eq!(pick(Some(75)), None);
+        assert_eq!(
+            current_video_mode(&modes, |mode| *mode, PhysicalSize::new(800, 600), None),
+            None
+        );
+    }
+
+    #[test]
+    fn script_error_fields_default_when_missing() {
+        let error = parse_script_error(
+            r#"{"__velox_error":{"message":"boom","source":"app.js","line":7}}"#,
+        )
+        .expect("script error");
+        assert_eq!(error.message, "boom");
+        assert_eq!(error.source, "app.js");
+        assert_eq!(error.line, 7);
+
+        let error = parse_script_error(r#"{"__velox_error":{}}"#).expect("script error");
+        assert_eq!(error.message, "");
+        assert_eq!(error.line, 0);
+
+        assert!(parse_script_error(r#"{"message":"boom"}"#).is_none());
+    }
+
+    #[test]
+    fn script_result_reports_thrown_marker_as_error_object() {
+        assert_eq!(script_result(String::new(), "m"), "null");
+        assert_eq!(script_result("42".to_string(), "m"), "42");
+        assert_eq!(
+            script_result(r#"{"m":"bad"}"#.to_string(), "m"),
+            r#"{"error":"bad"}"#
+        );
+        assert_eq!(
+            script_result(r#"{"other":1}"#.to_string(), "m"),
+            r#"{"other":1}"#
+        );
+    }
+
+    #[test]
+    fn base_tag_goes_at_start_of_head() {
+        let tag = r#"<base href="app://x/">"#;
+        assert_eq!(
+            with_base_tag("<html><head><title>t</title></head></html>", tag),
+            r#"<html><head><base href="app://x/"><title>t</title></head></html>"#
+        );
+        assert_eq!(
+            with_base_tag("<!DOCTYPE html><html lang=\"en\"><body></body></html>", tag),
+            r#"<!DOCTYPE html><html lang="en"><head><base href="app://x/"></head><body></body></html>"#
+        );
+        assert_eq!(
+            with_base_tag("<header>x</header>", tag),
+            r#"<head><base href="app://x/"></head><header>x</header>"#
+        );
+    }
+
+    #[test]
+    fn drag_region_press_requires_matching_token() {
+        let body = r#"{"__velox_drag":[12.5,40],"token":"abc"}"#;
+        assert_eq!(parse_drag_region_press(body, "abc"), Some((12.5, 40.0)));
+        assert_eq!(parse_drag_region_press(body, "other"), None);
+        assert_eq!(
+            parse_drag_region_press(r#"{"__velox_drag":[1,2]}"#, "abc"),
+            None
+        );
+        assert_eq!(parse_drag_region_press("not json", "abc"), None);
+    }
+
+    #[test]
+    fn drag_region_token_is_stable() {
+        assert_eq!(drag_region_token(), drag_region_token());
+        assert_eq!(drag_region_token().len(), 16);
+    }
+
+    #[test]
+    fn recorded_errors_update_last_error() {
+        assert_eq!(
+            record_error(Err::<(), _>("no such file"), VeloxErrorCode::IoError),
+            Err(VeloxErrorCode::IoError)
+        );
+        assert_eq!(velox_last_error_code(), VeloxErrorCode::IoError);
+        let message = unsafe { CStr::from_ptr(velox_last_error_message()) };
+        assert_eq!(message.to_str(), Ok("no such file"));
+
+        // Successful calls leave the last failure in place.
+        assert_eq!(
+            report_error(Ok::<_, String>(5), VeloxErrorCode::Unknown),
+            Some(5)
+        );
+        assert_eq!(velox_last_error_code(), VeloxErrorCode::IoError);
+
+        assert!(!guard_panic_bool(|| panic!("boom")));
+        assert_eq!(velox_last_error_code(), VeloxErrorCode::Unknown);
+        let message = unsafe { CStr::from_ptr(velox_last_error_message()) };
+        assert_eq!(message.to_str(), Ok("panicked: boom"));
+    }
+
+    #[test]
+    fn error_codes_map_results() {
+        assert_eq!(VeloxErrorCode::default(), VeloxErrorCode::Unknown);
+        assert_eq!(VeloxErrorCode::from_result(Ok(())), VeloxErrorCode::Success);
+        assert_eq!(
+            VeloxErrorCode::from_result(Err(VeloxErrorCode::IoError)),
+            VeloxErrorCode::IoError
+        );
+        assert_eq!(
+            VeloxErrorCode::from_external(Ok(())),
+            VeloxErrorCode::Success
+        );
+    }
+
+    #[test]
+    fn data_directory_defaults_and_validates() {
+        assert_eq!(data_directory_from_ffi(ptr::null()), Ok(None));
+        let empty = CString::new("").unwrap();
+        assert_eq!(data_directory_from_ffi(empty.as_ptr()), Ok(None));
+
+        let missing = std::env::temp_dir().join(format!("velox-missing-{}", std::process::id()));
+        let missing = CString::new(missing.to_str().unwrap()).unwrap();
+        assert!(data_directory_from_ffi(missing.as_ptr()).is_err());
+
+        let dir = std::env::temp_dir();
+        let path = CString::new(dir.to_str().unwrap()).unwrap();
+        assert_eq!(data_directory_from_ffi(path.as_ptr()), Ok(Some(dir)));
+    }
+
+    #[test]
+    fn protocol_redirect_target_resolves_location() {
+        let base = url::Url::parse("app://localhost/docs/index.html").unwrap();
+        let redirect = |status: u16, location: Option<&str>| {
+            let mut builder = WryHttpResponse::builder().status(status);
+            if let Some(location) = location {
+                builder = builder.header(LOCATION, location);
+            }
+            protocol_redirect_target(&builder.body(Vec::new()).unwrap(), &base)
+                .map(|url| url.to_string())
+        };
+        assert_eq!(
+            redirect(302, Some("next.html")).as_deref(),
+            Some("app://localhost/docs/next.html")
+        );
+        assert_eq!(
+            redirect(301, Some("https://example.com/")).as_deref(),
+            Some("https://example.com/")
+        );
+        assert_eq!(redirect(302, None), None);
+        assert_eq!(redirect(200, Some("next.html")), None);
+    }
+
+    #[test]
+    fn csp_meta_script_targets_head_and_escapes_policy() {
+        let script = csp_meta_script("default-src 'self'; script-src \"none\"</script>");
+        assert!(script.contains("meta.httpEquiv = 'Content-Security-Policy'"));
+        assert!(script
+            .contains(r#"meta.content = "default-src 'self'; script-src \"none\"</script>";"#));
+        assert!(script.contains("head.insertBefore(meta, head.firstChild)"));
+        assert!(!script.contains("documentElement"));
+        assert!(script.contains("subtree: true"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn parses_zenity_and_kdialog_colors() {
+        assert_eq!(parse_dialog_color("#ff8000"), Some([255, 128, 0]));
+        assert_eq!(parse_dialog_color("#ffff80800000"), Some([255, 128, 0]));
+        assert_eq!(parse_dialog_color("rgb(255,128,0)"), Some([255, 128, 0]));
+        assert_eq!(parse_dialog_color("rgba(1, 2, 3, 0.5)"), Some([1, 2, 3]));
+        assert_eq!(parse_dialog_color("#ff80"), None);
+        assert_eq!(parse_dialog_color("rgb(300,0,0)"), None);
+    }
+
+    #[test]
+    fn aspect_constrained_size_respects_min_and_max() {
+        assert_eq!(
+            aspect_constrained_size((800.0, 100.0), 2.0, false, None, None),
+            (800.0, 400.0)
+        );
+        assert_eq!(
+            aspect_constrained_size((100.0, 300.0), 2.0, true, None, None),
+            (600.0, 300.0)
+        );
+        let max = Some((1000.0, 400.0));
+        assert_eq!(
+            aspect_constrained_size((1000.0, 10.0), 2.0, false, None, max),
+            (800.0, 400.0)
+        );
+        let min = Some((300.0, 200.0));
+        assert_eq!(
+            aspect_constrained_size((200.0, 10.0), 2.0, false, min, None),
+            (400.0, 200.0)
+        );
+        // Limits that rule out the ratio win over it.
+        assert_eq!(
+            aspect_constrained_size(
+                (500.0, 10.0),
+                2.0,
+                false,
+                Some((100.0, 300.0)),
+                Some((500.0, 500.0))
+            ),
+            (500.0, 300.0)
+        );
+    }
+
+    #[test]
+    fn browser_url_allows_only_web_and_mail_schemes() {
+        assert!(browser_url("https://example.com/").is_ok());
+        assert!(browser_url("http://example.com/").is_ok());
+        assert!(browser_url("mailto:someone@example.com").is_ok());
+        assert!(browser_url("file:///etc/passwd").is_err());
+        assert!(browser_url("javascript:alert(1)").is_err());
+        assert!(browser_url("not a url").is_err());
+    }
+}